@@ -0,0 +1,231 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Benchmarks the step function on a dense grid against the layout it
+// replaced, a grid of rows indexed rows[y][x] with the turn matched against
+// the ant's facing on every move, and many ants moved one at a time against
+// the same ants moved as a swarm. Run with cargo bench --bench step.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use langtons_ant::rule::Rule;
+use langtons_ant::{
+    compute_ant_position, Ant, Boundary, Direction, Facing, Grid, ObstaclePolicy, Simulation,
+    BLANK, OBSTACLE,
+};
+use std::hint::black_box;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Cells along each side of the grid, wrapped so the ants never stall.
+const SIZE: usize = 512;
+
+// Moves made per iteration of each benchmark.
+const MOVES: u64 = 100_000;
+
+// Numbers of ants moved together.
+const SWARM_ANTS: [usize; 2] = [64, 1024];
+
+// Rules benchmarked, Langton's ant and a longer rule of more colours.
+const RULES: [&str; 2] = ["RL", "LLRRRLRLRLLR"];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The step function as it was, for comparison: a grid of rows indexed
+// rows[y][x], with the turn looked up and matched against the ant's facing
+// on every move, and the same checks for the edge of the grid and for
+// obstacles.
+struct Rows {
+    rows: Vec<Vec<usize>>,
+    left: i64,
+    top: i64,
+}
+
+impl Rows {
+    fn new() -> Rows {
+        let start = -(SIZE as i64 / 2);
+
+        Rows {
+            rows: vec![vec![BLANK; SIZE]; SIZE],
+            left: start,
+            top: start,
+        }
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        x >= self.left
+            && y >= self.top
+            && x < self.left + self.rows[0].len() as i64
+            && y < self.top + self.rows.len() as i64
+    }
+
+    fn get(&self, x: i64, y: i64) -> usize {
+        if self.contains(x, y) {
+            self.rows[(y - self.top) as usize][(x - self.left) as usize]
+        } else {
+            BLANK
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize) {
+        self.rows[(y - self.top) as usize][(x - self.left) as usize] = clr_idx;
+    }
+
+    fn step(&mut self, ant: &mut Ant, directions: &[Direction]) {
+        let mut clr_idx = self.get(ant.pos_x, ant.pos_y);
+
+        if clr_idx == BLANK || clr_idx == OBSTACLE {
+            clr_idx = 0;
+        }
+
+        let direction = directions[clr_idx];
+        clr_idx += 1;
+
+        if clr_idx == directions.len() {
+            clr_idx = 0;
+        }
+
+        self.set(ant.pos_x, ant.pos_y, clr_idx);
+
+        ant.facing = match direction {
+            Direction::L => ant.facing.clockwise().reverse(),
+            Direction::R => ant.facing.clockwise(),
+            Direction::U => ant.facing.reverse(),
+            Direction::N => ant.facing,
+            Direction::Face(facing) => facing,
+        };
+
+        let (dx, dy) = match ant.facing {
+            Facing::N => (0, -1),
+            Facing::E => (1, 0),
+            Facing::S => (0, 1),
+            Facing::W => (-1, 0),
+        };
+        let mut x = ant.pos_x + dx;
+        let mut y = ant.pos_y + dy;
+
+        if !self.contains(x, y) {
+            x = self.left + (x - self.left).rem_euclid(SIZE as i64);
+            y = self.top + (y - self.top).rem_euclid(SIZE as i64);
+        }
+
+        if self.get(x, y) == OBSTACLE {
+            ant.facing = ant.facing.reverse();
+        } else {
+            ant.pos_x = x;
+            ant.pos_y = y;
+        }
+
+        ant.iterations += 1;
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+
+    for rule in RULES.iter() {
+        group.bench_function(format!("rows/{}", rule), |b| {
+            let mut rows = Rows::new();
+            let mut ant = Ant::new(0, 0);
+            let directions = Rule::parse(rule).unwrap().directions().to_vec();
+
+            b.iter(|| {
+                for _ in 0..MOVES {
+                    rows.step(&mut ant, &directions);
+                }
+
+                black_box(ant.facing)
+            })
+        });
+
+        group.bench_function(format!("flat/{}", rule), |b| {
+            let mut grid = Grid::new(SIZE, SIZE, BLANK);
+            let mut ant = Ant::new(0, 0);
+            ant.rule = Rule::parse(rule).unwrap();
+
+            b.iter(|| {
+                for _ in 0..MOVES {
+                    compute_ant_position(
+                        &mut ant,
+                        &mut grid,
+                        Boundary::Wrap,
+                        ObstaclePolicy::Reverse,
+                    );
+                }
+
+                black_box(ant.facing)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// Many ants moved one at a time by step against the same ants moved as a
+// swarm by advance.
+fn swarm(c: &mut Criterion) {
+    let mut group = c.benchmark_group("swarm");
+
+    for &ants in SWARM_ANTS.iter() {
+        let mut sim = Simulation::with_seed("RL", SIZE, 1);
+        sim.set_boundary(Boundary::Wrap);
+
+        for i in 1..ants as i64 {
+            sim.add_ant((i * 37) % 200 - 100, (i * 53) % 200 - 100, Facing::N);
+        }
+
+        let moves = MOVES / ants as u64;
+
+        group.bench_function(format!("step/{}", ants), |b| {
+            let mut sim = sim.clone();
+
+            b.iter(|| {
+                for _ in 0..moves {
+                    sim.step();
+                }
+
+                black_box(sim.iterations())
+            })
+        });
+
+        group.bench_function(format!("advance/{}", ants), |b| {
+            let mut sim = sim.clone();
+
+            b.iter(|| {
+                sim.advance(moves);
+                black_box(sim.iterations())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step, swarm);
+criterion_main!(benches);
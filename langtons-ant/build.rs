@@ -0,0 +1,52 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Generates include/langtons_ant.h, the header for the C interface in
+// src/capi.rs, when building with the capi feature.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "capi")]
+    generate_c_header();
+}
+
+#[cfg(feature = "capi")]
+fn generate_c_header() {
+    use std::env;
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-changed=src/capi.rs");
+
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    cbindgen::Builder::new()
+        .with_src(dir.join("src").join("capi.rs"))
+        .with_language(cbindgen::Language::C)
+        .with_include_guard("LANGTONS_ANT_H")
+        .with_header("/* Generated by cbindgen from src/capi.rs, which documents each function. */")
+        .with_sys_include("stdbool.h")
+        .with_sys_include("stdint.h")
+        .with_no_includes()
+        .generate()
+        .expect("couldn't generate the C header")
+        .write_to_file(dir.join("include").join("langtons_ant.h"));
+}
@@ -0,0 +1,29 @@
+/* Generated by cbindgen from src/capi.rs, which documents each function. */
+
+#ifndef LANGTONS_ANT_H
+#define LANGTONS_ANT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define LANGTONS_ANT_BLANK -1
+
+#define LANGTONS_ANT_OBSTACLE -2
+
+typedef struct LangtonsAnt LangtonsAnt;
+
+struct LangtonsAnt *langtons_ant_create(const char *rule, uint32_t grid_size, uint64_t seed);
+
+uint64_t langtons_ant_step(struct LangtonsAnt *sim, uint64_t n);
+
+int32_t langtons_ant_read_cell(const struct LangtonsAnt *sim, int64_t x, int64_t y);
+
+uint64_t langtons_ant_iterations(const struct LangtonsAnt *sim);
+
+uint32_t langtons_ant_grid_size(const struct LangtonsAnt *sim);
+
+bool langtons_ant_ant_position(const struct LangtonsAnt *sim, int64_t *x, int64_t *y);
+
+void langtons_ant_destroy(struct LangtonsAnt *sim);
+
+#endif  /* LANGTONS_ANT_H */
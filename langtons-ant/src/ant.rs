@@ -0,0 +1,473 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{Extent, GridStorage, BLANK, OBSTACLE};
+use crate::rule::Rule;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// The facings in clockwise order, indexed by a facing's value, so turning
+// is adding quarter turns modulo 4.
+pub const FACINGS: [Facing; 4] = [Facing::N, Facing::E, Facing::S, Facing::W];
+
+// The change in (x, y) for a move one cell forward, indexed by facing.
+const OFFSETS: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Direction to move: turn left, turn right, u-turn, no turn, or face a given
+// compass direction regardless of the current facing, written "^N", "^E",
+// "^S" or "^W" in rules.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    L,
+    R,
+    U,
+    N,
+    Face(Facing),
+}
+
+impl Direction {
+    // Convert a rule character to a relative direction.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'L' => Some(Direction::L),
+            'R' => Some(Direction::R),
+            'U' => Some(Direction::U),
+            'N' => Some(Direction::N),
+            _ => None,
+        }
+    }
+
+    // Is the direction a turn relative to the current facing?
+    pub fn is_relative(self) -> bool {
+        !matches!(self, Direction::Face(_))
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::L => write!(f, "L"),
+            Direction::R => write!(f, "R"),
+            Direction::U => write!(f, "U"),
+            Direction::N => write!(f, "N"),
+            Direction::Face(facing) => write!(f, "^{}", facing.to_char()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Facing enum for encoding way ant is pointing.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Facing {
+    N,
+    E,
+    S,
+    W,
+}
+
+impl Facing {
+    // Convert a compass character, N, E, S or W, to a facing.
+    pub fn from_char(c: char) -> Option<Facing> {
+        match c {
+            'N' => Some(Facing::N),
+            'E' => Some(Facing::E),
+            'S' => Some(Facing::S),
+            'W' => Some(Facing::W),
+            _ => None,
+        }
+    }
+
+    // Convert a facing back to its compass character.
+    pub fn to_char(self) -> char {
+        match self {
+            Facing::N => 'N',
+            Facing::E => 'E',
+            Facing::S => 'S',
+            Facing::W => 'W',
+        }
+    }
+
+    // The change in (x, y) for a move one cell forward. North is towards the
+    // top of the grid, i.e. decreasing y.
+    pub fn offset(self) -> (i64, i64) {
+        OFFSETS[self as usize]
+    }
+
+    // The facing after turning in a direction, or the facing it names for
+    // an absolute direction.
+    pub fn turned(self, direction: Direction) -> Facing {
+        let quarter_turns = match direction {
+            Direction::N => 0,
+            Direction::R => 1,
+            Direction::U => 2,
+            Direction::L => 3,
+            Direction::Face(facing) => return facing,
+        };
+
+        FACINGS[(self as usize + quarter_turns) & 3]
+    }
+
+    // The facing a quarter turn clockwise.
+    pub fn clockwise(self) -> Facing {
+        match self {
+            Facing::N => Facing::E,
+            Facing::E => Facing::S,
+            Facing::S => Facing::W,
+            Facing::W => Facing::N,
+        }
+    }
+
+    // The facing pointing the opposite way.
+    pub fn reverse(self) -> Facing {
+        match self {
+            Facing::N => Facing::S,
+            Facing::E => Facing::W,
+            Facing::S => Facing::N,
+            Facing::W => Facing::E,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// What happens when the ant tries to move off the edge of a bounded grid:
+// either it stalls, ending the run, it bounces, reversing its facing as if
+// reflected by a mirror along the edge, or it wraps around to the opposite
+// edge, as if the grid were a torus.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Boundary {
+    #[default]
+    Stall,
+    Bounce,
+    Wrap,
+}
+
+impl Boundary {
+    // Parse a boundary mode from its name, e.g. "bounce".
+    pub fn parse(s: &str) -> Result<Boundary, String> {
+        match s.trim().to_lowercase().as_str() {
+            "stall" => Ok(Boundary::Stall),
+            "bounce" => Ok(Boundary::Bounce),
+            "wrap" => Ok(Boundary::Wrap),
+            _ => Err(format!("unknown boundary mode: {}", s)),
+        }
+    }
+
+    // The name of the boundary mode, as accepted by parse.
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Stall => "stall",
+            Boundary::Bounce => "bounce",
+            Boundary::Wrap => "wrap",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// What happens when the ant tries to move onto an obstacle cell, which it
+// can't enter: it stays where it is and either turns around, turns left,
+// turns right or stalls, ending the run.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ObstaclePolicy {
+    #[default]
+    Reverse,
+    Left,
+    Right,
+    Stall,
+}
+
+impl ObstaclePolicy {
+    // Parse an obstacle policy from its name, e.g. "left".
+    pub fn parse(s: &str) -> Result<ObstaclePolicy, String> {
+        match s.trim().to_lowercase().as_str() {
+            "reverse" => Ok(ObstaclePolicy::Reverse),
+            "left" => Ok(ObstaclePolicy::Left),
+            "right" => Ok(ObstaclePolicy::Right),
+            "stall" => Ok(ObstaclePolicy::Stall),
+            _ => Err(format!("unknown obstacle policy: {}", s)),
+        }
+    }
+
+    // The name of the obstacle policy, as accepted by parse.
+    pub fn name(self) -> &'static str {
+        match self {
+            ObstaclePolicy::Reverse => "reverse",
+            ObstaclePolicy::Left => "left",
+            ObstaclePolicy::Right => "right",
+            ObstaclePolicy::Stall => "stall",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// What happens when ants meet, i.e. a move leaves two or more ants on the
+// same cell that weren't already there together: either nothing, each ant
+// takes on the facing of the next as if they bounced off each other, the
+// ants annihilate each other, or a new ant is spawned on the cell, facing
+// the opposite way to the first of them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum CollisionPolicy {
+    #[default]
+    Ignore,
+    Swap,
+    Annihilate,
+    Spawn,
+}
+
+impl CollisionPolicy {
+    // Parse a collision policy from its name, e.g. "swap".
+    pub fn parse(s: &str) -> Result<CollisionPolicy, String> {
+        match s.trim().to_lowercase().as_str() {
+            "ignore" => Ok(CollisionPolicy::Ignore),
+            "swap" => Ok(CollisionPolicy::Swap),
+            "annihilate" => Ok(CollisionPolicy::Annihilate),
+            "spawn" => Ok(CollisionPolicy::Spawn),
+            _ => Err(format!("unknown collision policy: {}", s)),
+        }
+    }
+
+    // The name of the collision policy, as accepted by parse.
+    pub fn name(self) -> &'static str {
+        match self {
+            CollisionPolicy::Ignore => "ignore",
+            CollisionPolicy::Swap => "swap",
+            CollisionPolicy::Annihilate => "annihilate",
+            CollisionPolicy::Spawn => "spawn",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The Ant structure defining its position, movement rule and iteration
+// count. The state is only used by turmites, which carry an internal state
+// between moves. The colours the cells are drawn in belong to the
+// simulation, see ColourMap.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ant {
+    pub pos_x: i64,
+    pub pos_y: i64,
+    pub rule: Rule,
+    pub facing: Facing,
+    pub stalled: bool,
+    pub iterations: u64,
+    #[serde(default)]
+    pub state: usize,
+}
+
+impl Ant {
+    pub fn new(x: i64, y: i64) -> Ant {
+        Ant {
+            pos_x: x,
+            pos_y: y,
+            rule: Rule::default(),
+            facing: Facing::N,
+            stalled: false,
+            iterations: 0,
+            state: 0,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Write a rule's directions back as a string, as accepted by Rule::parse.
+pub fn rule_to_string(rule: &[Direction]) -> String {
+    rule.iter().map(|d| d.to_string()).collect()
+}
+
+//-----------------------------------------------------------------------------
+// Move ant one cell forward in the direction it is now facing. Checking for
+// hitting boundary, in which case we either mark ant as stalled, reverse
+// its facing and move it back the way it came, or wrap it around to the
+// opposite edge. If the cell it would move onto is an obstacle it stays
+// put and the obstacle policy is applied instead.
+pub(crate) fn move_forward<G: GridStorage>(
+    grid: &G,
+    ant: &mut Ant,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) {
+    let (dx, dy) = ant.facing.offset();
+    let mut x = ant.pos_x + dx;
+    let mut y = ant.pos_y + dy;
+
+    if !grid.contains(x, y) {
+        match boundary {
+            Boundary::Stall => {
+                ant.stalled = true;
+                return;
+            }
+            Boundary::Bounce => {
+                ant.facing = ant.facing.reverse();
+                x = ant.pos_x - dx;
+                y = ant.pos_y - dy;
+
+                // On a grid one cell wide there is nowhere to go, so stay put.
+                if !grid.contains(x, y) {
+                    return;
+                }
+            }
+            Boundary::Wrap => {
+                let (wx, wy) = wrap(grid.extent(), x, y);
+                x = wx;
+                y = wy;
+            }
+        }
+    }
+
+    if grid.get(x, y) == OBSTACLE {
+        match obstacles {
+            ObstaclePolicy::Reverse => ant.facing = ant.facing.reverse(),
+            // A quarter turn anticlockwise.
+            ObstaclePolicy::Left => ant.facing = ant.facing.clockwise().reverse(),
+            ObstaclePolicy::Right => ant.facing = ant.facing.clockwise(),
+            ObstaclePolicy::Stall => ant.stalled = true,
+        }
+
+        return;
+    }
+
+    ant.pos_x = x;
+    ant.pos_y = y;
+}
+
+//-----------------------------------------------------------------------------
+// Wrap a cell just outside a region around to the opposite edge.
+pub(crate) fn wrap(extent: Extent, x: i64, y: i64) -> (i64, i64) {
+    (
+        extent.x + (x - extent.x).rem_euclid(extent.width as i64),
+        extent.y + (y - extent.y).rem_euclid(extent.height as i64),
+    )
+}
+
+//-----------------------------------------------------------------------------
+// Compute new position of ant updating grif colours as we move ant.
+pub fn compute_ant_position<G: GridStorage>(
+    ant: &mut Ant,
+    grid: &mut G,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) {
+    // Has ant stalled?
+    if ant.stalled {
+        return;
+    }
+
+    // As compute_ant_position_by, but with the new colour and facing looked
+    // up in the rule's tables.
+    let mut cell_clr_idx = grid.get(ant.pos_x, ant.pos_y);
+
+    if BLANK == cell_clr_idx || OBSTACLE == cell_clr_idx {
+        cell_clr_idx = 0;
+    }
+
+    let (next_clr_idx, facing) = ant.rule.step(cell_clr_idx, ant.facing);
+    grid.set(ant.pos_x, ant.pos_y, next_clr_idx);
+    ant.facing = facing;
+
+    move_and_count(grid, ant, boundary, obstacles);
+}
+
+//-----------------------------------------------------------------------------
+// As compute_ant_position, but with the turn for each of num_colours colour
+// indices given by a function rather than the ant's rule, e.g. to choose it
+// at random.
+pub fn compute_ant_position_by<G, F>(
+    ant: &mut Ant,
+    grid: &mut G,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+    num_colours: usize,
+    turn: F,
+) where
+    G: GridStorage,
+    F: FnOnce(usize) -> Direction,
+{
+    // Has ant stalled?
+    if ant.stalled {
+        return;
+    }
+
+    // Grab the current colour index for the ant's current position. An
+    // obstacle painted under the ant counts as blank, and is overwritten.
+    let mut cell_clr_idx = grid.get(ant.pos_x, ant.pos_y);
+
+    if BLANK == cell_clr_idx || OBSTACLE == cell_clr_idx {
+        cell_clr_idx = 0;
+    }
+
+    // Grab direction we need to turn.
+    let ant_dir = turn(cell_clr_idx);
+
+    // Increment cell colour index.
+    cell_clr_idx += 1;
+
+    if num_colours == cell_clr_idx {
+        cell_clr_idx = 0;
+    }
+
+    grid.set(ant.pos_x, ant.pos_y, cell_clr_idx);
+
+    turn_and_move(ant_dir, grid, ant, boundary, obstacles);
+}
+
+//-----------------------------------------------------------------------------
+// Turn the ant and move it one cell, then count the iteration. Shared by the
+// plain rule and turmite step functions, and by rule engines.
+pub fn turn_and_move<G: GridStorage>(
+    ant_dir: Direction,
+    grid: &G,
+    ant: &mut Ant,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) {
+    ant.facing = ant.facing.turned(ant_dir);
+    move_and_count(grid, ant, boundary, obstacles);
+}
+
+//-----------------------------------------------------------------------------
+// Move the turned ant one cell and count the iteration.
+fn move_and_count<G: GridStorage>(
+    grid: &G,
+    ant: &mut Ant,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) {
+    move_forward(grid, ant, boundary, obstacles);
+
+    // Increment the iteration count.
+    if u64::max_value() == ant.iterations {
+        ant.stalled = true;
+    } else {
+        ant.iterations += 1;
+    }
+}
@@ -0,0 +1,307 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::BenchArgs;
+use langtons_ant::{export, Boundary, ConfigError, Extent, Simulation};
+use log::{error, info};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::hint;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// The cases timed, in the order they're reported.
+const CASES: &[Case] = &[
+    Case {
+        name: "dense",
+        unbounded: false,
+        render: false,
+    },
+    Case {
+        name: "dense-render",
+        unbounded: false,
+        render: true,
+    },
+    Case {
+        name: "sparse",
+        unbounded: true,
+        render: false,
+    },
+    Case {
+        name: "sparse-render",
+        unbounded: true,
+        render: true,
+    },
+];
+
+// Changes from the baseline smaller than this fraction are put down to
+// noise.
+const NOISE_THRESHOLD: f64 = 0.05;
+
+// Width of the case names column of the report.
+const NAME_WIDTH: usize = 24;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A way of running the simulation to time: on a dense grid, where the ant
+// wraps around the edges, or an unbounded sparse grid, and either flat out
+// or rendering a frame of the view around the ant every so often, as the
+// window would.
+struct Case {
+    name: &'static str,
+    unbounded: bool,
+    render: bool,
+}
+
+//-----------------------------------------------------------------------------
+// The moves per second of each sample of a case.
+struct Timing {
+    name: &'static str,
+    rates: Vec<f64>,
+    times: Vec<f64>,
+}
+
+impl Timing {
+    fn mean_rate(&self) -> f64 {
+        self.rates.iter().sum::<f64>() / self.rates.len() as f64
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Time each case over the requested number of samples and print the lowest,
+// mean and highest time and moves per second of each, comparing them with a
+// baseline saved by an earlier run if given.
+pub fn run(args: &BenchArgs) -> Result<(), ConfigError> {
+    let baseline = args
+        .baseline
+        .as_ref()
+        .map(|path| {
+            load_baseline(path).map_err(|e| ConfigError::Load {
+                name: "baseline",
+                path: path.clone(),
+                reason: e.to_string(),
+            })
+        })
+        .transpose()?;
+
+    let samples = args.samples.max(1);
+
+    info!(
+        "Benchmarking rule {} for {} iterations, {} samples of each case",
+        args.rule, args.iterations, samples
+    );
+
+    let mut timings = Vec::new();
+
+    for case in CASES {
+        let mut timing = Timing {
+            name: case.name,
+            rates: Vec::new(),
+            times: Vec::new(),
+        };
+
+        for _ in 0..samples {
+            let (moves, secs) = sample(args, case);
+            timing.rates.push(moves as f64 / secs.max(f64::EPSILON));
+            timing.times.push(secs);
+        }
+
+        report(&timing, baseline.as_ref().and_then(|b| b.get(case.name)));
+        timings.push(timing);
+    }
+
+    if let Some(path) = args.save.as_ref() {
+        match save(path, args, &timings) {
+            Ok(()) => info!("Saved results to {}", path.display()),
+            Err(e) => error!("Failed to save results to {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// Time one run of a case, returning the number of moves made and the time
+// taken in seconds. Setting up the grid isn't timed.
+fn sample(args: &BenchArgs, case: &Case) -> (u64, f64) {
+    let size = args.grid as usize;
+    let mut sim = Simulation::with_seed(&args.rule, size, args.seed);
+
+    if case.unbounded {
+        sim.make_unbounded();
+    } else {
+        sim.set_boundary(Boundary::Wrap);
+    }
+
+    let chunk = if case.render {
+        args.frame_every.max(1)
+    } else {
+        args.iterations
+    };
+
+    let start = Instant::now();
+
+    while sim.iterations() < args.iterations && !sim.is_stalled() {
+        sim.advance(chunk.min(args.iterations - sim.iterations()));
+
+        if case.render {
+            let view = Extent {
+                x: sim.ant().pos_x - size as i64 / 2,
+                y: sim.ant().pos_y - size as i64 / 2,
+                width: size,
+                height: size,
+            };
+
+            hint::black_box(export::render_region(&sim, view, 1));
+        }
+    }
+
+    (sim.iterations(), start.elapsed().as_secs_f64())
+}
+
+//-----------------------------------------------------------------------------
+// Print the lowest, mean and highest time and moves per second of a case,
+// and the change in its mean moves per second from the baseline.
+fn report(timing: &Timing, baseline: Option<&f64>) {
+    let (low, mean, high) = spread(&timing.times);
+    println!(
+        "{:width$}time:   [{} {} {}]",
+        timing.name,
+        format_time(low),
+        format_time(mean),
+        format_time(high),
+        width = NAME_WIDTH
+    );
+
+    let (low, mean, high) = spread(&timing.rates);
+    println!(
+        "{:width$}thrpt:  [{} {} {}]",
+        "",
+        format_rate(low),
+        format_rate(mean),
+        format_rate(high),
+        width = NAME_WIDTH
+    );
+
+    if let Some(&base) = baseline.filter(|&&b| b > 0.0) {
+        let change = timing.mean_rate() / base - 1.0;
+        let verdict = if change > NOISE_THRESHOLD {
+            "Performance has improved."
+        } else if change < -NOISE_THRESHOLD {
+            "Performance has regressed."
+        } else {
+            "No change in performance detected."
+        };
+
+        println!(
+            "{:width$}change: [{:+.2}%] {}",
+            "",
+            change * 100.0,
+            verdict,
+            width = NAME_WIDTH
+        );
+    }
+}
+
+// The lowest, mean and highest of some samples.
+fn spread(samples: &[f64]) -> (f64, f64, f64) {
+    let low = samples.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    (low, mean, high)
+}
+
+fn format_time(secs: f64) -> String {
+    if secs >= 1.0 {
+        format!("{:.3} s", secs)
+    } else if secs >= 1e-3 {
+        format!("{:.3} ms", secs * 1e3)
+    } else {
+        format!("{:.3} µs", secs * 1e6)
+    }
+}
+
+fn format_rate(rate: f64) -> String {
+    if rate >= 1e9 {
+        format!("{:.3} G moves/s", rate / 1e9)
+    } else if rate >= 1e6 {
+        format!("{:.3} M moves/s", rate / 1e6)
+    } else {
+        format!("{:.3} K moves/s", rate / 1e3)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Write the mean moves per second of each case to a CSV file.
+fn save(path: &Path, args: &BenchArgs, timings: &[Timing]) -> io::Result<()> {
+    let mut csv = BufWriter::new(File::create(path)?);
+    writeln!(csv, "case,rule,iterations,moves_per_second")?;
+
+    for timing in timings {
+        writeln!(
+            csv,
+            "{},{},{},{:.0}",
+            timing.name,
+            args.rule,
+            args.iterations,
+            timing.mean_rate()
+        )?;
+    }
+
+    csv.flush()
+}
+
+//-----------------------------------------------------------------------------
+// Read the mean moves per second of each case saved by an earlier run.
+fn load_baseline(path: &Path) -> io::Result<BTreeMap<String, f64>> {
+    let text = fs::read_to_string(path)?;
+    let mut baseline = BTreeMap::new();
+
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split(',').collect();
+
+        match (fields.first(), fields.get(3).and_then(|r| r.parse().ok())) {
+            (Some(name), Some(rate)) => {
+                baseline.insert(name.to_string(), rate);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid line: {}", line),
+                ))
+            }
+        }
+    }
+
+    Ok(baseline)
+}
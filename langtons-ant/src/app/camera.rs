@@ -0,0 +1,200 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::Extent;
+use piston_window::math::Matrix2d;
+use piston_window::Transformed;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+// Zoom limits in pixels per grid square and the zoom step per wheel notch.
+const MIN_ZOOM: f64 = 0.01;
+const MAX_ZOOM: f64 = 100.0;
+const ZOOM_STEP: f64 = 1.1;
+
+//-----------------------------------------------------------------------------
+// Camera transform mapping grid squares to window pixels, zoomed with the
+// mouse wheel about the cursor and panned by dragging with the middle mouse
+// button. In follow mode the view is kept centred on the ant instead. The
+// home view fits the grid to the window, centred with bars either side when
+// the window isn't square, and is refitted as the window is resized unless
+// the view has been moved away from it. Squares keep their grid coordinates,
+// centred on the origin, until translated here.
+pub struct Camera {
+    grid_size: f64,
+    grid_start: [f64; 2],
+    home_zoom: f64,
+    home_offset: [f64; 2],
+    zoom: f64,
+    offset: [f64; 2],
+    cursor: [f64; 2],
+    dragging: bool,
+    following: bool,
+    moved: bool,
+}
+
+impl Camera {
+    // Create a camera showing the given region of the grid at the given
+    // square size in pixels, with the top left of the region at the top left
+    // of the window.
+    pub fn new(square_size: f64, grid: Extent) -> Camera {
+        let grid_start = [grid.x as f64, grid.y as f64];
+        let home_offset = [-grid_start[0] * square_size, -grid_start[1] * square_size];
+
+        Camera {
+            grid_size: grid.width.max(grid.height).max(1) as f64,
+            grid_start,
+            home_zoom: square_size,
+            home_offset,
+            zoom: square_size,
+            offset: home_offset,
+            cursor: [0.0, 0.0],
+            dragging: false,
+            following: false,
+            moved: false,
+        }
+    }
+
+    // Reset to the home view.
+    pub fn reset(&mut self) {
+        self.zoom = self.home_zoom;
+        self.offset = self.home_offset;
+        self.moved = false;
+    }
+
+    // Fit the home view to a new window size in pixels, moving to it if the
+    // view hasn't been zoomed or panned.
+    pub fn resize(&mut self, view_size: [f64; 2]) {
+        let fit = view_size[0].min(view_size[1]) / self.grid_size;
+        self.home_zoom = fit.clamp(MIN_ZOOM, MAX_ZOOM);
+
+        let extent = self.grid_size * self.home_zoom;
+        self.home_offset = [
+            (view_size[0] - extent) / 2.0 - self.grid_start[0] * self.home_zoom,
+            (view_size[1] - extent) / 2.0 - self.grid_start[1] * self.home_zoom,
+        ];
+
+        if !self.moved {
+            self.reset();
+        }
+    }
+
+    // Track the cursor, panning if a drag is in progress.
+    pub fn cursor_moved(&mut self, pos: [f64; 2]) {
+        if self.dragging {
+            self.offset[0] += pos[0] - self.cursor[0];
+            self.offset[1] += pos[1] - self.cursor[1];
+            self.moved = true;
+        }
+        self.cursor = pos;
+    }
+
+    // Start or stop dragging. Dragging the view turns off follow mode.
+    pub fn set_dragging(&mut self, dragging: bool) {
+        self.dragging = dragging;
+
+        if dragging {
+            self.following = false;
+        }
+    }
+
+    // Toggle follow mode.
+    pub fn toggle_follow(&mut self) {
+        self.following = !self.following;
+    }
+
+    // Is follow mode on?
+    pub fn following(&self) -> bool {
+        self.following
+    }
+
+    // Pan so the centre of the given grid square is in the middle of a view
+    // of the given size in pixels.
+    pub fn centre_on(&mut self, x: i64, y: i64, view_size: [f64; 2]) {
+        self.offset[0] = view_size[0] / 2.0 - (x as f64 + 0.5) * self.zoom;
+        self.offset[1] = view_size[1] / 2.0 - (y as f64 + 0.5) * self.zoom;
+        self.moved = true;
+    }
+
+    // Centre the view on a grid square, turning off follow mode, e.g. on
+    // clicking the minimap.
+    pub fn jump_to(&mut self, x: i64, y: i64, view_size: [f64; 2]) {
+        self.following = false;
+        self.centre_on(x, y, view_size);
+    }
+
+    // Zoom in (positive) or out (negative) by a number of wheel notches,
+    // keeping the point under the cursor fixed.
+    pub fn scroll(&mut self, notches: f64) {
+        let new_zoom = (self.zoom * ZOOM_STEP.powf(notches)).clamp(MIN_ZOOM, MAX_ZOOM);
+        let ratio = new_zoom / self.zoom;
+
+        self.offset[0] = self.cursor[0] - (self.cursor[0] - self.offset[0]) * ratio;
+        self.offset[1] = self.cursor[1] - (self.cursor[1] - self.offset[1]) * ratio;
+        self.zoom = new_zoom;
+        self.moved = true;
+    }
+
+    // Size of a grid square in window pixels.
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    // The grid squares at least partly shown in a view of the given size in
+    // pixels.
+    pub fn visible_cells(&self, view_size: [f64; 2]) -> Extent {
+        let x = (-self.offset[0] / self.zoom).floor();
+        let y = (-self.offset[1] / self.zoom).floor();
+
+        Extent {
+            x: x as i64,
+            y: y as i64,
+            width: ((view_size[0] - self.offset[0]) / self.zoom - x)
+                .ceil()
+                .max(0.0) as usize,
+            height: ((view_size[1] - self.offset[1]) / self.zoom - y)
+                .ceil()
+                .max(0.0) as usize,
+        }
+    }
+
+    // The cursor's position in window pixels.
+    pub fn cursor(&self) -> [f64; 2] {
+        self.cursor
+    }
+
+    // The grid square under the cursor.
+    pub fn cursor_cell(&self) -> (i64, i64) {
+        (
+            ((self.cursor[0] - self.offset[0]) / self.zoom).floor() as i64,
+            ((self.cursor[1] - self.offset[1]) / self.zoom).floor() as i64,
+        )
+    }
+
+    // Apply the camera to a base transform, giving a transform from grid
+    // square coordinates to window pixels.
+    pub fn transform(&self, base: Matrix2d) -> Matrix2d {
+        base.trans(self.offset[0], self.offset[1])
+            .scale(self.zoom, self.zoom)
+    }
+}
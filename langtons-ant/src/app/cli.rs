@@ -0,0 +1,879 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cycle::CycleAction;
+use super::gpu::Backend;
+use super::highway::HighwayAction;
+use super::render::DisplayMode;
+use super::surface::Surface;
+use super::tui::RendererMode;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use langtons_ant::autosave::AutosaveSpec;
+use langtons_ant::config::{AntSpec, ConfigFile, ZoneSpec, UNLIMITED_MPS};
+use langtons_ant::cubic::TurnConvention;
+use langtons_ant::spawn::{SpawnFacing, SpawnPosition};
+use langtons_ant::stop::StopCondition;
+use langtons_ant::timelapse::TimelapseLayout;
+use langtons_ant::{presets, Boundary, CollisionPolicy, ConfigError, ObstaclePolicy};
+use log::LevelFilter;
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Suffixes of counts given in thousands, millions or billions, e.g. 50M.
+const COUNT_SUFFIXES: &[(char, u64)] = &[('K', 1_000), ('M', 1_000_000), ('G', 1_000_000_000)];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Command line arguments. Any argument not given takes the same default as
+// the interactive prompts.
+#[derive(Parser, Debug)]
+#[command(name = "langtons-ant", version, about = "Langton's Ant simulator")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Read settings from a TOML file, with the same names as these options
+    /// but with underscores for dashes. Options given on the command line
+    /// override the file's settings.
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Start from a built in preset of a famous rule, with its own colours,
+    /// grid size and speed, any of which can be overridden. Run with
+    /// --list-presets to list them.
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// List the built in presets and exit.
+    #[arg(long)]
+    pub list_presets: bool,
+
+    /// How much is logged to the console: off, error, warn, info, debug or
+    /// trace. Debug adds the app's state changes, stalled ants, highway and
+    /// cycle detection and the moves per second achieved.
+    #[arg(long, value_name = "LEVEL", default_value = "info")]
+    pub log_level: LevelFilter,
+
+    /// Rule using L, R, U (u-turn) and N (no turn) characters, e.g. LR or RLNU.
+    #[arg(long, default_value = "RL")]
+    pub rule: String,
+
+    /// Number of moves per second, at least 1 with no upper limit, e.g. 10,
+    /// 1e6 or 5M, or max to run as fast as the machine allows while keeping
+    /// the window responsive.
+    #[arg(long, default_value = "10", value_parser = parse_mps)]
+    pub mps: u64,
+
+    /// Grid size as a number of squares, at least 10 and limited only by memory.
+    #[arg(long, default_value_t = 150)]
+    pub grid: u32,
+
+    /// Size of a grid square as a number of pixels (1 - 20).
+    #[arg(long, default_value_t = 5.0)]
+    pub square: f64,
+
+    /// Seed for the random colour generation, so runs are reproducible.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Cell colours, either a comma separated list of hex colours, one per
+    /// rule step, e.g. "#000000,#ff0000", or a named palette (viridis, magma,
+    /// plasma, cividis, greys, okabe-ito).
+    #[arg(long)]
+    pub palette: Option<String>,
+
+    /// What the ant does at the edge of a bounded grid, either stall,
+    /// bounce, reversing its facing, or wrap around to the opposite edge.
+    /// Defaults to stall.
+    #[arg(long, value_name = "MODE", value_parser = Boundary::parse)]
+    pub boundary: Option<Boundary>,
+
+    /// What the ant does on reaching an obstacle cell, which it can't enter,
+    /// either reverse, turning around, left, right or stall. Defaults to
+    /// reverse.
+    #[arg(long, value_name = "POLICY", value_parser = ObstaclePolicy::parse)]
+    pub obstacles: Option<ObstaclePolicy>,
+
+    /// Initial grid pattern instead of a blank grid, either random noise
+    /// with a density, e.g. "random:0.3", "checker", or an image file whose
+    /// pixel brightness is mapped to colours.
+    #[arg(long, value_name = "PATTERN")]
+    pub seed_grid: Option<String>,
+
+    /// Use an unbounded sparse grid so the ant never stalls at a boundary.
+    /// The grid size sets the region initially shown.
+    #[arg(long)]
+    pub unbounded: bool,
+
+    /// Pack each cell of a bounded grid into 4 or 8 bits instead of a whole
+    /// machine word, cutting memory use 16 or 8 times on big grids. 4 bit
+    /// cells fit rules of up to 14 steps and 8 bit cells up to 254.
+    #[arg(long, value_name = "BITS", value_parser = clap::value_parser!(u8).range(4..=8), conflicts_with = "unbounded")]
+    pub cell_bits: Option<u8>,
+
+    /// Start the ant at this cell, given as X,Y from the centre of the grid,
+    /// e.g. 10,-20, or at a random cell with "random", rather than at 0,0.
+    /// Given more than once, each start after the first adds another ant.
+    #[arg(long, value_name = "X,Y", value_parser = SpawnPosition::parse, conflicts_with_all = ["load", "replay"])]
+    pub start: Vec<SpawnPosition>,
+
+    /// Start the ant facing this way, N, E, S or W, or a random way with
+    /// "random", rather than north. Given more than once, each facing goes
+    /// with the --start in the same place, and ants without one take the
+    /// last.
+    #[arg(long, value_name = "FACING", value_parser = SpawnFacing::parse, conflicts_with_all = ["load", "replay"])]
+    pub facing: Vec<SpawnFacing>,
+
+    /// Add another ant at the start, given as X,Y,FACING, e.g. 10,20,E.
+    /// Can be given more than once.
+    #[arg(long, value_name = "X,Y,FACING", value_parser = AntSpec::parse)]
+    pub ant: Vec<AntSpec>,
+
+    /// Add a rectangular zone where a different rule applies, given as
+    /// X,Y,WIDTH,HEIGHT,RULE, e.g. -25,-25,50,50,LR. The rule must have as many
+    /// steps as the main rule. Can be given more than once, later zones
+    /// taking precedence where they overlap.
+    #[arg(long, value_name = "X,Y,WIDTH,HEIGHT,RULE", value_parser = ZoneSpec::parse)]
+    pub zone: Vec<ZoneSpec>,
+
+    /// What happens when ants meet on the same cell, either ignore, swap,
+    /// each taking the next one's facing, annihilate, removing them, or
+    /// spawn, adding a new ant. Defaults to ignore.
+    #[arg(long, value_name = "POLICY", value_parser = CollisionPolicy::parse)]
+    pub collision: Option<CollisionPolicy>,
+
+    /// Number of dimensions, 2 or 3. The 3D mode is experimental: the ant
+    /// moves through a cubic lattice of grid size cells along each side
+    /// (at most 256), which wraps around at its faces, and the rule can
+    /// also use C to climb and D to dive. Only the window is supported.
+    #[arg(long, value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u8).range(2..=3))]
+    pub dimensions: u8,
+
+    /// How left and right turns are made in 3D, either planar, turning
+    /// about the ant's up axis, or roll, also rolling a quarter turn about
+    /// its new heading. Defaults to planar.
+    #[arg(long, value_name = "CONVENTION", value_parser = TurnConvention::parse)]
+    pub turn_convention: Option<TurnConvention>,
+
+    /// Surface the ant walks on, either a plane or a cube-sphere, the six
+    /// faces of a cube of grid size squares joined at their edges so there
+    /// is no boundary. The cube-sphere is drawn unwrapped, with V switching
+    /// between the cube's net and a map of the sphere, and only runs in the
+    /// window.
+    #[arg(long, value_name = "SURFACE", value_enum, default_value_t = Surface::Plane)]
+    pub surface: Surface,
+
+    /// Run several rules side by side in one window, given as a list
+    /// separated by semicolons, e.g. "RL;RLLR;LLRR". Each runs on a grid of
+    /// its own, tiled across the window, all kept at the same iteration.
+    #[arg(long, value_name = "RULES", conflicts_with_all = ["headless", "serve", "replay", "load", "turmite", "engine", "unbounded"])]
+    pub compare: Option<String>,
+
+    /// Run two copies of the rule side by side, the second with the cell at
+    /// X,Y flipped to the next colour, e.g. 1,0, and a third panel
+    /// showing where their grids differ, to see how a small change spreads.
+    #[arg(long, value_name = "X,Y", value_parser = parse_cell, conflicts_with_all = ["compare", "headless", "serve", "replay", "load", "turmite", "engine", "unbounded"])]
+    pub diverge: Option<(i64, i64)>,
+
+    /// Resume from a state file previously saved with the S key. The rule
+    /// and grid size are taken from the file. Files ending .rle are read as
+    /// RLE and .mc as Golly macrocell patterns, anything else as JSON.
+    #[arg(long)]
+    pub load: Option<PathBuf>,
+
+    /// Delete the --load state file once it's loaded, as forked windows do
+    /// with their temporary copies of the state.
+    #[arg(long, requires = "load", hide = true)]
+    pub load_once: bool,
+
+    /// Format of state files saved with the S key, json or rle. RLE files
+    /// are much smaller for large grids.
+    #[arg(long, value_name = "FORMAT", default_value = "json", value_parser = ["json", "rle"])]
+    pub save_format: String,
+
+    /// Run a turmite instead of a rule, defined by a TOML transition table,
+    /// a Golly .rule file, or a Golly turmite such as Turmite_120080 or
+    /// "{{{1,2,0},{0,8,0}}}".
+    #[arg(long, value_name = "TURMITE")]
+    pub turmite: Option<PathBuf>,
+
+    /// Run a custom rule engine: langton, which runs the rule, or parity,
+    /// which turns by the colours around the ant. With the plugins feature,
+    /// plugin:FILE loads an engine from a dynamic library.
+    #[arg(long, value_name = "ENGINE", conflicts_with = "turmite")]
+    pub engine: Option<String>,
+
+    /// Automatically save a PNG screenshot every N iterations.
+    #[arg(long, value_name = "N")]
+    pub screenshot_every: Option<u64>,
+
+    /// Size of a grid square in pixels in PNG screenshots.
+    #[arg(long, value_name = "PIXELS", default_value_t = 10)]
+    pub screenshot_scale: u32,
+
+    /// How the window colours the cells, cycled with the V key.
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = DisplayMode::Colours)]
+    pub display: DisplayMode,
+
+    /// Start with lines drawn between the grid squares, toggled with the L
+    /// key. They're only shown when the squares are at least 8 pixels
+    /// across.
+    #[arg(long)]
+    pub grid_lines: bool,
+
+    /// Number of moves over which cells fade out in the trail display mode.
+    #[arg(long, value_name = "N", default_value = "10000", value_parser = parse_count)]
+    pub trail_length: u64,
+
+    /// Where to draw the simulation, in a window or in the terminal. The
+    /// terminal works over SSH and without a display, with the arrow keys
+    /// panning, z and x zooming and . stepping while paused.
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = RendererMode::Window)]
+    pub renderer: RendererMode,
+
+    /// Graphics library the window is drawn with. The wgpu backend has the
+    /// basic controls only, without the HUD, control panel or editing.
+    #[arg(long, value_name = "BACKEND", value_enum, default_value_t = Backend::Piston)]
+    pub backend: Backend,
+
+    /// Run the simulation on a thread of its own, separate from drawing the
+    /// window, so even the fastest runs never make it stutter. Painting,
+    /// adding ants and resetting are unavailable.
+    #[arg(long)]
+    pub threaded: bool,
+
+    /// Run without a window as fast as possible and print timing stats.
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Run without a window, controlled over HTTP at this address, e.g.
+    /// 127.0.0.1:8080. GET /status, /stats, /grid.json and /grid.png?scale=N
+    /// read the run, and POST /start, /stop, /step?n=N, /speed?mps=N and
+    /// /quit control it. The websocket /stream streams changed cells, as
+    /// mirrored by the page at /.
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["headless", "replay"])]
+    pub serve: Option<String>,
+
+    /// Number of iterations to run in headless mode, e.g. 10_000_000.
+    #[arg(long, value_name = "N", default_value = "1_000_000", value_parser = parse_count)]
+    pub iterations: u64,
+
+    /// Write the final grid to this PNG file in headless mode.
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Write the final pattern to this SVG file, as rectangles of cells
+    /// that scale to any size, when the window closes or, in headless mode,
+    /// once the iterations are complete.
+    #[arg(long, value_name = "FILE")]
+    pub export_svg: Option<PathBuf>,
+
+    /// Record the run to this animated GIF, written when the window closes
+    /// or, in headless mode, once the iterations are complete.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Capture a GIF or video frame every N iterations.
+    #[arg(long, value_name = "N", default_value = "100", value_parser = parse_count)]
+    pub record_every: u64,
+
+    /// Number of pixels per grid square in recorded GIF frames.
+    #[arg(long, value_name = "PIXELS", default_value_t = 2)]
+    pub record_scale: u32,
+
+    /// Save a time-lapse of the run to this PNG file, combining snapshots
+    /// of the grid taken at --timelapse-frames evenly spaced iterations
+    /// over --iterations, drawn at --record-scale pixels per square.
+    #[arg(long, value_name = "FILE")]
+    pub timelapse: Option<PathBuf>,
+
+    /// Number of snapshots in the time-lapse.
+    #[arg(long, value_name = "K", default_value_t = 8)]
+    pub timelapse_frames: usize,
+
+    /// How the time-lapse snapshots are combined: "sheet" tiles them in
+    /// rows, earliest first, and "blend" lays them over each other, so
+    /// cells coloured early are solid and those reached late are faint.
+    #[arg(long, value_name = "LAYOUT", default_value = "sheet", value_parser = TimelapseLayout::parse)]
+    pub timelapse_layout: TimelapseLayout,
+
+    /// Record the run to this video file, e.g. out.mp4 or out.webm, by
+    /// streaming frames to ffmpeg, which must be on the PATH.
+    #[arg(long, value_name = "FILE")]
+    pub video: Option<PathBuf>,
+
+    /// Frame rate of the recorded video.
+    #[arg(long, value_name = "FPS", default_value_t = 30)]
+    pub video_fps: u32,
+
+    /// Frame size of the recorded video, the grid is scaled to fit.
+    #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "720x720", value_parser = parse_frame_size)]
+    pub video_size: (u32, u32),
+
+    /// Write statistics to this CSV file every --stats-every iterations:
+    /// the bounding box of the pattern, the ant's displacement from its
+    /// start and the number of cells of each colour.
+    #[arg(long, value_name = "FILE")]
+    pub stats: Option<PathBuf>,
+
+    /// Write statistics every N iterations.
+    #[arg(long, value_name = "N", default_value = "1000", value_parser = parse_count)]
+    pub stats_every: u64,
+
+    /// Write the first ant's trajectory to this file, e.g. trace.csv or
+    /// trace.json: the iteration, the ant's position and facing, and the
+    /// colour of its cell before and after each step.
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+
+    /// Trace every Nth step instead of every step.
+    #[arg(long, value_name = "N", default_value = "1", value_parser = parse_count)]
+    pub trace_every: u64,
+
+    /// Check the pattern for rotational and mirror symmetry every N
+    /// iterations, reporting when symmetry appears, changes or breaks.
+    #[arg(long, value_name = "N", value_parser = parse_count)]
+    pub symmetry_every: Option<u64>,
+
+    /// Print a canonical hash of the grid and ants every N iterations, as
+    /// "Checkpoint <iteration> <hash64> <sha256>", for checking that later
+    /// runs behave the same.
+    #[arg(long, value_name = "N", value_parser = parse_count)]
+    pub checkpoint_hash_every: Option<u64>,
+
+    /// Check the run against the checkpoints in this file, e.g. the saved
+    /// output of an earlier run with --checkpoint-hash-every, reporting any
+    /// that differ. A headless run stops at the first that differs.
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub checkpoint_verify: Option<PathBuf>,
+
+    /// Run a Rhai script alongside the simulation, e.g. hooks.rhai, calling
+    /// its on_step() function every --script-every iterations with access
+    /// to the grid and ants, e.g. to move the ant, flip cells or stop the
+    /// run.
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub script: Option<PathBuf>,
+
+    /// Call the script every N iterations.
+    #[arg(long, value_name = "N", default_value = "1", value_parser = parse_count)]
+    pub script_every: u64,
+
+    /// Watch for the ant building a highway, a periodic pattern moving off
+    /// in a straight line, and report it (report), also pausing or ending
+    /// the run (stop), or run at full speed from then on (fast).
+    #[arg(long, value_name = "ACTION")]
+    pub highway: Option<HighwayAction>,
+
+    /// Longest highway period, in moves, to watch for.
+    #[arg(long, value_name = "N", default_value_t = 1000)]
+    pub highway_period: usize,
+
+    /// Save the highway found as a pattern, the cells of its repeating unit
+    /// and the ant building it, that rebuilds the highway when loaded onto a
+    /// blank grid with --load. Saved as RLE if the file name ends .rle,
+    /// otherwise as a JSON state. Watches for a highway, reporting it, if
+    /// --highway isn't given.
+    #[arg(long, value_name = "FILE")]
+    pub highway_pattern: Option<PathBuf>,
+
+    /// Watch for the full state of a bounded grid repeating, which it must
+    /// eventually unless the ant stalls, and report the period and the
+    /// number of moves before the cycle (report), also pausing or ending the
+    /// run (stop).
+    #[arg(long, value_name = "ACTION")]
+    pub cycle: Option<CycleAction>,
+
+    /// End a headless run as soon as any ant stalls at the edge of the
+    /// grid, rather than once they all have.
+    #[arg(long)]
+    pub stop_on_stall: bool,
+
+    /// Stop once the ant builds a highway, the same as --highway stop.
+    #[arg(long, conflicts_with = "highway")]
+    pub stop_on_highway: bool,
+
+    /// End a headless run once a condition on the pattern holds, e.g.
+    /// "bbox>400", comparing iteration, bbox, width, height, area, visited,
+    /// distance, entropy or compression with a number. May be given more
+    /// than once, stopping when any holds.
+    #[arg(long, value_name = "CONDITION", value_parser = StopCondition::parse)]
+    pub stop_when: Vec<StopCondition>,
+
+    /// Check the --stop-when conditions every N iterations.
+    #[arg(long, value_name = "N", default_value = "1000", value_parser = parse_count)]
+    pub stop_every: u64,
+
+    /// Log every move and edit to this file, e.g. run.antlog, so the run can
+    /// be played back with --replay.
+    #[arg(long, value_name = "FILE")]
+    pub record_moves: Option<PathBuf>,
+
+    /// Number of moves undone by each press of Backspace, which rewinds the
+    /// simulation or, when playing back a move log, the playback.
+    #[arg(long, value_name = "N", default_value = "100", value_parser = parse_count)]
+    pub rewind: u64,
+
+    /// Save the state regularly while running, so a long run isn't lost if
+    /// the window is closed or the program dies, given as settings separated
+    /// by spaces or commas: every=DURATION, e.g. 60s, 5m or 1h, dir=PATH and
+    /// keep=N, the number of saves kept, e.g. "every=60s dir=autosaves/".
+    /// Defaults to every 60s in autosaves, keeping 3. Resume from a save
+    /// with --load.
+    #[arg(long, value_name = "SPEC", value_parser = AutosaveSpec::parse)]
+    pub autosave: Option<AutosaveSpec>,
+
+    /// Start running backwards, undoing the moves that built the pattern,
+    /// e.g. of a state loaded with --load, as the R key toggles. In headless
+    /// mode up to --iterations moves are undone. Only plain rules of
+    /// relative turns can be reversed.
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Play back a move log written with --record-moves. Space pauses, R
+    /// reverses the direction and, while paused, the left and right arrow
+    /// keys step backwards and forwards.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["record_moves", "headless", "load", "turmite", "engine"])]
+    pub replay: Option<PathBuf>,
+}
+
+impl Cli {
+    // Parse the command line, layering it over the settings of any config
+    // file given, which are in turn layered over any preset, then the
+    // defaults. The preset can be given in the file or on the command line.
+    pub fn parse_layered() -> Result<Cli, ConfigError> {
+        let matches = Cli::command().get_matches();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+        let mut file = match cli.config.as_ref() {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+
+        if let Some(name) = cli.preset.as_ref().or(file.preset.as_ref()) {
+            let preset =
+                presets::find(name).ok_or_else(|| ConfigError::UnknownPreset(name.clone()))?;
+            file = file.or(preset.settings());
+        }
+
+        cli.merge(file, &matches)?;
+
+        Ok(cli)
+    }
+
+    // Take each setting from the config file unless the option was given on
+    // the command line.
+    fn merge(&mut self, file: ConfigFile, matches: &ArgMatches) -> Result<(), ConfigError> {
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        macro_rules! layer {
+            ($setting:ident => $field:ident) => {
+                if let Some(value) = file.$setting {
+                    if !given(stringify!($field)) {
+                        self.$field = value.into();
+                    }
+                }
+            };
+            ($field:ident) => {
+                layer!($field => $field)
+            };
+        }
+
+        let boundary = file
+            .boundary
+            .as_deref()
+            .map(Boundary::parse)
+            .transpose()
+            .map_err(|reason| ConfigError::InvalidSetting {
+                name: "boundary",
+                reason,
+            })?;
+
+        let obstacles = file
+            .obstacles
+            .as_deref()
+            .map(ObstaclePolicy::parse)
+            .transpose()
+            .map_err(|reason| ConfigError::InvalidSetting {
+                name: "obstacles",
+                reason,
+            })?;
+
+        let collision = file
+            .collision
+            .as_deref()
+            .map(CollisionPolicy::parse)
+            .transpose()
+            .map_err(|reason| ConfigError::InvalidSetting {
+                name: "collision",
+                reason,
+            })?;
+
+        let turn_convention = file
+            .turn_convention
+            .as_deref()
+            .map(TurnConvention::parse)
+            .transpose()
+            .map_err(|reason| ConfigError::InvalidSetting {
+                name: "turn_convention",
+                reason,
+            })?;
+
+        let surface = file
+            .surface
+            .as_deref()
+            .map(|s| Surface::from_str(s, true))
+            .transpose()
+            .map_err(|reason| ConfigError::InvalidSetting {
+                name: "surface",
+                reason,
+            })?;
+
+        if let Some(dimensions) = file.dimensions {
+            if dimensions != 2 && dimensions != 3 {
+                return Err(ConfigError::InvalidSetting {
+                    name: "dimensions",
+                    reason: format!("must be 2 or 3: {}", dimensions),
+                });
+            }
+        }
+
+        if let Some(format) = file.save_format.as_deref() {
+            if format != "json" && format != "rle" {
+                return Err(ConfigError::InvalidSetting {
+                    name: "save_format",
+                    reason: format!("must be json or rle: {}", format),
+                });
+            }
+        }
+
+        if let Some((width, height)) = file.video_size {
+            if width < 2 || height < 2 {
+                return Err(ConfigError::InvalidSetting {
+                    name: "video_size",
+                    reason: format!("invalid frame size: {}x{}", width, height),
+                });
+            }
+        }
+
+        if let Some(boundary) = boundary {
+            if !given("boundary") {
+                self.boundary = Some(boundary);
+            }
+        }
+
+        if let Some(obstacles) = obstacles {
+            if !given("obstacles") {
+                self.obstacles = Some(obstacles);
+            }
+        }
+
+        if let Some(collision) = collision {
+            if !given("collision") {
+                self.collision = Some(collision);
+            }
+        }
+
+        if let Some(turn_convention) = turn_convention {
+            if !given("turn_convention") {
+                self.turn_convention = Some(turn_convention);
+            }
+        }
+
+        if let Some(surface) = surface {
+            if !given("surface") {
+                self.surface = surface;
+            }
+        }
+
+        layer!(rule);
+        layer!(mps);
+        layer!(grid);
+        layer!(square);
+        layer!(seed);
+        layer!(palette);
+        layer!(unbounded);
+        layer!(cell_bits);
+        layer!(seed_grid);
+        layer!(turmite);
+        layer!(engine);
+        layer!(ants => ant);
+        layer!(zones => zone);
+        layer!(dimensions);
+        layer!(save_format);
+        layer!(screenshot_every);
+        layer!(screenshot_scale);
+        layer!(record);
+        layer!(record_every);
+        layer!(record_scale);
+        layer!(video);
+        layer!(video_fps);
+        layer!(video_size);
+        layer!(stats);
+        layer!(stats_every);
+        layer!(trace);
+        layer!(trace_every);
+        layer!(symmetry_every);
+        layer!(script);
+        layer!(script_every);
+        layer!(record_moves);
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Subcommands run instead of the simulator.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run every rule of a given length headlessly across all cores, saving
+    /// a thumbnail of each outcome and a summary CSV.
+    Explore(ExploreArgs),
+
+    /// Time the simulation on dense and sparse grids, with and without
+    /// rendering, reporting moves per second so performance can be compared
+    /// across versions.
+    Bench(BenchArgs),
+
+    /// Render a saved state to an image at any scale, e.g. for posters,
+    /// without the window's size limits.
+    Render(RenderArgs),
+
+    /// Compare two move logs, e.g. of the same run recorded on different
+    /// machines, printing their hashes and the first change where they
+    /// differ. Exits with status 1 if they differ.
+    Verify(VerifyArgs),
+}
+
+//-----------------------------------------------------------------------------
+// Arguments of the explore subcommand.
+#[derive(Args, Debug)]
+pub struct ExploreArgs {
+    /// Length of the rules to run.
+    #[arg(long, default_value_t = 4)]
+    pub length: usize,
+
+    /// Turns the rules are built from, e.g. LR for only left and right.
+    #[arg(long, default_value = "LRUN")]
+    pub turns: String,
+
+    /// Number of iterations to run each rule for, e.g. 1_000_000 or 1e6.
+    #[arg(long, value_name = "N", default_value = "1_000_000", value_parser = parse_count)]
+    pub iterations: u64,
+
+    /// Grid size as a number of squares, at least 10 and limited only by memory.
+    #[arg(long, default_value_t = 500)]
+    pub grid: u32,
+
+    /// What the ant does at the edge of the grid, as for the simulator.
+    #[arg(long, value_name = "MODE", value_parser = Boundary::parse)]
+    pub boundary: Option<Boundary>,
+
+    /// Use an unbounded sparse grid so the ant never stalls.
+    #[arg(long)]
+    pub unbounded: bool,
+
+    /// Seed for the random colour generation and grid patterns, shared by
+    /// every rule.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Number of runs of each rule, with successive seeds from --seed.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub seeds: u64,
+
+    /// Initial grid pattern, as for the simulator, e.g. "random:0.3".
+    #[arg(long, value_name = "PATTERN")]
+    pub seed_grid: Option<String>,
+
+    /// Number of runs made at once, defaults to one per core.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub threads: usize,
+
+    /// Longest highway period, in moves, to watch for.
+    #[arg(long, value_name = "N", default_value_t = 1000)]
+    pub highway_period: usize,
+
+    /// Largest width or height of the thumbnails in pixels.
+    #[arg(long, value_name = "PIXELS", default_value_t = 128)]
+    pub thumbnail: u32,
+
+    /// Directory the thumbnails and summary.csv are written to, created if
+    /// it doesn't exist.
+    #[arg(long, value_name = "DIR", default_value = "explore")]
+    pub out: PathBuf,
+}
+
+//-----------------------------------------------------------------------------
+// Arguments of the bench subcommand.
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Rule to run, as for the simulator.
+    #[arg(long, default_value = "RL")]
+    pub rule: String,
+
+    /// Number of iterations in each sample, e.g. 50M or 5e7.
+    #[arg(long, value_name = "N", default_value = "10M", value_parser = parse_count)]
+    pub iterations: u64,
+
+    /// Size of the dense grid as a number of squares. The ant wraps around
+    /// its edges so it never stalls.
+    #[arg(long, default_value_t = 1000)]
+    pub grid: u32,
+
+    /// Number of timed runs of each case.
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub samples: u32,
+
+    /// Iterations between frames rendered when timing with rendering.
+    #[arg(long, value_name = "N", default_value = "1M", value_parser = parse_count)]
+    pub frame_every: u64,
+
+    /// Seed for the random colour generation.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Write the results to this CSV file, to compare later runs against.
+    #[arg(long, value_name = "FILE")]
+    pub save: Option<PathBuf>,
+
+    /// Compare the results with those saved by an earlier run with --save,
+    /// e.g. by an earlier version.
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+}
+
+//-----------------------------------------------------------------------------
+// Arguments of the render subcommand.
+#[derive(Args, Debug)]
+pub struct RenderArgs {
+    /// Saved state to render, as saved with S or loaded with --load.
+    pub state: PathBuf,
+
+    /// Size of each cell in pixels, which needn't be a whole number, e.g.
+    /// 2.5.
+    #[arg(long, value_name = "PIXELS", default_value_t = 10.0)]
+    pub px_per_cell: f64,
+
+    /// Draw the image this many times larger and scale it down smoothly,
+    /// antialiasing cell edges that fall between pixels.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub supersample: u32,
+
+    /// Cell colours, as for the simulator, in place of the saved ones.
+    #[arg(long)]
+    pub palette: Option<String>,
+
+    /// PNG file to write.
+    #[arg(long, value_name = "FILE", default_value = "render.png")]
+    pub out: PathBuf,
+}
+
+//-----------------------------------------------------------------------------
+// Arguments of the verify subcommand.
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Move log to check, as written with --record-moves.
+    pub a: PathBuf,
+
+    /// Move log to check it against.
+    pub b: PathBuf,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Parse a number of moves per second, a count as for parse_count or "max"
+// to run as fast as possible.
+pub fn parse_mps(s: &str) -> Result<u64, String> {
+    if s.trim().eq_ignore_ascii_case("max") {
+        return Ok(UNLIMITED_MPS);
+    }
+
+    parse_count(s).map(|mps| mps.min(UNLIMITED_MPS))
+}
+
+//-----------------------------------------------------------------------------
+// Parse a count allowing underscore digit separators, e.g. 10_000_000, a
+// whole number in exponent form, e.g. 1e7, or a number of thousands,
+// millions or billions, e.g. 50M or 2.5k.
+pub fn parse_count(s: &str) -> Result<u64, String> {
+    let err = || format!("invalid count: {}", s);
+    let digits = s.replace('_', "");
+
+    let (digits, scale) = COUNT_SUFFIXES
+        .iter()
+        .find_map(|&(suffix, scale)| {
+            digits
+                .strip_suffix(|c: char| c.eq_ignore_ascii_case(&suffix))
+                .map(|d| (d, scale))
+        })
+        .unwrap_or((&digits, 1));
+
+    if let Some(count) = digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|c| c.checked_mul(scale))
+    {
+        return Ok(count);
+    }
+
+    match digits.parse::<f64>().map(|c| c * scale as f64) {
+        Ok(count) if count >= 0.0 && count.fract() == 0.0 && count <= u64::MAX as f64 => {
+            Ok(count as u64)
+        }
+        _ => Err(err()),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Parse a cell given as X,Y, e.g. 1,0.
+fn parse_cell(s: &str) -> Result<(i64, i64), String> {
+    let err = || format!("invalid cell, expected X,Y: {}", s);
+    let (x, y) = s.split_once(',').ok_or_else(err)?;
+    let x = x.trim().parse().map_err(|_| err())?;
+    let y = y.trim().parse().map_err(|_| err())?;
+
+    Ok((x, y))
+}
+
+//-----------------------------------------------------------------------------
+// Parse a frame size given as WIDTHxHEIGHT, e.g. 1280x720.
+fn parse_frame_size(s: &str) -> Result<(u32, u32), String> {
+    let err = || format!("invalid frame size: {}", s);
+    let (width, height) = s.split_once(['x', 'X']).ok_or_else(err)?;
+    let width: u32 = width.trim().parse().map_err(|_| err())?;
+    let height: u32 = height.trim().parse().map_err(|_| err())?;
+
+    if width < 2 || height < 2 {
+        return Err(err());
+    }
+
+    Ok((width, height))
+}
@@ -0,0 +1,469 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::gpu::Backend;
+use super::hud::load_glyphs;
+use super::render::{create_texture, Display, GridRenderer, Renderer};
+use super::speed::{Speed, UPDATES_PER_SECOND};
+use super::tui::RendererMode;
+use image::{Rgba, RgbaImage};
+use langtons_ant::divergence;
+use langtons_ant::random;
+use langtons_ant::{config, ConfigError, Palette, Simulation};
+use log::info;
+use piston_window::*;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Most rules that can be compared at once.
+const MAX_PANELS: usize = 16;
+
+// Gap in pixels between the panels.
+const GAP: f64 = 4.0;
+
+const FONT_SIZE: u32 = 14;
+const MARGIN: f64 = 6.0;
+
+// Colours of the difference panel: cells where the runs differ, cells
+// both have coloured the same and cells neither has coloured.
+const DIFFERENT: [u8; 4] = [220, 30, 30, 255];
+const SAME: [u8; 4] = [210, 210, 210, 255];
+const UNVISITED: [u8; 4] = [255; 4];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A run being compared, with the renderer drawing its grid and the label
+// shown over it.
+struct Panel {
+    sim: Simulation,
+    renderer: GridRenderer,
+    label: String,
+}
+
+//-----------------------------------------------------------------------------
+// Shows where the grids of two runs differ, redrawn in full whenever
+// either run moves.
+struct DifferencePanel {
+    image: RgbaImage,
+    texture: G2dTexture,
+    texture_context: G2dTextureContext,
+    count: usize,
+    shown: Option<(u64, u64)>,
+}
+
+impl DifferencePanel {
+    fn new(window: &mut PistonWindow, size: u32) -> DifferencePanel {
+        let image = RgbaImage::from_pixel(size, size, Rgba(UNVISITED));
+        let mut texture_context = window.create_texture_context();
+        let texture = create_texture(&mut texture_context, &image);
+
+        DifferencePanel {
+            image,
+            texture,
+            texture_context,
+            count: 0,
+            shown: None,
+        }
+    }
+
+    fn update(&mut self, a: &Simulation, b: &Simulation) {
+        let iterations = Some((a.iterations(), b.iterations()));
+
+        if self.shown == iterations {
+            return;
+        }
+
+        for pixel in self.image.pixels_mut() {
+            *pixel = Rgba(UNVISITED);
+        }
+
+        a.cells().for_each_visited(|x, y, clr_idx| {
+            if clr_idx > 0 {
+                self.put(x, y, SAME);
+            }
+        });
+
+        b.cells().for_each_visited(|x, y, clr_idx| {
+            if clr_idx > 0 {
+                self.put(x, y, SAME);
+            }
+        });
+
+        let differences = divergence::differences(a, b);
+
+        for &(x, y) in &differences {
+            self.put(x, y, DIFFERENT);
+        }
+
+        self.count = differences.len();
+        self.shown = iterations;
+        self.texture
+            .update(&mut self.texture_context, &self.image)
+            .unwrap();
+    }
+
+    fn put(&mut self, x: i64, y: i64, colour: [u8; 4]) {
+        if x >= 0 && y >= 0 && x < self.image.width() as i64 && y < self.image.height() as i64 {
+            self.image.put_pixel(x as u32, y as u32, Rgba(colour));
+        }
+    }
+
+    fn draw(&mut self, transform: Matrix2d, g: &mut G2d, device: &mut GfxDevice) {
+        self.texture_context.encoder.flush(device);
+        piston_window::image(&self.texture, transform, g);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run several rules side by side in a window of their own, each on a grid
+// of the same size and with the same seed, tiled in rows and kept at the
+// same iteration so they can be compared as they develop. With --diverge
+// the rule is run twice instead, the second time with a cell flipped, and
+// a third panel shows where the two grids differ. Space pauses, the right
+// arrow key steps while paused, + and - change the speed and V cycles the
+// display mode of every panel.
+pub fn run(cli: &Cli) -> Result<(), ConfigError> {
+    if cli.renderer != RendererMode::Window || cli.backend != Backend::Piston {
+        return Err(ConfigError::InvalidSetting {
+            name: if cli.diverge.is_some() {
+                "diverge"
+            } else {
+                "compare"
+            },
+            reason: "runs can only be compared in the piston window".to_string(),
+        });
+    }
+
+    let mps = config::validate_mps(cli.mps)?;
+    let grid_size = config::validate_grid_size(cli.grid)?;
+    let square_size = config::validate_square_size(cli.square)?;
+    let seed = cli.seed.unwrap_or_else(random::random_seed);
+
+    let runs = match cli.diverge {
+        Some(cell) => diverging_runs(cli, grid_size, seed, cell)?,
+        None => compared_runs(cli, grid_size, seed)?,
+    };
+
+    let diverging = cli.diverge.is_some();
+    let tiles = runs.len() + diverging as usize;
+    let columns = if diverging {
+        tiles
+    } else {
+        (tiles as f64).sqrt().ceil() as usize
+    };
+    let rows = tiles.div_ceil(columns);
+
+    info!("");
+    info!(
+        "Runs = {}",
+        runs.iter()
+            .map(|(_, label)| label.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    info!("Seed = {}", seed);
+    info!("Moves per second = {}", mps);
+    info!("Grid size (number of squares) = {}", grid_size);
+
+    // The panels share the width the grid would have on its own.
+    let dim = config::window_size(grid_size, square_size);
+    let height = dim as usize * rows / columns;
+    let mut window: PistonWindow =
+        WindowSettings::new("Langton's Ant - Compare Runs", [dim, height as u32])
+            .exit_on_esc(true)
+            .resizable(true)
+            .build()
+            .unwrap();
+
+    let mut evs = window.get_event_settings();
+    evs.set_ups(UPDATES_PER_SECOND);
+    evs.set_max_fps(UPDATES_PER_SECOND);
+    window.set_event_settings(evs);
+
+    let mut display = Display {
+        mode: cli.display,
+        trail_length: cli.trail_length,
+    };
+
+    let mut panels: Vec<Panel> = runs
+        .into_iter()
+        .map(|(mut sim, label)| {
+            sim.set_track_dirty(true);
+            let renderer = GridRenderer::new(&mut window, &mut sim, display);
+            Panel {
+                sim,
+                renderer,
+                label,
+            }
+        })
+        .collect();
+
+    let mut difference = Some(DifferencePanel::new(&mut window, grid_size)).filter(|_| diverging);
+
+    let mut glyphs = load_glyphs(&mut window);
+    let mut speed = Speed::new(mps);
+    let mut paused = false;
+
+    // The iteration every panel is brought up to. Panels whose ants stall
+    // are left behind.
+    let mut iteration = 0;
+
+    while let Some(e) = window.next() {
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                Key::Space => paused = !paused,
+                Key::Right if paused => iteration += 1,
+                Key::Equals | Key::Plus | Key::NumPadPlus => speed.faster(),
+                Key::Minus | Key::NumPadMinus => speed.slower(),
+                Key::V => {
+                    display.mode = display.mode.next();
+
+                    for panel in panels.iter_mut() {
+                        panel.renderer.set_display(display, &mut panel.sim);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The panels are kept together by bringing each up to the iteration
+        // in turn, a batch at a time.
+        if let Some(args) = e.update_args().filter(|_| !paused) {
+            let moves = speed.moves(args.dt);
+
+            speed.advance(moves, |batch| {
+                iteration += batch;
+                catch_up(&mut panels, iteration);
+                false
+            });
+        }
+
+        catch_up(&mut panels, iteration);
+
+        if let Some(args) = e.render_args() {
+            let (_, scale) = panel_layout(args.window_size, columns, rows, grid_size);
+
+            for panel in panels.iter_mut() {
+                panel.renderer.set_zoom(scale);
+                panel.renderer.update(&mut panel.sim);
+            }
+
+            if let (Some(difference), [a, b]) = (difference.as_mut(), &panels[..]) {
+                difference.update(&a.sim, &b.sim);
+            }
+        }
+
+        window.draw_2d(&e, |c, g, device| {
+            let ([panel_width, panel_height], scale) =
+                panel_layout(c.get_view_size(), columns, rows, grid_size);
+            let origin = |i: usize| {
+                let x = (i % columns) as f64 * (panel_width + GAP);
+                let y = (i / columns) as f64 * (panel_height + GAP);
+                c.transform.trans(x, y)
+            };
+
+            clear([0.5, 0.5, 0.5, 1.0], g);
+
+            for (i, panel) in panels.iter_mut().enumerate() {
+                panel
+                    .renderer
+                    .draw_grid(origin(i).scale(scale, scale), g, device);
+
+                let mut label = format!("{}  N {}", panel.label, panel.sim.iterations());
+
+                if panel.sim.is_stalled() {
+                    label.push_str("  STALLED");
+                } else if paused {
+                    label.push_str("  PAUSED");
+                }
+
+                draw_label(&label, &mut glyphs, origin(i), g);
+            }
+
+            if let Some(difference) = difference.as_mut() {
+                let origin = origin(panels.len());
+                difference.draw(origin.scale(scale, scale), g, device);
+
+                let label = format!("Differ {} cells", difference.count);
+                draw_label(&label, &mut glyphs, origin, g);
+            }
+
+            // Glyphs are uploaded to the cache texture lazily, so they must
+            // be flushed before the frame is presented.
+            glyphs.factory.encoder.flush(device);
+        });
+    }
+
+    for panel in &panels {
+        info!(
+            "{} stopped after {} iterations",
+            panel.label,
+            panel.sim.iterations()
+        );
+    }
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// The size of each panel in a view of the given size, and the scale the
+// grids are drawn at in window pixels per cell.
+fn panel_layout(
+    view_size: [f64; 2],
+    columns: usize,
+    rows: usize,
+    grid_size: u32,
+) -> ([f64; 2], f64) {
+    let panel_width = (view_size[0] - GAP * (columns - 1) as f64) / columns as f64;
+    let panel_height = (view_size[1] - GAP * (rows - 1) as f64) / rows as f64;
+    let scale = panel_width.min(panel_height) / f64::from(grid_size);
+
+    ([panel_width, panel_height], scale)
+}
+
+//-----------------------------------------------------------------------------
+// The runs of each rule given with --compare, labelled with their rules.
+fn compared_runs(
+    cli: &Cli,
+    grid_size: u32,
+    seed: u64,
+) -> Result<Vec<(Simulation, String)>, ConfigError> {
+    let rules: Vec<&str> = cli
+        .compare
+        .as_deref()
+        .unwrap_or_default()
+        .split(';')
+        .map(|r| r.trim())
+        .filter(|r| !r.is_empty())
+        .collect();
+
+    if rules.is_empty() || rules.len() > MAX_PANELS {
+        return Err(ConfigError::InvalidSetting {
+            name: "compare",
+            reason: format!("between 1 and {} rules must be given", MAX_PANELS),
+        });
+    }
+
+    rules
+        .into_iter()
+        .map(|rule| {
+            let sim = create_run(cli, rule, grid_size, seed)?;
+            let label = sim.rule();
+            Ok((sim, label))
+        })
+        .collect()
+}
+
+//-----------------------------------------------------------------------------
+// Two runs of the rule, the second with the given cell flipped.
+fn diverging_runs(
+    cli: &Cli,
+    grid_size: u32,
+    seed: u64,
+    (x, y): (i64, i64),
+) -> Result<Vec<(Simulation, String)>, ConfigError> {
+    let sim = create_run(cli, &cli.rule, grid_size, seed)?;
+    let mut flipped = sim.clone();
+
+    if !divergence::flip(&mut flipped, x, y) {
+        return Err(ConfigError::InvalidSetting {
+            name: "diverge",
+            reason: format!("cell {},{} is outside the grid", x, y),
+        });
+    }
+
+    let label = sim.rule();
+    let flipped_label = format!("{} flipped {},{}", label, x, y);
+
+    Ok(vec![(sim, label), (flipped, flipped_label)])
+}
+
+//-----------------------------------------------------------------------------
+// Create a run of a rule with the boundary mode and palette given on the
+// command line.
+fn create_run(cli: &Cli, rule: &str, grid_size: u32, seed: u64) -> Result<Simulation, ConfigError> {
+    let mut sim = Simulation::with_seed(&config::validate_rule(rule)?, grid_size as usize, seed);
+
+    if let Some(boundary) = cli.boundary {
+        sim.set_boundary(boundary);
+    }
+
+    if let Some(palette) = cli.palette.as_ref() {
+        let colours = Palette::parse(palette)
+            .and_then(|p| p.colours(sim.num_colours()))
+            .map_err(|reason| ConfigError::InvalidPalette {
+                palette: palette.to_string(),
+                reason,
+            })?;
+
+        sim.set_colours(colours);
+    }
+
+    Ok(sim)
+}
+
+//-----------------------------------------------------------------------------
+// Bring every panel up to the given iteration. Panels whose ants stall are
+// left behind.
+fn catch_up(panels: &mut [Panel], iteration: u64) {
+    for panel in panels.iter_mut() {
+        let behind = iteration.saturating_sub(panel.sim.iterations());
+        panel.sim.advance(behind);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Draw a label in the top left corner of a panel.
+fn draw_label(label: &str, glyphs: &mut Glyphs, origin: Matrix2d, g: &mut G2d) {
+    let width = label.chars().count() as f64 * f64::from(FONT_SIZE) * 0.6;
+
+    rectangle(
+        [0.0, 0.0, 0.0, 0.6],
+        [
+            0.0,
+            0.0,
+            width + 2.0 * MARGIN,
+            f64::from(FONT_SIZE) + 2.0 * MARGIN,
+        ],
+        origin,
+        g,
+    );
+
+    text(
+        [1.0, 1.0, 1.0, 1.0],
+        FONT_SIZE,
+        label,
+        glyphs,
+        origin.trans(MARGIN, MARGIN + f64::from(FONT_SIZE)),
+        g,
+    )
+    .unwrap();
+}
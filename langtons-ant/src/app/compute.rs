@@ -0,0 +1,616 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::render::BACKGROUND;
+use langtons_ant::ant::FACINGS;
+use langtons_ant::colour::OBSTACLE_GREY;
+use langtons_ant::{
+    Ant, Boundary, CollisionPolicy, Delta, Extent, GridBackend, GridStorage, ObstaclePolicy,
+    Simulation, BLANK, OBSTACLE,
+};
+use std::borrow::Cow;
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Steps each ant with one invocation of the step shader, making params.steps
+// moves. Cells hold 0 for blank, 1 for an obstacle or the colour index plus
+// 2, and are changed atomically as ants sharing a cell may be moving at the
+// same time. Each colour's entry in the rule is the colour it changes to and
+// the facing an ant turns to from each facing, N, E, S and W.
+const STEP_SHADER: &str = r#"
+struct Params {
+    width: u32,
+    height: u32,
+    steps: u32,
+    num_ants: u32,
+    boundary: u32,
+    obstacles: u32,
+};
+
+struct Ant {
+    x: u32,
+    y: u32,
+    facing: u32,
+    stalled: u32,
+    iterations_low: u32,
+    iterations_high: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> cells: array<atomic<u32>>;
+@group(0) @binding(2) var<storage, read_write> ants: array<Ant>;
+@group(0) @binding(3) var<storage, read> rule: array<u32>;
+
+var<private> DX: array<i32, 4> = array<i32, 4>(0, 1, 0, -1);
+var<private> DY: array<i32, 4> = array<i32, 4>(-1, 0, 1, 0);
+
+fn inside(x: i32, y: i32) -> bool {
+    return x >= 0 && y >= 0 && x < i32(params.width) && y < i32(params.height);
+}
+
+fn count(ant: ptr<function, Ant>) {
+    (*ant).iterations_low += 1u;
+
+    if ((*ant).iterations_low == 0u) {
+        (*ant).iterations_high += 1u;
+    }
+}
+
+@compute @workgroup_size(64)
+fn step(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.num_ants) {
+        return;
+    }
+
+    var ant = ants[id.x];
+
+    for (var i = 0u; i < params.steps && ant.stalled == 0u; i += 1u) {
+        let cell = ant.y * params.width + ant.x;
+        var old = atomicLoad(&cells[cell]);
+        var colour = 0u;
+
+        loop {
+            colour = select(old - 2u, 0u, old < 2u);
+            let result = atomicCompareExchangeWeak(&cells[cell], old, rule[colour * 5u] + 2u);
+
+            if (result.exchanged) {
+                break;
+            }
+
+            old = result.old_value;
+        }
+
+        ant.facing = rule[colour * 5u + 1u + ant.facing];
+
+        var x = i32(ant.x) + DX[ant.facing];
+        var y = i32(ant.y) + DY[ant.facing];
+
+        if (!inside(x, y)) {
+            if (params.boundary == 0u) {
+                ant.stalled = 1u;
+                count(&ant);
+                break;
+            } else if (params.boundary == 1u) {
+                ant.facing = (ant.facing + 2u) & 3u;
+                x = i32(ant.x) + DX[ant.facing];
+                y = i32(ant.y) + DY[ant.facing];
+
+                if (!inside(x, y)) {
+                    x = i32(ant.x);
+                    y = i32(ant.y);
+                }
+            } else {
+                x = (x + i32(params.width)) % i32(params.width);
+                y = (y + i32(params.height)) % i32(params.height);
+            }
+        }
+
+        if (atomicLoad(&cells[u32(y) * params.width + u32(x)]) == 1u) {
+            switch params.obstacles {
+                case 0u: { ant.facing = (ant.facing + 2u) & 3u; }
+                case 1u: { ant.facing = (ant.facing + 3u) & 3u; }
+                case 2u: { ant.facing = (ant.facing + 1u) & 3u; }
+                default: { ant.stalled = 1u; }
+            }
+        } else {
+            ant.x = u32(x);
+            ant.y = u32(y);
+        }
+
+        count(&ant);
+    }
+
+    ants[id.x] = ant;
+}
+"#;
+
+// Draws the cells straight from the buffer the step shader works on, looking
+// up each cell's colour in the palette: the background, the obstacle colour
+// then the rule's colours.
+const DRAW_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct Params {
+    width: u32,
+    height: u32,
+    steps: u32,
+    num_ants: u32,
+    boundary: u32,
+    obstacles: u32,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> cells: array<u32>;
+@group(0) @binding(2) var<storage, read> palette: array<vec4<f32>>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let x = min(u32(in.uv.x * f32(params.width)), params.width - 1u);
+    let y = min(u32(in.uv.y * f32(params.height)), params.height - 1u);
+    return palette[cells[y * params.width + x]];
+}
+"#;
+
+// Ants are stepped by workgroups of this many invocations, one per ant.
+const WORKGROUP_SIZE: u32 = 64;
+
+// The most moves each ant makes in a single dispatch, so no dispatch runs
+// long enough for the graphics driver to give up on it.
+const MAX_DISPATCH_STEPS: u64 = 1 << 16;
+
+// The most moves made between frames, so the GPU isn't queued more work
+// than it can do while a frame is shown.
+pub const MAX_FRAME_STEPS: u64 = 1 << 22;
+
+// Sizes in bytes of the parameters and of each ant, as laid out in the
+// shaders.
+const PARAMS_SIZE: u64 = 6 * 4;
+const ANT_SIZE: u64 = 6 * 4;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Steps the simulation on the GPU with a compute shader, keeping the grid in
+// a buffer there that the window is drawn from directly, so nothing is
+// uploaded each frame. Only plain rules shared by all the ants on bounded
+// grids are supported, and the ants move at the same time rather than in
+// turn, so ants sharing a cell take their turns in whatever order the GPU
+// runs them and runs of several ants may differ from the CPU's. The cells
+// and ants are read back into the simulation when stepping is finished.
+pub struct GpuCompute {
+    extent: Extent,
+    num_ants: u32,
+    boundary: u32,
+    obstacles: u32,
+    cells: wgpu::Buffer,
+    ants: wgpu::Buffer,
+    params: wgpu::Buffer,
+    step_pipeline: wgpu::ComputePipeline,
+    step_group: wgpu::BindGroup,
+    draw_layout: wgpu::BindGroupLayout,
+    draw_group: wgpu::BindGroup,
+}
+
+impl GpuCompute {
+    // Upload the simulation's grid, ants and rule, and build the pipelines
+    // stepping and colouring them. Fails if the simulation or the adapter
+    // can't be stepped on the GPU.
+    pub fn new(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        sim: &Simulation,
+        srgb: bool,
+    ) -> Result<GpuCompute, String> {
+        let flags = adapter.get_downlevel_capabilities().flags;
+
+        if !flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            || !flags.contains(wgpu::DownlevelFlags::FRAGMENT_STORAGE)
+        {
+            return Err("the graphics adapter doesn't support compute shaders".to_string());
+        }
+
+        let rule = sim
+            .shared_rule()
+            .ok_or("only plain rules shared by every ant can be stepped on the GPU")?;
+
+        if let GridBackend::Sparse(_) = sim.cells() {
+            return Err("unbounded grids can't be stepped on the GPU".to_string());
+        }
+
+        if sim.collision() != CollisionPolicy::Ignore {
+            return Err("colliding ants can't be stepped on the GPU".to_string());
+        }
+
+        let extent = sim.cells().extent();
+        let cell_bytes = (extent.width * extent.height * 4) as u64;
+
+        if cell_bytes > device.limits().max_storage_buffer_binding_size {
+            return Err("the grid is too big to be stepped on the GPU".to_string());
+        }
+
+        let mut cells = Vec::with_capacity(extent.width * extent.height);
+
+        for y in extent.y..extent.y + extent.height as i64 {
+            for x in extent.x..extent.x + extent.width as i64 {
+                cells.push(match sim.cells().get(x, y) {
+                    BLANK => 0,
+                    OBSTACLE => 1,
+                    clr_idx => clr_idx as u32 + 2,
+                });
+            }
+        }
+
+        let mut table = Vec::with_capacity(rule.len() * 5);
+
+        for clr_idx in 0..rule.len() {
+            table.push(rule.step(clr_idx, FACINGS[0]).0 as u32);
+            table.extend(FACINGS.iter().map(|&f| rule.step(clr_idx, f).1 as u32));
+        }
+
+        let ants: Vec<u32> = sim
+            .ants()
+            .iter()
+            .flat_map(|a| {
+                [
+                    (a.pos_x - extent.x) as u32,
+                    (a.pos_y - extent.y) as u32,
+                    a.facing as u32,
+                    u32::from(a.stalled),
+                    a.iterations as u32,
+                    (a.iterations >> 32) as u32,
+                ]
+            })
+            .collect();
+
+        // The background and obstacle colours come first, for cells 0 and 1.
+        let mut palette: Vec<[u8; 4]> = vec![BACKGROUND, OBSTACLE_GREY.to_rgba8()];
+        palette.extend(sim.colours().iter().map(|c| c.to_rgba8()));
+
+        let palette: Vec<f32> = palette
+            .iter()
+            .flatten()
+            .map(|&c| channel(c, srgb))
+            .collect();
+
+        let storage = |label: &str, contents: &[u8], usage: wgpu::BufferUsages| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+            })
+        };
+
+        let cells = storage("cells", &bytes(&cells), wgpu::BufferUsages::COPY_SRC);
+        let ant_buffer = storage("ants", &bytes(&ants), wgpu::BufferUsages::COPY_SRC);
+        let table = storage("rule", &bytes(&table), wgpu::BufferUsages::empty());
+        let palette = storage("palette", &bytes(&palette), wgpu::BufferUsages::empty());
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("params"),
+            size: PARAMS_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let step_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("step"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(STEP_SHADER)),
+        });
+
+        let step_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("step"),
+            layout: None,
+            module: &step_shader,
+            entry_point: Some("step"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let step_group = bind_group(
+            device,
+            &step_pipeline.get_bind_group_layout(0),
+            &[&params, &cells, &ant_buffer, &table],
+        );
+
+        let draw_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("cells"),
+            entries: &[
+                layout_entry(0, wgpu::BufferBindingType::Uniform),
+                layout_entry(1, wgpu::BufferBindingType::Storage { read_only: true }),
+                layout_entry(2, wgpu::BufferBindingType::Storage { read_only: true }),
+            ],
+        });
+
+        let draw_group = bind_group(device, &draw_layout, &[&params, &cells, &palette]);
+
+        Ok(GpuCompute {
+            extent,
+            num_ants: sim.ants().len() as u32,
+            boundary: match sim.boundary() {
+                Boundary::Stall => 0,
+                Boundary::Bounce => 1,
+                Boundary::Wrap => 2,
+            },
+            obstacles: match sim.obstacles() {
+                ObstaclePolicy::Reverse => 0,
+                ObstaclePolicy::Left => 1,
+                ObstaclePolicy::Right => 2,
+                ObstaclePolicy::Stall => 3,
+            },
+            cells,
+            ants: ant_buffer,
+            params,
+            step_pipeline,
+            step_group,
+            draw_layout,
+            draw_group,
+        })
+    }
+
+    // The shader colouring the cells, drawn with draw_layout and
+    // draw_group.
+    pub fn draw_shader(device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cells"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(DRAW_SHADER)),
+        })
+    }
+
+    pub fn draw_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.draw_layout
+    }
+
+    pub fn draw_group(&self) -> &wgpu::BindGroup {
+        &self.draw_group
+    }
+
+    // Make up to MAX_FRAME_STEPS moves of every ant, queued in dispatches of
+    // at most MAX_DISPATCH_STEPS moves.
+    pub fn step(&self, device: &wgpu::Device, queue: &wgpu::Queue, moves: u64) {
+        let mut remaining = moves.min(MAX_FRAME_STEPS);
+
+        while remaining > 0 {
+            let steps = remaining.min(MAX_DISPATCH_STEPS);
+            remaining -= steps;
+
+            self.write_params(queue, steps as u32);
+
+            let mut encoder = device.create_command_encoder(&Default::default());
+
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("step"),
+                    timestamp_writes: None,
+                });
+
+                pass.set_pipeline(&self.step_pipeline);
+                pass.set_bind_group(0, &self.step_group, &[]);
+                pass.dispatch_workgroups(self.num_ants.div_ceil(WORKGROUP_SIZE), 1, 1);
+            }
+
+            queue.submit([encoder.finish()]);
+        }
+    }
+
+    // Write the parameters shared by the shaders, with the moves each ant
+    // makes per dispatch.
+    fn write_params(&self, queue: &wgpu::Queue, steps: u32) {
+        let params = [
+            self.extent.width as u32,
+            self.extent.height as u32,
+            steps,
+            self.num_ants,
+            self.boundary,
+            self.obstacles,
+        ];
+
+        queue.write_buffer(&self.params, 0, &bytes(&params));
+    }
+
+    // Copy the cells and ants back into the simulation, waiting for the GPU
+    // to finish the moves queued.
+    pub fn read_back(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sim: &mut Simulation,
+    ) -> Result<(), String> {
+        let cells = read_buffer(device, queue, &self.cells)?;
+        let ants = read_buffer(device, queue, &self.ants)?;
+        let e = self.extent;
+
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(i, &code)| {
+                let x = e.x + (i % e.width) as i64;
+                let y = e.y + (i / e.width) as i64;
+
+                let clr_idx = match code {
+                    0 => BLANK,
+                    1 => OBSTACLE,
+                    _ => code as usize - 2,
+                };
+
+                (x, y, clr_idx)
+            })
+            .filter(|&(x, y, clr_idx)| sim.cells().get(x, y) != clr_idx)
+            .collect();
+
+        let ants = sim
+            .ants()
+            .iter()
+            .zip(ants.chunks(ANT_SIZE as usize / 4))
+            .map(|(ant, fields)| Ant {
+                pos_x: e.x + i64::from(fields[0]),
+                pos_y: e.y + i64::from(fields[1]),
+                facing: FACINGS[fields[2] as usize & 3],
+                stalled: fields[3] != 0,
+                iterations: u64::from(fields[4]) | u64::from(fields[5]) << 32,
+                ..ant.clone()
+            })
+            .collect();
+
+        sim.apply_delta(Delta { cells, ants });
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The bytes of a slice of 32 bit values, in the GPU's byte order.
+fn bytes<T: Copy + Into<Word>>(values: &[T]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| v.into().0.to_ne_bytes())
+        .collect()
+}
+
+//-----------------------------------------------------------------------------
+// A 32 bit value as uploaded to the GPU, either a u32 or the bits of an f32.
+struct Word(u32);
+
+impl From<u32> for Word {
+    fn from(v: u32) -> Word {
+        Word(v)
+    }
+}
+
+impl From<f32> for Word {
+    fn from(v: f32) -> Word {
+        Word(v.to_bits())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A colour channel as the fragment shader outputs it, converted from sRGB
+// to linear if the window's surface converts it back.
+fn channel(c: u8, srgb: bool) -> f32 {
+    let c = f32::from(c) / 255.0;
+
+    if !srgb {
+        c
+    } else if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A bind group layout entry for a buffer used by the draw shader.
+fn layout_entry(binding: u32, ty: wgpu::BufferBindingType) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A bind group of whole buffers, bound in order from 0.
+fn bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffers: &[&wgpu::Buffer],
+) -> wgpu::BindGroup {
+    let entries: Vec<wgpu::BindGroupEntry> = buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| wgpu::BindGroupEntry {
+            binding: i as u32,
+            resource: buffer.as_entire_binding(),
+        })
+        .collect();
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout,
+        entries: &entries,
+    })
+}
+
+//-----------------------------------------------------------------------------
+// Copy a buffer back from the GPU as 32 bit values, waiting for it.
+fn read_buffer(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    buffer: &wgpu::Buffer,
+) -> Result<Vec<u32>, String> {
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("read back"),
+        size: buffer.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, buffer.size());
+    queue.submit([encoder.finish()]);
+
+    let (sender, receiver) = mpsc::channel();
+    staging.map_async(wgpu::MapMode::Read, .., move |result| {
+        let _ = sender.send(result);
+    });
+
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .map_err(|e| e.to_string())?;
+
+    receiver
+        .recv()
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let values = staging
+        .get_mapped_range(..)
+        .map_err(|e| e.to_string())?
+        .chunks_exact(4)
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+
+    staging.unmap();
+    Ok(values)
+}
@@ -0,0 +1,83 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use clap::ValueEnum;
+use langtons_ant::cycle::CycleDetector;
+use langtons_ant::Simulation;
+use log::{debug, info};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// What to do once the state of the run is found to repeat.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum CycleAction {
+    /// Just report the cycle.
+    Report,
+    /// Pause the window, or end a headless run.
+    Stop,
+}
+
+//-----------------------------------------------------------------------------
+// Watches a run for its state repeating, as requested on the command line,
+// reporting the cycle once found.
+pub struct CycleWatch {
+    detector: CycleDetector,
+    action: CycleAction,
+}
+
+impl CycleWatch {
+    pub fn from_cli(cli: &Cli) -> Option<CycleWatch> {
+        cli.cycle.map(|action| CycleWatch {
+            detector: CycleDetector::new(),
+            action,
+        })
+    }
+
+    // Is the run still being watched, i.e. no cycle found yet? Watching
+    // needs every move to be seen, so the fast path can't be used.
+    pub fn is_watching(&self) -> bool {
+        self.detector.found().is_none()
+    }
+
+    // Observe a move, returning the action to take if the state has
+    // repeated. The starting state must be observed before the first move.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<CycleAction> {
+        let cycle = self.detector.observe(sim)?;
+
+        debug!(
+            "cycle period={} start={} iteration={} action={:?}",
+            cycle.period,
+            cycle.start,
+            sim.iterations(),
+            self.action
+        );
+        info!(
+            "State repeats with period {} after transient {}",
+            cycle.period, cycle.start
+        );
+
+        Some(self.action)
+    }
+}
@@ -0,0 +1,336 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::ExploreArgs;
+use indicatif::{ProgressBar, ProgressStyle};
+use langtons_ant::highway::{Highway, HighwayDetector};
+use langtons_ant::random;
+use langtons_ant::seeding::GridSeed;
+use langtons_ant::stats::Stats;
+use langtons_ant::{batch, export, ConfigError, Direction, GridStorage, Simulation};
+use log::info;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Most runs explored at once, so a typo in the length doesn't start a
+// survey that will never finish.
+const MAX_RUNS: u64 = 1 << 20;
+
+// Number of the most common highway periods listed in the totals.
+const MAX_PERIODS_SHOWN: usize = 5;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The outcome of running a single rule with a single seed.
+struct Outcome {
+    rule: String,
+    seed: u64,
+    iterations: u64,
+    stats: Stats,
+    stalled: bool,
+    highway: Option<Highway>,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run every rule of the requested length headlessly, once for each seed,
+// spread across the available cores. A thumbnail of each final grid is
+// saved to the output directory along with a summary.csv giving the bounding
+// box of each pattern and whether its ant stalled or built a highway.
+pub fn run(args: &ExploreArgs) -> Result<(), ConfigError> {
+    let turns = validate_turns(&args.turns)?;
+    let count = (turns.len() as u64)
+        .checked_pow(args.length as u32)
+        .and_then(|n| n.checked_mul(args.seeds.max(1)))
+        .filter(|&n| args.length > 0 && n <= MAX_RUNS);
+
+    let count = count.ok_or_else(|| ConfigError::InvalidSetting {
+        name: "length",
+        reason: format!(
+            "too many rules of length {}, at most {} runs can be explored",
+            args.length, MAX_RUNS
+        ),
+    })?;
+
+    let grid_seed = args
+        .seed_grid
+        .as_ref()
+        .map(|pattern| {
+            GridSeed::parse(pattern).map_err(|reason| ConfigError::InvalidGridSeed {
+                pattern: pattern.clone(),
+                reason,
+            })
+        })
+        .transpose()?;
+
+    let seed = args.seed.unwrap_or_else(random::random_seed);
+
+    info!(
+        "Exploring {} runs of rules of length {} for {} iterations each",
+        count, args.length, args.iterations
+    );
+    info!("Seed = {}", seed);
+
+    explore(args, &turns, count, seed, grid_seed.as_ref()).map_err(|e| ConfigError::Write {
+        name: "results",
+        path: args.out.clone(),
+        reason: e.to_string(),
+    })
+}
+
+//-----------------------------------------------------------------------------
+// Check the turns the rules are built from, each of which must be a valid
+// turn given only once.
+fn validate_turns(turns: &str) -> Result<Vec<char>, ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidSetting {
+        name: "turns",
+        reason,
+    };
+    let mut valid: Vec<char> = Vec::new();
+
+    for c in turns.chars().map(|c| c.to_ascii_uppercase()) {
+        if Direction::from_char(c).is_none() || valid.contains(&c) {
+            return Err(invalid(format!("invalid turns: {}", turns)));
+        }
+
+        valid.push(c);
+    }
+
+    if valid.is_empty() {
+        return Err(invalid(String::from("no turns given")));
+    }
+
+    Ok(valid)
+}
+
+//-----------------------------------------------------------------------------
+// Run each rule and seed as a batch job, each writing its own thumbnail,
+// then write the summary in rule order and print the totals.
+fn explore(
+    args: &ExploreArgs,
+    turns: &[char],
+    count: u64,
+    seed: u64,
+    grid_seed: Option<&GridSeed>,
+) -> io::Result<()> {
+    fs::create_dir_all(&args.out)?;
+
+    let seeds = args.seeds.max(1);
+    let start = Instant::now();
+    let progress = ProgressBar::new(count);
+
+    if let Ok(style) =
+        ProgressStyle::with_template("{bar:40} {pos}/{len} runs [{elapsed_precise}, {eta} left]")
+    {
+        progress.set_style(style);
+    }
+
+    let jobs: Vec<u64> = (0..count).collect();
+    let outcomes = batch::run(
+        jobs,
+        args.threads,
+        |job| {
+            let rule = nth_rule(turns, args.length, job / seeds);
+            let seed = seed.wrapping_add(job % seeds);
+            let name = match seeds {
+                1 => rule.clone(),
+                _ => format!("{}-{}", rule, seed),
+            };
+
+            run_rule(args, rule, seed, grid_seed).and_then(|(sim, outcome)| {
+                let path = args.out.join(format!("{}.png", name));
+                save_thumbnail(&sim, &outcome.stats, &path, args.thumbnail)?;
+                Ok(outcome)
+            })
+        },
+        || progress.inc(1),
+    )?;
+
+    progress.finish_and_clear();
+
+    let outcomes = outcomes.into_iter().collect::<io::Result<Vec<_>>>()?;
+    write_summary(&args.out.join("summary.csv"), &outcomes)?;
+    print_totals(&outcomes);
+
+    info!(
+        "Explored {} runs in {:.3} s, results written to {}",
+        count,
+        start.elapsed().as_secs_f64(),
+        args.out.display()
+    );
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// Write a row of the summary for each run.
+fn write_summary(path: &Path, outcomes: &[Outcome]) -> io::Result<()> {
+    let mut csv = BufWriter::new(File::create(path)?);
+    writeln!(
+        csv,
+        "rule,seed,iterations,stalled,highway_period,highway_start,min_x,min_y,width,height,visited,distance"
+    )?;
+
+    for outcome in outcomes {
+        let (min_x, min_y, width, height) = match outcome.stats.bounds {
+            Some(e) => (e.x.to_string(), e.y.to_string(), e.width, e.height),
+            None => (String::new(), String::new(), 0, 0),
+        };
+        let (period, from) = match outcome.highway {
+            Some(h) => (h.period.to_string(), h.start.to_string()),
+            None => (String::new(), String::new()),
+        };
+
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},{:.3}",
+            outcome.rule,
+            outcome.seed,
+            outcome.iterations,
+            outcome.stalled,
+            period,
+            from,
+            min_x,
+            min_y,
+            width,
+            height,
+            outcome.stats.visited(),
+            outcome.stats.distance()
+        )?;
+    }
+
+    csv.flush()
+}
+
+//-----------------------------------------------------------------------------
+// Print how many runs built a highway, otherwise stalled, or did neither,
+// and the most common highway periods.
+fn print_totals(outcomes: &[Outcome]) {
+    let mut periods: BTreeMap<u64, usize> = BTreeMap::new();
+    let mut stalled = 0;
+
+    for outcome in outcomes {
+        match outcome.highway {
+            Some(highway) => *periods.entry(highway.period).or_default() += 1,
+            None if outcome.stalled => stalled += 1,
+            None => {}
+        }
+    }
+
+    let highways: usize = periods.values().sum();
+
+    println!("Highways = {}", highways);
+    println!("Stalled = {}", stalled);
+    println!("Neither = {}", outcomes.len() - highways - stalled);
+
+    let mut common: Vec<_> = periods.into_iter().collect();
+    common.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    for (period, runs) in common.iter().take(MAX_PERIODS_SHOWN) {
+        println!("  Period {} highways = {}", period, runs);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The rule with the given index among all rules of the given length, taking
+// the index as a number written in base turns.len().
+fn nth_rule(turns: &[char], length: usize, mut index: u64) -> String {
+    let base = turns.len() as u64;
+    let mut rule = vec![turns[0]; length];
+
+    for c in rule.iter_mut().rev() {
+        *c = turns[(index % base) as usize];
+        index /= base;
+    }
+
+    rule.into_iter().collect()
+}
+
+//-----------------------------------------------------------------------------
+// Run a rule with a seed for the requested number of iterations or until it
+// stalls, moving singly while watching for a highway and on the fast path
+// once one is found.
+fn run_rule(
+    args: &ExploreArgs,
+    rule: String,
+    seed: u64,
+    grid_seed: Option<&GridSeed>,
+) -> io::Result<(Simulation, Outcome)> {
+    let mut sim = Simulation::with_seed(&rule, args.grid as usize, seed);
+
+    if let Some(boundary) = args.boundary {
+        sim.set_boundary(boundary);
+    }
+
+    if args.unbounded {
+        sim.make_unbounded();
+    }
+
+    if let Some(pattern) = grid_seed {
+        pattern.apply(&mut sim).map_err(io::Error::other)?;
+    }
+
+    let mut detector = HighwayDetector::new(args.highway_period);
+
+    while sim.iterations() < args.iterations && !sim.is_stalled() {
+        if detector.found().is_some() {
+            sim.advance(args.iterations - sim.iterations());
+            break;
+        }
+
+        sim.step();
+        detector.observe(&sim);
+    }
+
+    let outcome = Outcome {
+        rule,
+        seed,
+        iterations: sim.iterations(),
+        stats: Stats::measure(&sim),
+        stalled: sim.is_stalled(),
+        highway: detector.found(),
+    };
+
+    Ok((sim, outcome))
+}
+
+//-----------------------------------------------------------------------------
+// Save a thumbnail of the visited part of the grid.
+fn save_thumbnail(sim: &Simulation, stats: &Stats, path: &Path, size: u32) -> io::Result<()> {
+    let extent = stats.bounds.unwrap_or_else(|| sim.cells().extent());
+
+    export::render_thumbnail(sim, extent, size)
+        .save(path)
+        .map_err(io::Error::other)
+}
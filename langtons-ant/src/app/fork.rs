@@ -0,0 +1,104 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::Simulation;
+use log::{error, info};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Forks the simulation into new windows, each a separate process loading a
+// copy of the current state, so different futures of the same moment can
+// be watched side by side. The copies are saved to the temporary directory
+// and deleted by the new window once it has loaded them.
+#[derive(Default)]
+pub struct Forks {
+    made: usize,
+}
+
+impl Forks {
+    // Open a new window carrying on from the simulation's current state,
+    // with a new, already validated, rule if given, and at the given number
+    // of moves per second.
+    pub fn fork(&mut self, sim: &Simulation, rule: Option<&str>, mps: u64) {
+        let mut forked = sim.clone();
+
+        if let Some(rule) = rule.filter(|&r| r != sim.rule()) {
+            if let Err(e) = forked.set_rule(rule) {
+                error!("Failed to fork with rule {}: {}", rule, e);
+                return;
+            }
+        }
+
+        self.made += 1;
+        let path = env::temp_dir().join(format!(
+            "langtons-ant-fork-{}-{}-{}.json",
+            std::process::id(),
+            sim.iterations(),
+            self.made
+        ));
+
+        if let Err(e) = forked.save(&path) {
+            error!("Failed to save the fork to {}: {}", path.display(), e);
+            return;
+        }
+
+        match spawn(path.clone(), mps) {
+            Ok(()) => info!(
+                "Forked iteration {} with rule {} into a new window",
+                sim.iterations(),
+                forked.rule()
+            ),
+            Err(e) => {
+                error!("Failed to open a window for the fork: {}", e);
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Start another copy of the program loading the saved state, which it
+// deletes once loaded. It isn't waited for, so it runs on independently,
+// even after this window closes.
+fn spawn(state: PathBuf, mps: u64) -> Result<(), String> {
+    let exe = env::current_exe().map_err(|e| e.to_string())?;
+
+    Command::new(exe)
+        .arg("--load")
+        .arg(state)
+        .arg("--load-once")
+        .arg("--mps")
+        .arg(mps.to_string())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,760 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::camera::Camera;
+use super::compute::GpuCompute;
+use super::recording::Recordings;
+use super::render::{CanvasUpdate, Display, GridCanvas, Renderer};
+use super::speed::Speed;
+use super::stepper::{Stepper, SCRUB_FRACTION};
+use clap::ValueEnum;
+use image::RgbaImage;
+use langtons_ant::config;
+use langtons_ant::{Config, GridStorage, Simulation};
+use log::{info, warn};
+use piston_window::math::Matrix2d;
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Instant;
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{Key, NamedKey};
+use winit::window::{Fullscreen, Window, WindowId};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Draws the grid texture on a quad given in normalised device coordinates.
+const SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@group(0) @binding(0) var grid: texture_2d<f32>;
+@group(0) @binding(1) var grid_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(grid, grid_sampler, in.uv);
+}
+"#;
+
+// Each vertex is a position followed by texture coordinates.
+const VERTEX_SIZE: u64 = 4 * 4;
+const QUAD_VERTICES: u32 = 6;
+
+// Colour shown around the grid.
+const BACKGROUND: wgpu::Color = wgpu::Color {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 1.0,
+};
+
+// Pixels of touchpad scrolling treated as one wheel notch.
+const PIXELS_PER_NOTCH: f64 = 50.0;
+
+// The camera's transform is applied to this to give grid squares to pixels.
+const IDENTITY: Matrix2d = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The graphics library the window is drawn with.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum Backend {
+    /// Piston, with every feature of the window.
+    Piston,
+    /// wgpu, using Vulkan, Metal, DirectX 12 or OpenGL, with the basic
+    /// controls only.
+    Wgpu,
+    /// wgpu, also stepping the simulation on the GPU. Experimental, for
+    /// plain rules on bounded grids, with pausing, the speed and the camera.
+    WgpuCompute,
+}
+
+//-----------------------------------------------------------------------------
+// Draws the grid with wgpu, as a single texture uploaded from the canvas
+// and drawn on a quad placed by the camera. When stepping on the GPU the
+// quad is instead coloured straight from the cells there, and the canvas
+// is left alone.
+pub struct GpuRenderer {
+    canvas: GridCanvas,
+    window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    vertices: wgpu::Buffer,
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    compute: Option<GpuCompute>,
+}
+
+impl Renderer for GpuRenderer {
+    fn update(&mut self, sim: &mut Simulation) {
+        if self.compute.is_some() {
+            return;
+        }
+
+        match self.canvas.update(sim) {
+            CanvasUpdate::Unchanged => return,
+            CanvasUpdate::Cells => {}
+            CanvasUpdate::Resized => self.recreate_texture(),
+        }
+
+        upload(&self.queue, &self.texture, self.canvas.image());
+    }
+
+    fn redraw(&mut self) {
+        self.canvas.redraw();
+    }
+
+    fn set_display(&mut self, display: Display, sim: &mut Simulation) {
+        self.canvas.set_display(display, sim);
+    }
+
+    fn set_zoom(&mut self, zoom: f64) {
+        if self.compute.is_none() && self.canvas.set_zoom(zoom) {
+            self.recreate_texture();
+            upload(&self.queue, &self.texture, self.canvas.image());
+        }
+    }
+}
+
+impl GpuRenderer {
+    pub fn new(
+        event_loop: &ActiveEventLoop,
+        window: Arc<Window>,
+        sim: &mut Simulation,
+        display: Display,
+        compute: bool,
+    ) -> Result<GpuRenderer, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_with_display_handle(
+            Box::new(event_loop.owned_display_handle()),
+        ));
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|e| e.to_string())?;
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        }))
+        .map_err(|e| e.to_string())?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                .map_err(|e| e.to_string())?;
+
+        let size = window.inner_size();
+        let config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or("the graphics adapter can't draw to the window")?;
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("grid"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("grid"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline = create_pipeline(&device, &shader, &layout, config.format);
+
+        // Cells are drawn without smoothing so they keep sharp edges.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("grid"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let vertices = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("grid"),
+            size: VERTEX_SIZE * u64::from(QUAD_VERTICES),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let canvas = GridCanvas::new(sim, display);
+        let (texture, bind_group) = create_texture(&device, &layout, &sampler, canvas.image());
+
+        let (compute, pipeline) = if compute {
+            let compute = GpuCompute::new(&adapter, &device, sim, config.format.is_srgb())?;
+            let shader = GpuCompute::draw_shader(&device);
+            let pipeline = create_pipeline(&device, &shader, compute.draw_layout(), config.format);
+            (Some(compute), pipeline)
+        } else {
+            (None, pipeline)
+        };
+
+        Ok(GpuRenderer {
+            canvas,
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            pipeline,
+            layout,
+            sampler,
+            vertices,
+            texture,
+            bind_group,
+            compute,
+        })
+    }
+
+    // Make moves on the GPU, if stepping there.
+    pub fn step_on_gpu(&self, moves: u64) {
+        if let Some(compute) = self.compute.as_ref() {
+            compute.step(&self.device, &self.queue, moves);
+        }
+    }
+
+    // Bring the simulation up to date with the moves made on the GPU, if
+    // stepping there.
+    pub fn read_back(&self, sim: &mut Simulation) -> Result<(), String> {
+        match self.compute.as_ref() {
+            Some(compute) => compute.read_back(&self.device, &self.queue, sim),
+            None => Ok(()),
+        }
+    }
+
+    // Replace the texture with one matching the canvas image's size.
+    fn recreate_texture(&mut self) {
+        let (texture, bind_group) = create_texture(
+            &self.device,
+            &self.layout,
+            &self.sampler,
+            self.canvas.image(),
+        );
+        self.texture = texture;
+        self.bind_group = bind_group;
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.config.width = size.width;
+            self.config.height = size.height;
+            self.surface.configure(&self.device, &self.config);
+        }
+    }
+
+    // Draw the texture using a transform from grid square coordinates to
+    // window pixels.
+    pub fn draw(&mut self, transform: Matrix2d) {
+        let frame = match self.surface.get_current_texture() {
+            wgpu::CurrentSurfaceTexture::Success(frame)
+            | wgpu::CurrentSurfaceTexture::Suboptimal(frame) => frame,
+            wgpu::CurrentSurfaceTexture::Outdated | wgpu::CurrentSurfaceTexture::Lost => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+            _ => return,
+        };
+
+        // The grid's corners in window pixels, then in device coordinates.
+        let region = self.canvas.region();
+        let (width, height) = (f64::from(self.config.width), f64::from(self.config.height));
+        let corner = |x: i64, y: i64, u: f32, v: f32| {
+            let (x, y) = (x as f64, y as f64);
+            let px = transform[0][0] * x + transform[0][1] * y + transform[0][2];
+            let py = transform[1][0] * x + transform[1][1] * y + transform[1][2];
+            [
+                (2.0 * px / width - 1.0) as f32,
+                (1.0 - 2.0 * py / height) as f32,
+                u,
+                v,
+            ]
+        };
+
+        // Zoomed out, the texture's last blocks of cells can overhang the
+        // grid's far edges.
+        let lod = self.canvas.lod();
+        let (x0, y0) = (region.x, region.y);
+        let x1 = x0 + region.width.next_multiple_of(lod as usize) as i64;
+        let y1 = y0 + region.height.next_multiple_of(lod as usize) as i64;
+        let quad = [
+            corner(x0, y0, 0.0, 0.0),
+            corner(x1, y0, 1.0, 0.0),
+            corner(x0, y1, 0.0, 1.0),
+            corner(x0, y1, 0.0, 1.0),
+            corner(x1, y0, 1.0, 0.0),
+            corner(x1, y1, 1.0, 1.0),
+        ];
+        let bytes: Vec<u8> = quad
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_ne_bytes())
+            .collect();
+        self.queue.write_buffer(&self.vertices, 0, &bytes);
+
+        let view = frame.texture.create_view(&Default::default());
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("grid"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(BACKGROUND),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(
+                0,
+                self.compute
+                    .as_ref()
+                    .map_or(&self.bind_group, |c| c.draw_group()),
+                &[],
+            );
+            pass.set_vertex_buffer(0, self.vertices.slice(..));
+            pass.draw(0..QUAD_VERTICES, 0..1);
+        }
+
+        self.queue.submit([encoder.finish()]);
+        self.queue.present(frame);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The window application run by winit's event loop, which owns the loop so
+// the simulation is advanced and drawn as each frame is requested.
+struct GpuApp<'a> {
+    sim: &'a mut Simulation,
+    stepper: Stepper,
+    recordings: &'a mut Recordings,
+    rewind_moves: u64,
+    display: Display,
+    size: u32,
+    speed: Speed,
+    camera: Camera,
+    paused: bool,
+    last_update: Instant,
+    renderer: Option<GpuRenderer>,
+    error: Option<String>,
+    compute: bool,
+}
+
+impl ApplicationHandler for GpuApp<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.renderer.is_some() {
+            return;
+        }
+
+        let attributes = Window::default_attributes()
+            .with_title("Langton's Ant")
+            .with_inner_size(PhysicalSize::new(self.size, self.size));
+
+        let renderer = event_loop
+            .create_window(attributes)
+            .map_err(|e| e.to_string())
+            .and_then(|window| {
+                GpuRenderer::new(
+                    event_loop,
+                    Arc::new(window),
+                    self.sim,
+                    self.display,
+                    self.compute,
+                )
+            });
+
+        match renderer {
+            Ok(renderer) => self.renderer = Some(renderer),
+            Err(e) => {
+                self.error = Some(e);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.resize(size);
+                }
+
+                self.camera
+                    .resize([f64::from(size.width), f64::from(size.height)]);
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                self.key_pressed(event_loop, &event.logical_key)
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.camera.cursor_moved([position.x, position.y])
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Middle,
+                ..
+            } => self.camera.set_dragging(state == ElementState::Pressed),
+            WindowEvent::MouseWheel { delta, .. } => self.camera.scroll(match delta {
+                MouseScrollDelta::LineDelta(_, y) => f64::from(y),
+                MouseScrollDelta::PixelDelta(pos) => pos.y / PIXELS_PER_NOTCH,
+            }),
+            WindowEvent::RedrawRequested => self.frame(),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, _: &ActiveEventLoop) {
+        if let Some(renderer) = self.renderer.as_ref() {
+            renderer.window.request_redraw();
+        }
+    }
+}
+
+impl GpuApp<'_> {
+    fn key_pressed(&mut self, event_loop: &ActiveEventLoop, key: &Key) {
+        // While stepping on the GPU the simulation here isn't kept up to
+        // date, so can't be stepped, rewound or followed.
+        if self.compute {
+            let unavailable = match key {
+                Key::Named(NamedKey::ArrowRight) if self.paused => {
+                    if let Some(renderer) = self.renderer.as_ref() {
+                        renderer.step_on_gpu(1);
+                    }
+
+                    return;
+                }
+                Key::Named(NamedKey::ArrowLeft | NamedKey::Backspace) => true,
+                Key::Character(c) => matches!(c.as_str(), "f" | "r" | "[" | "]" | "v"),
+                _ => false,
+            };
+
+            if unavailable {
+                warn!("Only pausing, the speed and the camera are available while stepping on the GPU");
+                return;
+            }
+        }
+
+        match key {
+            Key::Named(NamedKey::Escape) => event_loop.exit(),
+            Key::Named(NamedKey::Space) => self.paused = !self.paused,
+            Key::Named(NamedKey::ArrowRight) if self.paused => self.stepper.step_forward(self.sim),
+            Key::Named(NamedKey::ArrowLeft) if self.paused => self.stepper.step_back(self.sim),
+            Key::Named(NamedKey::Backspace) => {
+                match self.stepper.rewind(self.sim, self.rewind_moves) {
+                    Some(undone) => info!(
+                        "Rewound {} moves to iteration {}",
+                        undone,
+                        self.sim.iterations()
+                    ),
+                    None => warn!("Rewinding is unavailable while logging moves"),
+                }
+            }
+            Key::Named(NamedKey::Home) => self.camera.reset(),
+            Key::Named(NamedKey::F11) => {
+                if let Some(renderer) = self.renderer.as_ref() {
+                    let window = &renderer.window;
+
+                    window.set_fullscreen(match window.fullscreen() {
+                        Some(_) => None,
+                        None => Some(Fullscreen::Borderless(None)),
+                    });
+                }
+            }
+            Key::Character(c) => match c.as_str() {
+                "+" | "=" => self.speed.faster(),
+                "-" => self.speed.slower(),
+                "f" => self.camera.toggle_follow(),
+                "r" => self.stepper.toggle_reverse(self.sim),
+                "[" | "]" => {
+                    let fraction = if c == "[" {
+                        -SCRUB_FRACTION
+                    } else {
+                        SCRUB_FRACTION
+                    };
+
+                    self.paused = true;
+
+                    if self.stepper.scrub_by(self.sim, fraction) {
+                        info!("Scrubbed to iteration {}", self.sim.iterations());
+                    } else {
+                        warn!("Scrubbing is unavailable while logging moves");
+                    }
+                }
+                "v" => {
+                    self.display.mode = self.display.mode.next();
+
+                    if let Some(renderer) = self.renderer.as_mut() {
+                        renderer.set_display(self.display, self.sim);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    // Advance the simulation for the time since the last frame and draw it.
+    fn frame(&mut self) {
+        let dt = self.last_update.elapsed().as_secs_f64();
+        self.last_update = Instant::now();
+
+        if !self.paused {
+            let moves = self.speed.moves(dt);
+
+            if self.compute {
+                if let Some(renderer) = self.renderer.as_ref() {
+                    renderer.step_on_gpu(moves);
+                }
+            } else if self
+                .stepper
+                .advance(self.sim, moves, self.recordings, &mut self.speed)
+            {
+                self.paused = true;
+            }
+        }
+
+        if let Some(renderer) = self.renderer.as_mut() {
+            renderer.set_zoom(self.camera.zoom());
+            renderer.update(self.sim);
+
+            if self.camera.following() {
+                let size = renderer.window.inner_size();
+                let ant = self.sim.ant();
+                self.camera.centre_on(
+                    ant.pos_x,
+                    ant.pos_y,
+                    [f64::from(size.width), f64::from(size.height)],
+                );
+            }
+
+            renderer.draw(self.camera.transform(IDENTITY));
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the simulation in a window drawn with wgpu until it's closed. The
+// controls are a subset of the piston window's: pausing, stepping,
+// rewinding, the speed, the display mode and the camera. With compute set
+// the simulation is stepped on the GPU, see GpuCompute, and brought up to
+// date once the window is closed.
+pub fn run(
+    sim: &mut Simulation,
+    stepper: Stepper,
+    recordings: &mut Recordings,
+    config: &Config,
+    rewind_moves: u64,
+    display: Display,
+    compute: bool,
+) -> Result<(), String> {
+    let event_loop = EventLoop::new().map_err(|e| e.to_string())?;
+
+    recordings.capture(sim);
+
+    let grid_size = sim.size();
+    let home = sim.cells().extent();
+
+    let mut app = GpuApp {
+        size: config::window_size(grid_size as u32, config.square_size),
+        sim,
+        stepper,
+        recordings,
+        rewind_moves,
+        display,
+        speed: Speed::new(config.mps),
+        camera: Camera::new(config.square_size, home),
+        paused: false,
+        last_update: Instant::now(),
+        renderer: None,
+        error: None,
+        compute,
+    };
+
+    event_loop.run_app(&mut app).map_err(|e| e.to_string())?;
+
+    if let Some(renderer) = app.renderer.as_ref() {
+        renderer.read_back(app.sim)?;
+    }
+
+    match app.error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Create the pipeline drawing the grid's quad with a shader's vs_main and
+// fs_main entry points.
+fn create_pipeline(
+    device: &wgpu::Device,
+    shader: &wgpu::ShaderModule,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("grid"),
+        bind_group_layouts: &[Some(layout)],
+        immediate_size: 0,
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("grid"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[Some(wgpu::VertexBufferLayout {
+                array_stride: VERTEX_SIZE,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+            })],
+        },
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+//-----------------------------------------------------------------------------
+// Create a texture matching a canvas image, with the bind group drawing it.
+fn create_texture(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    image: &RgbaImage,
+) -> (wgpu::Texture, wgpu::BindGroup) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("grid"),
+        size: texture_size(image),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let view = texture.create_view(&Default::default());
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("grid"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    });
+
+    (texture, bind_group)
+}
+
+//-----------------------------------------------------------------------------
+// Copy a canvas image to its texture.
+fn upload(queue: &wgpu::Queue, texture: &wgpu::Texture, image: &RgbaImage) {
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        image.as_raw(),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * image.width()),
+            rows_per_image: Some(image.height()),
+        },
+        texture_size(image),
+    );
+}
+
+fn texture_size(image: &RgbaImage) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: image.width(),
+        height: image.height(),
+        depth_or_array_layers: 1,
+    }
+}
@@ -0,0 +1,155 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::camera::Camera;
+use langtons_ant::{Extent, GridStorage, Simulation};
+use piston_window::math::Matrix2d;
+use piston_window::triangulation::{tx, ty};
+use piston_window::{DrawState, G2d, Graphics, BACK_END_MAX_VERTEX_COUNT};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Smallest grid square size, in window pixels, at which the lines are
+// drawn. Below it they'd hide the cells rather than separate them.
+const MIN_SQUARE_PIXELS: f64 = 8.0;
+
+// Colour of the lines, a translucent grey that shows against both light
+// and dark cells.
+const LINE_COLOUR: [f32; 4] = [0.5, 0.5, 0.5, 0.5];
+
+// Width of the lines in window pixels.
+const LINE_WIDTH: f64 = 1.0;
+
+// Vertices making up each line, drawn as a pair of triangles.
+const LINE_VERTICES: usize = 6;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Draws the borders between grid squares over the grid once they're big
+// enough on screen, so single cells can be made out at high zoom. Rather
+// than stroking every cell, each row and column border in view is one long
+// line, and all of them are sent to the graphics backend together as
+// batches of triangles.
+pub struct GridLines {
+    visible: bool,
+    vertices: Vec<[f32; 2]>,
+}
+
+impl GridLines {
+    pub fn new(visible: bool) -> GridLines {
+        GridLines {
+            visible,
+            vertices: Vec::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Draw the lines over the part of the grid in a view of the given size,
+    // using a transform from grid squares to the window.
+    pub fn draw(
+        &mut self,
+        sim: &Simulation,
+        camera: &Camera,
+        view_size: [f64; 2],
+        transform: Matrix2d,
+        g: &mut G2d,
+    ) {
+        if !self.visible || camera.zoom() < MIN_SQUARE_PIXELS {
+            return;
+        }
+
+        let area = match intersect(camera.visible_cells(view_size), sim.cells().extent()) {
+            Some(area) => area,
+            None => return,
+        };
+
+        let (left, top) = (area.x as f64, area.y as f64);
+        let (right, bottom) = (left + area.width as f64, top + area.height as f64);
+        let half = LINE_WIDTH / camera.zoom() / 2.0;
+
+        self.vertices.clear();
+
+        for i in 0..=area.width {
+            let x = left + i as f64;
+            self.push_quad([x - half, top, x + half, bottom], transform);
+        }
+
+        for i in 0..=area.height {
+            let y = top + i as f64;
+            self.push_quad([left, y - half, right, y + half], transform);
+        }
+
+        let batch = BACK_END_MAX_VERTEX_COUNT / LINE_VERTICES * LINE_VERTICES;
+        let vertices = &self.vertices;
+
+        g.tri_list(&DrawState::default(), &LINE_COLOUR, |f| {
+            for chunk in vertices.chunks(batch) {
+                f(chunk);
+            }
+        });
+    }
+
+    // Add the two triangles covering a rectangle, given as its left, top,
+    // right and bottom edges in grid squares.
+    fn push_quad(&mut self, [x0, y0, x1, y1]: [f64; 4], transform: Matrix2d) {
+        let corner = |x, y| [tx(transform, x, y), ty(transform, x, y)];
+        let (a, b, c, d) = (
+            corner(x0, y0),
+            corner(x1, y0),
+            corner(x1, y1),
+            corner(x0, y1),
+        );
+
+        self.vertices.extend_from_slice(&[a, b, c, a, c, d]);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The cells in both extents, if any.
+fn intersect(a: Extent, b: Extent) -> Option<Extent> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width as i64).min(b.x + b.width as i64);
+    let bottom = (a.y + a.height as i64).min(b.y + b.height as i64);
+
+    if right <= x || bottom <= y {
+        return None;
+    }
+
+    Some(Extent {
+        x,
+        y,
+        width: (right - x) as usize,
+        height: (bottom - y) as usize,
+    })
+}
@@ -0,0 +1,269 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::cycle::{CycleAction, CycleWatch};
+use super::highway::{HighwayAction, HighwayWatch};
+use super::logging::{self, PerfCounter};
+use super::recording::Recordings;
+use super::savestate;
+use super::script::ScriptHooks;
+use langtons_ant::autosave::Autosave;
+use langtons_ant::stop::StopWatch;
+use langtons_ant::{export, Simulation};
+use log::{error, info, warn};
+use std::fmt;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Most moves made between checks of whether an autosave is due.
+const AUTOSAVE_CHECK_MOVES: u64 = 1 << 20;
+
+// Exit status for a run that couldn't start, e.g. due to a bad argument or
+// file. Distinct from the stop reasons' statuses below, and from 0 so a
+// failed run never looks like a completed one.
+pub const ERROR_EXIT_CODE: i32 = 1;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Why a headless run ended, given by the process's exit status so scripts
+// running it unattended can tell.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StopReason {
+    // The iterations asked for were all made.
+    Completed,
+    // The ants stalled at the edge of the grid, or any did with
+    // --stop-on-stall.
+    Stalled,
+    Highway,
+    Cycle,
+    // A --stop-when condition held.
+    Condition,
+    // The script called stop().
+    Script,
+    // A checkpoint differed from the one given with --checkpoint-verify.
+    Mismatch,
+}
+
+impl StopReason {
+    // The exit status for the reason: 0 when complete, else 2 to 7, leaving
+    // 1 for ERROR_EXIT_CODE.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            StopReason::Completed => 0,
+            StopReason::Stalled => 2,
+            StopReason::Highway => 3,
+            StopReason::Cycle => 4,
+            StopReason::Condition => 5,
+            StopReason::Script => 6,
+            StopReason::Mismatch => 7,
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            StopReason::Completed => "iterations complete",
+            StopReason::Stalled => "ant stalled",
+            StopReason::Highway => "highway found",
+            StopReason::Cycle => "cycle found",
+            StopReason::Condition => "stop condition met",
+            StopReason::Script => "stopped by the script",
+            StopReason::Mismatch => "checkpoint mismatch",
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the simulation in a tight loop without creating a window, stopping
+// after the iterations given on the command line, when the ant stalls, when
+// a stop condition holds, when a checkpoint differs from the one expected
+// or when the script stops the run, then print timing stats and optionally
+// write the final grid to a PNG, returning why the run stopped. With
+// --reverse the moves are undone instead. When recording, the run is broken
+// up at each frame, or regularly when logging moves or autosaving, so the
+// fast path can still be used in between, as it is between calls of a
+// script and checks of the stop conditions. While watching for a highway
+// or a cycle, or for any one of several ants stalling, every move is made
+// singly, until one is found.
+pub fn run(
+    sim: &mut Simulation,
+    cli: &Cli,
+    recordings: &mut Recordings,
+    mut script: Option<ScriptHooks>,
+) -> StopReason {
+    let iterations = cli.iterations;
+    let mut highway = HighwayWatch::from_cli(cli);
+    let mut cycle = CycleWatch::from_cli(cli);
+    let mut stop = Some(StopWatch::new(cli.stop_when.clone(), cli.stop_every))
+        .filter(|_| !cli.stop_when.is_empty());
+    let any_stall = cli.stop_on_stall && sim.ants().len() > 1;
+    let mut autosave = cli.autosave.clone().map(Autosave::new);
+
+    info!("Running {} iterations headless...", iterations);
+
+    let start = Instant::now();
+    let start_iterations = sim.iterations();
+    let mut reason = StopReason::Completed;
+
+    if cli.reverse {
+        reverse(sim, iterations);
+    } else if recordings.is_empty()
+        && highway.is_none()
+        && cycle.is_none()
+        && script.is_none()
+        && stop.is_none()
+        && autosave.is_none()
+        && !any_stall
+    {
+        sim.advance(iterations);
+    } else {
+        let end = start_iterations.saturating_add(iterations);
+        recordings.capture(sim);
+
+        if let Some(cycle) = cycle.as_mut() {
+            cycle.observe(sim);
+        }
+
+        if script.as_mut().is_some_and(|s| s.run(sim)) {
+            reason = StopReason::Script;
+        } else if recordings.mismatched() {
+            reason = StopReason::Mismatch;
+        } else if let Some(condition) = stop.as_mut().and_then(|s| s.observe(sim)) {
+            info!(
+                "Stop condition {} met at iteration {}",
+                condition,
+                sim.iterations()
+            );
+            reason = StopReason::Condition;
+        }
+
+        let mut perf = PerfCounter::new(sim);
+
+        while reason == StopReason::Completed && sim.iterations() < end && !sim.is_stalled() {
+            perf.observe(sim);
+            let watch = highway.as_mut().filter(|h| h.is_watching());
+            let cycle_watch = cycle.as_mut().filter(|c| c.is_watching());
+
+            if watch.is_some() || cycle_watch.is_some() || any_stall {
+                sim.step();
+            } else {
+                let due = recordings
+                    .remaining(sim)
+                    .min(script.as_ref().map_or(u64::MAX, |s| s.remaining(sim)))
+                    .min(stop.as_ref().map_or(u64::MAX, |s| s.remaining(sim)))
+                    .min(autosave.as_ref().map_or(u64::MAX, |_| AUTOSAVE_CHECK_MOVES));
+
+                sim.advance(due.min(end - sim.iterations()));
+            }
+
+            let stopped = script.as_mut().is_some_and(|s| s.run(sim));
+            recordings.capture(sim);
+
+            if let Some(autosave) = autosave.as_mut() {
+                savestate::autosave(autosave, sim);
+            }
+
+            if stopped {
+                reason = StopReason::Script;
+            } else if recordings.mismatched() {
+                reason = StopReason::Mismatch;
+            } else if watch.and_then(|w| w.observe(sim)) == Some(HighwayAction::Stop) {
+                reason = StopReason::Highway;
+            } else if cycle_watch.and_then(|c| c.observe(sim)) == Some(CycleAction::Stop) {
+                reason = StopReason::Cycle;
+            } else if let Some(condition) = stop.as_mut().and_then(|s| s.observe(sim)) {
+                info!(
+                    "Stop condition {} met at iteration {}",
+                    condition,
+                    sim.iterations()
+                );
+                reason = StopReason::Condition;
+            } else if any_stall && sim.ants().iter().any(|a| a.stalled) {
+                reason = StopReason::Stalled;
+            }
+        }
+    }
+
+    if reason == StopReason::Completed && sim.is_stalled() {
+        reason = StopReason::Stalled;
+    }
+
+    let elapsed = start.elapsed();
+    let moves = sim.iterations().abs_diff(start_iterations);
+    let secs = elapsed.as_secs_f64();
+
+    println!("Moves = {}", moves);
+    println!("Total iterations = {}", sim.iterations());
+    println!("Elapsed time = {:.3} s", secs);
+
+    if secs > 0.0 {
+        println!("Moves per second = {:.0}", moves as f64 / secs);
+    }
+
+    if sim.is_stalled() {
+        logging::log_stalls(sim);
+        println!("Ant stalled at the grid boundary");
+    }
+
+    println!("Stopped: {} (exit status {})", reason, reason.exit_code());
+
+    if let Some(path) = cli.output.as_ref() {
+        match export::save_png(sim, path, cli.screenshot_scale) {
+            Ok(()) => info!("Saved final grid to {}", path.display()),
+            Err(e) => error!("Failed to save final grid to {}: {}", path.display(), e),
+        }
+    }
+
+    reason
+}
+
+//-----------------------------------------------------------------------------
+// Undo up to n moves of a reversible simulation.
+fn reverse(sim: &mut Simulation, n: u64) {
+    if let Err(reason) = sim.reversible() {
+        warn!("Can't reverse: {}", reason);
+        return;
+    }
+
+    for _ in 0..n {
+        if !sim.unstep() {
+            info!(
+                "Reversed to iteration {}, no further moves can be undone",
+                sim.iterations()
+            );
+            break;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use clap::ValueEnum;
+use langtons_ant::highway::HighwayDetector;
+use langtons_ant::{rle, Simulation};
+use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// What to do once the ant is found to be building a highway.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum HighwayAction {
+    /// Just report the highway.
+    Report,
+    /// Pause the window, or end a headless run.
+    Stop,
+    /// Run at full speed from then on.
+    Fast,
+}
+
+//-----------------------------------------------------------------------------
+// Watches a run for a highway, as requested on the command line, reporting
+// it once found.
+pub struct HighwayWatch {
+    detector: HighwayDetector,
+    action: HighwayAction,
+    // Where to save the highway as a pattern, if anywhere.
+    pattern: Option<PathBuf>,
+}
+
+impl HighwayWatch {
+    pub fn from_cli(cli: &Cli) -> Option<HighwayWatch> {
+        let action = if cli.stop_on_highway {
+            Some(HighwayAction::Stop)
+        } else if cli.highway.is_none() && cli.highway_pattern.is_some() {
+            Some(HighwayAction::Report)
+        } else {
+            cli.highway
+        };
+
+        action.map(|action| HighwayWatch {
+            detector: HighwayDetector::new(cli.highway_period),
+            action,
+            pattern: cli.highway_pattern.clone(),
+        })
+    }
+
+    // Is the run still being watched, i.e. no highway found yet? Watching
+    // needs every move to be seen, so the fast path can't be used.
+    pub fn is_watching(&self) -> bool {
+        self.detector.found().is_none()
+    }
+
+    // Observe a move, returning the action to take if it completes a
+    // highway.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<HighwayAction> {
+        let highway = self.detector.observe(sim)?;
+
+        debug!(
+            "highway period={} dx={} dy={} start={} iteration={} action={:?}",
+            highway.period,
+            highway.displacement.0,
+            highway.displacement.1,
+            highway.start,
+            sim.iterations(),
+            self.action
+        );
+        info!(
+            "Highway found with period {} moving ({}, {}) each period, from iteration {}",
+            highway.period, highway.displacement.0, highway.displacement.1, highway.start
+        );
+
+        if let Some(path) = self.pattern.as_ref() {
+            match self.detector.pattern(sim) {
+                Some(pattern) => save_pattern(&pattern, path),
+                None => warn!("The highway couldn't be cut out as a pattern that rebuilds it"),
+            }
+        }
+
+        Some(self.action)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Save a highway pattern, as RLE or a JSON state by the file's extension.
+fn save_pattern(pattern: &Simulation, path: &Path) {
+    let saved = match path.extension().and_then(|e| e.to_str()) {
+        Some("rle") => rle::save(pattern, path),
+        _ => pattern.save(path),
+    };
+
+    match saved {
+        Ok(()) => info!("Saved the highway pattern to {}", path.display()),
+        Err(e) => error!(
+            "Failed to save the highway pattern to {}: {}",
+            path.display(),
+            e
+        ),
+    }
+}
@@ -0,0 +1,216 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::speed;
+use super::state::AppState;
+use langtons_ant::stats::Stats;
+use langtons_ant::Simulation;
+use piston_window::{
+    rectangle, text, Context, G2d, GfxDevice, Glyphs, PistonWindow, TextureSettings, Transformed,
+};
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+// The font is compiled into the binary so the HUD works wherever the
+// executable is run from.
+const FONT: &[u8] = include_bytes!("../../assets/DejaVuSansMono.ttf");
+
+const FONT_SIZE: u32 = 14;
+const LINE_HEIGHT: f64 = 18.0;
+const MARGIN: f64 = 8.0;
+
+// How often, in seconds, the measured rates and statistics are refreshed.
+const SAMPLE_PERIOD: f64 = 0.5;
+
+// Most colours whose cell counts are listed individually.
+const MAX_COUNTS: usize = 8;
+
+//-----------------------------------------------------------------------------
+// On-screen overlay showing the iteration count, rule, speed and frame rate.
+// The moves per second and frames per second shown are measured, averaged
+// over a short period so they are readable, with the target speed alongside.
+// Statistics about the pattern are refreshed at the same rate.
+pub struct Hud {
+    glyphs: Glyphs,
+    visible: bool,
+    sample_start: Instant,
+    sample_iterations: u64,
+    sample_frames: u32,
+    moves_per_sec: f64,
+    fps: f64,
+    stats: Option<Stats>,
+}
+
+impl Hud {
+    pub fn new(window: &mut PistonWindow) -> Hud {
+        Hud {
+            glyphs: load_glyphs(window),
+            visible: true,
+            sample_start: Instant::now(),
+            sample_iterations: 0,
+            sample_frames: 0,
+            moves_per_sec: 0.0,
+            fps: 0.0,
+            stats: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Move the glyph cache to a newly opened window.
+    pub fn reattach(&mut self, window: &mut PistonWindow) {
+        self.glyphs = load_glyphs(window);
+    }
+
+    // Count a rendered frame and refresh the measured rates once enough
+    // time has passed.
+    pub fn frame(&mut self, sim: &Simulation) {
+        self.sample_frames += 1;
+
+        let elapsed = self.sample_start.elapsed().as_secs_f64();
+
+        if elapsed >= SAMPLE_PERIOD {
+            let moves = sim.iterations().saturating_sub(self.sample_iterations);
+            self.moves_per_sec = moves as f64 / elapsed;
+            self.fps = f64::from(self.sample_frames) / elapsed;
+            self.sample_start = Instant::now();
+            self.sample_iterations = sim.iterations();
+            self.sample_frames = 0;
+
+            // Measuring scans the whole grid, so is skipped when hidden.
+            self.stats = if self.visible {
+                Some(Stats::measure(sim))
+            } else {
+                None
+            };
+        }
+    }
+
+    // Draw the overlay in the top left corner of the window, on top of
+    // whatever has already been drawn.
+    pub fn draw(
+        &mut self,
+        sim: &Simulation,
+        target_mps: u64,
+        state: AppState,
+        c: Context,
+        g: &mut G2d,
+        device: &mut GfxDevice,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let mut lines = vec![
+            format!("N     {}", sim.iterations()),
+            format!("Rule  {}", sim.rule()),
+            format!(
+                "Moves {}/s (target {})",
+                speed::format_mps(self.moves_per_sec),
+                speed::format_mps(target_mps as f64)
+            ),
+            format!("FPS   {:.1}", self.fps),
+        ];
+
+        if sim.ants().len() > 1 {
+            lines.push(format!("Ants  {}", sim.ants().len()));
+        }
+
+        if let Some(ref stats) = self.stats {
+            if let Some(b) = stats.bounds {
+                lines.push(format!("Box   {} x {}", b.width, b.height));
+            }
+
+            lines.push(format!("Dist  {:.1}", stats.distance()));
+            lines.push(format!("Sym   {}", stats.symmetry));
+            lines.push(format!(
+                "Entr  {:.3} bits/cell, zlib {:.2}",
+                stats.entropy, stats.compression
+            ));
+
+            if stats.counts.len() <= MAX_COUNTS {
+                let counts: Vec<String> = stats.counts.iter().map(|c| c.to_string()).collect();
+                lines.push(format!("Cells {}", counts.join("/")));
+            } else {
+                lines.push(format!("Cells {}", stats.visited()));
+            }
+        }
+
+        match state {
+            AppState::Paused => lines.push(String::from("PAUSED")),
+            AppState::Finished => lines.push(String::from("FINISHED")),
+            _ => {}
+        }
+
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as f64
+            * f64::from(FONT_SIZE)
+            * 0.6;
+        let height = lines.len() as f64 * LINE_HEIGHT;
+
+        rectangle(
+            [0.0, 0.0, 0.0, 0.6],
+            [0.0, 0.0, width + 2.0 * MARGIN, height + MARGIN],
+            c.transform,
+            g,
+        );
+
+        for (i, line) in lines.iter().enumerate() {
+            let transform = c.transform.trans(
+                MARGIN,
+                MARGIN + f64::from(FONT_SIZE) + i as f64 * LINE_HEIGHT,
+            );
+
+            text(
+                [1.0, 1.0, 1.0, 1.0],
+                FONT_SIZE,
+                line,
+                &mut self.glyphs,
+                transform,
+                g,
+            )
+            .unwrap();
+        }
+
+        // Glyphs are uploaded to the cache texture lazily, so they must be
+        // flushed before the frame is presented.
+        self.glyphs.factory.encoder.flush(device);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Load the font into a glyph cache for the window.
+pub fn load_glyphs(window: &mut PistonWindow) -> Glyphs {
+    Glyphs::from_bytes(
+        FONT,
+        window.create_texture_context(),
+        TextureSettings::new(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,170 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::camera::Camera;
+use super::hud::load_glyphs;
+use langtons_ant::{GridStorage, Simulation, BLANK, OBSTACLE};
+use piston_window::{rectangle, text, Context, G2d, GfxDevice, Glyphs, PistonWindow, Transformed};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+const FONT_SIZE: u32 = 12;
+const LINE_HEIGHT: f64 = 16.0;
+const MARGIN: f64 = 6.0;
+
+// Distance of the readout's corner from the cursor, in pixels, so it
+// doesn't hide the cell being inspected.
+const CURSOR_OFFSET: f64 = 16.0;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Readout beside the cursor of the cell under it, toggled with I: its grid
+// coordinates, colour index, how many times ants have left it and the
+// iteration they last did. Visits are recorded from when the inspector is
+// first shown, as for the display modes that need them, and kept after
+// it's hidden.
+pub struct Inspector {
+    glyphs: Glyphs,
+    visible: bool,
+}
+
+impl Inspector {
+    pub fn new(window: &mut PistonWindow) -> Inspector {
+        Inspector {
+            glyphs: load_glyphs(window),
+            visible: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Move the glyph cache to a newly opened window.
+    pub fn reattach(&mut self, window: &mut PistonWindow) {
+        self.glyphs = load_glyphs(window);
+    }
+
+    // Start recording visits while shown, including in a simulation that's
+    // just replaced the last, e.g. on restarting.
+    pub fn frame(&mut self, sim: &mut Simulation) {
+        if self.visible {
+            sim.set_track_visits(true);
+        }
+    }
+
+    // Draw the readout beside the cursor, kept inside the window, on top of
+    // whatever has already been drawn.
+    pub fn draw(
+        &mut self,
+        sim: &Simulation,
+        camera: &Camera,
+        c: Context,
+        g: &mut G2d,
+        device: &mut GfxDevice,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let lines = describe(sim, camera.cursor_cell());
+
+        let [view_width, view_height] = c.get_view_size();
+        let [cx, cy] = camera.cursor();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as f64
+            * f64::from(FONT_SIZE)
+            * 0.6
+            + 2.0 * MARGIN;
+        let height = lines.len() as f64 * LINE_HEIGHT + MARGIN;
+
+        // Flipped to the other side of the cursor near the window's edges.
+        let x = if cx + CURSOR_OFFSET + width > view_width {
+            (cx - CURSOR_OFFSET - width).max(0.0)
+        } else {
+            cx + CURSOR_OFFSET
+        };
+        let y = if cy + CURSOR_OFFSET + height > view_height {
+            (cy - CURSOR_OFFSET - height).max(0.0)
+        } else {
+            cy + CURSOR_OFFSET
+        };
+
+        rectangle([0.0, 0.0, 0.0, 0.75], [x, y, width, height], c.transform, g);
+
+        for (i, line) in lines.iter().enumerate() {
+            let transform = c.transform.trans(
+                x + MARGIN,
+                y + MARGIN + f64::from(FONT_SIZE) + i as f64 * LINE_HEIGHT,
+            );
+
+            text(
+                [1.0, 1.0, 1.0, 1.0],
+                FONT_SIZE,
+                line,
+                &mut self.glyphs,
+                transform,
+                g,
+            )
+            .unwrap();
+        }
+
+        // Glyphs are uploaded to the cache texture lazily, so they must be
+        // flushed before the frame is presented.
+        self.glyphs.factory.encoder.flush(device);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The lines of the readout for a cell.
+fn describe(sim: &Simulation, (x, y): (i64, i64)) -> Vec<String> {
+    let mut lines = vec![format!("Cell   {}, {}", x, y)];
+    let cells = sim.cells();
+
+    if !cells.contains(x, y) {
+        lines.push(String::from("Outside the grid"));
+        return lines;
+    }
+
+    lines.push(match cells.get(x, y) {
+        BLANK => String::from("Colour blank"),
+        OBSTACLE => String::from("Colour obstacle"),
+        clr_idx => format!("Colour {}", clr_idx),
+    });
+
+    match sim.visits().and_then(|v| v.get(x, y)) {
+        Some(visit) => {
+            lines.push(format!("Visits {}", visit.count));
+            lines.push(format!("Last   {}", visit.last));
+        }
+        None => lines.push(String::from("Visits 0")),
+    }
+
+    lines
+}
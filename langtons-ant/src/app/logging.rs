@@ -0,0 +1,127 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use env_logger::{Builder, Target};
+use langtons_ant::Simulation;
+use log::{debug, Level, LevelFilter};
+use std::io::Write;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Shortest time, in seconds, between logs of the performance counters.
+const PERF_LOG_PERIOD: f64 = 1.0;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Counts the moves made while running, logging the rate achieved at most
+// once every PERF_LOG_PERIOD seconds at debug level.
+pub struct PerfCounter {
+    sample_start: Instant,
+    sample_iterations: u64,
+}
+
+impl PerfCounter {
+    pub fn new(sim: &Simulation) -> PerfCounter {
+        PerfCounter {
+            sample_start: Instant::now(),
+            sample_iterations: sim.iterations(),
+        }
+    }
+
+    pub fn observe(&mut self, sim: &Simulation) {
+        let elapsed = self.sample_start.elapsed().as_secs_f64();
+
+        if elapsed < PERF_LOG_PERIOD || !log::log_enabled!(Level::Debug) {
+            return;
+        }
+
+        let moves = sim.iterations().abs_diff(self.sample_iterations);
+
+        debug!(
+            "perf iteration={} moves={} secs={:.3} moves_per_sec={:.0}",
+            sim.iterations(),
+            moves,
+            elapsed,
+            moves as f64 / elapsed
+        );
+
+        self.sample_start = Instant::now();
+        self.sample_iterations = sim.iterations();
+    }
+}
+
+impl Default for PerfCounter {
+    fn default() -> PerfCounter {
+        PerfCounter {
+            sample_start: Instant::now(),
+            sample_iterations: 0,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Send log messages to the console. Messages are plain at info level, as
+// the console output has always been, with errors and warnings marked and
+// debug and trace messages tagged with their level and module. Only
+// errors are shown from the libraries used, e.g. wgpu. Info and above are
+// shown until set_level is called.
+pub fn init() {
+    Builder::new()
+        .filter_level(LevelFilter::Error)
+        .filter_module("langtons_ant", LevelFilter::Trace)
+        .target(Target::Stdout)
+        .format(|buf, record| match record.level() {
+            Level::Info => writeln!(buf, "{}", record.args()),
+            Level::Warn => writeln!(buf, "WARNING - {}", record.args()),
+            Level::Error => writeln!(buf, "ERROR - {}", record.args()),
+            level => writeln!(buf, "{} [{}] {}", level, record.target(), record.args()),
+        })
+        .init();
+
+    set_level(LevelFilter::Info);
+}
+
+//-----------------------------------------------------------------------------
+// Show messages up to the given level, e.g. from --log-level.
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+//-----------------------------------------------------------------------------
+// Log where each stalled ant stopped, once every ant has stalled.
+pub fn log_stalls(sim: &Simulation) {
+    for (i, ant) in sim.ants().iter().enumerate().filter(|(_, a)| a.stalled) {
+        debug!(
+            "stall ant={} x={} y={} facing={:?} iteration={}",
+            i, ant.pos_x, ant.pos_y, ant.facing, ant.iterations
+        );
+    }
+}
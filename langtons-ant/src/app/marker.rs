@@ -0,0 +1,113 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{GridStorage, Simulation, BLANK, OBSTACLE};
+use piston_window::math::Matrix2d;
+use piston_window::{polygon, G2d};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Distance from the centre of the cell to the marker's tip and base, in
+// cells, leaving a small gap so the cell's colour still shows around it.
+const MARKER_SIZE: f64 = 0.4;
+
+// Cells brighter than this are marked in black, darker ones in white.
+const LIGHT_LUMINANCE: f64 = 0.5;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Marks each ant with a triangle pointing the way it faces, drawn on top of
+// the grid in black or white, whichever stands out more against the cell
+// the ant is on.
+pub struct AntMarkers {
+    visible: bool,
+}
+
+impl Default for AntMarkers {
+    fn default() -> AntMarkers {
+        AntMarkers { visible: true }
+    }
+}
+
+impl AntMarkers {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Draw the markers using a transform from grid squares to the window.
+    pub fn draw(&self, sim: &Simulation, transform: Matrix2d, g: &mut G2d) {
+        if !self.visible {
+            return;
+        }
+
+        for ant in sim.ants() {
+            let (dx, dy) = ant.facing.offset();
+            let (dx, dy) = (dx as f64 * MARKER_SIZE, dy as f64 * MARKER_SIZE);
+            let (cx, cy) = (ant.pos_x as f64 + 0.5, ant.pos_y as f64 + 0.5);
+
+            // The tip is ahead of the centre and the base corners behind it,
+            // either side.
+            let points = [
+                [cx + dx, cy + dy],
+                [cx - dx - dy, cy - dy + dx],
+                [cx - dx + dy, cy - dy - dx],
+            ];
+
+            polygon(
+                contrasting(sim, ant.pos_x, ant.pos_y),
+                &points,
+                transform,
+                g,
+            );
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Black or white, whichever contrasts more with the colour of a cell, blank
+// cells being white.
+fn contrasting(sim: &Simulation, x: i64, y: i64) -> [f32; 4] {
+    let cells = sim.cells();
+
+    let luminance = if !cells.contains(x, y) || cells.get(x, y) == BLANK {
+        1.0
+    } else if cells.get(x, y) == OBSTACLE {
+        0.0
+    } else {
+        let [r, g, b, _] = sim.colours()[cells.get(x, y)].to_rgba8();
+        (0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b)) / 255.0
+    };
+
+    if luminance > LIGHT_LUMINANCE {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        [1.0, 1.0, 1.0, 1.0]
+    }
+}
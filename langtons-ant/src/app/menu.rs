@@ -0,0 +1,236 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli;
+use super::hud;
+use langtons_ant::config;
+use langtons_ant::{Config, ConfigError};
+use piston_window::{
+    clear, rectangle, text, Button, Context, G2d, GenericEvent, GfxDevice, Glyphs, Key,
+    PistonWindow, Transformed,
+};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Width and height of the window the menu is shown in, in pixels.
+pub const MENU_SIZE: u32 = 560;
+
+const FONT_SIZE: u32 = 16;
+const LINE_HEIGHT: f64 = 24.0;
+const MARGIN: f64 = 24.0;
+
+const RULE: usize = 0;
+const MPS: usize = 1;
+const GRID_SIZE: usize = 2;
+const SQUARE_SIZE: usize = 3;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Start screen drawn in the window, taking the place of the console prompts
+// when the program is run without any arguments, e.g. by double-clicking it.
+// Each setting is a text box, chosen with the up and down arrow keys or tab
+// and edited by typing, and enter starts the run once every setting is
+// valid.
+pub struct StartMenu {
+    glyphs: Glyphs,
+    values: [String; 4],
+    selected: usize,
+    error: Option<String>,
+    reusing: bool,
+}
+
+impl StartMenu {
+    // Set up the menu with each text box holding the given setting. When
+    // reusing the last run's settings the menu says so, as enter alone
+    // starts another run with them.
+    pub fn new(window: &mut PistonWindow, defaults: &Config, reusing: bool) -> StartMenu {
+        StartMenu {
+            glyphs: hud::load_glyphs(window),
+            values: [
+                defaults.rule.clone(),
+                defaults.mps.to_string(),
+                defaults.grid_size.to_string(),
+                defaults.square_size.to_string(),
+            ],
+            selected: RULE,
+            error: None,
+            reusing,
+        }
+    }
+
+    // Edit the settings with the keyboard, returning the configuration
+    // once enter is pressed with every setting valid.
+    pub fn handle_event<E: GenericEvent>(&mut self, e: &E) -> Option<Config> {
+        if let Some(text) = e.text_args() {
+            self.values[self.selected].extend(text.chars().filter(|c| !c.is_control()));
+            self.error = None;
+        }
+
+        match e.press_args() {
+            Some(Button::Keyboard(Key::Up)) => {
+                self.selected = (self.selected + self.values.len() - 1) % self.values.len();
+            }
+            Some(Button::Keyboard(Key::Down | Key::Tab)) => {
+                self.selected = (self.selected + 1) % self.values.len();
+            }
+            Some(Button::Keyboard(Key::Backspace)) => {
+                self.values[self.selected].pop();
+                self.error = None;
+            }
+            Some(Button::Keyboard(Key::Return | Key::NumPadEnter)) => match self.config() {
+                Ok(config) => return Some(config),
+                Err(e) => self.error = Some(e.to_string()),
+            },
+            _ => {}
+        }
+
+        None
+    }
+
+    // Check every setting, as the console prompts do.
+    fn config(&self) -> Result<Config, ConfigError> {
+        Config::new(
+            &self.values[RULE],
+            cli::parse_mps(self.values[MPS].trim()).map_err(|_| ConfigError::InvalidNumber {
+                name: "moves per second",
+                input: self.values[MPS].clone(),
+            })?,
+            config::parse_number("grid size", &self.values[GRID_SIZE])?,
+            config::parse_number("grid square size", &self.values[SQUARE_SIZE])?,
+        )
+    }
+
+    pub fn draw(&mut self, c: Context, g: &mut G2d, device: &mut GfxDevice) {
+        clear([0.1, 0.1, 0.1, 1.0], g);
+
+        let labels = [
+            String::from("Rule, using L, R, U and N, e.g. RLLR"),
+            String::from("Moves per second (at least 1, or max)"),
+            String::from("Grid size in squares (at least 10)"),
+            String::from("Square size in pixels (1 - 20)"),
+        ];
+
+        let mut y = MARGIN + f64::from(FONT_SIZE);
+        self.line("LANGTON'S ANT SIMULATOR", [1.0; 4], y, c, g);
+        y += 2.0 * LINE_HEIGHT;
+
+        if self.reusing {
+            self.line(
+                "Press enter to reuse the last run's settings",
+                [0.6, 0.9, 0.6, 1.0],
+                y,
+                c,
+                g,
+            );
+            y += 1.5 * LINE_HEIGHT;
+        }
+
+        for (i, label) in labels.iter().enumerate() {
+            self.line(label, [0.7, 0.7, 0.7, 1.0], y, c, g);
+            y += LINE_HEIGHT;
+
+            let selected = i == self.selected;
+            let width = f64::from(MENU_SIZE) - 2.0 * MARGIN;
+
+            rectangle(
+                if selected {
+                    [0.25, 0.35, 0.6, 1.0]
+                } else {
+                    [0.2, 0.2, 0.2, 1.0]
+                },
+                [
+                    MARGIN - 4.0,
+                    y - f64::from(FONT_SIZE) - 2.0,
+                    width + 8.0,
+                    LINE_HEIGHT,
+                ],
+                c.transform,
+                g,
+            );
+
+            // A caret marks the text box being edited.
+            let value = if selected {
+                format!("{}_", self.values[i])
+            } else {
+                self.values[i].clone()
+            };
+
+            self.line(&value, [1.0; 4], y, c, g);
+            y += 1.5 * LINE_HEIGHT;
+        }
+
+        self.line(
+            "Up, down or tab to choose, enter to start, esc to quit",
+            [0.7, 0.7, 0.7, 1.0],
+            y,
+            c,
+            g,
+        );
+
+        if let Some(error) = self.error.clone() {
+            self.line(&error, [1.0, 0.4, 0.4, 1.0], y + 1.5 * LINE_HEIGHT, c, g);
+        }
+
+        // Glyphs are uploaded to the cache texture lazily, so they must be
+        // flushed before the frame is presented.
+        self.glyphs.factory.encoder.flush(device);
+    }
+
+    // Draw a line of text with its baseline at the given height.
+    fn line(&mut self, line: &str, colour: [f32; 4], y: f64, c: Context, g: &mut G2d) {
+        text(
+            colour,
+            FONT_SIZE,
+            line,
+            &mut self.glyphs,
+            c.transform.trans(MARGIN, y),
+            g,
+        )
+        .unwrap();
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Show the start menu in the window until a run is started, returning its
+// configuration, or the window is closed, returning None. The menu starts
+// with the given settings, e.g. those of the last run when reusing them.
+pub fn show(window: &mut PistonWindow, defaults: &Config, reusing: bool) -> Option<Config> {
+    let mut menu = StartMenu::new(window, defaults, reusing);
+
+    while let Some(e) = window.next() {
+        if let Some(config) = menu.handle_event(&e) {
+            return Some(config);
+        }
+
+        window.draw_2d(&e, |c, g, device| menu.draw(c, g, device));
+    }
+
+    None
+}
@@ -0,0 +1,192 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::camera::Camera;
+use super::render::GridRenderer;
+use langtons_ant::{Extent, Simulation};
+use piston_window::{rectangle, Context, G2d, GfxDevice, Rectangle, Transformed};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Length of the minimap's longer side, and its distance from the corner of
+// the window, in pixels.
+const SIZE: f64 = 160.0;
+const MARGIN: f64 = 8.0;
+
+const FRAME_COLOUR: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+const VIEW_COLOUR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const ANT_COLOUR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+// Side of the dot marking each ant, in pixels, so ants still show when
+// their cells are far smaller than a pixel.
+const ANT_SIZE: f64 = 3.0;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Small map of the whole grid in the bottom right corner of the window,
+// toggled with M, with a rectangle marking the part in view and a dot on
+// each ant. The grid's texture is drawn again scaled down, so the map costs
+// no more to keep up to date than the view itself. Clicking or dragging on
+// the map centres the view there.
+#[derive(Default)]
+pub struct Minimap {
+    visible: bool,
+    dragging: bool,
+    // The map's area in the window, [x, y, width, height], and the cells it
+    // covers, as last drawn, so clicks land where the map was seen.
+    area: [f64; 4],
+    region: Option<Extent>,
+    view_size: [f64; 2],
+}
+
+impl Minimap {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.dragging = false;
+    }
+
+    // Handle a left click, jumping the view to the cell clicked if it's on
+    // the map. Returns true if it was, so the click isn't also taken as
+    // painting.
+    pub fn press(&mut self, camera: &mut Camera) -> bool {
+        self.dragging = self.jump(camera);
+        self.dragging
+    }
+
+    // Keep jumping the view while dragging over the map.
+    pub fn drag(&mut self, camera: &mut Camera) {
+        if self.dragging {
+            self.jump(camera);
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.dragging = false;
+    }
+
+    // Centre the view on the cell of the map under the cursor, returning
+    // false if the cursor isn't over the map.
+    fn jump(&self, camera: &mut Camera) -> bool {
+        let region = match self.region.filter(|_| self.visible) {
+            Some(region) => region,
+            None => return false,
+        };
+
+        let [x, y, width, height] = self.area;
+        let [cx, cy] = camera.cursor();
+
+        if cx < x || cy < y || cx >= x + width || cy >= y + height {
+            return false;
+        }
+
+        let scale = width / region.width as f64;
+        camera.jump_to(
+            region.x + ((cx - x) / scale) as i64,
+            region.y + ((cy - y) / scale) as i64,
+            self.view_size,
+        );
+
+        true
+    }
+
+    // Draw the map in the corner of the window, on top of whatever has
+    // already been drawn.
+    pub fn draw(
+        &mut self,
+        sim: &Simulation,
+        renderer: &mut GridRenderer,
+        camera: &Camera,
+        c: Context,
+        g: &mut G2d,
+        device: &mut GfxDevice,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        let view_size = c.get_view_size();
+        let region = renderer.region();
+        let scale = SIZE / region.width.max(region.height).max(1) as f64;
+        let (width, height) = (region.width as f64 * scale, region.height as f64 * scale);
+        let (x, y) = (
+            view_size[0] - MARGIN - width,
+            view_size[1] - MARGIN - height,
+        );
+
+        self.area = [x, y, width, height];
+        self.region = Some(region);
+        self.view_size = view_size;
+
+        rectangle(
+            FRAME_COLOUR,
+            [x - 1.0, y - 1.0, width + 2.0, height + 2.0],
+            c.transform,
+            g,
+        );
+
+        // The texture is positioned in grid coordinates, so is drawn with
+        // the grid's corner moved to the map's.
+        let transform = c
+            .transform
+            .trans(x, y)
+            .scale(scale, scale)
+            .trans(-region.x as f64, -region.y as f64);
+        renderer.draw_grid(transform, g, device);
+
+        // The cells in view, clipped to the map.
+        let visible = camera.visible_cells(view_size);
+        let x0 = visible.x.max(region.x);
+        let y0 = visible.y.max(region.y);
+        let x1 = (visible.x + visible.width as i64).min(region.x + region.width as i64);
+        let y1 = (visible.y + visible.height as i64).min(region.y + region.height as i64);
+
+        if x0 < x1 && y0 < y1 {
+            Rectangle::new_border(VIEW_COLOUR, 0.5).draw(
+                [
+                    x + (x0 - region.x) as f64 * scale,
+                    y + (y0 - region.y) as f64 * scale,
+                    (x1 - x0) as f64 * scale,
+                    (y1 - y0) as f64 * scale,
+                ],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+        }
+
+        for ant in sim.ants() {
+            let ax = x + ((ant.pos_x - region.x) as f64 + 0.5) * scale;
+            let ay = y + ((ant.pos_y - region.y) as f64 + 0.5) * scale;
+
+            rectangle(
+                ANT_COLOUR,
+                [ax - ANT_SIZE / 2.0, ay - ANT_SIZE / 2.0, ANT_SIZE, ANT_SIZE],
+                c.transform,
+                g,
+            );
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+pub mod bench;
+pub mod camera;
+pub mod cli;
+pub mod compare;
+pub mod compute;
+pub mod cycle;
+pub mod explore;
+pub mod fork;
+pub mod gpu;
+pub mod gridlines;
+pub mod headless;
+pub mod highway;
+pub mod hud;
+pub mod inspector;
+pub mod logging;
+pub mod marker;
+pub mod menu;
+pub mod minimap;
+pub mod paint;
+pub mod panel;
+pub mod playback;
+pub mod plot;
+pub mod poster;
+pub mod recording;
+pub mod render;
+pub mod savestate;
+pub mod script;
+pub mod server;
+pub mod settings;
+pub mod speed;
+pub mod state;
+pub mod stepper;
+pub mod surface;
+pub mod tui;
+pub mod verify;
+pub mod volume;
+pub mod worker;
@@ -0,0 +1,98 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{Simulation, BLANK, OBSTACLE};
+use log::info;
+use piston_window::Key;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Paints cells with the left mouse button, so the grid can be seeded with
+// patterns before or during a run. The colour index painted is cycled
+// through the rule's colours with the [ and ] keys, with 0 erasing cells
+// back to blank and O painting obstacles. The number keys 1 to 9 are left
+// for the quick save slots.
+#[derive(Default)]
+pub struct Painter {
+    colour: usize,
+    last: Option<(i64, i64)>,
+}
+
+impl Painter {
+    // Select the previous or next paint colour with [ or ], erasing with 0
+    // or obstacles with O. Other keys are ignored. Stepping on from erasing
+    // or obstacles starts from the first or last colour.
+    pub fn select_key(&mut self, key: Key, num_colours: usize) {
+        let painting = self.colour != BLANK && self.colour != OBSTACLE;
+
+        let colour = match key {
+            Key::D0 => BLANK,
+            Key::O => OBSTACLE,
+            Key::LeftBracket if painting => (self.colour + num_colours - 1) % num_colours,
+            Key::LeftBracket => num_colours - 1,
+            Key::RightBracket if painting => (self.colour + 1) % num_colours,
+            Key::RightBracket => 0,
+            _ => return,
+        };
+
+        self.colour = colour;
+
+        match colour {
+            BLANK => info!("Paint colour = erase"),
+            OBSTACLE => info!("Paint colour = obstacle"),
+            _ => info!("Paint colour = {}", colour),
+        }
+    }
+
+    // Start painting at the given cell.
+    pub fn press(&mut self, sim: &mut Simulation, cell: (i64, i64)) {
+        sim.paint(cell.0, cell.1, self.colour);
+        self.last = Some(cell);
+    }
+
+    // Continue painting to the given cell, filling in the cells in between
+    // so fast drags don't leave gaps.
+    pub fn drag(&mut self, sim: &mut Simulation, cell: (i64, i64)) {
+        let (x0, y0) = match self.last {
+            Some(last) if last != cell => last,
+            _ => return,
+        };
+
+        let (x1, y1) = cell;
+        let steps = (x1 - x0).abs().max((y1 - y0).abs());
+
+        for i in 1..=steps {
+            let t = i as f64 / steps as f64;
+            let x = x0 + ((x1 - x0) as f64 * t).round() as i64;
+            let y = y0 + ((y1 - y0) as f64 * t).round() as i64;
+            sim.paint(x, y, self.colour);
+        }
+
+        self.last = Some(cell);
+    }
+
+    pub fn release(&mut self) {
+        self.last = None;
+    }
+}
@@ -0,0 +1,510 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use egui::epaint::{ClippedPrimitive, ImageData, Primitive};
+use egui::{Color32, Modifiers, Pos2, RawInput, Rect, TextureId};
+use image::{imageops, Rgba, RgbaImage};
+use langtons_ant::config::{self, UNLIMITED_MPS};
+use langtons_ant::{Colour, Simulation};
+use piston_window::{
+    Button, Context, G2d, G2dTexture, G2dTextureContext, GenericEvent, GfxDevice, Graphics, Key,
+    MouseButton, PistonWindow, Texture, TextureSettings,
+};
+use std::collections::HashMap;
+use std::mem;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+// Most vertices passed to the graphics backend in one go, a multiple of 3 so
+// triangles are never split between batches.
+const BATCH_SIZE: usize = 1023;
+
+// Highest moves per second the speed slider goes up to.
+const SLIDER_MAX_MPS: u64 = 1_000_000_000;
+
+//-----------------------------------------------------------------------------
+// Changes asked for through the control panel, applied by the main loop.
+#[derive(Clone, Debug)]
+pub enum PanelAction {
+    TogglePause,
+    Step,
+    // Restart with a new, already validated, rule.
+    Reset(String),
+    // Carry on from the current grid with a new, already validated, rule.
+    ChangeRule(String),
+    SetSpeed(u64),
+    // Scrub to a position in the recent history or playback.
+    Scrub(usize),
+    SetColour(usize, Colour),
+    // Fork the run into a new window, with a new, already validated, rule.
+    Fork(String),
+}
+
+//-----------------------------------------------------------------------------
+// An egui window drawn over the grid with controls for the speed, pausing,
+// stepping, scrubbing through the history, the rule and the colours.
+// Piston's input events are translated into egui's and egui's output is
+// drawn as textured triangles through piston's own graphics, so the
+// existing event loop is kept. Events egui uses, e.g. clicks on the panel
+// or typing into the rule box, are reported as captured so they don't also
+// act on the grid.
+pub struct ControlPanel {
+    ctx: egui::Context,
+    visible: bool,
+    start: Instant,
+    events: Vec<egui::Event>,
+    modifiers: Modifiers,
+    cursor: Pos2,
+    rule: String,
+    error: Option<String>,
+    keep_grid: bool,
+    focus_rule: bool,
+    textures: HashMap<TextureId, (RgbaImage, G2dTexture)>,
+    texture_context: G2dTextureContext,
+    primitives: Vec<ClippedPrimitive>,
+    pending_free: Vec<TextureId>,
+}
+
+impl ControlPanel {
+    pub fn new(window: &mut PistonWindow, rule: &str) -> ControlPanel {
+        ControlPanel {
+            ctx: egui::Context::default(),
+            visible: true,
+            start: Instant::now(),
+            events: Vec::new(),
+            modifiers: Modifiers::default(),
+            cursor: Pos2::ZERO,
+            rule: rule.to_string(),
+            error: None,
+            keep_grid: false,
+            focus_rule: false,
+            textures: HashMap::new(),
+            texture_context: window.create_texture_context(),
+            primitives: Vec::new(),
+            pending_free: Vec::new(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Show the panel with the rule box ready to type into.
+    pub fn edit_rule(&mut self) {
+        self.visible = true;
+        self.focus_rule = true;
+    }
+
+    // Move to a newly opened window. The textures are recreated from
+    // scratch, which a new egui context does by sending its font atlas
+    // again on the next frame.
+    pub fn reattach(&mut self, window: &mut PistonWindow) {
+        self.ctx = egui::Context::default();
+        self.texture_context = window.create_texture_context();
+        self.textures.clear();
+        self.pending_free.clear();
+        self.primitives.clear();
+    }
+
+    // Pass an input event on to egui, returning true if egui has captured
+    // it so it should be ignored by the rest of the application.
+    pub fn handle_event<E: GenericEvent>(&mut self, e: &E) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        if let Some(pos) = e.mouse_cursor_args() {
+            self.cursor = Pos2::new(pos[0] as f32, pos[1] as f32);
+            self.events.push(egui::Event::PointerMoved(self.cursor));
+            return self.ctx.is_using_pointer();
+        }
+
+        if let Some(scroll) = e.mouse_scroll_args() {
+            self.events.push(egui::Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: egui::vec2(scroll[0] as f32, scroll[1] as f32),
+                modifiers: self.modifiers,
+            });
+            return self.ctx.is_pointer_over_area();
+        }
+
+        if let Some(button) = e.press_args() {
+            return self.button(button, true);
+        }
+
+        // Releases are never captured, so a drag started on the grid always
+        // ends there.
+        if let Some(button) = e.release_args() {
+            self.button(button, false);
+            return false;
+        }
+
+        if let Some(text) = e.text_args() {
+            let text: String = text.chars().filter(|c| !c.is_control()).collect();
+
+            if self.ctx.wants_keyboard_input() && !text.is_empty() {
+                self.events.push(egui::Event::Text(text));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Run the panel for a frame, returning the changes asked for. The
+    // simulation and current settings are shown as they are before any of
+    // the changes are applied. The timeline, if any, is the range of the
+    // history that can be scrubbed through and the current position in it,
+    // as (start, position, end).
+    pub fn frame(
+        &mut self,
+        window_size: [f64; 2],
+        sim: &Simulation,
+        mps: u64,
+        paused: bool,
+        can_reset: bool,
+        timeline: Option<(usize, usize, usize)>,
+    ) -> Vec<PanelAction> {
+        let mut actions = Vec::new();
+
+        if !self.visible {
+            self.primitives.clear();
+            return actions;
+        }
+
+        let size = egui::vec2(window_size[0] as f32, window_size[1] as f32);
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+            time: Some(self.start.elapsed().as_secs_f64()),
+            modifiers: self.modifiers,
+            events: mem::take(&mut self.events),
+            ..RawInput::default()
+        };
+
+        let ctx = self.ctx.clone();
+        let rule = &mut self.rule;
+        let error = &mut self.error;
+        let keep_grid = &mut self.keep_grid;
+        let focus_rule = mem::take(&mut self.focus_rule);
+
+        let output = ctx.run(input, |ctx| {
+            egui::Window::new("Controls")
+                .default_pos([size.x - 240.0, 8.0])
+                .resizable(false)
+                .show(ctx, |ui| {
+                    // There's no upper limit on the speed, but the slider
+                    // needs one, with a box to run as fast as possible.
+                    let mut unlimited = mps == UNLIMITED_MPS;
+                    let mut target = mps.min(SLIDER_MAX_MPS);
+                    let slider = egui::Slider::new(&mut target, 1..=SLIDER_MAX_MPS)
+                        .logarithmic(true)
+                        .text("moves/s");
+
+                    if ui.add_enabled(!unlimited, slider).changed() {
+                        actions.push(PanelAction::SetSpeed(target));
+                    }
+
+                    if ui.checkbox(&mut unlimited, "As fast as possible").changed() {
+                        actions.push(PanelAction::SetSpeed(if unlimited {
+                            UNLIMITED_MPS
+                        } else {
+                            SLIDER_MAX_MPS
+                        }));
+                    }
+
+                    if let Some((start, position, end)) = timeline {
+                        let mut target = position;
+                        let slider = egui::Slider::new(&mut target, start..=end.max(start + 1))
+                            .show_value(false)
+                            .text("history");
+
+                        if ui.add_enabled(end > start, slider).changed() {
+                            actions.push(PanelAction::Scrub(target));
+                        }
+                    }
+
+                    let mut reset = false;
+                    let mut fork = false;
+
+                    ui.horizontal(|ui| {
+                        if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                            actions.push(PanelAction::TogglePause);
+                        }
+
+                        if ui.add_enabled(paused, egui::Button::new("Step")).clicked() {
+                            actions.push(PanelAction::Step);
+                        }
+
+                        reset = ui
+                            .add_enabled(can_reset, egui::Button::new("Reset"))
+                            .clicked();
+
+                        fork = ui.button("Fork").clicked();
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Rule");
+                        let edit = ui.add_enabled(can_reset, egui::TextEdit::singleline(rule));
+
+                        if focus_rule && can_reset {
+                            edit.request_focus();
+                        }
+
+                        if edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            reset = true;
+                        }
+                    });
+
+                    ui.add_enabled(
+                        can_reset,
+                        egui::Checkbox::new(keep_grid, "Keep grid on rule change"),
+                    );
+
+                    // The rule is checked here so mistakes can be shown
+                    // alongside it.
+                    if reset {
+                        match config::validate_rule(rule.trim()) {
+                            Ok(valid) if *keep_grid => {
+                                *error = None;
+                                actions.push(PanelAction::ChangeRule(valid));
+                            }
+                            Ok(valid) => {
+                                *error = None;
+                                actions.push(PanelAction::Reset(valid));
+                            }
+                            Err(e) => *error = Some(e.to_string()),
+                        }
+                    }
+
+                    // A fork takes the rule in the box, if it can be
+                    // edited, leaving this run as it is.
+                    if fork && !can_reset {
+                        actions.push(PanelAction::Fork(sim.rule()));
+                    } else if fork {
+                        match config::validate_rule(rule.trim()) {
+                            Ok(valid) => {
+                                *error = None;
+                                actions.push(PanelAction::Fork(valid));
+                            }
+                            Err(e) => *error = Some(e.to_string()),
+                        }
+                    }
+
+                    if let Some(ref e) = error {
+                        ui.colored_label(Color32::LIGHT_RED, e);
+                    }
+
+                    ui.label("Colours");
+                    ui.horizontal_wrapped(|ui| {
+                        for (i, colour) in sim.colours().iter().enumerate() {
+                            let [r, g, b, _] = colour.to_rgba8();
+                            let mut rgb = [r, g, b];
+
+                            if ui.color_edit_button_srgb(&mut rgb).changed() {
+                                actions.push(PanelAction::SetColour(
+                                    i,
+                                    Colour {
+                                        r: f32::from(rgb[0]) / 255.0,
+                                        g: f32::from(rgb[1]) / 255.0,
+                                        b: f32::from(rgb[2]) / 255.0,
+                                        a: colour.a,
+                                    },
+                                ));
+                            }
+                        }
+                    });
+                });
+        });
+
+        // Textures freed last frame are no longer drawn, so can go now.
+        for id in self.pending_free.drain(..) {
+            self.textures.remove(&id);
+        }
+
+        for (id, delta) in output.textures_delta.set {
+            let ImageData::Color(ref image) = delta.image;
+            let patch = RgbaImage::from_fn(image.size[0] as u32, image.size[1] as u32, |x, y| {
+                let i = y as usize * image.size[0] + x as usize;
+                Rgba(image.pixels[i].to_srgba_unmultiplied())
+            });
+
+            // Partial updates are patched into the CPU copy of the texture,
+            // which is then uploaded whole.
+            match (delta.pos, self.textures.get_mut(&id)) {
+                (Some([x, y]), Some((canvas, texture))) => {
+                    imageops::replace(canvas, &patch, x as i64, y as i64);
+                    texture.update(&mut self.texture_context, canvas).unwrap();
+                }
+                _ => {
+                    let texture = Texture::from_image(
+                        &mut self.texture_context,
+                        &patch,
+                        &TextureSettings::new(),
+                    )
+                    .unwrap();
+                    self.textures.insert(id, (patch, texture));
+                }
+            }
+        }
+
+        self.pending_free = output.textures_delta.free;
+        self.primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        actions
+    }
+
+    // Draw the panel on top of whatever has already been drawn.
+    pub fn draw(&mut self, c: Context, g: &mut G2d, device: &mut GfxDevice) {
+        if !self.visible {
+            return;
+        }
+
+        self.texture_context.encoder.flush(device);
+
+        let m = c.transform;
+        let to_view = |p: Pos2| {
+            let (x, y) = (f64::from(p.x), f64::from(p.y));
+            [
+                (m[0][0] * x + m[0][1] * y + m[0][2]) as f32,
+                (m[1][0] * x + m[1][1] * y + m[1][2]) as f32,
+            ]
+        };
+
+        for primitive in self.primitives.iter() {
+            let mesh = match primitive.primitive {
+                Primitive::Mesh(ref mesh) => mesh,
+                Primitive::Callback(_) => continue,
+            };
+
+            let texture = match self.textures.get(&mesh.texture_id) {
+                Some((_, texture)) => texture,
+                None => continue,
+            };
+
+            let clip = primitive.clip_rect;
+            let draw_state = c.draw_state.scissor([
+                clip.min.x.max(0.0) as u32,
+                clip.min.y.max(0.0) as u32,
+                clip.width().max(0.0) as u32,
+                clip.height().max(0.0) as u32,
+            ]);
+
+            g.tri_list_uv_c(&draw_state, texture, |f| {
+                for batch in mesh.indices.chunks(BATCH_SIZE) {
+                    let vertices = batch.iter().map(|&i| &mesh.vertices[i as usize]);
+                    let positions: Vec<[f32; 2]> =
+                        vertices.clone().map(|v| to_view(v.pos)).collect();
+                    let uvs: Vec<[f32; 2]> = vertices.clone().map(|v| [v.uv.x, v.uv.y]).collect();
+                    let colours: Vec<[f32; 4]> = vertices
+                        .map(|v| {
+                            let [r, g, b, a] = v.color.to_srgba_unmultiplied();
+                            [
+                                f32::from(r) / 255.0,
+                                f32::from(g) / 255.0,
+                                f32::from(b) / 255.0,
+                                f32::from(a) / 255.0,
+                            ]
+                        })
+                        .collect();
+
+                    f(&positions, &uvs, &colours);
+                }
+            });
+        }
+    }
+
+    // Pass a button press or release on to egui, returning true if egui has
+    // captured it.
+    fn button(&mut self, button: Button, pressed: bool) -> bool {
+        match button {
+            Button::Mouse(mouse_button) => {
+                let button = match mouse_button {
+                    MouseButton::Left => egui::PointerButton::Primary,
+                    MouseButton::Right => egui::PointerButton::Secondary,
+                    MouseButton::Middle => egui::PointerButton::Middle,
+                    _ => return false,
+                };
+
+                self.events.push(egui::Event::PointerButton {
+                    pos: self.cursor,
+                    button,
+                    pressed,
+                    modifiers: self.modifiers,
+                });
+
+                pressed && self.ctx.is_pointer_over_area()
+            }
+            Button::Keyboard(key) => {
+                match key {
+                    Key::LShift | Key::RShift => self.modifiers.shift = pressed,
+                    Key::LCtrl | Key::RCtrl => {
+                        self.modifiers.ctrl = pressed;
+                        self.modifiers.command = pressed;
+                    }
+                    Key::LAlt | Key::RAlt => self.modifiers.alt = pressed,
+                    _ => {}
+                }
+
+                if let Some(key) = egui_key(key) {
+                    self.events.push(egui::Event::Key {
+                        key,
+                        physical_key: None,
+                        pressed,
+                        repeat: false,
+                        modifiers: self.modifiers,
+                    });
+                }
+
+                // Every key is captured while typing, so letters typed into
+                // the rule box don't also act as shortcuts.
+                pressed && self.ctx.wants_keyboard_input()
+            }
+            _ => false,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The egui key for the piston keys used in editing text.
+fn egui_key(key: Key) -> Option<egui::Key> {
+    match key {
+        Key::Backspace => Some(egui::Key::Backspace),
+        Key::Delete => Some(egui::Key::Delete),
+        Key::Return | Key::NumPadEnter => Some(egui::Key::Enter),
+        Key::Left => Some(egui::Key::ArrowLeft),
+        Key::Right => Some(egui::Key::ArrowRight),
+        Key::Up => Some(egui::Key::ArrowUp),
+        Key::Down => Some(egui::Key::ArrowDown),
+        Key::Home => Some(egui::Key::Home),
+        Key::End => Some(egui::Key::End),
+        Key::Tab => Some(egui::Key::Tab),
+        Key::Escape => Some(egui::Key::Escape),
+        Key::A => Some(egui::Key::A),
+        _ => None,
+    }
+}
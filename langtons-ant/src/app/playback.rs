@@ -0,0 +1,112 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::recording::Recordings;
+use langtons_ant::replay::Replay;
+use langtons_ant::Simulation;
+use log::info;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Plays back a move log in the window in place of running the simulation,
+// either forwards or, toggled with R, backwards. While paused the arrow keys
+// step through the log a change at a time.
+pub struct Playback {
+    replay: Replay,
+    reverse: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            reverse: false,
+        }
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.reverse = !self.reverse;
+        info!(
+            "Replay direction = {}",
+            if self.reverse {
+                "backwards"
+            } else {
+                "forwards"
+            }
+        );
+    }
+
+    // Play up to n changes in the current direction, stopping at either end
+    // of the log. Returns false once an end is reached.
+    pub fn play(&mut self, sim: &mut Simulation, n: u64, recordings: &mut Recordings) -> bool {
+        for _ in 0..n {
+            let more = if self.reverse {
+                self.replay.back(sim)
+            } else {
+                self.replay.forward(sim)
+            };
+
+            if !more {
+                return false;
+            }
+
+            recordings.capture(sim);
+        }
+
+        true
+    }
+
+    pub fn step_forward(&mut self, sim: &mut Simulation) {
+        self.replay.forward(sim);
+    }
+
+    pub fn step_back(&mut self, sim: &mut Simulation) {
+        self.replay.back(sim);
+    }
+
+    // Number of changes in the log, and the number applied so far.
+    pub fn len(&self) -> usize {
+        self.replay.len()
+    }
+
+    pub fn position(&self) -> usize {
+        self.replay.position()
+    }
+
+    // Jump to a position in the log.
+    pub fn seek(&mut self, sim: &mut Simulation, position: usize) {
+        self.replay.seek(sim, position);
+    }
+
+    // Go back up to n changes, returning the number undone.
+    pub fn rewind(&mut self, sim: &mut Simulation, n: u64) -> u64 {
+        let mut undone = 0;
+
+        while undone < n && self.replay.back(sim) {
+            undone += 1;
+        }
+
+        undone
+    }
+}
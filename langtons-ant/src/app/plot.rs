@@ -0,0 +1,202 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::hud::load_glyphs;
+use langtons_ant::stats::{Sample, Stats, StatsHistory};
+use langtons_ant::Simulation;
+use piston_window::{
+    line_from_to, rectangle, text, Context, G2d, GfxDevice, Glyphs, PistonWindow, Transformed,
+};
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+const FONT_SIZE: u32 = 12;
+const MARGIN: f64 = 8.0;
+
+// Fraction of the window's height taken by the plot, along its bottom.
+const HEIGHT_FRACTION: f64 = 0.3;
+
+// How often, in seconds, the statistics are sampled.
+const SAMPLE_PERIOD: f64 = 0.2;
+
+// Most samples plotted, enough for a point every couple of pixels across
+// a typical window.
+const MAX_SAMPLES: usize = 500;
+
+const DISTANCE_COLOUR: [f32; 4] = [0.3, 0.8, 1.0, 1.0];
+const AREA_COLOUR: [f32; 4] = [1.0, 0.6, 0.2, 1.0];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Live line chart of the first ant's distance from its start and the area
+// of the pattern's bounding box against the iteration, shown along the
+// bottom of the window and toggled with G. Each line is scaled to its own
+// maximum, so the switch from a chaotic pattern's slow, ragged growth to a
+// highway's steady climb stands out whatever the numbers. Samples are taken
+// while the plot is hidden too, so opening it shows the whole run.
+pub struct Plot {
+    glyphs: Glyphs,
+    visible: bool,
+    history: StatsHistory,
+    sample_start: Instant,
+}
+
+impl Plot {
+    pub fn new(window: &mut PistonWindow) -> Plot {
+        Plot {
+            glyphs: load_glyphs(window),
+            visible: false,
+            history: StatsHistory::new(MAX_SAMPLES),
+            sample_start: Instant::now(),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Move the glyph cache to a newly opened window.
+    pub fn reattach(&mut self, window: &mut PistonWindow) {
+        self.glyphs = load_glyphs(window);
+    }
+
+    // Sample the statistics once enough time has passed.
+    pub fn frame(&mut self, sim: &Simulation) {
+        if self.sample_start.elapsed().as_secs_f64() >= SAMPLE_PERIOD {
+            self.history.record(&Stats::measure(sim));
+            self.sample_start = Instant::now();
+        }
+    }
+
+    // Draw the plot along the bottom of the window, on top of whatever has
+    // already been drawn.
+    pub fn draw(&mut self, c: Context, g: &mut G2d, device: &mut GfxDevice) {
+        if !self.visible {
+            return;
+        }
+
+        let [width, height] = c.get_view_size();
+        let plot_height = (height * HEIGHT_FRACTION).round();
+        let top = height - plot_height;
+
+        rectangle(
+            [0.0, 0.0, 0.0, 0.8],
+            [0.0, top, width, plot_height],
+            c.transform,
+            g,
+        );
+
+        let samples = self.history.samples();
+
+        // The lines fill the area below the legend.
+        let area = [
+            MARGIN,
+            top + MARGIN + 2.0 * f64::from(FONT_SIZE),
+            width - 2.0 * MARGIN,
+            plot_height - 3.0 * MARGIN - 2.0 * f64::from(FONT_SIZE),
+        ];
+
+        let first = samples.first().map_or(0, |s| s.iteration);
+        let last = samples.last().map_or(0, |s| s.iteration);
+        let max_distance = samples.iter().map(|s| s.distance).fold(0.0, f64::max);
+        let max_area = samples.iter().map(|s| s.area).max().unwrap_or(0);
+
+        if area[2] > 0.0 && area[3] > 0.0 {
+            draw_line(samples, |s| s.distance, DISTANCE_COLOUR, area, c, g);
+            draw_line(samples, |s| s.area as f64, AREA_COLOUR, area, c, g);
+        }
+
+        let legend = [
+            (
+                format!("Distance (max {:.1})", max_distance),
+                DISTANCE_COLOUR,
+            ),
+            (format!("Box area (max {})", max_area), AREA_COLOUR),
+            (format!("N {} to {}", first, last), [1.0, 1.0, 1.0, 1.0]),
+        ];
+
+        let mut x = MARGIN;
+
+        for (label, colour) in legend.iter() {
+            let transform = c.transform.trans(x, top + MARGIN + f64::from(FONT_SIZE));
+
+            text(*colour, FONT_SIZE, label, &mut self.glyphs, transform, g).unwrap();
+            x += (label.chars().count() as f64 + 3.0) * f64::from(FONT_SIZE) * 0.6;
+        }
+
+        // Glyphs are uploaded to the cache texture lazily, so they must be
+        // flushed before the frame is presented.
+        self.glyphs.factory.encoder.flush(device);
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Draw a line through the samples' values in the area [x, y, width,
+// height], from the first iteration at the left to the last at the right
+// and from 0 at the bottom to the largest value at the top.
+fn draw_line<F: Fn(&Sample) -> f64>(
+    samples: &[Sample],
+    value: F,
+    colour: [f32; 4],
+    area: [f64; 4],
+    c: Context,
+    g: &mut G2d,
+) {
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) if last.iteration > first.iteration => {
+            (first.iteration, last.iteration)
+        }
+        _ => return,
+    };
+
+    let max = samples.iter().map(&value).fold(0.0, f64::max);
+
+    if max <= 0.0 {
+        return;
+    }
+
+    let [x, y, width, height] = area;
+    let span = (last - first) as f64;
+
+    let points: Vec<[f64; 2]> = samples
+        .iter()
+        .map(|s| {
+            [
+                x + (s.iteration - first) as f64 / span * width,
+                y + height - value(s) / max * height,
+            ]
+        })
+        .collect();
+
+    for pair in points.windows(2) {
+        line_from_to(colour, 1.0, pair[0], pair[1], c.transform, g);
+    }
+}
@@ -0,0 +1,59 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::RenderArgs;
+use langtons_ant::{export, ConfigError, GridStorage, Simulation};
+use log::{error, info};
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Render a loaded state to a PNG file at the requested scale, covering
+// every visited cell of an unbounded grid. Unlike screenshots from the
+// window, the image size is limited only by memory.
+pub fn run(args: &RenderArgs, sim: &Simulation) -> Result<(), ConfigError> {
+    let img = export::render_scaled(
+        sim,
+        sim.cells().extent(),
+        args.px_per_cell,
+        args.supersample,
+    )
+    .map_err(|reason| ConfigError::InvalidSetting {
+        name: "render",
+        reason,
+    })?;
+
+    info!(
+        "Rendering {} x {} pixels to {}...",
+        img.width(),
+        img.height(),
+        args.out.display()
+    );
+
+    match img.save(&args.out) {
+        Ok(()) => info!("Saved render to {}", args.out.display()),
+        Err(e) => error!("Failed to save render to {}: {}", args.out.display(), e),
+    }
+
+    Ok(())
+}
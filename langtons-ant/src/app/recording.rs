@@ -0,0 +1,280 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use langtons_ant::checkpoint::{self, CheckpointWatch};
+use langtons_ant::export;
+use langtons_ant::record::{GifRecorder, Recorder};
+use langtons_ant::replay::MoveLog;
+use langtons_ant::stats::StatsRecorder;
+use langtons_ant::symmetry::SymmetryWatch;
+use langtons_ant::timelapse::TimelapseRecorder;
+use langtons_ant::trace::TraceRecorder;
+use langtons_ant::video::VideoRecorder;
+use langtons_ant::{ConfigError, Simulation};
+use log::{error, info};
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Most moves made between writes to a move log, limiting the changes held
+// in memory when running headless.
+const MOVE_LOG_CHUNK: u64 = 100_000;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The recordings requested on the command line, along with any check of
+// the pattern's symmetry, which is reported as it changes, and any
+// checkpoints of the run's state, which are printed or checked against
+// those expected, and any vector image of the final pattern. A recording
+// that fails part way through is reported and dropped, leaving the rest
+// running.
+#[derive(Default)]
+pub struct Recordings {
+    recorders: Vec<Box<dyn Recorder>>,
+    moves: Option<MoveLog>,
+    symmetry: Option<SymmetryWatch>,
+    checkpoints: Option<CheckpointWatch>,
+    mismatched: bool,
+    svg: Option<PathBuf>,
+}
+
+impl Recordings {
+    // Start the recordings requested, with any move log starting from the
+    // simulation's current state. Fails if a recording can't be started or
+    // the checkpoints to verify can't be read.
+    pub fn from_cli(cli: &Cli, sim: &mut Simulation) -> Result<Recordings, ConfigError> {
+        let mut recorders: Vec<Box<dyn Recorder>> = Vec::new();
+
+        if let Some(path) = cli.record.as_ref() {
+            recorders.push(Box::new(GifRecorder::new(
+                path,
+                cli.record_every,
+                cli.record_scale,
+            )));
+        }
+
+        if let Some(path) = cli.timelapse.as_ref() {
+            let frames = cli.timelapse_frames.max(1);
+
+            recorders.push(Box::new(TimelapseRecorder::new(
+                path,
+                cli.iterations / frames as u64,
+                frames,
+                cli.timelapse_layout,
+                cli.record_scale,
+            )));
+        }
+
+        if let Some(path) = cli.video.as_ref() {
+            let (width, height) = cli.video_size;
+
+            let recorder = VideoRecorder::new(path, cli.record_every, width, height, cli.video_fps)
+                .map_err(|e| ConfigError::Write {
+                    name: "video",
+                    path: path.clone(),
+                    reason: format!("failed to start ffmpeg: {}", e),
+                })?;
+            recorders.push(Box::new(recorder));
+        }
+
+        if let Some(path) = cli.stats.as_ref() {
+            recorders.push(Box::new(StatsRecorder::new(path, cli.stats_every)));
+        }
+
+        if let Some(path) = cli.trace.as_ref() {
+            recorders.push(Box::new(TraceRecorder::new(path, cli.trace_every)));
+        }
+
+        let moves = cli
+            .record_moves
+            .as_ref()
+            .map(|path| {
+                MoveLog::create(path, sim).map_err(|e| ConfigError::Write {
+                    name: "move log",
+                    path: path.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .transpose()?;
+
+        let expected = cli
+            .checkpoint_verify
+            .as_ref()
+            .map(|path| {
+                let reason = match checkpoint::load(path) {
+                    Ok(expected) if !expected.is_empty() => return Ok(expected),
+                    Ok(_) => String::from("no checkpoints found"),
+                    Err(e) => e.to_string(),
+                };
+
+                Err(ConfigError::Load {
+                    name: "checkpoints",
+                    path: path.clone(),
+                    reason,
+                })
+            })
+            .transpose()?;
+
+        let checkpoints = Some(CheckpointWatch::new(
+            cli.checkpoint_hash_every,
+            expected.unwrap_or_default(),
+        ))
+        .filter(|_| cli.checkpoint_hash_every.is_some() || cli.checkpoint_verify.is_some());
+
+        Ok(Recordings {
+            recorders,
+            moves,
+            symmetry: cli.symmetry_every.map(SymmetryWatch::new),
+            checkpoints,
+            mismatched: false,
+            svg: cli.export_svg.clone(),
+        })
+    }
+
+    // Whether a move log is being written, which takes every change made
+    // to the simulation.
+    pub fn logging_moves(&self) -> bool {
+        self.moves.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recorders.is_empty()
+            && self.moves.is_none()
+            && self.symmetry.is_none()
+            && self.checkpoints.is_none()
+    }
+
+    // Whether a checkpoint has differed from the one expected.
+    pub fn mismatched(&self) -> bool {
+        self.mismatched
+    }
+
+    // Number of iterations until any recording next wants a frame, the
+    // move log should next be written, the symmetry next checked or the
+    // next checkpoint taken.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        let remaining = self
+            .recorders
+            .iter()
+            .map(|r| r.remaining(sim))
+            .chain(self.symmetry.iter().map(|s| s.remaining(sim)))
+            .chain(self.checkpoints.iter().map(|c| c.remaining(sim)))
+            .min()
+            .unwrap_or(u64::MAX);
+
+        match self.moves {
+            Some(_) => remaining.min(MOVE_LOG_CHUNK),
+            None => remaining,
+        }
+    }
+
+    pub fn capture(&mut self, sim: &mut Simulation) {
+        self.recorders.retain_mut(|r| match r.capture(sim) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Stopped recording to {}: {}", r.path().display(), e);
+                false
+            }
+        });
+
+        if let Some(log) = self.moves.as_mut() {
+            if let Err(e) = log.write(&sim.take_changes()) {
+                error!("Stopped move log {}: {}", log.path().display(), e);
+                sim.set_track_changes(false);
+                self.moves = None;
+            }
+        }
+
+        if let Some((old, new)) = self.symmetry.as_mut().and_then(|s| s.observe(sim)) {
+            if !old.is_symmetric() {
+                info!(
+                    "Symmetry appeared at iteration {}: {}",
+                    sim.iterations(),
+                    new
+                );
+            } else if !new.is_symmetric() {
+                info!(
+                    "Symmetry broke at iteration {}, was {}",
+                    sim.iterations(),
+                    old
+                );
+            } else {
+                info!(
+                    "Symmetry changed at iteration {}: {}, was {}",
+                    sim.iterations(),
+                    new,
+                    old
+                );
+            }
+        }
+
+        if let Some((taken, expected)) = self.checkpoints.as_mut().and_then(|c| c.observe(sim)) {
+            println!("{}", taken);
+
+            if let Some(expected) = expected.filter(|e| e.digest != taken.digest) {
+                error!(
+                    "Checkpoint mismatch at iteration {}: expected {}",
+                    expected.iteration, expected.digest
+                );
+                self.mismatched = true;
+            }
+        }
+    }
+
+    pub fn finish(&mut self, sim: &mut Simulation) {
+        if let Some(unchecked) = self.checkpoints.as_ref().map(|c| c.unchecked()) {
+            if unchecked > 0 {
+                info!("Expected checkpoints not reached = {}", unchecked);
+            }
+        }
+
+        if let Some(mut log) = self.moves.take() {
+            let finished = log.write(&sim.take_changes()).and_then(|_| log.finish());
+
+            match finished {
+                Ok(()) => info!("Saved move log to {}", log.path().display()),
+                Err(e) => error!("Failed to save move log to {}: {}", log.path().display(), e),
+            }
+        }
+
+        if let Some(path) = self.svg.take() {
+            match export::save_svg(sim, &path) {
+                Ok(()) => info!("Saved final pattern to {}", path.display()),
+                Err(e) => error!("Failed to save final pattern to {}: {}", path.display(), e),
+            }
+        }
+
+        for mut r in self.recorders.drain(..) {
+            info!("Finishing recording to {}...", r.path().display());
+
+            match r.finish() {
+                Ok(()) => info!("Saved recording to {}", r.path().display()),
+                Err(e) => error!("Failed to save recording to {}: {}", r.path().display(), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,567 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use clap::ValueEnum;
+use image::{Rgba, RgbaImage};
+use langtons_ant::colour::OBSTACLE_GREY;
+use langtons_ant::{
+    Extent, GridBackend, GridStorage, Palette, Simulation, BLANK, CHUNK_SIZE, OBSTACLE,
+};
+use piston_window::math::Matrix2d;
+use piston_window::{
+    clear, Filter, G2d, G2dTexture, G2dTextureContext, GfxDevice, PistonWindow, Texture,
+    TextureSettings, Transformed,
+};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Minimum number of cells of margin added around the grid extent when the
+// buffer has to grow to cover an unbounded grid.
+const GROW_MARGIN: usize = 64;
+
+// Colour of cells that have never been visited, and that trails fade to.
+pub const BACKGROUND: [u8; 4] = [255; 4];
+
+// Largest grid, along each side, drawn in a window. The grid is uploaded as
+// a single texture, which graphics cards limit in size.
+pub const MAX_TEXTURE_SIZE: usize = 8192;
+
+// Largest block of cells averaged into a single pixel when zoomed out, a
+// power of two.
+const MAX_LOD_BLOCK: u32 = 64;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A backend drawing the grid in a window. Each keeps a GridCanvas of the
+// cells and uploads it to the GPU as a texture when it changes. Drawing
+// itself needs each backend's own frame, e.g. piston's graphics, so is left
+// to the backends.
+pub trait Renderer {
+    // Bring the drawing up to date with the cells changed since the last
+    // call.
+    fn update(&mut self, sim: &mut Simulation);
+
+    // Redraw every cell on the next update, e.g. after the colours change.
+    fn redraw(&mut self);
+
+    // Change how the cells are coloured.
+    fn set_display(&mut self, display: Display, sim: &mut Simulation);
+
+    // Set the zoom the grid is drawn at, in window pixels per cell, which
+    // decides how many cells are averaged into each pixel of the texture.
+    fn set_zoom(&mut self, zoom: f64);
+}
+
+//-----------------------------------------------------------------------------
+// How the cells are coloured.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum DisplayMode {
+    /// Each cell in the colour of the rule step it's on.
+    Colours,
+    /// As colours, but fading cells toward the background the longer it's
+    /// been since an ant last left them, so the recent path stands out.
+    Trail,
+    /// Cells coloured by how many times the ants have visited them, on a
+    /// logarithmic scale from dark to bright.
+    Heatmap,
+}
+
+impl DisplayMode {
+    // The next mode, cycled through with a key.
+    pub fn next(self) -> DisplayMode {
+        match self {
+            DisplayMode::Colours => DisplayMode::Trail,
+            DisplayMode::Trail => DisplayMode::Heatmap,
+            DisplayMode::Heatmap => DisplayMode::Colours,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The display mode and its settings.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Display {
+    pub mode: DisplayMode,
+    // Number of iterations over which a trail fades out completely.
+    pub trail_length: u64,
+}
+
+//-----------------------------------------------------------------------------
+// What changed in a canvas on an update, and so needs uploading.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CanvasUpdate {
+    Unchanged,
+    Cells,
+    // The canvas was regrown to cover more of an unbounded grid, so needs
+    // a new texture.
+    Resized,
+}
+
+//-----------------------------------------------------------------------------
+// An off-screen RGBA buffer holding one pixel per cell, shared by the
+// renderers. Only the cells that changed since the last update are written,
+// so the buffer can be uploaded as a single texture and drawn scaled up to
+// the window, rather than issuing a draw call per cell. For unbounded grids
+// the buffer is regrown, with a margin, whenever the ant leaves it, and is
+// updated a chunk at a time, only drawing the chunks that hold cells or
+// have changed. Zoomed out until cells are smaller than a pixel, blocks of
+// cells are averaged into a second, smaller buffer, which is drawn instead,
+// so the texture drawn never has many more pixels than the window.
+pub struct GridCanvas {
+    region: Extent,
+    image: RgbaImage,
+    // Side of the blocks of cells averaged into each pixel of lod_image,
+    // 1 when cells are drawn individually and lod_image is unused.
+    lod: u32,
+    lod_image: RgbaImage,
+    full_update: bool,
+    display: Display,
+    // The highest visit count when the heatmap was last drawn in full,
+    // which sets the scale of its colours.
+    heat_max: u64,
+}
+
+impl GridCanvas {
+    pub fn new(sim: &mut Simulation, display: Display) -> GridCanvas {
+        let region = sim.cells().extent();
+
+        let mut canvas = GridCanvas {
+            region,
+            image: blank_image(region),
+            lod: 1,
+            lod_image: lod_image(region, 1),
+            full_update: true,
+            display,
+            heat_max: 0,
+        };
+
+        canvas.set_display(display, sim);
+        canvas
+    }
+
+    // The cells covered by the buffer.
+    pub fn region(&self) -> Extent {
+        self.region
+    }
+
+    // The buffer to draw, one pixel per block of lod() cells along each
+    // side.
+    pub fn image(&self) -> &RgbaImage {
+        if self.lod > 1 {
+            &self.lod_image
+        } else {
+            &self.image
+        }
+    }
+
+    pub fn lod(&self) -> u32 {
+        self.lod
+    }
+
+    // Choose the block size for drawing at a zoom, in window pixels per
+    // cell. Returns true if it changed, when the image is resized and needs
+    // a new texture.
+    pub fn set_zoom(&mut self, zoom: f64) -> bool {
+        let lod = lod_block(zoom);
+
+        if lod == self.lod {
+            return false;
+        }
+
+        self.lod = lod;
+        self.lod_image = lod_image(self.region, lod);
+
+        self.downsample(self.region);
+        true
+    }
+
+    pub fn redraw(&mut self) {
+        self.full_update = true;
+    }
+
+    // Change the display mode, starting to record the ants' visits if the
+    // mode needs them. Visits are then kept, so they aren't lost when
+    // switching modes back and forth.
+    pub fn set_display(&mut self, display: Display, sim: &mut Simulation) {
+        if display.mode != DisplayMode::Colours {
+            sim.set_track_visits(true);
+        }
+
+        self.display = display;
+        self.full_update = true;
+    }
+
+    // Write the cells changed since the last call to the buffer.
+    pub fn update(&mut self, sim: &mut Simulation) -> CanvasUpdate {
+        let dirty = sim.take_dirty();
+        let dirty_chunks = sim.take_dirty_chunks();
+        let extent = sim.cells().extent();
+        let mut update = CanvasUpdate::Cells;
+
+        if !covers(self.region, extent) {
+            let margin = GROW_MARGIN.max(extent.width.max(extent.height) / 4);
+            self.region = Extent {
+                x: extent.x - margin as i64,
+                y: extent.y - margin as i64,
+                width: extent.width + 2 * margin,
+                height: extent.height + 2 * margin,
+            };
+
+            self.image = blank_image(self.region);
+            self.full_update = true;
+            update = CanvasUpdate::Resized;
+            self.lod_image = lod_image(self.region, self.lod);
+        }
+
+        // Every trail fades a little with each move, not just the cells the
+        // ants changed.
+        if self.display.mode == DisplayMode::Trail && !dirty.is_empty() {
+            self.full_update = true;
+        }
+
+        // Likewise every heatmap colour changes when its scale does.
+        let heat_max = sim.visits().map_or(0, |v| v.max_count());
+
+        if self.display.mode == DisplayMode::Heatmap && heat_max != self.heat_max {
+            self.heat_max = heat_max;
+            self.full_update = true;
+        }
+
+        if self.full_update {
+            self.full_update = false;
+
+            // Chunks that were never visited, or have been emptied, are
+            // blank, unless shown in the heatmap by their visits.
+            match sim.cells() {
+                GridBackend::Sparse(g) if self.display.mode != DisplayMode::Heatmap => {
+                    self.image = blank_image(self.region);
+
+                    for chunk in g.chunk_extents() {
+                        self.update_region(sim, chunk);
+                    }
+                }
+                _ => self.update_region(sim, self.region),
+            }
+
+            self.downsample(self.region);
+        } else if dirty.is_empty() {
+            return CanvasUpdate::Unchanged;
+        } else if let Some(chunks) =
+            dirty_chunks.filter(|c| c.len() * CHUNK_SIZE * CHUNK_SIZE < dirty.len())
+        {
+            // Many moves in few chunks are quicker to redraw chunk by chunk.
+            for chunk in chunks {
+                self.update_region(sim, chunk);
+                self.downsample(chunk);
+            }
+        } else {
+            for &(x, y) in &dirty {
+                self.update_cell(sim, x, y);
+            }
+
+            // Each block is averaged once, however many of its cells
+            // changed.
+            if self.lod > 1 {
+                let lod = i64::from(self.lod);
+                let mut blocks: Vec<(i64, i64)> = dirty
+                    .iter()
+                    .filter(|&&(x, y)| self.region.contains(x, y))
+                    .map(|&(x, y)| ((x - self.region.x) / lod, (y - self.region.y) / lod))
+                    .collect();
+                blocks.sort_unstable();
+                blocks.dedup();
+
+                for (bx, by) in blocks {
+                    self.downsample(Extent {
+                        x: self.region.x + bx * lod,
+                        y: self.region.y + by * lod,
+                        width: 1,
+                        height: 1,
+                    });
+                }
+            }
+        }
+
+        update
+    }
+
+    // Average the blocks of cells overlapping a region into the level of
+    // detail buffer, if it's in use. Blocks at the far edges that overhang
+    // the buffer average only the cells they cover.
+    fn downsample(&mut self, region: Extent) {
+        if self.lod <= 1 {
+            return;
+        }
+
+        let lod = i64::from(self.lod);
+        let (width, height) = (self.region.width as i64, self.region.height as i64);
+        let bx0 = ((region.x - self.region.x).max(0)) / lod;
+        let by0 = ((region.y - self.region.y).max(0)) / lod;
+        let bx1 = (region.x + region.width as i64 - self.region.x).min(width) + lod - 1;
+        let by1 = (region.y + region.height as i64 - self.region.y).min(height) + lod - 1;
+
+        for by in by0..by1 / lod {
+            for bx in bx0..bx1 / lod {
+                let mut sum = [0u32; 4];
+                let mut count = 0;
+
+                for y in by * lod..((by + 1) * lod).min(height) {
+                    for x in bx * lod..((bx + 1) * lod).min(width) {
+                        let pixel = self.image.get_pixel(x as u32, y as u32);
+
+                        for (s, c) in sum.iter_mut().zip(pixel.0.iter()) {
+                            *s += u32::from(*c);
+                        }
+
+                        count += 1;
+                    }
+                }
+
+                let average = sum.map(|s| ((s + count / 2) / count) as u8);
+                self.lod_image
+                    .put_pixel(bx as u32, by as u32, Rgba(average));
+            }
+        }
+    }
+
+    // Write the colours of the cells in a region, where it overlaps the
+    // buffer.
+    fn update_region(&mut self, sim: &Simulation, region: Extent) {
+        let x0 = region.x.max(self.region.x);
+        let y0 = region.y.max(self.region.y);
+        let x1 = (region.x + region.width as i64).min(self.region.x + self.region.width as i64);
+        let y1 = (region.y + region.height as i64).min(self.region.y + self.region.height as i64);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.update_cell(sim, x, y);
+            }
+        }
+    }
+
+    // Write a single cell's colour into the buffer, unvisited cells are
+    // written in the background colour.
+    fn update_cell(&mut self, sim: &Simulation, x: i64, y: i64) {
+        if !self.region.contains(x, y) {
+            return;
+        }
+
+        let cell = sim.cells().get(x, y);
+
+        let colour = match cell {
+            BLANK => BACKGROUND,
+            OBSTACLE => OBSTACLE_GREY.to_rgba8(),
+            _ => sim.colours()[cell].to_rgba8(),
+        };
+
+        let pixel = match self.display.mode {
+            // Obstacles are always shown, whatever the mode.
+            _ if cell == OBSTACLE => colour,
+            DisplayMode::Colours => colour,
+            DisplayMode::Trail => {
+                // Cells no ant has left since recording began are fully
+                // faded.
+                let age = sim.visits().and_then(|v| v.get(x, y)).map_or(1.0, |visit| {
+                    sim.iterations().saturating_sub(visit.last) as f64
+                        / self.display.trail_length.max(1) as f64
+                });
+
+                fade(colour, age.min(1.0))
+            }
+            DisplayMode::Heatmap => match sim.visits().and_then(|v| v.get(x, y)) {
+                Some(visit) => {
+                    let scale = ((self.heat_max + 1) as f64).ln();
+                    let t = ((visit.count + 1) as f64).ln() / scale;
+                    Palette::heat().sample(t as f32).to_rgba8()
+                }
+                None => BACKGROUND,
+            },
+        };
+
+        self.image.put_pixel(
+            (x - self.region.x) as u32,
+            (y - self.region.y) as u32,
+            Rgba(pixel),
+        );
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Draws the grid with piston, as a single texture uploaded from the canvas.
+pub struct GridRenderer {
+    canvas: GridCanvas,
+    texture: G2dTexture,
+    texture_context: G2dTextureContext,
+}
+
+impl Renderer for GridRenderer {
+    fn update(&mut self, sim: &mut Simulation) {
+        match self.canvas.update(sim) {
+            CanvasUpdate::Unchanged => {}
+            CanvasUpdate::Cells => self
+                .texture
+                .update(&mut self.texture_context, self.canvas.image())
+                .unwrap(),
+            CanvasUpdate::Resized => {
+                self.texture = create_texture(&mut self.texture_context, self.canvas.image())
+            }
+        }
+    }
+
+    fn redraw(&mut self) {
+        self.canvas.redraw();
+    }
+
+    fn set_display(&mut self, display: Display, sim: &mut Simulation) {
+        self.canvas.set_display(display, sim);
+    }
+
+    fn set_zoom(&mut self, zoom: f64) {
+        if self.canvas.set_zoom(zoom) {
+            self.texture = create_texture(&mut self.texture_context, self.canvas.image());
+        }
+    }
+}
+
+impl GridRenderer {
+    pub fn new(window: &mut PistonWindow, sim: &mut Simulation, display: Display) -> GridRenderer {
+        let canvas = GridCanvas::new(sim, display);
+        let mut texture_context = window.create_texture_context();
+        let texture = create_texture(&mut texture_context, canvas.image());
+
+        GridRenderer {
+            canvas,
+            texture,
+            texture_context,
+        }
+    }
+
+    // The cells covered by the texture.
+    pub fn region(&self) -> Extent {
+        self.canvas.region()
+    }
+
+    // Move the texture to a newly opened window.
+    pub fn reattach(&mut self, window: &mut PistonWindow) {
+        self.texture_context = window.create_texture_context();
+        self.texture = create_texture(&mut self.texture_context, self.canvas.image());
+    }
+
+    // Draw the texture using a transform from grid square coordinates to
+    // window pixels.
+    pub fn draw(&mut self, transform: Matrix2d, g: &mut G2d, device: &mut GfxDevice) {
+        clear([0.5, 0.5, 0.5, 1.0], g);
+        self.draw_grid(transform, g, device);
+    }
+
+    // Draw the texture as draw does, without first clearing the window, so
+    // several grids can share it.
+    pub fn draw_grid(&mut self, transform: Matrix2d, g: &mut G2d, device: &mut GfxDevice) {
+        let region = self.canvas.region();
+        let lod = f64::from(self.canvas.lod());
+
+        self.texture_context.encoder.flush(device);
+        piston_window::image(
+            &self.texture,
+            transform
+                .trans(region.x as f64, region.y as f64)
+                .scale(lod, lod),
+            g,
+        );
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Create a blank buffer covering the given region.
+fn blank_image(region: Extent) -> RgbaImage {
+    RgbaImage::from_pixel(region.width as u32, region.height as u32, Rgba(BACKGROUND))
+}
+
+//-----------------------------------------------------------------------------
+// Create the buffer holding a region's cells averaged in blocks of the given
+// side, empty if cells are drawn individually.
+fn lod_image(region: Extent, lod: u32) -> RgbaImage {
+    if lod > 1 {
+        RgbaImage::new(
+            region.width.div_ceil(lod as usize) as u32,
+            region.height.div_ceil(lod as usize) as u32,
+        )
+    } else {
+        RgbaImage::new(0, 0)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The side of the blocks of cells averaged into each pixel at a zoom, in
+// window pixels per cell: the largest power of two cells fitting in a
+// pixel, so cells are only averaged once they're smaller than a pixel.
+fn lod_block(zoom: f64) -> u32 {
+    let mut lod = 1;
+
+    while lod < MAX_LOD_BLOCK && f64::from(lod * 2) * zoom <= 1.0 {
+        lod *= 2;
+    }
+
+    lod
+}
+
+//-----------------------------------------------------------------------------
+// Blend a colour toward the background, by an amount from 0, leaving it
+// unchanged, to 1, giving the background.
+fn fade(colour: [u8; 4], amount: f64) -> [u8; 4] {
+    let mut faded = colour;
+
+    for (c, b) in faded.iter_mut().zip(BACKGROUND.iter()) {
+        *c = (f64::from(*c) + (f64::from(*b) - f64::from(*c)) * amount).round() as u8;
+    }
+
+    faded
+}
+
+//-----------------------------------------------------------------------------
+// Create a texture from a canvas image, drawn without smoothing so cells
+// keep sharp edges.
+pub fn create_texture(texture_context: &mut G2dTextureContext, image: &RgbaImage) -> G2dTexture {
+    Texture::from_image(
+        texture_context,
+        image,
+        &TextureSettings::new().filter(Filter::Nearest),
+    )
+    .unwrap()
+}
+
+//-----------------------------------------------------------------------------
+// Does the outer region completely cover the inner one?
+fn covers(outer: Extent, inner: Extent) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.width as i64 <= outer.x + outer.width as i64
+        && inner.y + inner.height as i64 <= outer.y + outer.height as i64
+}
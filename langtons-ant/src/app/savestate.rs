@@ -0,0 +1,112 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::autosave::Autosave;
+use langtons_ant::Simulation;
+use log::{debug, error, info};
+use piston_window::Key;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Number of quick save slots, one for each of the keys 1 to 9.
+const NUM_SLOTS: usize = 9;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Quick save slots holding full copies of the simulation in memory, stored
+// with Ctrl and a number key and restored with the number key alone, so
+// experiments can branch from an interesting moment without writing files.
+#[derive(Default)]
+pub struct Savestates {
+    slots: [Option<Simulation>; NUM_SLOTS],
+}
+
+impl Savestates {
+    // Store a copy of the simulation in the slot for a number key, ignoring
+    // other keys.
+    pub fn store(&mut self, key: Key, sim: &Simulation) {
+        if let Some(slot) = slot_index(key) {
+            self.slots[slot] = Some(sim.clone());
+            info!("Stored iteration {} in slot {}", sim.iterations(), slot + 1);
+        }
+    }
+
+    // A copy of the simulation stored in the slot for a number key, if
+    // there is one.
+    pub fn restore(&self, key: Key) -> Option<Simulation> {
+        let slot = slot_index(key)?;
+
+        match self.slots[slot].as_ref() {
+            Some(sim) => {
+                info!(
+                    "Restored iteration {} from slot {}",
+                    sim.iterations(),
+                    slot + 1
+                );
+                Some(sim.clone())
+            }
+            None => {
+                info!("Slot {} is empty", slot + 1);
+                None
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The slot for a number key, 1 for the first slot up to 9 for the last.
+pub fn slot_index(key: Key) -> Option<usize> {
+    match key {
+        Key::D1 | Key::NumPad1 => Some(0),
+        Key::D2 | Key::NumPad2 => Some(1),
+        Key::D3 | Key::NumPad3 => Some(2),
+        Key::D4 | Key::NumPad4 => Some(3),
+        Key::D5 | Key::NumPad5 => Some(4),
+        Key::D6 | Key::NumPad6 => Some(5),
+        Key::D7 | Key::NumPad7 => Some(6),
+        Key::D8 | Key::NumPad8 => Some(7),
+        Key::D9 | Key::NumPad9 => Some(8),
+        _ => None,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Autosave the simulation if a save is due, as set with --autosave.
+pub fn autosave(autosave: &mut Autosave, sim: &Simulation) {
+    match autosave.observe(sim) {
+        Some(Ok(path)) => debug!(
+            "autosave path={} iteration={}",
+            path.display(),
+            sim.iterations()
+        ),
+        Some(Err(e)) => error!("{}", e),
+        None => {}
+    }
+}
@@ -0,0 +1,87 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use langtons_ant::script::{Script, ScriptAction};
+use langtons_ant::{ConfigError, Simulation};
+use log::{error, info};
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The script given on the command line, called as the run goes. A script
+// that fails is reported and dropped, leaving the run going without it.
+pub struct ScriptHooks {
+    path: PathBuf,
+    script: Option<Script>,
+}
+
+impl ScriptHooks {
+    pub fn from_cli(cli: &Cli) -> Result<Option<ScriptHooks>, ConfigError> {
+        let path = match cli.script.as_ref() {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        match Script::load(path, cli.script_every) {
+            Ok(script) => Ok(Some(ScriptHooks {
+                path: path.clone(),
+                script: Some(script),
+            })),
+            Err(e) => Err(ConfigError::Load {
+                name: "script",
+                path: path.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    // Number of iterations until the script is next due to be called.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        self.script
+            .as_ref()
+            .map_or(u64::MAX, |script| script.remaining(sim))
+    }
+
+    // Call the script if it's due, returning true if it stopped the run.
+    pub fn run(&mut self, sim: &mut Simulation) -> bool {
+        let result = match self.script.as_mut() {
+            Some(script) => script.run(sim),
+            None => return false,
+        };
+
+        match result {
+            Ok(ScriptAction::Continue) => false,
+            Ok(ScriptAction::Stop) => {
+                info!("Script stopped the run at iteration {}", sim.iterations());
+                true
+            }
+            Err(e) => {
+                error!("Stopped script {}: {}", self.path.display(), e);
+                self.script = None;
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,424 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli;
+use super::recording::Recordings;
+use super::speed::{Speed, UPDATES_PER_SECOND};
+use super::stepper::Stepper;
+use langtons_ant::config::UNLIMITED_MPS;
+use langtons_ant::stats::Stats;
+use langtons_ant::{export, GridStorage, Simulation};
+use log::info;
+use serde_json::{json, Value};
+use std::io;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Largest pixels per cell a PNG of the grid can be asked for.
+const MAX_PNG_SCALE: u32 = 32;
+
+// Largest PNG of the grid that can be asked for, in pixels, about 256 MB of
+// RGBA while it's encoded.
+const MAX_PNG_PIXELS: u64 = 1 << 26;
+
+// Most moves a single step request can ask for. They're made before the
+// reply is sent, blocking other requests and updates, so this keeps a
+// request to about a second at most.
+const MAX_STEP_MOVES: u64 = 1 << 24;
+
+// Most messages queued for a streaming client before it's judged too slow
+// to keep up and disconnected, about two seconds of updates.
+const STREAM_BACKLOG: usize = 120;
+
+// Page served at / that mirrors the simulation from /stream.
+const STREAM_PAGE: &str = include_str!("../../web/stream.html");
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A response to a request: its status code, content type and body.
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn json(value: Value) -> Reply {
+        Reply {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn png(body: Vec<u8>) -> Reply {
+        Reply {
+            status: 200,
+            content_type: "image/png",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Reply {
+        Reply {
+            status,
+            ..Reply::json(json!({ "error": message }))
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The run as controlled over HTTP: whether it's paused, its speed, and
+// whether it's been asked to quit.
+struct Control {
+    paused: bool,
+    speed: Speed,
+    quit: bool,
+}
+
+//-----------------------------------------------------------------------------
+// Clients streaming the run over websockets from /stream. Each is sent the
+// whole grid when it connects, then after every update the cells changed
+// and the ants, as JSON messages. Every client has its own thread writing
+// to its socket, so a slow client can't hold up the run. Changed cells are
+// only tracked while there are clients, as it stops the simulation using
+// its fast path.
+#[derive(Default)]
+struct Streams {
+    clients: Vec<SyncSender<String>>,
+    last_iteration: u64,
+}
+
+impl Streams {
+    // Complete a websocket handshake, then send the new client the grid.
+    fn connect(&mut self, request: Request, sim: &mut Simulation) {
+        let key = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Sec-WebSocket-Key"))
+            .map(|h| h.value.to_string());
+
+        let key = match key {
+            Some(key) => key,
+            None => return respond(request, Reply::error(400, "expected a websocket upgrade")),
+        };
+
+        let response = Response::empty(101)
+            .with_header(header("Upgrade", "websocket"))
+            .with_header(header("Connection", "Upgrade"))
+            .with_header(header(
+                "Sec-WebSocket-Accept",
+                &derive_accept_key(key.as_bytes()),
+            ));
+
+        let stream = request.upgrade("websocket", response);
+        let (sender, receiver) = mpsc::sync_channel::<String>(STREAM_BACKLOG);
+
+        thread::spawn(move || {
+            let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+            for message in receiver {
+                if socket.send(Message::text(message)).is_err() {
+                    return;
+                }
+            }
+
+            let _ = socket.close(None);
+        });
+
+        if self.clients.is_empty() {
+            sim.set_track_dirty(true);
+        }
+
+        let mut snapshot = export::grid_json(sim);
+        snapshot["type"] = json!("snapshot");
+        snapshot["iteration"] = json!(sim.iterations());
+        snapshot["ants"] = export::ants_json(sim.ants());
+
+        if sender.try_send(snapshot.to_string()).is_ok() {
+            self.clients.push(sender);
+        }
+    }
+
+    // Send every client the changes since the last update, if any,
+    // dropping clients that have gone away or fallen too far behind.
+    fn send_changes(&mut self, sim: &mut Simulation) {
+        if self.clients.is_empty() {
+            return;
+        }
+
+        let delta = sim.take_delta();
+
+        if delta.is_empty() && sim.iterations() == self.last_iteration {
+            return;
+        }
+
+        self.last_iteration = sim.iterations();
+
+        let mut message = export::delta_json(&delta, sim.iterations());
+        message["type"] = json!("delta");
+        let message = message.to_string();
+
+        self.clients
+            .retain(|client| client.try_send(message.clone()).is_ok());
+
+        if self.clients.is_empty() {
+            sim.set_track_dirty(false);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the simulation without a window, controlled over HTTP by requests to
+// the given address until one asks it to quit. Between requests the
+// simulation runs at its speed, unless paused, so dashboards can drive it
+// and poll its state, grid and stats. Every response is JSON, apart from
+// the grid as a PNG and the page at /, which mirrors the run in a browser
+// using the websocket stream at /stream.
+pub fn run(
+    sim: &mut Simulation,
+    mut stepper: Stepper,
+    recordings: &mut Recordings,
+    addr: &str,
+    mps: u64,
+) -> io::Result<()> {
+    let server = Server::http(addr).map_err(|e| io::Error::other(e.to_string()))?;
+
+    let mut control = Control {
+        paused: false,
+        speed: Speed::new(mps),
+        quit: false,
+    };
+    let mut streams = Streams::default();
+
+    info!("Serving on http://{}", addr);
+    recordings.capture(sim);
+
+    let update = Duration::from_millis(1000 / UPDATES_PER_SECOND);
+    let mut last_update = Instant::now();
+
+    while !control.quit {
+        // Answer requests until the next update is due.
+        let deadline = last_update + update;
+
+        while let Some(request) =
+            server.recv_timeout(deadline.saturating_duration_since(Instant::now()))?
+        {
+            if request.url().split('?').next() == Some("/stream") {
+                streams.connect(request, sim);
+                continue;
+            }
+
+            let reply = handle(&request, sim, &mut stepper, recordings, &mut control);
+            respond(request, reply);
+        }
+
+        let dt = last_update.elapsed().as_secs_f64();
+        last_update = Instant::now();
+
+        if !control.paused {
+            let moves = control.speed.moves(dt);
+
+            if stepper.advance(sim, moves, recordings, &mut control.speed) {
+                control.paused = true;
+            }
+        }
+
+        streams.send_changes(sim);
+    }
+
+    info!("Stopped serving at iteration {}", sim.iterations());
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// Decide the reply to a request, carrying out any command it gives.
+fn handle(
+    request: &Request,
+    sim: &mut Simulation,
+    stepper: &mut Stepper,
+    recordings: &mut Recordings,
+    control: &mut Control,
+) -> Reply {
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (request.url(), ""),
+    };
+
+    let count = |name: &str, default: u64| match query_value(query, name) {
+        Some(value) => cli::parse_count(value),
+        None => Ok(default),
+    };
+
+    match (request.method(), path) {
+        (Method::Get, "/") => Reply {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: STREAM_PAGE.as_bytes().to_vec(),
+        },
+        (Method::Get, "/status") => Reply::json(status(sim, control)),
+        (Method::Get, "/stats") => Reply::json(Stats::measure(sim).to_json()),
+        (Method::Get, "/grid.json") => Reply::json(export::grid_json(sim)),
+        (Method::Get, "/grid.png") => match count("scale", 1) {
+            Ok(scale) => {
+                let scale = scale.clamp(1, u64::from(MAX_PNG_SCALE));
+                let extent = sim.cells().extent();
+                let pixels =
+                    (extent.width as u64 * scale).saturating_mul(extent.height as u64 * scale);
+
+                if pixels > MAX_PNG_PIXELS {
+                    return Reply::error(
+                        400,
+                        &format!(
+                            "a {} pixel image is larger than the limit of {} pixels",
+                            pixels, MAX_PNG_PIXELS
+                        ),
+                    );
+                }
+
+                match export::encode_png(sim, scale as u32) {
+                    Ok(png) => Reply::png(png),
+                    Err(e) => Reply::error(500, &e.to_string()),
+                }
+            }
+            Err(e) => Reply::error(400, &e),
+        },
+        (Method::Post, "/start") => {
+            control.paused = false;
+            Reply::json(status(sim, control))
+        }
+        (Method::Post, "/stop") => {
+            control.paused = true;
+            Reply::json(status(sim, control))
+        }
+        (Method::Post, "/step") => match count("n", 1) {
+            Ok(n) if n > MAX_STEP_MOVES => Reply::error(
+                400,
+                &format!(
+                    "at most {} moves can be made at once, not {}",
+                    MAX_STEP_MOVES, n
+                ),
+            ),
+            Ok(n) => {
+                step(sim, stepper, recordings, n);
+                Reply::json(status(sim, control))
+            }
+            Err(e) => Reply::error(400, &e),
+        },
+        (Method::Post, "/speed") => match query_value(query, "mps").map(cli::parse_mps) {
+            Some(Ok(mps)) if mps >= 1 => {
+                control.speed.set(mps);
+                Reply::json(status(sim, control))
+            }
+            Some(Ok(mps)) => Reply::error(
+                400,
+                &format!("moves per second must be at least 1, not {}", mps),
+            ),
+            Some(Err(e)) => Reply::error(400, &e),
+            None => Reply::json(status(sim, control)),
+        },
+        (Method::Post, "/quit") => {
+            control.quit = true;
+            Reply::json(status(sim, control))
+        }
+        (_, "/status") | (_, "/stats") | (_, "/grid.json") | (_, "/grid.png") => {
+            Reply::error(405, "use GET")
+        }
+        (_, "/start") | (_, "/stop") | (_, "/step") | (_, "/speed") | (_, "/quit") => {
+            Reply::error(405, "use POST")
+        }
+        _ => Reply::error(404, "no such endpoint"),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Make up to n moves straight away, e.g. while paused, stopping early if the
+// ant stalls, or a highway, cycle or script stops the run. Moves are made
+// in slices that fit an update's time budget, as in a normal update, until
+// no more can be made.
+fn step(sim: &mut Simulation, stepper: &mut Stepper, recordings: &mut Recordings, n: u64) {
+    let end = sim.iterations().saturating_add(n);
+
+    while sim.iterations() < end && !sim.is_stalled() {
+        let before = sim.iterations();
+        let mut budget = Speed::new(UNLIMITED_MPS);
+
+        if stepper.advance(sim, end - before, recordings, &mut budget) || sim.iterations() <= before
+        {
+            break;
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The state of the run as JSON.
+fn status(sim: &Simulation, control: &Control) -> Value {
+    json!({
+        "iteration": sim.iterations(),
+        "rule": sim.rule(),
+        "mps": control.speed.mps(),
+        "paused": control.paused,
+        "stalled": sim.is_stalled(),
+        "ants": export::ants_json(sim.ants()),
+    })
+}
+
+//-----------------------------------------------------------------------------
+// The value of a parameter in a URL query string, e.g. "n=100&scale=2".
+fn query_value<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+//-----------------------------------------------------------------------------
+// Send a reply, ignoring clients that have gone away.
+fn respond(request: Request, reply: Reply) {
+    let response = Response::from_data(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header("Content-Type", reply.content_type));
+
+    let _ = request.respond(response);
+}
+
+//-----------------------------------------------------------------------------
+// A response header, from a name and value known to be valid.
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
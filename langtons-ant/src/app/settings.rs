@@ -0,0 +1,73 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use directories::ProjectDirs;
+use langtons_ant::Config;
+use log::error;
+use std::fs;
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Name of the file the last run's settings are kept in, within the
+// platform's config directory.
+const LAST_RUN_FILE: &str = "last-run.toml";
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Where the last run's settings are kept, e.g.
+// ~/.config/langtons-ant/last-run.toml on Linux or
+// %APPDATA%\langtons-ant\config\last-run.toml on Windows. None if the
+// platform has no config directory.
+fn last_run_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "langtons-ant").map(|dirs| dirs.config_dir().join(LAST_RUN_FILE))
+}
+
+//-----------------------------------------------------------------------------
+// Read the settings of the last run started from the start menu, if there
+// was one and its settings are still valid.
+pub fn load_last_run() -> Option<Config> {
+    let text = fs::read_to_string(last_run_path()?).ok()?;
+    Config::from_toml(&text).ok()
+}
+
+//-----------------------------------------------------------------------------
+// Keep a run's settings to offer again in the start menu next time.
+pub fn save_last_run(config: &Config) {
+    let path = match last_run_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let saved = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, config.to_toml()));
+
+    if let Err(e) = saved {
+        error!("Failed to save settings to {}: {}", path.display(), e);
+    }
+}
@@ -0,0 +1,172 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::config::UNLIMITED_MPS;
+use std::time::Instant;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Rate at which the window is updated and redrawn.
+pub const UPDATES_PER_SECOND: u64 = 60;
+
+// Longest period, in seconds, of owed moves carried between updates. Stops
+// the simulation trying to catch up all at once after a stall, e.g. while
+// the window is being dragged.
+const MAX_BACKLOG: f64 = 0.25;
+
+// Longest time, in seconds, an update may spend making moves, leaving the
+// rest of the update period for drawing so the frame rate holds up however
+// fast the simulation is asked to run.
+pub const UPDATE_BUDGET: f64 = 0.75 / UPDATES_PER_SECOND as f64;
+
+// Number of batches of moves an update's time budget is split into, so an
+// update overruns its budget by at most a batch.
+const BATCHES_PER_BUDGET: f64 = 8.0;
+
+// Fewest and most moves in a batch.
+const MIN_BATCH: u64 = 64;
+const MAX_BATCH: u64 = 1 << 24;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Paces the simulation at a given number of moves per second, independent
+// of the update rate. Each update adds the moves owed for the elapsed time
+// to an accumulator and the whole moves are taken out of it, so fractional
+// moves carry over to the next update rather than being lost. There's no
+// upper limit: the moves are made in batches, checking the time between
+// batches, until they're done or the update's time budget is spent, so the
+// simulation runs as fast as the machine allows while the frame rate holds.
+// The batches are sized from the rate achieved on earlier updates, so the
+// clock is checked a few times an update whether moves take nanoseconds or
+// milliseconds.
+pub struct Speed {
+    mps: u64,
+    pending: f64,
+    batch: u64,
+}
+
+impl Speed {
+    pub fn new(mps: u64) -> Speed {
+        Speed {
+            mps: mps.clamp(1, UNLIMITED_MPS),
+            pending: 0.0,
+            batch: MIN_BATCH,
+        }
+    }
+
+    pub fn mps(&self) -> u64 {
+        self.mps
+    }
+
+    pub fn set(&mut self, mps: u64) {
+        self.mps = mps.clamp(1, UNLIMITED_MPS);
+    }
+
+    pub fn faster(&mut self) {
+        self.mps = self.mps.saturating_mul(2).min(UNLIMITED_MPS);
+    }
+
+    pub fn fastest(&mut self) {
+        self.mps = UNLIMITED_MPS;
+    }
+
+    pub fn slower(&mut self) {
+        self.mps = (self.mps / 2).max(1);
+    }
+
+    // Number of moves to make for an update covering dt seconds.
+    pub fn moves(&mut self, dt: f64) -> u64 {
+        let limit = self.mps as f64 * MAX_BACKLOG;
+        self.pending = (self.pending + self.mps as f64 * dt).min(limit.max(1.0));
+
+        let moves = self.pending.floor();
+        self.pending -= moves;
+        moves as u64
+    }
+
+    // Hand back moves an update didn't have time to make, so they're made
+    // on later updates, within the backlog limit.
+    pub fn defer(&mut self, moves: u64) {
+        let limit = self.mps as f64 * MAX_BACKLOG;
+        self.pending = (self.pending + moves as f64).min(limit.max(1.0));
+    }
+
+    // Make up to n moves for an update, with a function making a batch of
+    // the given number of moves and returning true to stop early. Moves
+    // there wasn't time for are handed back for later updates. Returns true
+    // if stopped early.
+    pub fn advance<F: FnMut(u64) -> bool>(&mut self, n: u64, mut make: F) -> bool {
+        let start = Instant::now();
+        let mut made = 0;
+        let mut stopped = false;
+
+        while made < n {
+            let batch = self.batch.min(n - made);
+            stopped = make(batch);
+            made += batch;
+
+            if stopped {
+                break;
+            }
+
+            if start.elapsed().as_secs_f64() > UPDATE_BUDGET {
+                self.defer(n - made);
+                break;
+            }
+        }
+
+        // Size the next update's batches from the rate just achieved.
+        let elapsed = start.elapsed().as_secs_f64();
+
+        if elapsed > 0.0 && made > 0 {
+            let rate = made as f64 / elapsed;
+            self.batch =
+                ((rate * UPDATE_BUDGET / BATCHES_PER_BUDGET) as u64).clamp(MIN_BATCH, MAX_BATCH);
+        }
+
+        stopped
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A number of moves per second to show, shortened with k, M or G, e.g.
+// "2.5M", or "max" for an unlimited speed.
+pub fn format_mps(mps: f64) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "k")];
+
+    if mps >= UNLIMITED_MPS as f64 {
+        return String::from("max");
+    }
+
+    match UNITS.iter().find(|&&(scale, _)| mps >= 10.0 * scale) {
+        Some(&(scale, unit)) => format!("{:.1}{}", mps / scale, unit),
+        None => format!("{:.0}", mps),
+    }
+}
@@ -0,0 +1,124 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::Simulation;
+use log::{debug, info};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The modes the app moves between. Setup while the rule and sizes are being
+// chosen, running and paused while the window is open and finished once
+// every ant has stalled or the window has been closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    Setup,
+    Running,
+    Paused,
+    Finished,
+}
+
+//-----------------------------------------------------------------------------
+// Moves the app between its states. When the app was started without any
+// arguments, choosing each setting in the start menu, a finished run can go
+// back to setup to try a different rule rather than ending the program.
+pub struct AppController {
+    state: AppState,
+    interactive: bool,
+}
+
+impl AppController {
+    pub fn new(interactive: bool) -> AppController {
+        AppController {
+            state: AppState::Setup,
+            interactive,
+        }
+    }
+
+    pub fn state(&self) -> AppState {
+        self.state
+    }
+
+    // Are moves held back? A finished run has nothing left to move.
+    pub fn is_paused(&self) -> bool {
+        self.state != AppState::Running
+    }
+
+    // Start running, e.g. once set up or after the simulation has been
+    // restarted.
+    pub fn start(&mut self) {
+        self.set(AppState::Running);
+    }
+
+    // Pause or resume the run. Finished runs stay finished.
+    pub fn toggle_pause(&mut self) {
+        match self.state {
+            AppState::Running => self.set(AppState::Paused),
+            AppState::Paused => self.set(AppState::Running),
+            _ => {}
+        }
+    }
+
+    // Pause the run, e.g. on finding a highway or cycle.
+    pub fn pause(&mut self) {
+        if self.state == AppState::Running {
+            self.set(AppState::Paused);
+        }
+    }
+
+    // Finish the run once every ant has stalled. A finished run whose ants
+    // can move again, e.g. after rewinding, is paused rather than finished.
+    pub fn observe(&mut self, sim: &Simulation) {
+        match self.state {
+            AppState::Running | AppState::Paused if sim.is_stalled() => {
+                self.set(AppState::Finished);
+                info!("Run finished at iteration {}", sim.iterations());
+
+                if self.interactive {
+                    info!("Close the window to set up another run");
+                }
+            }
+            AppState::Finished if !sim.is_stalled() => self.set(AppState::Paused),
+            _ => {}
+        }
+    }
+
+    // Finish the run as the window has been closed.
+    pub fn close(&mut self) {
+        self.set(AppState::Finished);
+    }
+
+    // Go back to setup from a finished interactive run.
+    pub fn set_up_again(&mut self) {
+        if self.interactive && self.state == AppState::Finished {
+            self.set(AppState::Setup);
+        }
+    }
+
+    fn set(&mut self, state: AppState) {
+        if state != self.state {
+            debug!("state from={:?} to={:?}", self.state, state);
+            self.state = state;
+        }
+    }
+}
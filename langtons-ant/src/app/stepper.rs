@@ -0,0 +1,348 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::cycle::{CycleAction, CycleWatch};
+use super::highway::{HighwayAction, HighwayWatch};
+use super::logging::{self, PerfCounter};
+use super::playback::Playback;
+use super::recording::Recordings;
+use super::savestate;
+use super::script::ScriptHooks;
+use super::speed::Speed;
+use langtons_ant::autosave::Autosave;
+use langtons_ant::timeline::Timeline;
+use langtons_ant::{GridStorage, Simulation};
+use log::{info, warn};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Number of recent changes kept so the simulation can be rewound and
+// scrubbed through.
+const HISTORY_LENGTH: usize = 500_000;
+
+// Number of changes between keyframes of the history, copies of the grid
+// that scrubbing can jump to. Big grids are copied less often, at most once
+// every as many moves as they have cells, so copying them costs no more than
+// making the moves.
+const KEYFRAME_INTERVAL: usize = 25_000;
+
+// Fraction of the recent history, or of a move log being played back,
+// jumped over by each press of the scrubbing keys.
+pub const SCRUB_FRACTION: f64 = 0.05;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Advances the simulation for the interactive displays, the window and the
+// terminal, either playing back a move log or running the simulation while
+// keeping its recent history as a timeline, watching for highways and
+// cycles and calling any script. The simulation can also be run in reverse,
+// un-building the pattern, which the history, highways, cycles, scripts and
+// recordings ignore.
+#[derive(Default)]
+pub struct Stepper {
+    pub playback: Option<Playback>,
+    pub history: Option<Timeline>,
+    highway: Option<HighwayWatch>,
+    cycle: Option<CycleWatch>,
+    script: Option<ScriptHooks>,
+    reversing: bool,
+    logging_moves: bool,
+    perf: PerfCounter,
+    stalled: bool,
+    autosave: Option<Autosave>,
+}
+
+impl Stepper {
+    // Set up for the simulation's current state. Recent changes, undone with
+    // backspace, aren't kept during playback or while logging moves, as the
+    // log takes every change and can't represent them being undone. Cycle
+    // detection starts from the current state. Any script isn't called
+    // during playback.
+    pub fn new(
+        sim: &mut Simulation,
+        playback: Option<Playback>,
+        cli: Option<&Cli>,
+        recordings: &Recordings,
+        script: Option<ScriptHooks>,
+    ) -> Stepper {
+        let script = script.filter(|_| playback.is_none());
+
+        let mut stepper = Stepper {
+            playback,
+            history: None,
+            highway: cli.and_then(HighwayWatch::from_cli),
+            cycle: cli.and_then(CycleWatch::from_cli),
+            script,
+            reversing: false,
+            logging_moves: recordings.logging_moves(),
+            perf: PerfCounter::new(sim),
+            stalled: sim.is_stalled(),
+            autosave: cli.and_then(|c| c.autosave.clone()).map(Autosave::new),
+        };
+
+        if cli.is_some_and(|c| c.reverse) {
+            stepper.toggle_reverse(sim);
+        }
+
+        if stepper.playback.is_none() && !recordings.logging_moves() {
+            stepper.reset_history(sim);
+        }
+
+        if let Some(cycle) = stepper.cycle.as_mut() {
+            cycle.observe(sim);
+        }
+
+        // The script is first called before any moves are made.
+        if let Some(script) = stepper.script.as_mut() {
+            script.run(sim);
+        }
+
+        stepper
+    }
+
+    // Start keeping recent changes afresh, e.g. after the simulation has
+    // been replaced.
+    pub fn reset_history(&mut self, sim: &mut Simulation) {
+        sim.set_track_changes(true);
+        let extent = sim.cells().extent();
+        let interval = KEYFRAME_INTERVAL.max(extent.width * extent.height);
+        self.history = Some(Timeline::new(sim, HISTORY_LENGTH, interval));
+    }
+
+    // Make up to n moves, or play n changes of a move log, capturing any
+    // recordings. Returns true if a highway or cycle was found, or the
+    // script stopped the run, and the run should pause, which stops the
+    // moves early. Moves that don't fit in the update's time budget are
+    // handed back to the speed for later updates.
+    pub fn advance(
+        &mut self,
+        sim: &mut Simulation,
+        n: u64,
+        recordings: &mut Recordings,
+        speed: &mut Speed,
+    ) -> bool {
+        // The moves of the previous updates are counted, and the ants logged
+        // once they've all stalled. Any autosave due is made between
+        // updates.
+        self.perf.observe(sim);
+
+        if sim.is_stalled() != self.stalled {
+            self.stalled = !self.stalled;
+
+            if self.stalled {
+                logging::log_stalls(sim);
+            }
+        }
+
+        if let Some(autosave) = self.autosave.as_mut() {
+            savestate::autosave(autosave, sim);
+        }
+
+        if let Some(playback) = self.playback.as_mut() {
+            speed.advance(n, |batch| !playback.play(sim, batch, recordings));
+            return false;
+        }
+
+        if self.reversing {
+            return speed.advance(n, |batch| self.reverse(sim, batch));
+        }
+
+        let mut fast = false;
+
+        let stopped = speed.advance(n, |batch| {
+            for _ in 0..batch {
+                sim.step();
+
+                // Changes the script makes are kept to undo with the move's.
+                let stopped = self.script.as_mut().is_some_and(|s| s.run(sim));
+
+                if let Some(history) = self.history.as_mut() {
+                    history.record(sim);
+                }
+
+                recordings.capture(sim);
+
+                if stopped {
+                    return true;
+                }
+
+                match self.highway.as_mut().and_then(|h| h.observe(sim)) {
+                    Some(HighwayAction::Stop) => return true,
+                    Some(HighwayAction::Fast) => fast = true,
+                    _ => {}
+                }
+
+                if self.cycle.as_mut().and_then(|c| c.observe(sim)) == Some(CycleAction::Stop) {
+                    return true;
+                }
+            }
+
+            false
+        });
+
+        if fast {
+            speed.fastest();
+        }
+
+        stopped
+    }
+
+    // Undo up to n moves while running in reverse, returning true, and
+    // turning reverse off, if the simulation can't be reversed any further.
+    fn reverse(&mut self, sim: &mut Simulation, n: u64) -> bool {
+        for _ in 0..n {
+            if !sim.unstep() {
+                info!(
+                    "Reversed to iteration {}, no further moves can be undone",
+                    sim.iterations()
+                );
+                self.set_reversing(sim, false);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Make a single move, or play a single change, while paused. While
+    // running in reverse a single move is undone instead.
+    pub fn step_forward(&mut self, sim: &mut Simulation) {
+        match self.playback.as_mut() {
+            Some(playback) => playback.step_forward(sim),
+            None if self.reversing => {
+                self.reverse(sim, 1);
+            }
+            None => {
+                sim.step();
+
+                if let Some(script) = self.script.as_mut() {
+                    script.run(sim);
+                }
+            }
+        }
+    }
+
+    // Undo a single change of a move log while paused.
+    pub fn step_back(&mut self, sim: &mut Simulation) {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.step_back(sim);
+        }
+    }
+
+    // Reverse the direction of playback, or otherwise start or stop running
+    // the simulation in reverse, if its moves can be undone. Reversing isn't
+    // possible while logging moves, as the log can't represent it.
+    pub fn toggle_reverse(&mut self, sim: &mut Simulation) {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.toggle_reverse();
+        } else if self.reversing {
+            self.set_reversing(sim, false);
+        } else if self.logging_moves {
+            warn!("Reversing is unavailable while logging moves");
+        } else {
+            match sim.reversible() {
+                Ok(()) => self.set_reversing(sim, true),
+                Err(reason) => warn!("Can't reverse: {}", reason),
+            }
+        }
+    }
+
+    // The recent history can't follow moves being undone, so is started
+    // afresh whenever the direction changes.
+    fn set_reversing(&mut self, sim: &mut Simulation, reversing: bool) {
+        self.reversing = reversing;
+
+        if self.history.is_some() {
+            self.reset_history(sim);
+        }
+
+        info!(
+            "Run direction = {}",
+            if reversing { "backwards" } else { "forwards" }
+        );
+    }
+
+    // Undo up to n moves, either of the playback or of the simulation using
+    // its recent history, returning the number undone. None if neither is
+    // available, i.e. while logging moves.
+    pub fn rewind(&mut self, sim: &mut Simulation, n: u64) -> Option<u64> {
+        match (self.playback.as_mut(), self.history.as_mut()) {
+            (Some(playback), _) => Some(playback.rewind(sim, n)),
+            (None, Some(history)) => Some(history.rewind(sim, n)),
+            (None, None) => None,
+        }
+    }
+
+    // The range of positions that can be scrubbed through and the current
+    // one, as (start, position, end), of the playback or of the recent
+    // history. None while logging moves.
+    pub fn timeline(&self) -> Option<(usize, usize, usize)> {
+        match (self.playback.as_ref(), self.history.as_ref()) {
+            (Some(playback), _) => Some((0, playback.position(), playback.len())),
+            (None, Some(history)) => Some((history.start(), history.position(), history.end())),
+            (None, None) => None,
+        }
+    }
+
+    // Move to a position in the playback or recent history, returning
+    // false if neither is available.
+    pub fn scrub(&mut self, sim: &mut Simulation, position: usize) -> bool {
+        match (self.playback.as_mut(), self.history.as_mut()) {
+            (Some(playback), _) => playback.seek(sim, position),
+            (None, Some(history)) => {
+                history.seek(sim, position);
+            }
+            (None, None) => return false,
+        }
+
+        true
+    }
+
+    // Move a fraction of the way through the playback or recent history,
+    // backwards if negative, returning false if neither is available.
+    pub fn scrub_by(&mut self, sim: &mut Simulation, fraction: f64) -> bool {
+        match self.timeline() {
+            Some((start, position, end)) => {
+                let step = ((end - start) as f64 * fraction.abs()).ceil() as usize;
+                let target = if fraction < 0.0 {
+                    position.saturating_sub(step).max(start)
+                } else {
+                    position + step
+                };
+
+                self.scrub(sim, target)
+            }
+            None => false,
+        }
+    }
+
+    // Can the simulation be edited or replaced? Not during playback, as the
+    // log holds every change.
+    pub fn is_editable(&self) -> bool {
+        self.playback.is_none()
+    }
+}
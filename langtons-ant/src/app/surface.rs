@@ -0,0 +1,185 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::gpu::Backend;
+use super::render::create_texture;
+use super::speed::{Speed, UPDATES_PER_SECOND};
+use super::tui::RendererMode;
+use clap::ValueEnum;
+use langtons_ant::config;
+use langtons_ant::random;
+use langtons_ant::surface::{CubeSphere, Projection};
+use langtons_ant::{ConfigError, Palette};
+use log::info;
+use piston_window::*;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Colour of the square marking the ant.
+const ANT_COLOUR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The surface the ant walks on.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum Surface {
+    /// A flat grid, with edges handled by the boundary mode. Wrapping
+    /// makes it a torus.
+    Plane,
+    /// The six faces of a cube joined at their edges, a closed surface
+    /// without any boundary.
+    CubeSphere,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the ant on a cube-sphere in a window of its own, drawn unwrapped into
+// 2D. Space pauses, the right arrow key steps while paused, + and - change
+// the speed and V switches between the cube's net and a map of the sphere.
+pub fn run(cli: &Cli) -> Result<(), ConfigError> {
+    if cli.headless || cli.renderer != RendererMode::Window || cli.backend != Backend::Piston {
+        return Err(ConfigError::InvalidSetting {
+            name: "surface",
+            reason: "the cube-sphere only runs in the piston window".to_string(),
+        });
+    }
+
+    let mps = config::validate_mps(cli.mps)?;
+    let grid_size = config::validate_grid_size(cli.grid)?;
+    let square_size = config::validate_square_size(cli.square)?;
+
+    let seed = cli.seed.unwrap_or_else(random::random_seed);
+    let mut sphere =
+        CubeSphere::with_seed(&cli.rule, grid_size as usize, seed).map_err(|reason| {
+            ConfigError::InvalidSetting {
+                name: "rule",
+                reason,
+            }
+        })?;
+
+    if let Some(palette) = cli.palette.as_ref() {
+        let colours = Palette::parse(palette)
+            .and_then(|p| p.colours(sphere.num_colours()))
+            .map_err(|reason| ConfigError::InvalidPalette {
+                palette: palette.to_string(),
+                reason,
+            })?;
+
+        sphere.set_colours(colours);
+    }
+
+    info!("");
+    info!("Rule = {}", sphere.rule());
+    info!("Seed = {}", sphere.seed());
+    info!("Moves per second = {}", mps);
+    info!("Face size (number of squares) = {}", sphere.size());
+
+    // The net is four faces wide and three high, fitted to the width the
+    // grid would have on a plane.
+    let dim = config::window_size(grid_size, square_size);
+    let mut window: PistonWindow =
+        WindowSettings::new("Langton's Ant on a Cube-Sphere", [dim, dim * 3 / 4])
+            .exit_on_esc(true)
+            .resizable(true)
+            .build()
+            .unwrap();
+
+    let mut evs = window.get_event_settings();
+    evs.set_ups(UPDATES_PER_SECOND);
+    evs.set_max_fps(UPDATES_PER_SECOND);
+    window.set_event_settings(evs);
+
+    let mut speed = Speed::new(mps);
+    let mut paused = false;
+    let mut projection = Projection::default();
+
+    let mut texture_context = window.create_texture_context();
+    let mut image = sphere.render(projection);
+    let mut texture = create_texture(&mut texture_context, &image);
+    let mut shown = Some((projection, sphere.iterations()));
+
+    while let Some(e) = window.next() {
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                Key::Space => paused = !paused,
+                Key::Right if paused => sphere.step(),
+                Key::Equals | Key::Plus | Key::NumPadPlus => speed.faster(),
+                Key::Minus | Key::NumPadMinus => speed.slower(),
+                Key::V => {
+                    projection = projection.next();
+                    info!("Projection = {}", projection.name());
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(args) = e.update_args() {
+            if !paused {
+                let moves = speed.moves(args.dt);
+                speed.advance(moves, |batch| {
+                    (0..batch).for_each(|_| sphere.step());
+                    false
+                });
+            }
+        }
+
+        if e.render_args().is_some() && shown != Some((projection, sphere.iterations())) {
+            // The two projections have different sizes, so each switch
+            // needs a new texture.
+            let resized = shown.map(|(p, _)| p) != Some(projection);
+            image = sphere.render(projection);
+
+            if resized {
+                texture = create_texture(&mut texture_context, &image);
+            } else {
+                texture.update(&mut texture_context, &image).unwrap();
+            }
+
+            shown = Some((projection, sphere.iterations()));
+        }
+
+        window.draw_2d(&e, |c, g, device| {
+            let [width, height] = c.get_view_size();
+            let scale = (width / image.width() as f64).min(height / image.height() as f64);
+            let transform = c.transform.scale(scale, scale);
+
+            texture_context.encoder.flush(device);
+            clear([0.5, 0.5, 0.5, 1.0], g);
+            piston_window::image(&texture, transform, g);
+
+            let [x, y] = sphere.ant_pixel(projection);
+            rectangle(ANT_COLOUR, [x - 0.5, y - 0.5, 1.0, 1.0], transform, g);
+        });
+    }
+
+    info!("Stopped after {} iterations", sphere.iterations());
+
+    Ok(())
+}
@@ -0,0 +1,367 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::recording::Recordings;
+use super::speed::{self, Speed};
+use super::stepper::{Stepper, SCRUB_FRACTION};
+use clap::ValueEnum;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use langtons_ant::{export, rle, GridStorage, Simulation, BLANK};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Rate at which the terminal is redrawn. Much lower than the window's, as
+// every frame is written out in full.
+const FRAMES_PER_SECOND: u64 = 30;
+
+// Most grid cells covered by each character when zoomed out.
+const MAX_ZOOM: i64 = 64;
+
+// Colour drawn outside a bounded grid.
+const OUTSIDE: Color = Color::Rgb {
+    r: 128,
+    g: 128,
+    b: 128,
+};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Where the simulation is drawn.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub enum RendererMode {
+    /// A window drawn with piston.
+    Window,
+    /// The terminal, using coloured block characters, e.g. over SSH.
+    Tui,
+}
+
+//-----------------------------------------------------------------------------
+// Puts the terminal into raw mode on an alternate screen, restoring it when
+// dropped so it's left usable even if the run panics.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> io::Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The part of the grid shown in the terminal. Each character shows two cells
+// one above the other, using the upper half block character with the top
+// cell as its foreground colour and the bottom cell as its background, so
+// the cells come out roughly square. When zoomed out each character covers
+// several cells and shows the first of each block.
+struct View {
+    centre_x: i64,
+    centre_y: i64,
+    zoom: i64,
+    follow: bool,
+}
+
+impl View {
+    fn new(sim: &Simulation) -> View {
+        let extent = sim.cells().extent();
+
+        View {
+            centre_x: extent.x + (extent.width / 2) as i64,
+            centre_y: extent.y + (extent.height / 2) as i64,
+            zoom: 1,
+            follow: false,
+        }
+    }
+
+    // Move the view by a fraction of the screen, dx and dy being -1, 0 or 1.
+    fn pan(&mut self, dx: i64, dy: i64, columns: u16, rows: u16) {
+        self.follow = false;
+        self.centre_x += dx * (i64::from(columns) / 8).max(1) * self.zoom;
+        self.centre_y += dy * (i64::from(rows) / 4).max(1) * self.zoom;
+    }
+
+    fn zoom_in(&mut self) {
+        self.zoom = (self.zoom / 2).max(1);
+    }
+
+    fn zoom_out(&mut self) {
+        self.zoom = (self.zoom * 2).min(MAX_ZOOM);
+    }
+
+    // Write the visible part of the grid to the terminal, above the given
+    // number of status lines.
+    fn draw<W: Write>(
+        &mut self,
+        out: &mut W,
+        sim: &Simulation,
+        columns: u16,
+        rows: u16,
+    ) -> io::Result<()> {
+        if self.follow {
+            let ant = sim.ant();
+            self.centre_x = ant.pos_x;
+            self.centre_y = ant.pos_y;
+        }
+
+        let left = self.centre_x - i64::from(columns) * self.zoom / 2;
+        let top = self.centre_y - i64::from(rows) * self.zoom;
+
+        for row in 0..rows {
+            queue!(out, cursor::MoveTo(0, row))?;
+
+            let y = top + 2 * i64::from(row) * self.zoom;
+            let mut colours = None;
+
+            for column in 0..columns {
+                let x = left + i64::from(column) * self.zoom;
+                let pair = (cell_colour(sim, x, y), cell_colour(sim, x, y + self.zoom));
+
+                // Colours are only sent when they change along the row.
+                if colours != Some(pair) {
+                    queue!(out, SetForegroundColor(pair.0), SetBackgroundColor(pair.1))?;
+                    colours = Some(pair);
+                }
+
+                queue!(out, Print('\u{2580}'))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the simulation drawn in the terminal until the user quits, with
+// keyboard controls along the lines of the window's. Messages that would
+// otherwise be printed are shown on the status line.
+pub fn run(
+    sim: &mut Simulation,
+    mut stepper: Stepper,
+    recordings: &mut Recordings,
+    cli: &Cli,
+    mps: u64,
+) -> io::Result<()> {
+    let mut speed = Speed::new(mps);
+    let mut view = View::new(sim);
+    let mut paused = false;
+    let mut message = String::new();
+    let frame = Duration::from_millis(1000 / FRAMES_PER_SECOND);
+
+    recordings.capture(sim);
+
+    let _guard = TerminalGuard::new()?;
+    let mut out = io::BufWriter::new(io::stdout());
+    let mut last_update = Instant::now();
+
+    loop {
+        let (columns, rows) = terminal::size()?;
+        let grid_rows = rows.saturating_sub(1);
+
+        // Wait for input until the next frame is due.
+        let deadline = last_update + frame;
+
+        while event::poll(deadline.saturating_duration_since(Instant::now()))? {
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                _ => continue,
+            };
+
+            if is_quit(key) {
+                return Ok(());
+            }
+
+            match key.code {
+                KeyCode::Char(' ') => paused = !paused,
+                KeyCode::Char('.') if paused => stepper.step_forward(sim),
+                KeyCode::Char(',') if paused => stepper.step_back(sim),
+                KeyCode::Backspace => {
+                    message = match stepper.rewind(sim, cli.rewind) {
+                        Some(undone) => {
+                            format!("Rewound {} moves to iteration {}", undone, sim.iterations())
+                        }
+                        None => String::from("Rewinding is unavailable while logging moves"),
+                    };
+                }
+                KeyCode::Char('r') => stepper.toggle_reverse(sim),
+                KeyCode::Char(c @ '[') | KeyCode::Char(c @ ']') => {
+                    let fraction = if c == '[' {
+                        -SCRUB_FRACTION
+                    } else {
+                        SCRUB_FRACTION
+                    };
+
+                    paused = true;
+                    message = if stepper.scrub_by(sim, fraction) {
+                        format!("Scrubbed to iteration {}", sim.iterations())
+                    } else {
+                        String::from("Scrubbing is unavailable while logging moves")
+                    };
+                }
+                KeyCode::Char('s') => message = save_simulation(sim, &cli.save_format),
+                KeyCode::Char('p') => message = save_screenshot(sim, cli.screenshot_scale),
+                KeyCode::Char('+') | KeyCode::Char('=') => speed.faster(),
+                KeyCode::Char('-') => speed.slower(),
+                KeyCode::Char('x') => view.zoom_in(),
+                KeyCode::Char('z') => view.zoom_out(),
+                KeyCode::Char('f') => view.follow = !view.follow,
+                KeyCode::Home | KeyCode::Char('c') => view = View::new(sim),
+                KeyCode::Left => view.pan(-1, 0, columns, grid_rows),
+                KeyCode::Right => view.pan(1, 0, columns, grid_rows),
+                KeyCode::Up => view.pan(0, -1, columns, grid_rows),
+                KeyCode::Down => view.pan(0, 1, columns, grid_rows),
+                _ => {}
+            }
+        }
+
+        let dt = last_update.elapsed().as_secs_f64();
+        last_update = Instant::now();
+
+        if !paused && stepper.advance(sim, speed.moves(dt), recordings, &mut speed) {
+            paused = true;
+        }
+
+        view.draw(&mut out, sim, columns, grid_rows)?;
+
+        let state = if paused {
+            " PAUSED"
+        } else if sim.is_stalled() {
+            " STALLED"
+        } else {
+            ""
+        };
+
+        let status = format!(
+            "N {}  Rule {}  {} moves/s  Zoom 1:{}{}  {}",
+            sim.iterations(),
+            sim.rule(),
+            speed::format_mps(speed.mps() as f64),
+            view.zoom,
+            state,
+            if message.is_empty() {
+                "(q quit, space pause, arrows pan, z/x zoom, +/- speed)"
+            } else {
+                &message
+            }
+        );
+
+        queue!(
+            out,
+            ResetColor,
+            cursor::MoveTo(0, grid_rows),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            Print(
+                status
+                    .chars()
+                    .take(usize::from(columns))
+                    .collect::<String>()
+            )
+        )?;
+
+        out.flush()?;
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Is the key one that ends the run? Raw mode stops Ctrl+C interrupting the
+// process, so it's handled here too.
+fn is_quit(key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => true,
+        KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The terminal colour of a cell, blank cells are white, obstacles dark grey
+// and cells outside a bounded grid grey.
+fn cell_colour(sim: &Simulation, x: i64, y: i64) -> Color {
+    if !sim.cells().contains(x, y) {
+        return OUTSIDE;
+    }
+
+    match sim.cells().get(x, y) {
+        BLANK => Color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        },
+        cell => {
+            let [r, g, b, _] = sim.colour_map().cell_colour(cell).to_rgba8();
+            Color::Rgb { r, g, b }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Save the simulation state as with the S key in the window, returning a
+// message describing the result.
+fn save_simulation(sim: &Simulation, format: &str) -> String {
+    let path = format!("langtons-ant-{}.{}", sim.iterations(), format);
+
+    let saved = match format {
+        "rle" => rle::save(sim, &path),
+        _ => sim.save(&path),
+    };
+
+    match saved {
+        Ok(()) => format!("Saved state to {}", path),
+        Err(e) => format!("ERROR - Failed to save state to {}: {}", path, e),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Save a PNG screenshot as with the P key in the window, returning a
+// message describing the result.
+fn save_screenshot(sim: &Simulation, px_per_cell: u32) -> String {
+    let path = format!("langtons-ant-{}.png", sim.iterations());
+
+    match export::save_png(sim, &path, px_per_cell) {
+        Ok(()) => format!("Saved screenshot to {}", path),
+        Err(e) => format!("ERROR - Failed to save screenshot to {}: {}", path, e),
+    }
+}
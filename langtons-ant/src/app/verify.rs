@@ -0,0 +1,67 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::VerifyArgs;
+use langtons_ant::replay::{Replay, ReplayDifference};
+use langtons_ant::ConfigError;
+use log::{error, info};
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Compare two move logs, e.g. of the same run recorded on different
+// machines, printing their hashes and where they first differ. Returns
+// whether they record the same run.
+pub fn run(args: &VerifyArgs) -> Result<bool, ConfigError> {
+    let a = load(&args.a)?;
+    let b = load(&args.b)?;
+
+    info!("{}  {}", a.hash(), args.a.display());
+    info!("{}  {}", b.hash(), args.b.display());
+
+    match a.first_difference(&b) {
+        None => info!("Replays are identical, {} changes", a.len()),
+        Some(ReplayDifference::Initial) => error!("Replays start from different states"),
+        Some(ReplayDifference::Change(i)) => error!(
+            "Replays differ from change {} of {} and {}",
+            i,
+            a.len(),
+            b.len()
+        ),
+    }
+
+    Ok(a.hash() == b.hash())
+}
+
+//-----------------------------------------------------------------------------
+// Load a move log, keeping only its changes.
+fn load(path: &Path) -> Result<Replay, ConfigError> {
+    Replay::load(path)
+        .map(|(_, replay)| replay)
+        .map_err(|e| ConfigError::Load {
+            name: "move log",
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })
+}
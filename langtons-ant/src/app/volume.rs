@@ -0,0 +1,244 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::cli::Cli;
+use super::gpu::Backend;
+use super::render::create_texture;
+use super::speed::{Speed, UPDATES_PER_SECOND};
+use super::surface::Surface;
+use super::tui::RendererMode;
+use image::RgbaImage;
+use langtons_ant::config;
+use langtons_ant::cubic::{Axis, Lattice, MAX_LATTICE_SIZE};
+use langtons_ant::random;
+use langtons_ant::{ConfigError, Palette};
+use log::info;
+use piston_window::*;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Colour of the square marking the ant.
+const ANT_COLOUR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// How the lattice is shown, switched with V: a single slice across an axis,
+// or a projection along it showing the nearest cells.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum View {
+    Slice,
+    Projection,
+}
+
+//-----------------------------------------------------------------------------
+// What's shown in the window, so the image is only rebuilt when it changes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Shown {
+    view: View,
+    axis: Axis,
+    depth: usize,
+    iterations: u64,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run the experimental 3D mode in a window of its own. Space pauses, the
+// right arrow key steps while paused, + and - change the speed, V switches
+// between a slice and a projection, tab cycles the axis, page up and page
+// down move the slice and F makes it follow the ant.
+pub fn run(cli: &Cli) -> Result<(), ConfigError> {
+    if cli.headless || cli.renderer != RendererMode::Window || cli.backend != Backend::Piston {
+        return Err(ConfigError::InvalidSetting {
+            name: "dimensions",
+            reason: "the 3D mode only runs in the piston window".to_string(),
+        });
+    }
+
+    if cli.surface != Surface::Plane {
+        return Err(ConfigError::InvalidSetting {
+            name: "surface",
+            reason: "the 3D mode has no surface to walk on".to_string(),
+        });
+    }
+
+    let mps = config::validate_mps(cli.mps)?;
+    let square_size = config::validate_square_size(cli.square)?;
+
+    if cli.grid as usize > MAX_LATTICE_SIZE {
+        return Err(ConfigError::InvalidSetting {
+            name: "grid",
+            reason: format!("3D lattices can be at most {} cells wide", MAX_LATTICE_SIZE),
+        });
+    }
+
+    let seed = cli.seed.unwrap_or_else(random::random_seed);
+    let convention = cli.turn_convention.unwrap_or_default();
+    let mut lattice =
+        Lattice::with_seed(&cli.rule, cli.grid as usize, convention, seed).map_err(|reason| {
+            ConfigError::InvalidSetting {
+                name: "rule",
+                reason,
+            }
+        })?;
+
+    if let Some(palette) = cli.palette.as_ref() {
+        let colours = Palette::parse(palette)
+            .and_then(|p| p.colours(lattice.num_colours()))
+            .map_err(|reason| ConfigError::InvalidPalette {
+                palette: palette.to_string(),
+                reason,
+            })?;
+
+        lattice.set_colours(colours);
+    }
+
+    info!("");
+    info!("Rule = {}", lattice.rule());
+    info!("Seed = {}", lattice.seed());
+    info!("Turn convention = {}", convention.name());
+    info!("Moves per second = {}", mps);
+    info!("Lattice size (number of cells) = {}", lattice.size());
+
+    let dim = config::window_size(cli.grid, square_size);
+    let mut window: PistonWindow = WindowSettings::new("Langton's Ant 3D", [dim, dim])
+        .exit_on_esc(true)
+        .resizable(true)
+        .build()
+        .unwrap();
+
+    let mut evs = window.get_event_settings();
+    evs.set_ups(UPDATES_PER_SECOND);
+    evs.set_max_fps(UPDATES_PER_SECOND);
+    window.set_event_settings(evs);
+
+    let mut speed = Speed::new(mps);
+    let mut paused = false;
+    let mut follow = false;
+    let mut view = View::Slice;
+    let mut axis = Axis::Z;
+    let mut depth = lattice.size() / 2;
+
+    let mut texture_context = window.create_texture_context();
+    let mut image = render(&lattice, view, axis, depth);
+    let mut texture = create_texture(&mut texture_context, &image);
+    let mut shown = None;
+
+    while let Some(e) = window.next() {
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                Key::Space => paused = !paused,
+                Key::Right if paused => lattice.step(),
+                Key::Equals | Key::Plus | Key::NumPadPlus => speed.faster(),
+                Key::Minus | Key::NumPadMinus => speed.slower(),
+                Key::V => {
+                    view = match view {
+                        View::Slice => View::Projection,
+                        View::Projection => View::Slice,
+                    };
+                    info!("View = {:?} along {}", view, axis);
+                }
+                Key::Tab => {
+                    axis = axis.next();
+                    info!("View = {:?} along {}", view, axis);
+                }
+                Key::PageUp | Key::Up => {
+                    depth = (depth + 1).min(lattice.size() - 1);
+                    info!("Slice {} = {}", axis, depth);
+                }
+                Key::PageDown | Key::Down => {
+                    depth = depth.saturating_sub(1);
+                    info!("Slice {} = {}", axis, depth);
+                }
+                Key::F => {
+                    follow = !follow;
+                    info!("Following the ant = {}", follow);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(args) = e.update_args() {
+            if !paused {
+                let moves = speed.moves(args.dt);
+                speed.advance(moves, |batch| {
+                    (0..batch).for_each(|_| lattice.step());
+                    false
+                });
+            }
+
+            if follow {
+                depth = axis.to_slice(lattice.position()).1;
+            }
+        }
+
+        if e.render_args().is_some() {
+            let now = Shown {
+                view,
+                axis,
+                depth,
+                iterations: lattice.iterations(),
+            };
+
+            if shown != Some(now) {
+                image = render(&lattice, view, axis, depth);
+                texture.update(&mut texture_context, &image).unwrap();
+                shown = Some(now);
+            }
+        }
+
+        window.draw_2d(&e, |c, g, device| {
+            let [width, height] = c.get_view_size();
+            let scale = width.min(height) / lattice.size() as f64;
+            let transform = c.transform.scale(scale, scale);
+
+            texture_context.encoder.flush(device);
+            clear([0.5, 0.5, 0.5, 1.0], g);
+            piston_window::image(&texture, transform, g);
+
+            let ([u, v], ant_depth) = axis.to_slice(lattice.position());
+
+            if view == View::Projection || ant_depth == depth {
+                rectangle(ANT_COLOUR, [u as f64, v as f64, 1.0, 1.0], transform, g);
+            }
+        });
+    }
+
+    info!("Stopped after {} iterations", lattice.iterations());
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// Render the current view of the lattice.
+fn render(lattice: &Lattice, view: View, axis: Axis, depth: usize) -> RgbaImage {
+    match view {
+        View::Slice => lattice.render_slice(axis, depth),
+        View::Projection => lattice.render_projection(axis),
+    }
+}
@@ -0,0 +1,223 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use super::recording::Recordings;
+use super::speed::{Speed, UPDATES_PER_SECOND};
+use super::stepper::Stepper;
+use langtons_ant::{Colour, Delta, Simulation};
+use log::{info, warn};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Requests sent from the window to the simulation thread.
+pub enum Request {
+    SetPaused(bool),
+    SetSpeed(u64),
+    SetColours(Vec<Colour>),
+    Step,
+    StepBack,
+    ToggleReverse,
+    Rewind(u64),
+    // Scrub a fraction of the way through the recent history, backwards if
+    // negative.
+    ScrubBy(f64),
+    Stop,
+}
+
+//-----------------------------------------------------------------------------
+// Messages sent from the simulation thread to the window.
+enum Report {
+    Changed(Delta),
+    // The run paused itself, e.g. on finding a highway.
+    Paused,
+}
+
+//-----------------------------------------------------------------------------
+// Runs the simulation on a thread of its own, so however fast it runs it
+// never holds up drawing. The window keeps a copy of the simulation, which
+// it draws from, and the thread sends the changes it makes over a channel
+// to be applied to the copy at each update. The recordings are made on the
+// thread, and handed back with the simulation when it's stopped.
+pub struct Worker {
+    requests: Sender<Request>,
+    reports: Receiver<Report>,
+    thread: Option<JoinHandle<(Simulation, Recordings)>>,
+    paused: bool,
+    mps: u64,
+}
+
+impl Worker {
+    // Start running the simulation at the given moves per second.
+    pub fn spawn(sim: Simulation, stepper: Stepper, recordings: Recordings, mps: u64) -> Worker {
+        let (requests, request_rx) = mpsc::channel();
+        let (report_tx, reports) = mpsc::channel();
+
+        let thread =
+            thread::spawn(move || run(sim, stepper, recordings, mps, request_rx, report_tx));
+
+        Worker {
+            requests,
+            reports,
+            thread: Some(thread),
+            paused: false,
+            mps,
+        }
+    }
+
+    // Send a request to the thread. Requests made after the thread has
+    // stopped are ignored.
+    pub fn send(&self, request: Request) {
+        let _ = self.requests.send(request);
+    }
+
+    // Pass on any change to the window's pause state or speed.
+    pub fn sync_settings(&mut self, paused: bool, mps: u64) {
+        if paused != self.paused {
+            self.paused = paused;
+            self.send(Request::SetPaused(paused));
+        }
+
+        if mps != self.mps {
+            self.mps = mps;
+            self.send(Request::SetSpeed(mps));
+        }
+    }
+
+    // Apply the changes received so far to the window's copy of the
+    // simulation. Returns true if the run paused itself.
+    pub fn sync(&mut self, sim: &mut Simulation) -> bool {
+        let mut paused = false;
+
+        for report in self.reports.try_iter() {
+            match report {
+                Report::Changed(delta) => sim.apply_delta(delta),
+                Report::Paused => paused = true,
+            }
+        }
+
+        if paused {
+            self.paused = true;
+        }
+
+        paused
+    }
+
+    // Stop the thread, returning the simulation and recordings.
+    pub fn finish(mut self) -> (Simulation, Recordings) {
+        self.send(Request::Stop);
+
+        self.thread
+            .take()
+            .unwrap()
+            .join()
+            .expect("simulation thread panicked")
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The simulation thread's loop, handling requests and making moves at the
+// update rate, sending the changes made by each update. While paused it
+// waits for the next request rather than spinning.
+fn run(
+    mut sim: Simulation,
+    mut stepper: Stepper,
+    mut recordings: Recordings,
+    mps: u64,
+    requests: Receiver<Request>,
+    reports: Sender<Report>,
+) -> (Simulation, Recordings) {
+    let tick = Duration::from_secs_f64(1.0 / UPDATES_PER_SECOND as f64);
+    let mut speed = Speed::new(mps);
+    let mut paused = false;
+    let mut last_update = Instant::now();
+
+    sim.set_track_dirty(true);
+
+    loop {
+        let start = Instant::now();
+        let was_paused = paused;
+        let mut wait = paused;
+
+        loop {
+            let request = if wait {
+                wait = false;
+                requests.recv().map_err(|_| TryRecvError::Disconnected)
+            } else {
+                requests.try_recv()
+            };
+
+            match request {
+                Ok(Request::SetPaused(p)) => paused = p,
+                Ok(Request::SetSpeed(mps)) => speed.set(mps),
+                Ok(Request::SetColours(colours)) => sim.set_colours(colours),
+                Ok(Request::Step) => stepper.step_forward(&mut sim),
+                Ok(Request::StepBack) => stepper.step_back(&mut sim),
+                Ok(Request::ToggleReverse) => stepper.toggle_reverse(&mut sim),
+                Ok(Request::Rewind(n)) => match stepper.rewind(&mut sim, n) {
+                    Some(undone) => {
+                        info!("Rewound {} moves to iteration {}", undone, sim.iterations())
+                    }
+                    None => warn!("Rewinding is unavailable while logging moves"),
+                },
+                Ok(Request::ScrubBy(fraction)) => {
+                    if stepper.scrub_by(&mut sim, fraction) {
+                        info!("Scrubbed to iteration {}", sim.iterations());
+                    } else {
+                        warn!("Scrubbing is unavailable while logging moves");
+                    }
+                }
+                Ok(Request::Stop) | Err(TryRecvError::Disconnected) => return (sim, recordings),
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        // Time spent paused isn't owed as moves.
+        let dt = last_update.elapsed().as_secs_f64();
+        last_update = Instant::now();
+
+        if !paused && !was_paused {
+            let moves = speed.moves(dt);
+
+            if stepper.advance(&mut sim, moves, &mut recordings, &mut speed) {
+                paused = true;
+                let _ = reports.send(Report::Paused);
+            }
+        }
+
+        let delta = sim.take_delta();
+
+        if !delta.is_empty() && reports.send(Report::Changed(delta)).is_err() {
+            return (sim, recordings);
+        }
+
+        thread::sleep(tick.saturating_sub(start.elapsed()));
+    }
+}
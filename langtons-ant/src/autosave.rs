@@ -0,0 +1,172 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::simulation::Simulation;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Defaults for settings not given in an autosave spec.
+const DEFAULT_EVERY: Duration = Duration::from_secs(60);
+const DEFAULT_DIR: &str = "autosaves";
+const DEFAULT_KEEP: usize = 3;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// How often and where states are autosaved, and how many are kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutosaveSpec {
+    pub every: Duration,
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl Default for AutosaveSpec {
+    fn default() -> AutosaveSpec {
+        AutosaveSpec {
+            every: DEFAULT_EVERY,
+            dir: PathBuf::from(DEFAULT_DIR),
+            keep: DEFAULT_KEEP,
+        }
+    }
+}
+
+impl AutosaveSpec {
+    // Parse an autosave spec of settings separated by spaces or commas, any
+    // of every=DURATION, e.g. 60s, 5m or 1h, dir=PATH and keep=N, e.g.
+    // "every=60s dir=autosaves/". Settings not given take their defaults.
+    pub fn parse(s: &str) -> Result<AutosaveSpec, String> {
+        let mut spec = AutosaveSpec::default();
+
+        for setting in s.split([' ', ',']).filter(|s| !s.is_empty()) {
+            match setting.split_once('=') {
+                Some(("every", every)) => spec.every = parse_duration(every)?,
+                Some(("dir", dir)) if !dir.is_empty() => spec.dir = PathBuf::from(dir),
+                Some(("keep", keep)) => {
+                    spec.keep = keep
+                        .parse()
+                        .ok()
+                        .filter(|&keep| keep > 0)
+                        .ok_or_else(|| format!("invalid autosave count: {}", keep))?;
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid autosave setting, expected every=DURATION, dir=PATH or keep=N: {}",
+                        setting
+                    ))
+                }
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Saves the simulation's state regularly while it runs, so a long run isn't
+// lost if the window is closed by accident or the program dies. The last
+// few saves are kept, overwriting the oldest, in files named
+// autosave-1.json, autosave-2.json and so on, which can be resumed with
+// --load. Each is written to a temporary file first and then renamed over
+// the old one, so a save interrupted part way never replaces a good one.
+pub struct Autosave {
+    spec: AutosaveSpec,
+    last: Instant,
+    next: usize,
+}
+
+impl Autosave {
+    pub fn new(spec: AutosaveSpec) -> Autosave {
+        Autosave {
+            spec,
+            last: Instant::now(),
+            next: 0,
+        }
+    }
+
+    // Has long enough passed since the last save?
+    pub fn is_due(&self) -> bool {
+        self.last.elapsed() >= self.spec.every
+    }
+
+    // Save the state if a save is due, returning the file written or why it
+    // couldn't be. A failed save is tried again after the same interval.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<Result<PathBuf, String>> {
+        if !self.is_due() {
+            return None;
+        }
+
+        self.last = Instant::now();
+        Some(self.save(sim))
+    }
+
+    // Save the state now, over the oldest of the files kept.
+    pub fn save(&mut self, sim: &Simulation) -> Result<PathBuf, String> {
+        let path = self
+            .spec
+            .dir
+            .join(format!("autosave-{}.json", self.next + 1));
+        let partial = path.with_extension("json.partial");
+
+        fs::create_dir_all(&self.spec.dir)
+            .and_then(|_| sim.save(&partial))
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to autosave to {}: {}", path.display(), e))?;
+
+        self.next = (self.next + 1) % self.spec.keep;
+        Ok(path)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Parse a duration in milliseconds, seconds, minutes or hours, e.g. 500ms,
+// 60s, 5m or 1h. A plain number is taken as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let err = || format!("invalid duration, expected e.g. 60s, 5m or 1h: {}", s);
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().map_err(|_| err())?;
+
+    let duration = match unit {
+        "ms" => Duration::from_millis(number),
+        "" | "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number * 60),
+        "h" => Duration::from_secs(number * 3600),
+        _ => return Err(err()),
+    };
+
+    if duration.is_zero() {
+        return Err(err());
+    }
+
+    Ok(duration)
+}
@@ -0,0 +1,56 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::io;
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Run a batch of jobs, e.g. simulations of many rules or seeds, concurrently
+// across the given number of threads, or one per core if 0. The results are
+// returned in the same order as the jobs, and done is called as each job
+// completes so progress can be shown.
+pub fn run<J, R, F, D>(jobs: Vec<J>, threads: usize, job: F, done: D) -> io::Result<Vec<R>>
+where
+    J: Send,
+    R: Send,
+    F: Fn(J) -> R + Sync,
+    D: Fn() + Sync,
+{
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(io::Error::other)?;
+
+    Ok(pool.install(|| {
+        jobs.into_par_iter()
+            .map(|j| {
+                let result = job(j);
+                done();
+                result
+            })
+            .collect()
+    }))
+}
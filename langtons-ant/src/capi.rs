@@ -0,0 +1,190 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// A C interface for embedding the simulator in other applications, built
+// with the capi feature. Simulations are handed out as opaque pointers, made
+// by langtons_ant_create and freed by langtons_ant_destroy, and every other
+// function takes one. Null pointers are accepted everywhere, read as a
+// simulation that can't move. Building with the feature also writes the
+// header include/langtons_ant.h, generated by cbindgen from this file.
+
+// The safety requirements of each function are given in its comment, which
+// clippy can't see as it only reads doc comments.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::config;
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::simulation::Simulation;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Value read from a cell that has never been visited.
+pub const LANGTONS_ANT_BLANK: i32 = -1;
+
+// Value read from an obstacle cell.
+pub const LANGTONS_ANT_OBSTACLE: i32 = -2;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A simulation, opaque to C.
+pub struct LangtonsAnt {
+    sim: Simulation,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Create a simulation of a rule, e.g. "RL", on a square grid of size x size
+// cells with the ant centred in the grid, colouring the rule's steps from the
+// seed. Returns null if the rule or grid size is invalid.
+//
+// # Safety
+// rule must be null or point to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_create(
+    rule: *const c_char,
+    grid_size: u32,
+    seed: u64,
+) -> *mut LangtonsAnt {
+    if rule.is_null() {
+        return ptr::null_mut();
+    }
+
+    let rule = match CStr::from_ptr(rule).to_str() {
+        Ok(rule) => rule,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    match (
+        config::validate_rule(rule),
+        config::validate_grid_size(grid_size),
+    ) {
+        (Ok(rule), Ok(size)) => Box::into_raw(Box::new(LangtonsAnt {
+            sim: Simulation::with_seed(&rule, size as usize, seed),
+        })),
+        _ => ptr::null_mut(),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Make up to n moves, stopping early if the ant stalls, returning the number
+// of moves made.
+//
+// # Safety
+// sim must have been returned by langtons_ant_create and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_step(sim: *mut LangtonsAnt, n: u64) -> u64 {
+    match sim.as_mut().map(|s| &mut s.sim) {
+        Some(sim) => {
+            let before = sim.iterations();
+            sim.advance(n);
+            sim.iterations() - before
+        }
+        None => 0,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Read a cell's colour index, or LANGTONS_ANT_BLANK for unvisited cells and
+// cells outside the grid, or LANGTONS_ANT_OBSTACLE for obstacles.
+//
+// # Safety
+// sim must have been returned by langtons_ant_create and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_read_cell(sim: *const LangtonsAnt, x: i64, y: i64) -> i32 {
+    match sim.as_ref().map(|s| s.sim.cells().get(x, y)) {
+        None | Some(BLANK) => LANGTONS_ANT_BLANK,
+        Some(OBSTACLE) => LANGTONS_ANT_OBSTACLE,
+        Some(clr_idx) => clr_idx as i32,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Number of moves made so far.
+//
+// # Safety
+// sim must have been returned by langtons_ant_create and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_iterations(sim: *const LangtonsAnt) -> u64 {
+    sim.as_ref().map_or(0, |s| s.sim.iterations())
+}
+
+//-----------------------------------------------------------------------------
+// Width and height of the grid in cells.
+//
+// # Safety
+// sim must have been returned by langtons_ant_create and not yet destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_grid_size(sim: *const LangtonsAnt) -> u32 {
+    sim.as_ref().map_or(0, |s| s.sim.size() as u32)
+}
+
+//-----------------------------------------------------------------------------
+// Write the ant's position to x and y, either of which may be null. Returns
+// false if the ant has stalled against the edge of the grid.
+//
+// # Safety
+// sim must have been returned by langtons_ant_create and not yet destroyed,
+// and x and y must each be null or point to an int64_t.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_ant_position(
+    sim: *const LangtonsAnt,
+    x: *mut i64,
+    y: *mut i64,
+) -> bool {
+    let sim = match sim.as_ref() {
+        Some(s) => &s.sim,
+        None => return false,
+    };
+
+    if let Some(x) = x.as_mut() {
+        *x = sim.ant().pos_x;
+    }
+
+    if let Some(y) = y.as_mut() {
+        *y = sim.ant().pos_y;
+    }
+
+    !sim.is_stalled()
+}
+
+//-----------------------------------------------------------------------------
+// Free a simulation. Passing null does nothing.
+//
+// # Safety
+// sim must be null or have been returned by langtons_ant_create, and must
+// not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn langtons_ant_destroy(sim: *mut LangtonsAnt) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
@@ -0,0 +1,259 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Facing;
+use crate::grid::{BLANK, OBSTACLE};
+use crate::interval::Interval;
+use crate::simulation::Simulation;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Written ahead of the state, so a change to how it is laid out changes
+// every hash rather than quietly colliding with the old ones.
+const DIGEST_VERSION: &[u8] = b"langtons-ant state v1";
+
+// Colour written for obstacle cells.
+const OBSTACLE_COLOUR: u64 = u64::MAX;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A canonical hash of a simulation's state, i.e. its grid and ants, the same
+// whichever grid backend holds the cells and however the moves were made.
+// It is the SHA-256 of the ants' positions, facings, states and whether they
+// have stalled, followed by every coloured cell in row order, with cells of
+// colour 0 left out as they can't be told from cells never visited. The
+// first 8 bytes give a shorter 64 bit hash for quick comparisons. Cells
+// are hashed by colour index, never by the colour drawn, as random colours
+// may differ between platforms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StateDigest {
+    pub sha256: [u8; 32],
+}
+
+impl StateDigest {
+    pub fn of(sim: &Simulation) -> StateDigest {
+        let mut hasher = Sha256::new();
+        hasher.update(DIGEST_VERSION);
+        hasher.update((sim.ants().len() as u64).to_le_bytes());
+
+        for ant in sim.ants() {
+            hasher.update(ant.pos_x.to_le_bytes());
+            hasher.update(ant.pos_y.to_le_bytes());
+            hasher.update([facing_index(ant.facing), ant.stalled as u8]);
+            hasher.update((ant.state as u64).to_le_bytes());
+        }
+
+        let mut cells = Vec::new();
+
+        sim.cells().for_each_visited(|x, y, clr_idx| match clr_idx {
+            0 | BLANK => {}
+            OBSTACLE => cells.push((y, x, OBSTACLE_COLOUR)),
+            _ => cells.push((y, x, clr_idx as u64)),
+        });
+
+        cells.sort_unstable();
+
+        for (y, x, colour) in cells {
+            hasher.update(x.to_le_bytes());
+            hasher.update(y.to_le_bytes());
+            hasher.update(colour.to_le_bytes());
+        }
+
+        let mut sha256 = [0; 32];
+        sha256.copy_from_slice(&hasher.finalize());
+        StateDigest { sha256 }
+    }
+
+    // The 64 bit hash, the first 8 bytes of the SHA-256.
+    pub fn hash64(&self) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.sha256[..8]);
+        u64::from_be_bytes(bytes)
+    }
+
+    // Read a digest from its SHA-256 written as 64 hex digits.
+    pub fn parse(hex: &str) -> Option<StateDigest> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+
+        let mut sha256 = [0; 32];
+
+        for (i, byte) in sha256.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+
+        Some(StateDigest { sha256 })
+    }
+}
+
+// The 64 bit hash and the SHA-256, both in hex.
+impl fmt::Display for StateDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x} ", self.hash64())?;
+
+        for byte in &self.sha256 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The digest of a simulation's state at an iteration. Written as e.g.
+// "Checkpoint 1000 <hash64> <sha256>", the form printed during a run, so a
+// run's output can be saved and checked against later runs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub iteration: u64,
+    pub digest: StateDigest,
+}
+
+impl Checkpoint {
+    pub fn of(sim: &Simulation) -> Checkpoint {
+        Checkpoint {
+            iteration: sim.iterations(),
+            digest: StateDigest::of(sim),
+        }
+    }
+
+    // Read a checkpoint line, returning None if the line isn't one. The 64
+    // bit hash must agree with the SHA-256.
+    pub fn parse(line: &str) -> Option<Checkpoint> {
+        let mut fields = line.split_whitespace();
+
+        if fields.next() != Some("Checkpoint") {
+            return None;
+        }
+
+        let iteration = fields.next()?.parse().ok()?;
+        let hash64 = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let digest = StateDigest::parse(fields.next()?)?;
+
+        if fields.next().is_some() || digest.hash64() != hash64 {
+            return None;
+        }
+
+        Some(Checkpoint { iteration, digest })
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Checkpoint {} {}", self.iteration, self.digest)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Takes checkpoints of a run every given number of iterations, and at the
+// iterations of any expected checkpoints, which the run's state is checked
+// against. Expected checkpoints the run has already passed are skipped.
+#[derive(Clone, Debug)]
+pub struct CheckpointWatch {
+    interval: Option<Interval>,
+    expected: VecDeque<Checkpoint>,
+}
+
+impl CheckpointWatch {
+    pub fn new(every: Option<u64>, mut expected: Vec<Checkpoint>) -> CheckpointWatch {
+        expected.sort_by_key(|c| c.iteration);
+
+        CheckpointWatch {
+            interval: every.map(Interval::new),
+            expected: expected.into(),
+        }
+    }
+
+    // Number of expected checkpoints not yet checked.
+    pub fn unchecked(&self) -> usize {
+        self.expected.len()
+    }
+
+    // Number of iterations until the next checkpoint is due.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        let expected = self
+            .expected
+            .front()
+            .map_or(u64::MAX, |c| c.iteration.saturating_sub(sim.iterations()));
+
+        self.interval
+            .map_or(u64::MAX, |i| i.remaining(sim))
+            .min(expected)
+    }
+
+    // Take a checkpoint if one is due, returning it along with the
+    // checkpoint expected at this iteration, if any.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<(Checkpoint, Option<Checkpoint>)> {
+        while self
+            .expected
+            .front()
+            .is_some_and(|c| c.iteration < sim.iterations())
+        {
+            self.expected.pop_front();
+        }
+
+        let expected = match self.expected.front() {
+            Some(c) if c.iteration == sim.iterations() => self.expected.pop_front(),
+            _ => None,
+        };
+
+        let due = self.interval.as_mut().is_some_and(|i| i.due(sim));
+
+        if due || expected.is_some() {
+            Some((Checkpoint::of(sim), expected))
+        } else {
+            None
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Read the checkpoints in a file, e.g. the saved output of an earlier run,
+// ignoring any other lines.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Checkpoint>> {
+    let text = fs::read_to_string(path)?;
+    Ok(text.lines().filter_map(Checkpoint::parse).collect())
+}
+
+fn facing_index(facing: Facing) -> u8 {
+    match facing {
+        Facing::N => 0,
+        Facing::E => 1,
+        Facing::S => 2,
+        Facing::W => 3,
+    }
+}
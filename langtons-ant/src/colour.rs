@@ -0,0 +1,370 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{BLANK, OBSTACLE};
+use float_cmp::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Smallest perceptual difference, as CIEDE2000, between randomly generated
+// colours, so the cells of different colours can be told apart. Relaxed
+// when a rule has too many colours for it.
+pub const MIN_COLOUR_DISTANCE: f32 = 20.0;
+
+// Smallest perceptual difference between a randomly generated colour and
+// the white background, never relaxed.
+pub const MIN_BACKGROUND_DISTANCE: f32 = 15.0;
+
+// Random colours tried before the difference wanted between colours is
+// halved.
+const DISTINCT_ATTEMPTS: u32 = 100;
+
+// White point of the sRGB colour space, D65.
+const D65: [f64; 3] = [0.950_47, 1.0, 1.088_83];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Structure to hold colour information.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct Colour {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Colour {
+    // Compare this Colour to another Colour instance.
+    pub fn compare(&self, other: &Colour) -> bool {
+        approx_eq!(f32, self.r, other.r)
+            && approx_eq!(f32, self.g, other.g)
+            && approx_eq!(f32, self.b, other.b)
+            && approx_eq!(f32, self.a, other.a)
+    }
+
+    pub fn to_rgba(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    // Parse a colour from a hex string, "#rrggbb" or "#rrggbbaa", where the
+    // leading # is optional.
+    pub fn from_hex(s: &str) -> Result<Colour, String> {
+        let hex = s.trim().trim_start_matches('#');
+
+        if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+            return Err(format!("invalid hex colour: {}", s));
+        }
+
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map(|v| v as f32 / 255.0)
+                .map_err(|_| format!("invalid hex colour: {}", s))
+        };
+
+        Ok(Colour {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: if hex.len() == 8 { channel(6)? } else { 1.0 },
+        })
+    }
+
+    // Format as a hex string, "#rrggbb", or "#rrggbbaa" if not opaque.
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.to_rgba8();
+
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    // Convert to CIELAB, under the D65 white point of sRGB, ignoring alpha.
+    pub fn to_lab(&self) -> [f32; 3] {
+        let linear = |c: f32| {
+            let c = f64::from(c.clamp(0.0, 1.0));
+
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let (r, g, b) = (linear(self.r), linear(self.g), linear(self.b));
+        let xyz = [
+            0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+            0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b,
+            0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b,
+        ];
+
+        let f = |t: f64| {
+            if t > 216.0 / 24_389.0 {
+                t.cbrt()
+            } else {
+                (24_389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+
+        let [fx, fy, fz] = [0, 1, 2].map(|i| f(xyz[i] / D65[i]));
+
+        [
+            (116.0 * fy - 16.0) as f32,
+            (500.0 * (fx - fy)) as f32,
+            (200.0 * (fy - fz)) as f32,
+        ]
+    }
+
+    // Perceptual difference from another colour, as CIEDE2000. About 2 is
+    // just noticeable side by side, and 20 or more is easy to tell apart.
+    pub fn distance(&self, other: &Colour) -> f32 {
+        ciede2000(self.to_lab(), other.to_lab())
+    }
+
+    // Convert to 8 bit per channel RGBA, as used by image files.
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        [
+            (self.r * 255.0).round() as u8,
+            (self.g * 255.0).round() as u8,
+            (self.b * 255.0).round() as u8,
+            (self.a * 255.0).round() as u8,
+        ]
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The colour drawn for each colour index of the grid. It's owned by the
+// simulation and shared by all its ants, which only carry how they move, so
+// colours can be changed, e.g. from a palette, without touching the ants.
+// Saved as a list of colours.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ColourMap {
+    colours: Vec<Colour>,
+}
+
+impl ColourMap {
+    pub fn new(colours: Vec<Colour>) -> ColourMap {
+        ColourMap { colours }
+    }
+
+    // A map of n random colours, each perceptually distinct from the
+    // background and from each other.
+    pub fn distinct<R: Rng>(rng: &mut R, n: usize) -> ColourMap {
+        ColourMap::new(create_distinct_colours(rng, n))
+    }
+
+    pub fn colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    // The colour of a colour index, if the map has one.
+    pub fn get(&self, clr_idx: usize) -> Option<Colour> {
+        self.colours.get(clr_idx).copied()
+    }
+
+    // The colour to draw a cell, white for blank cells and grey for
+    // obstacles.
+    pub fn cell_colour(&self, cell: usize) -> Colour {
+        match cell {
+            BLANK => WHITE,
+            OBSTACLE => OBSTACLE_GREY,
+            clr_idx => self.colours[clr_idx],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.colours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+
+    // Keep the first len colours, adding random ones distinct from the rest
+    // if there are too few, e.g. when the rule changes length.
+    pub fn resize<R: Rng>(&mut self, rng: &mut R, len: usize) {
+        self.colours.truncate(len);
+
+        while self.colours.len() < len {
+            let col = create_distinct_colour(rng, &self.colours);
+            self.colours.push(col);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The background colour of a blank cell.
+pub const WHITE: Colour = Colour {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+//-----------------------------------------------------------------------------
+// The colour of an obstacle cell.
+pub const OBSTACLE_GREY: Colour = Colour {
+    r: 0.25,
+    g: 0.25,
+    b: 0.25,
+    a: 1.0,
+};
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Function to generate a random colour.
+pub fn generate_random_colour<R: Rng>(rng: &mut R) -> [f32; 4] {
+    let r: f32 = rng.gen(); // generates a float between 0 and 1
+    let g: f32 = rng.gen(); // generates a float between 0 and 1
+    let b: f32 = rng.gen(); // generates a float between 0 and 1
+    let rgba = [r, g, b, 1.0]; // [Red, Green, Blue, Alpha]
+    rgba
+}
+
+//-----------------------------------------------------------------------------
+// Function to create a colour object and randomly fill its values.
+pub fn create_random_colour<R: Rng>(rng: &mut R) -> Colour {
+    let c = generate_random_colour(rng);
+    let colour: Colour = Colour {
+        r: c[0],
+        g: c[1],
+        b: c[2],
+        a: c[3],
+    };
+    colour
+}
+
+//-----------------------------------------------------------------------------
+// Function to create a random colour that is perceptually distinct from the
+// white background and from the given colours, e.g. those of the rule's
+// other steps. The difference wanted from the other colours is halved each
+// time enough attempts fail, so any number of colours can be made.
+//
+// The differences are floating point, using powf, cbrt, atan2 and the
+// like, whose last bits may vary between platforms and so flip a close
+// comparison. Colours are therefore outside the guarantee that seeded runs
+// are bit-identical everywhere. They're drawn from a stream of their own
+// and only ever drawn, never read back by a move, so the grid and ants, and
+// their StateDigest, don't depend on them.
+pub fn create_distinct_colour<R: Rng>(rng: &mut R, others: &[Colour]) -> Colour {
+    let mut min_distance = MIN_COLOUR_DISTANCE;
+
+    loop {
+        for _ in 0..DISTINCT_ATTEMPTS {
+            let col = create_random_colour(rng);
+
+            if col.distance(&WHITE) >= MIN_BACKGROUND_DISTANCE
+                && others.iter().all(|c| c.distance(&col) >= min_distance)
+            {
+                return col;
+            }
+        }
+
+        min_distance /= 2.0;
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Function to create n random colours, each perceptually distinct from the
+// background and from each other.
+pub fn create_distinct_colours<R: Rng>(rng: &mut R, n: usize) -> Vec<Colour> {
+    let mut colours = Vec::with_capacity(n);
+
+    for _ in 0..n {
+        let col = create_distinct_colour(rng, &colours);
+        colours.push(col);
+    }
+
+    colours
+}
+
+//-----------------------------------------------------------------------------
+// The CIEDE2000 colour difference between two CIELAB colours, following
+// Sharma, Wu and Dalal's implementation notes.
+pub fn ciede2000(lab1: [f32; 3], lab2: [f32; 3]) -> f32 {
+    let [l1, a1, b1] = lab1.map(f64::from);
+    let [l2, a2, b2] = lab2.map(f64::from);
+    let pow7 = |v: f64| v.powi(7);
+    let hue = |b: f64, a: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+
+    let c_bar = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+    let g = 0.5 * (1.0 - (pow7(c_bar) / (pow7(c_bar) + pow7(25.0))).sqrt());
+    let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0.0 {
+        0.0
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else if h2 - h1 < -180.0 {
+        h2 - h1 + 360.0
+    } else {
+        h2 - h1
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+    let l_bar = (l1 + l2) / 2.0;
+    let c_bar = (c1 + c2) / 2.0;
+    let h_bar = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+
+    let cos = |deg: f64| deg.to_radians().cos();
+    let t =
+        1.0 - 0.17 * cos(h_bar - 30.0) + 0.24 * cos(2.0 * h_bar) + 0.32 * cos(3.0 * h_bar + 6.0)
+            - 0.20 * cos(4.0 * h_bar - 63.0);
+    let d_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+    let rc = 2.0 * (pow7(c_bar) / (pow7(c_bar) + pow7(25.0))).sqrt();
+    let sl = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let sc = 1.0 + 0.045 * c_bar;
+    let sh = 1.0 + 0.015 * c_bar * t;
+    let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+    let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+    (l * l + c * c + h * h + rt * c * h).sqrt() as f32
+}
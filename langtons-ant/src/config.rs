@@ -0,0 +1,571 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Facing;
+use crate::rule::{Rule, RuleParseError};
+use crate::simulation::Simulation;
+use crate::weighted::WeightedRule;
+use crate::zones::Zone;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Moves per second meaning as fast as the machine allows. There's no upper
+// limit on the speed, as moves are only made while each frame has time for
+// them. The largest number a TOML file can hold, so it can be saved.
+pub const UNLIMITED_MPS: u64 = i64::MAX as u64;
+
+// Smallest grid size as a number of squares. The largest is limited only by
+// the memory available for its cells.
+pub const MIN_GRID_SIZE: u32 = 10;
+
+// Limits on the size of a grid square as a number of pixels.
+pub const MIN_SQUARE_SIZE: f64 = 1.0;
+pub const MAX_SQUARE_SIZE: f64 = 20.0;
+
+// Largest initial window dimension in pixels. Grids whose size * square
+// size is bigger are scaled down to fit, and can be zoomed into.
+pub const MAX_WINDOW_SIZE: u32 = 1000;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Reasons the control parameters, or the files and patterns used to set up
+// a simulation, can be rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    // The rule is empty or has characters other than L, R, U and N, or is
+    // a malformed probabilistic rule.
+    InvalidRule(String),
+    // A plain rule couldn't be parsed, with where and why.
+    InvalidRuleSyntax {
+        rule: String,
+        error: RuleParseError,
+    },
+    // A value that should be a number couldn't be read as one.
+    InvalidNumber {
+        name: &'static str,
+        input: String,
+    },
+    MpsOutOfRange(u64),
+    GridSizeOutOfRange(u32),
+    SquareSizeOutOfRange(f64),
+    // The grid's cells need more memory, in bytes, than is available.
+    GridTooLarge {
+        size: u32,
+        bytes: u64,
+        available: u64,
+    },
+    // A file, e.g. a saved state or turmite, couldn't be read.
+    Load {
+        name: &'static str,
+        path: PathBuf,
+        reason: String,
+    },
+    // A file, e.g. a recording or results, couldn't be created or written.
+    Write {
+        name: &'static str,
+        path: PathBuf,
+        reason: String,
+    },
+    InvalidPalette {
+        palette: String,
+        reason: String,
+    },
+    InvalidGridSeed {
+        pattern: String,
+        reason: String,
+    },
+    // A value in a config file, e.g. the boundary mode, is invalid.
+    InvalidSetting {
+        name: &'static str,
+        reason: String,
+    },
+    // An extra ant is off the grid.
+    InvalidAnt(AntSpec),
+    // There is no preset with the given name.
+    UnknownPreset(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidRule(rule) => write!(f, "Invalid rule input: {}", rule),
+            ConfigError::InvalidRuleSyntax { rule, error } => {
+                write!(f, "Invalid rule input {}: {}", rule, error)
+            }
+            ConfigError::InvalidNumber { name, input } => {
+                write!(f, "Invalid {} = {}", name, input.trim())
+            }
+            ConfigError::MpsOutOfRange(mps) => {
+                write!(f, "Invalid moves per second = {}, must be at least 1", mps)
+            }
+            ConfigError::GridSizeOutOfRange(size) => write!(
+                f,
+                "Invalid grid size = {}, must be at least {}",
+                size, MIN_GRID_SIZE
+            ),
+            ConfigError::SquareSizeOutOfRange(size) => write!(
+                f,
+                "Invalid grid square size = {}, must be {} - {}",
+                size, MIN_SQUARE_SIZE, MAX_SQUARE_SIZE
+            ),
+            ConfigError::GridTooLarge {
+                size,
+                bytes,
+                available,
+            } => write!(
+                f,
+                "Invalid grid size = {}, its cells need {} MB but only {} MB of memory is available",
+                size,
+                bytes >> 20,
+                available >> 20
+            ),
+            ConfigError::Load { name, path, reason } => write!(
+                f,
+                "Failed to load {} from {}: {}",
+                name,
+                path.display(),
+                reason
+            ),
+            ConfigError::Write { name, path, reason } => write!(
+                f,
+                "Failed to write {} to {}: {}",
+                name,
+                path.display(),
+                reason
+            ),
+            ConfigError::InvalidPalette { palette, reason } => {
+                write!(f, "Invalid palette {}: {}", palette, reason)
+            }
+            ConfigError::InvalidGridSeed { pattern, reason } => {
+                write!(f, "Invalid grid seed {}: {}", pattern, reason)
+            }
+            ConfigError::InvalidSetting { name, reason } => {
+                write!(f, "Invalid {} setting: {}", name, reason)
+            }
+            ConfigError::InvalidAnt(ant) => write!(
+                f,
+                "Invalid ant at {}, {}, it must be on the grid",
+                ant.x, ant.y
+            ),
+            ConfigError::UnknownPreset(name) => write!(
+                f,
+                "Unknown preset: {}, run with --list-presets to list them",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+//-----------------------------------------------------------------------------
+// The control parameters of a run, each checked to be within limits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub rule: String,
+    pub mps: u64,
+    pub grid_size: u32,
+    pub square_size: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            rule: String::from("RL"),
+            mps: 10,
+            grid_size: 150,
+            square_size: 5.0,
+        }
+    }
+}
+
+impl Config {
+    // Create a configuration, checking every parameter.
+    pub fn new(
+        rule: &str,
+        mps: u64,
+        grid_size: u32,
+        square_size: f64,
+    ) -> Result<Config, ConfigError> {
+        Ok(Config {
+            rule: validate_rule(rule)?,
+            mps: validate_mps(mps)?,
+            grid_size: validate_grid_size(grid_size)?,
+            square_size: validate_square_size(square_size)?,
+        })
+    }
+
+    // Create a simulation of the rule on a grid of the configured size.
+    pub fn simulation(&self, seed: u64) -> Simulation {
+        Simulation::with_seed(&self.rule, self.grid_size as usize, seed)
+    }
+
+    // The parameters as the text of a config file, e.g. to remember them for
+    // the next run.
+    pub fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+        table.insert("rule".to_string(), self.rule.clone().into());
+        table.insert("mps".to_string(), (self.mps as i64).into());
+        table.insert("grid".to_string(), i64::from(self.grid_size).into());
+        table.insert("square".to_string(), self.square_size.into());
+        toml::to_string(&table).unwrap()
+    }
+
+    // Read the parameters back from a config file's text, checking every
+    // parameter. Any not given take their default values.
+    pub fn from_toml(text: &str) -> Result<Config, ConfigError> {
+        let file = ConfigFile::parse(text).map_err(|reason| ConfigError::InvalidSetting {
+            name: "config file",
+            reason,
+        })?;
+        let default = Config::default();
+
+        Config::new(
+            file.rule.as_deref().unwrap_or(&default.rule),
+            file.mps.unwrap_or(default.mps),
+            file.grid.unwrap_or(default.grid_size),
+            file.square.unwrap_or(default.square_size),
+        )
+    }
+}
+
+//-----------------------------------------------------------------------------
+// An extra ant to add to a simulation at the start.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AntSpec {
+    pub x: i64,
+    pub y: i64,
+    pub facing: Facing,
+}
+
+impl AntSpec {
+    // Parse an ant given as X,Y,FACING, e.g. "10,20,E".
+    pub fn parse(s: &str) -> Result<AntSpec, String> {
+        let err = || format!("invalid ant, expected X,Y,FACING: {}", s);
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+
+        match parts[..] {
+            [x, y, facing] if facing.len() == 1 => Ok(AntSpec {
+                x: x.parse().map_err(|_| err())?,
+                y: y.parse().map_err(|_| err())?,
+                facing: facing
+                    .chars()
+                    .next()
+                    .and_then(|c| Facing::from_char(c.to_ascii_uppercase()))
+                    .ok_or_else(err)?,
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A rule zone to add to a simulation at the start.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ZoneSpec {
+    pub x: i64,
+    pub y: i64,
+    pub width: usize,
+    pub height: usize,
+    pub rule: String,
+}
+
+impl ZoneSpec {
+    // Parse a zone given as X,Y,WIDTH,HEIGHT,RULE, e.g. "0,0,50,50,LR".
+    pub fn parse(s: &str) -> Result<ZoneSpec, String> {
+        let err = || format!("invalid zone, expected X,Y,WIDTH,HEIGHT,RULE: {}", s);
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+
+        match parts[..] {
+            [x, y, width, height, rule] => Ok(ZoneSpec {
+                x: x.parse().map_err(|_| err())?,
+                y: y.parse().map_err(|_| err())?,
+                width: width.parse().map_err(|_| err())?,
+                height: height.parse().map_err(|_| err())?,
+                rule: rule.to_string(),
+            }),
+            _ => Err(err()),
+        }
+    }
+
+    // The zone, checking its rule is a plain rule.
+    pub fn to_zone(&self) -> Result<Zone, ConfigError> {
+        if WeightedRule::is_weighted(&self.rule) {
+            return Err(ConfigError::InvalidRule(self.rule.clone()));
+        }
+
+        let rule = Rule::parse(&self.rule).map_err(|error| ConfigError::InvalidRuleSyntax {
+            rule: self.rule.clone(),
+            error,
+        })?;
+
+        Ok(Zone {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            rule,
+        })
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Settings read from a TOML config file, e.g. sim.toml. Every setting is
+// optional and is named after the matching command line option, with
+// underscores in place of dashes. Any option given on the command line
+// overrides the file's setting.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub preset: Option<String>,
+    pub rule: Option<String>,
+    pub mps: Option<u64>,
+    pub grid: Option<u32>,
+    pub square: Option<f64>,
+    pub seed: Option<u64>,
+    pub palette: Option<String>,
+    pub boundary: Option<String>,
+    pub obstacles: Option<String>,
+    pub unbounded: Option<bool>,
+    pub cell_bits: Option<u8>,
+    pub seed_grid: Option<String>,
+    pub turmite: Option<PathBuf>,
+    pub engine: Option<String>,
+    pub ants: Option<Vec<AntSpec>>,
+    pub zones: Option<Vec<ZoneSpec>>,
+    pub collision: Option<String>,
+    pub dimensions: Option<u8>,
+    pub turn_convention: Option<String>,
+    pub surface: Option<String>,
+    pub save_format: Option<String>,
+    pub screenshot_every: Option<u64>,
+    pub screenshot_scale: Option<u32>,
+    pub record: Option<PathBuf>,
+    pub record_every: Option<u64>,
+    pub record_scale: Option<u32>,
+    pub video: Option<PathBuf>,
+    pub video_fps: Option<u32>,
+    pub video_size: Option<(u32, u32)>,
+    pub stats: Option<PathBuf>,
+    pub stats_every: Option<u64>,
+    pub trace: Option<PathBuf>,
+    pub trace_every: Option<u64>,
+    pub symmetry_every: Option<u64>,
+    pub script: Option<PathBuf>,
+    pub script_every: Option<u64>,
+    pub record_moves: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    // Parse the settings from TOML text.
+    pub fn parse(text: &str) -> Result<ConfigFile, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    // Layer these settings over another set, taking each setting from self
+    // where given, otherwise from other.
+    pub fn or(self, other: ConfigFile) -> ConfigFile {
+        macro_rules! layered {
+            ($($setting:ident),*) => {
+                ConfigFile {
+                    $($setting: self.$setting.or(other.$setting)),*
+                }
+            };
+        }
+
+        layered!(
+            preset,
+            rule,
+            mps,
+            grid,
+            square,
+            seed,
+            palette,
+            boundary,
+            obstacles,
+            unbounded,
+            cell_bits,
+            seed_grid,
+            turmite,
+            engine,
+            ants,
+            zones,
+            collision,
+            dimensions,
+            turn_convention,
+            surface,
+            save_format,
+            screenshot_every,
+            screenshot_scale,
+            record,
+            record_every,
+            record_scale,
+            video,
+            video_fps,
+            video_size,
+            stats,
+            stats_every,
+            trace,
+            trace_every,
+            symmetry_every,
+            script,
+            script_every,
+            record_moves
+        )
+    }
+
+    // Load the settings from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ConfigFile, ConfigError> {
+        let path = path.as_ref();
+
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| ConfigFile::parse(&text))
+            .map_err(|reason| ConfigError::Load {
+                name: "config",
+                path: path.to_path_buf(),
+                reason,
+            })
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Check a rule is made of only L, R, U and N characters and absolute
+// facings, e.g. "^N", or is a valid probabilistic rule, giving the rule in
+// its standard form, e.g. "rl ^n" as "RL^N". Plain rules are parsed by
+// Rule::parse, so errors say where the rule went wrong.
+pub fn validate_rule(rule: &str) -> Result<String, ConfigError> {
+    if WeightedRule::is_weighted(rule) {
+        return match WeightedRule::parse(rule) {
+            Ok(_) => Ok(rule.to_string()),
+            Err(_) => Err(ConfigError::InvalidRule(rule.to_string())),
+        };
+    }
+
+    Rule::parse(rule)
+        .map(|parsed| parsed.to_string())
+        .map_err(|error| ConfigError::InvalidRuleSyntax {
+            rule: rule.to_string(),
+            error,
+        })
+}
+
+//-----------------------------------------------------------------------------
+// Check the moves per second is at least 1, limiting it to UNLIMITED_MPS.
+pub fn validate_mps(mps: u64) -> Result<u64, ConfigError> {
+    if mps == 0 {
+        return Err(ConfigError::MpsOutOfRange(mps));
+    }
+
+    Ok(mps.min(UNLIMITED_MPS))
+}
+
+//-----------------------------------------------------------------------------
+// Check the grid size isn't too small, and that its cells fit in the memory
+// available, where that can be found.
+pub fn validate_grid_size(grid_size: u32) -> Result<u32, ConfigError> {
+    if grid_size < MIN_GRID_SIZE {
+        return Err(ConfigError::GridSizeOutOfRange(grid_size));
+    }
+
+    let bytes = u64::from(grid_size)
+        .saturating_mul(u64::from(grid_size))
+        .saturating_mul(mem::size_of::<usize>() as u64);
+
+    match available_memory() {
+        Some(available) if bytes > available => Err(ConfigError::GridTooLarge {
+            size: grid_size,
+            bytes,
+            available,
+        }),
+        _ => Ok(grid_size),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Check the square size is within limits. The window is sized independently
+// of the grid, so any grid size can be drawn with any square size.
+pub fn validate_square_size(square_size: f64) -> Result<f64, ConfigError> {
+    if !(MIN_SQUARE_SIZE..=MAX_SQUARE_SIZE).contains(&square_size) {
+        return Err(ConfigError::SquareSizeOutOfRange(square_size));
+    }
+
+    Ok(square_size)
+}
+
+//-----------------------------------------------------------------------------
+// The size of the initial window in pixels for a grid of the given size,
+// drawn at the given square size, up to the largest initial window.
+pub fn window_size(grid_size: u32, square_size: f64) -> u32 {
+    (f64::from(grid_size) * square_size.floor()).min(f64::from(MAX_WINDOW_SIZE)) as u32
+}
+
+//-----------------------------------------------------------------------------
+// The memory available for new allocations in bytes, read from
+// /proc/meminfo, or None where that isn't available.
+pub fn available_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))
+        .and_then(|rest| {
+            rest.trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .map(|kb| kb * 1024)
+}
+
+//-----------------------------------------------------------------------------
+// Parse a number typed in or given on the command line, naming the value in
+// any error.
+pub fn parse_number<T: std::str::FromStr>(
+    name: &'static str,
+    input: &str,
+) -> Result<T, ConfigError> {
+    input
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError::InvalidNumber {
+            name,
+            input: input.to_string(),
+        })
+}
@@ -0,0 +1,450 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::colour::{create_distinct_colours, Colour, WHITE};
+use crate::random::{SeededRng, COLOURS_STREAM};
+use crate::rule::SEPARATORS;
+use image::{Rgba, RgbaImage};
+use std::fmt;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Largest cubic lattice, along each side. The cells of a lattice grow with
+// the cube of its size, so it's kept well below the largest 2D grid.
+pub const MAX_LATTICE_SIZE: usize = 256;
+
+// Most steps in a 3D rule, as each cell's state is stored in a byte with 0
+// for blank.
+pub const MAX_LATTICE_STEPS: usize = 255;
+
+// Brightness of the farthest cells in a projection, the nearest being drawn
+// at full brightness, so depth can be made out.
+const FAR_SHADE: f32 = 0.35;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A turn in a 3D rule, made relative to the ant's heading and the way it
+// considers up: turn left, turn right, u-turn, no turn, climb, pitching up,
+// or dive, pitching down.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Turn {
+    L,
+    R,
+    U,
+    N,
+    Climb,
+    Dive,
+}
+
+impl Turn {
+    // Convert a 3D rule character to a turn, where C climbs and D dives.
+    pub fn from_char(c: char) -> Option<Turn> {
+        match c.to_ascii_uppercase() {
+            'L' => Some(Turn::L),
+            'R' => Some(Turn::R),
+            'U' => Some(Turn::U),
+            'N' => Some(Turn::N),
+            'C' => Some(Turn::Climb),
+            'D' => Some(Turn::Dive),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Turn::L => 'L',
+            Turn::R => 'R',
+            Turn::U => 'U',
+            Turn::N => 'N',
+            Turn::Climb => 'C',
+            Turn::Dive => 'D',
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// How left and right turns are made in 3D. With planar turns the ant turns
+// about its up axis, so only climbing and diving take it out of the plane it
+// starts in. With roll turns it also rolls a quarter turn about its new
+// heading after turning left or right, so even a rule like RL wanders
+// through all three dimensions.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum TurnConvention {
+    #[default]
+    Planar,
+    Roll,
+}
+
+impl TurnConvention {
+    // Parse a turn convention from its name, e.g. "roll".
+    pub fn parse(s: &str) -> Result<TurnConvention, String> {
+        match s.trim().to_lowercase().as_str() {
+            "planar" => Ok(TurnConvention::Planar),
+            "roll" => Ok(TurnConvention::Roll),
+            _ => Err(format!("unknown turn convention: {}", s)),
+        }
+    }
+
+    // The name of the turn convention, as accepted by parse.
+    pub fn name(self) -> &'static str {
+        match self {
+            TurnConvention::Planar => "planar",
+            TurnConvention::Roll => "roll",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// An axis of the lattice, which slices are taken across.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    // The next axis, cycling X, Y, Z.
+    pub fn next(self) -> Axis {
+        match self {
+            Axis::X => Axis::Y,
+            Axis::Y => Axis::Z,
+            Axis::Z => Axis::X,
+        }
+    }
+
+    // Where a lattice position appears in slices across this axis, as the
+    // (u, v) cell drawn and the depth of the slice it's in.
+    pub fn to_slice(self, [x, y, z]: [usize; 3]) -> ([usize; 2], usize) {
+        match self {
+            Axis::X => ([y, z], x),
+            Axis::Y => ([x, z], y),
+            Axis::Z => ([x, y], z),
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Axis::X => write!(f, "X"),
+            Axis::Y => write!(f, "Y"),
+            Axis::Z => write!(f, "Z"),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// An experimental 3D Langton's ant, moving through a cubic lattice that
+// wraps around at its faces. The ant starts in the centre heading north, in
+// the -y direction, with up along -z, so a planar run on the centre slice
+// across the z axis matches the 2D ant on a wrapping grid.
+#[derive(Clone, Debug)]
+pub struct Lattice {
+    size: usize,
+    // Each cell's state plus one, with 0 for a blank cell.
+    cells: Vec<u8>,
+    rule: Vec<Turn>,
+    colours: Vec<Colour>,
+    convention: TurnConvention,
+    position: [usize; 3],
+    heading: [i32; 3],
+    up: [i32; 3],
+    iterations: u64,
+    seed: u64,
+}
+
+impl Lattice {
+    // Create a lattice of the given size with the ant at its centre, giving
+    // each step of the rule a random colour from the seed.
+    pub fn with_seed(
+        rule: &str,
+        size: usize,
+        convention: TurnConvention,
+        seed: u64,
+    ) -> Result<Lattice, String> {
+        let rule = parse_rule(rule)?;
+
+        if size == 0 || size > MAX_LATTICE_SIZE {
+            return Err(format!(
+                "lattice size must be 1 - {}: {}",
+                MAX_LATTICE_SIZE, size
+            ));
+        }
+
+        let mut rng = SeededRng::new(seed, COLOURS_STREAM);
+        let colours = create_distinct_colours(&mut rng, rule.len());
+
+        let centre = size / 2;
+
+        Ok(Lattice {
+            size,
+            cells: vec![0; size * size * size],
+            rule,
+            colours,
+            convention,
+            position: [centre; 3],
+            heading: [0, -1, 0],
+            up: [0, 0, -1],
+            iterations: 0,
+            seed,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn convention(&self) -> TurnConvention {
+        self.convention
+    }
+
+    pub fn colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    pub fn set_colours(&mut self, colours: Vec<Colour>) {
+        self.colours = colours;
+    }
+
+    pub fn num_colours(&self) -> usize {
+        self.rule.len()
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.iter().map(|t| t.to_char()).collect()
+    }
+
+    pub fn position(&self) -> [usize; 3] {
+        self.position
+    }
+
+    pub fn heading(&self) -> [i32; 3] {
+        self.heading
+    }
+
+    pub fn up(&self) -> [i32; 3] {
+        self.up
+    }
+
+    // The state of the cell at the given position, or None if it's blank.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+        match self.cells[self.index([x, y, z])] {
+            0 => None,
+            state => Some(state as usize - 1),
+        }
+    }
+
+    // Number of cells that aren't blank.
+    pub fn visited(&self) -> usize {
+        self.cells.iter().filter(|&&c| c != 0).count()
+    }
+
+    // Turn according to the state of the ant's cell, advance that cell to
+    // its next state and move the ant one cell forward.
+    pub fn step(&mut self) {
+        let index = self.index(self.position);
+        let state = self.cells[index].saturating_sub(1) as usize;
+
+        self.turn(self.rule[state]);
+        self.cells[index] = ((state + 1) % self.rule.len()) as u8 + 1;
+
+        for (p, h) in self.position.iter_mut().zip(self.heading.iter()) {
+            *p = (*p as i64 + *h as i64).rem_euclid(self.size as i64) as usize;
+        }
+
+        self.iterations += 1;
+    }
+
+    // Make n moves.
+    pub fn advance(&mut self, n: u64) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    // Render the slice through the lattice across the given axis at the
+    // given depth, one pixel per cell, with blank cells white. Slices across
+    // z are drawn with x across and y down, as the 2D grid is, slices across
+    // x with y across and z down, and slices across y with x across and z
+    // down.
+    pub fn render_slice(&self, axis: Axis, depth: usize) -> RgbaImage {
+        let size = self.size as u32;
+
+        RgbaImage::from_fn(size, size, |u, v| {
+            let position = slice_position(axis, depth, u as usize, v as usize);
+
+            match self.cells[self.index(position)] {
+                0 => Rgba(WHITE.to_rgba8()),
+                state => Rgba(self.colours[state as usize - 1].to_rgba8()),
+            }
+        })
+    }
+
+    // Render the lattice as seen along the given axis from its low side,
+    // each pixel showing the nearest cell that isn't blank, shaded darker
+    // the deeper it is, or white if the whole line of cells is blank.
+    pub fn render_projection(&self, axis: Axis) -> RgbaImage {
+        let size = self.size as u32;
+
+        RgbaImage::from_fn(size, size, |u, v| {
+            let nearest = (0..self.size).find_map(|depth| {
+                let position = slice_position(axis, depth, u as usize, v as usize);
+
+                match self.cells[self.index(position)] {
+                    0 => None,
+                    state => Some((depth, state as usize - 1)),
+                }
+            });
+
+            match nearest {
+                Some((depth, state)) => {
+                    let far = depth as f32 / self.size.max(2) as f32;
+                    let shade = 1.0 - (1.0 - FAR_SHADE) * far;
+                    let colour = self.colours[state];
+
+                    Rgba(
+                        Colour {
+                            r: colour.r * shade,
+                            g: colour.g * shade,
+                            b: colour.b * shade,
+                            a: colour.a,
+                        }
+                        .to_rgba8(),
+                    )
+                }
+                None => Rgba(WHITE.to_rgba8()),
+            }
+        })
+    }
+
+    // Turn the ant, rolling it afterwards for left and right turns under
+    // the roll convention.
+    fn turn(&mut self, turn: Turn) {
+        let (heading, up) = (self.heading, self.up);
+
+        match turn {
+            Turn::L => self.heading = cross(up, heading),
+            Turn::R => self.heading = cross(heading, up),
+            Turn::U => self.heading = negate(heading),
+            Turn::N => {}
+            Turn::Climb => {
+                self.heading = up;
+                self.up = negate(heading);
+            }
+            Turn::Dive => {
+                self.heading = negate(up);
+                self.up = heading;
+            }
+        }
+
+        if self.convention == TurnConvention::Roll && matches!(turn, Turn::L | Turn::R) {
+            self.up = cross(self.heading, self.up);
+        }
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        (z * self.size + y) * self.size + x
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Parse a 3D rule, e.g. "LRCD", using L, R, U and N as in 2D rules along
+// with C to climb and D to dive. Written as Rule::parse accepts, so case,
+// whitespace and separators don't matter and errors give the position.
+pub fn parse_rule(rule: &str) -> Result<Vec<Turn>, String> {
+    let turns = rule
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| !c.is_whitespace() && !SEPARATORS.contains(c))
+        .map(|(i, c)| {
+            Turn::from_char(c).ok_or_else(|| {
+                format!(
+                    "unexpected '{}' at position {}, expected L, R, U, N, C or D",
+                    c,
+                    i + 1
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if turns.is_empty() || turns.len() > MAX_LATTICE_STEPS {
+        return Err(format!(
+            "3D rules must have 1 - {} steps: {}",
+            MAX_LATTICE_STEPS, rule
+        ));
+    }
+
+    Ok(turns)
+}
+
+//-----------------------------------------------------------------------------
+// The lattice position of the cell drawn at (u, v) in a slice across the
+// given axis at the given depth.
+fn slice_position(axis: Axis, depth: usize, u: usize, v: usize) -> [usize; 3] {
+    let mut position = match axis {
+        Axis::X => [0, u, v],
+        Axis::Y => [u, 0, v],
+        Axis::Z => [u, v, 0],
+    };
+
+    position[axis.index()] = depth;
+    position
+}
+
+//-----------------------------------------------------------------------------
+// Cross product of two vectors.
+fn cross(a: [i32; 3], b: [i32; 3]) -> [i32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn negate(a: [i32; 3]) -> [i32; 3] {
+    [-a[0], -a[1], -a[2]]
+}
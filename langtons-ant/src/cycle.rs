@@ -0,0 +1,244 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Facing;
+use crate::grid::{GridStorage, BLANK};
+use crate::simulation::Simulation;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A cycle in the full state of a simulation, i.e. its grid and ants. On a
+// bounded grid where the ants don't stall the state must eventually repeat,
+// after which the run goes round the same cycle forever.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cycle {
+    // Number of moves before the state repeats.
+    pub period: u64,
+    // Iteration at which the state first enters the cycle.
+    pub start: u64,
+}
+
+//-----------------------------------------------------------------------------
+// A Zobrist style hash of a simulation's state that is updated as the ants
+// move rather than recomputed from the whole grid. Each cell contributes a
+// pseudo-random key for its position and colour, XORed together, so only
+// the cells the ants have just left need updating. The hash is relative to
+// the state it started from, which is enough to tell states apart.
+struct StateHash {
+    cells: u64,
+    // Cells under each ant, and their colours, before the next move.
+    under: Vec<(i64, i64, usize)>,
+}
+
+impl StateHash {
+    fn new(sim: &Simulation) -> StateHash {
+        StateHash {
+            cells: 0,
+            under: cells_under_ants(sim),
+        }
+    }
+
+    // Account for a move of every ant since the last update.
+    fn update(&mut self, sim: &Simulation) {
+        for (i, &(x, y, old)) in self.under.iter().enumerate() {
+            // Ants sharing a cell must only count it once.
+            if self.under[..i]
+                .iter()
+                .any(|&(ux, uy, _)| (ux, uy) == (x, y))
+            {
+                continue;
+            }
+
+            self.cells ^= cell_key(x, y, old) ^ cell_key(x, y, sim.cells().get(x, y));
+        }
+
+        self.under = cells_under_ants(sim);
+    }
+
+    // The hash of the full state, including the ants.
+    fn value(&self, sim: &Simulation) -> u64 {
+        sim.ants()
+            .iter()
+            .enumerate()
+            .fold(self.cells, |h, (i, ant)| {
+                h ^ mix(
+                    mix(mix(i as u64 ^ mix(ant.pos_x as u64)) ^ ant.pos_y as u64)
+                        ^ ((ant.state as u64) << 2 | facing_index(ant.facing)),
+                )
+            })
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Watches a run for its state repeating, using Brent's cycle detection over
+// the state hash after every move. This needs no memory of past states, so
+// can run indefinitely. Once the period is known the start of the cycle is
+// found by replaying from the state the detector started from.
+pub struct CycleDetector {
+    origin: Option<Simulation>,
+    iterations: Option<u64>,
+    hash: Option<StateHash>,
+    saved: u64,
+    power: u64,
+    length: u64,
+    found: Option<Cycle>,
+}
+
+impl Default for CycleDetector {
+    fn default() -> CycleDetector {
+        CycleDetector::new()
+    }
+}
+
+impl CycleDetector {
+    pub fn new() -> CycleDetector {
+        CycleDetector {
+            origin: None,
+            iterations: None,
+            hash: None,
+            saved: 0,
+            power: 1,
+            length: 0,
+            found: None,
+        }
+    }
+
+    // The cycle found, if any.
+    pub fn found(&self) -> Option<Cycle> {
+        self.found
+    }
+
+    // Note the state after a move, returning the cycle the first time one
+    // is found. The first call only notes the starting state. Moves made
+    // without observing them restart the detection from there, but cells
+    // painted or ants added aren't accounted for.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<Cycle> {
+        if self.found.is_some() || sim.is_stalled() || self.iterations == Some(sim.iterations()) {
+            return None;
+        }
+
+        if self.iterations.is_some_and(|i| i + 1 != sim.iterations()) {
+            *self = CycleDetector::new();
+        }
+
+        self.iterations = Some(sim.iterations());
+
+        let hash = match self.hash.as_mut() {
+            Some(hash) => hash,
+            None => {
+                let hash = StateHash::new(sim);
+                self.saved = hash.value(sim);
+                self.origin = Some(sim.clone());
+                self.hash = Some(hash);
+                return None;
+            }
+        };
+
+        hash.update(sim);
+        let value = hash.value(sim);
+        self.length += 1;
+
+        if value == self.saved {
+            let origin = self.origin.take()?;
+            self.found = Some(Cycle {
+                period: self.length,
+                start: cycle_start(origin, self.length),
+            });
+            return self.found;
+        }
+
+        // Brent's algorithm moves the saved state on at each power of two.
+        if self.length == self.power {
+            self.saved = value;
+            self.power *= 2;
+            self.length = 0;
+        }
+
+        None
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Find the iteration at which a run starting from origin enters a cycle of
+// the given period, by stepping two copies a period apart until their
+// states match.
+fn cycle_start(origin: Simulation, period: u64) -> u64 {
+    let mut behind = origin.clone();
+    let mut ahead = origin;
+    let mut behind_hash = StateHash::new(&behind);
+    let mut ahead_hash = StateHash::new(&ahead);
+
+    for _ in 0..period {
+        ahead.step();
+        ahead_hash.update(&ahead);
+    }
+
+    while behind_hash.value(&behind) != ahead_hash.value(&ahead) {
+        behind.step();
+        behind_hash.update(&behind);
+        ahead.step();
+        ahead_hash.update(&ahead);
+    }
+
+    behind.iterations()
+}
+
+// The cells under each ant, with their current colours.
+fn cells_under_ants(sim: &Simulation) -> Vec<(i64, i64, usize)> {
+    sim.ants()
+        .iter()
+        .map(|a| (a.pos_x, a.pos_y, sim.cells().get(a.pos_x, a.pos_y)))
+        .collect()
+}
+
+// The key of a cell with a given colour. Blank cells behave exactly as
+// colour 0, so both have no key.
+fn cell_key(x: i64, y: i64, clr_idx: usize) -> u64 {
+    if clr_idx == 0 || clr_idx == BLANK {
+        return 0;
+    }
+
+    mix(mix(mix(x as u64) ^ y as u64) ^ clr_idx as u64)
+}
+
+// The SplitMix64 finaliser, scrambling a value into a pseudo-random key.
+fn mix(v: u64) -> u64 {
+    let mut z = v.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn facing_index(facing: Facing) -> u64 {
+    match facing {
+        Facing::N => 0,
+        Facing::E => 1,
+        Facing::S => 2,
+        Facing::W => 3,
+    }
+}
@@ -0,0 +1,45 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Ant;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The changes made to a simulation over part of a run, used to keep a copy
+// of it in step with the original, e.g. when the original runs on another
+// thread. Only the cells that changed are included, while the ants, of
+// which there are few, are included in full.
+#[derive(Clone, Default)]
+pub struct Delta {
+    // Each changed cell's coordinates and its new colour index, or BLANK.
+    pub cells: Vec<(i64, i64, usize)>,
+    pub ants: Vec<Ant>,
+}
+
+impl Delta {
+    // Did nothing change?
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
@@ -0,0 +1,72 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::simulation::Simulation;
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Flip a cell to the next colour of the rule, as an ant leaving it would,
+// e.g. to perturb a copy of a run and watch how far the change spreads.
+// Returns false, changing nothing, for obstacles and cells outside the grid.
+pub fn flip(sim: &mut Simulation, x: i64, y: i64) -> bool {
+    if !sim.cells().contains(x, y) {
+        return false;
+    }
+
+    let clr_idx = match sim.cells().get(x, y) {
+        OBSTACLE => return false,
+        BLANK => 0,
+        clr_idx => clr_idx,
+    };
+
+    sim.paint(x, y, (clr_idx + 1) % sim.num_colours());
+    true
+}
+
+//-----------------------------------------------------------------------------
+// The cells whose colours differ between two runs, in row order, covering
+// the cells of both grids. Unvisited cells count as colour 0.
+pub fn differences(a: &Simulation, b: &Simulation) -> Vec<(i64, i64)> {
+    let region = a.cells().extent().union(b.cells().extent());
+    let mut cells = Vec::new();
+
+    for y in region.y..region.y + region.height as i64 {
+        for x in region.x..region.x + region.width as i64 {
+            if colour(a, x, y) != colour(b, x, y) {
+                cells.push((x, y));
+            }
+        }
+    }
+
+    cells
+}
+
+// A cell's colour, with unvisited cells read as colour 0.
+fn colour(sim: &Simulation, x: i64, y: i64) -> usize {
+    match sim.cells().get(x, y) {
+        BLANK => 0,
+        clr_idx => clr_idx,
+    }
+}
@@ -0,0 +1,221 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Rule engines, pluggable alternatives to the built in plain rules and
+// turmites. An engine makes each move of an ant, reading and writing the
+// grid however it likes, then turns and moves the ant with turn_and_move so
+// the boundary and obstacles are handled as for any other ant. Engines are
+// either built in, chosen by name, or, with the plugins feature, loaded
+// from dynamic libraries (see plugin.rs).
+
+use crate::ant::{
+    compute_ant_position_by, turn_and_move, Ant, Boundary, Direction, ObstaclePolicy,
+};
+use crate::grid::{GridBackend, GridStorage, BLANK, OBSTACLE};
+use crate::rule::Rule;
+use crate::turmite::{compute_turmite_position, Turmite};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Names of the built in engines, as given to builtin.
+pub const BUILTIN_ENGINES: &[&str] = &["langton", "parity"];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Interface to a rule engine, deciding each move of an ant. Engines can be
+// sent and shared between threads with the simulation that owns them.
+pub trait RuleEngine: Send + Sync {
+    // A short description shown in place of the rule.
+    fn name(&self) -> String;
+
+    // Number of colours the engine paints cells with, indexed from 0. Blank
+    // cells are normally read as colour 0.
+    fn num_colours(&self) -> usize;
+
+    // Make a single move of an ant that hasn't stalled, changing the grid
+    // as the engine's rules say, then turning and moving the ant with
+    // turn_and_move, which also counts the iteration.
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    );
+
+    // A copy of the engine, so simulations using it can be cloned.
+    fn box_clone(&self) -> Box<dyn RuleEngine>;
+}
+
+impl Clone for Box<dyn RuleEngine> {
+    fn clone(&self) -> Box<dyn RuleEngine> {
+        self.box_clone()
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Langton's ant with a plain rule, as run by the simulation itself, and the
+// model for writing other engines.
+#[derive(Clone, Debug)]
+pub struct LangtonEngine {
+    rule: Rule,
+}
+
+impl LangtonEngine {
+    // An engine for a rule, e.g. "RL", which must be a valid plain rule.
+    pub fn new(rule: &str) -> Result<LangtonEngine, String> {
+        let rule = Rule::parse(rule).map_err(|e| e.to_string())?;
+
+        Ok(LangtonEngine { rule })
+    }
+}
+
+impl RuleEngine for LangtonEngine {
+    fn name(&self) -> String {
+        self.rule.to_string()
+    }
+
+    fn num_colours(&self) -> usize {
+        self.rule.len()
+    }
+
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        let rule = &self.rule;
+        compute_ant_position_by(ant, grid, boundary, obstacles, rule.len(), |clr_idx| {
+            rule.turn(clr_idx)
+        });
+    }
+
+    fn box_clone(&self) -> Box<dyn RuleEngine> {
+        Box::new(self.clone())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// An ant that looks at its neighbours rather than just its own cell. It
+// toggles the colour of its cell and turns right if an even number of the
+// four cells around it are colour 1, otherwise left.
+#[derive(Clone, Debug, Default)]
+pub struct ParityEngine;
+
+impl RuleEngine for ParityEngine {
+    fn name(&self) -> String {
+        String::from("parity")
+    }
+
+    fn num_colours(&self) -> usize {
+        2
+    }
+
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        let (x, y) = (ant.pos_x, ant.pos_y);
+
+        let coloured = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .filter(|(dx, dy)| grid.contains(x + dx, y + dy) && grid.get(x + dx, y + dy) == 1)
+            .count();
+
+        let write = match grid.get(x, y) {
+            BLANK | OBSTACLE | 0 => 1,
+            _ => 0,
+        };
+
+        grid.set(x, y, write);
+
+        let turn = if coloured % 2 == 0 {
+            Direction::R
+        } else {
+            Direction::L
+        };
+
+        turn_and_move(turn, grid, ant, boundary, obstacles);
+    }
+
+    fn box_clone(&self) -> Box<dyn RuleEngine> {
+        Box::new(self.clone())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Turmites are engines too, so can be run alongside custom ones.
+impl RuleEngine for Turmite {
+    fn name(&self) -> String {
+        format!(
+            "turmite ({} states, {} colours)",
+            self.num_states(),
+            Turmite::num_colours(self)
+        )
+    }
+
+    fn num_colours(&self) -> usize {
+        Turmite::num_colours(self)
+    }
+
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        compute_turmite_position(ant, self, grid, boundary, obstacles);
+    }
+
+    fn box_clone(&self) -> Box<dyn RuleEngine> {
+        Box::new(self.clone())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Create a built in engine by name, one of BUILTIN_ENGINES. The langton
+// engine runs the given rule, which the others ignore.
+pub fn builtin(name: &str, rule: &str) -> Result<Box<dyn RuleEngine>, String> {
+    match name {
+        "langton" => Ok(Box::new(LangtonEngine::new(rule)?)),
+        "parity" => Ok(Box::new(ParityEngine)),
+        _ => Err(format!(
+            "no engine {}, expected one of {}",
+            name,
+            BUILTIN_ENGINES.join(", ")
+        )),
+    }
+}
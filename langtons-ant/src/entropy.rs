@@ -0,0 +1,128 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Estimates of how disordered a pattern is, to tell a chaotic pattern from
+// an ordered one such as a highway. Both look at the cells within the
+// pattern's bounding box, as bytes holding 0 for a blank cell or obstacle,
+// otherwise the colour index plus one.
+//
+// Block entropy is the Shannon entropy of the 2 by 2 blocks of cells, over
+// every position in the box, divided by 4 to give bits per cell. Random
+// cells of n colours approach log2(n + 1) bits per cell, while regular
+// patterns, being made of few distinct blocks, score far lower.
+//
+// The compression ratio is the size of the cells compressed with zlib over
+// their uncompressed size, lower for more ordered patterns.
+
+use crate::grid::{Extent, GridBackend, GridStorage, BLANK, OBSTACLE};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::Write;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Width and height of the blocks whose entropy is measured.
+const BLOCK_SIZE: i64 = 2;
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The block entropy of the pattern in bits per cell, 0 when there is no
+// pattern.
+pub fn block_entropy(cells: &GridBackend, bounds: Option<Extent>) -> f64 {
+    let b = match bounds {
+        Some(b) => b,
+        None => return 0.0,
+    };
+
+    // A box smaller than a block is measured as a single, partial block.
+    let (w, h) = (b.width as i64, b.height as i64);
+    let mut counts: HashMap<[u8; 4], u64> = HashMap::new();
+
+    for y in 0..(h - BLOCK_SIZE + 1).max(1) {
+        for x in 0..(w - BLOCK_SIZE + 1).max(1) {
+            let mut block = [0; 4];
+
+            for (i, cell) in block.iter_mut().enumerate() {
+                let (dx, dy) = (i as i64 % BLOCK_SIZE, i as i64 / BLOCK_SIZE);
+
+                if dx < w && dy < h {
+                    *cell = cell_byte(cells, b.x + x + dx, b.y + y + dy);
+                }
+            }
+
+            *counts.entry(block).or_insert(0) += 1;
+        }
+    }
+
+    let total = counts.values().sum::<u64>() as f64;
+
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+
+    entropy / (BLOCK_SIZE * BLOCK_SIZE) as f64
+}
+
+//-----------------------------------------------------------------------------
+// The pattern's size compressed with zlib as a fraction of its uncompressed
+// size, 0 when there is no pattern.
+pub fn compression_ratio(cells: &GridBackend, bounds: Option<Extent>) -> f64 {
+    let b = match bounds {
+        Some(b) => b,
+        None => return 0.0,
+    };
+
+    let mut bytes = Vec::with_capacity(b.width * b.height);
+
+    for y in b.y..b.y + b.height as i64 {
+        for x in b.x..b.x + b.width as i64 {
+            bytes.push(cell_byte(cells, x, y));
+        }
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+
+    // Writing to memory can't fail.
+    encoder.write_all(&bytes).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    compressed.len() as f64 / bytes.len() as f64
+}
+
+//-----------------------------------------------------------------------------
+// A cell as a byte, 0 for a blank cell or obstacle, otherwise the colour
+// index plus one, capped at the largest byte.
+fn cell_byte(cells: &GridBackend, x: i64, y: i64) -> u8 {
+    match cells.get(x, y) {
+        BLANK | OBSTACLE => 0,
+        clr_idx => (clr_idx + 1).min(usize::from(u8::MAX)) as u8,
+    }
+}
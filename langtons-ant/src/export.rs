@@ -0,0 +1,362 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Ant;
+use crate::colour::{OBSTACLE_GREY, WHITE};
+use crate::delta::Delta;
+use crate::grid::{Extent, GridStorage, BLANK, OBSTACLE};
+use crate::simulation::Simulation;
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, ImageResult, Rgba, RgbaImage};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Cursor, Write};
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Largest image, in pixels, rendered by render_scaled, including any
+// supersampling, about 1 GB of RGBA.
+pub const MAX_RENDER_PIXELS: u64 = 1 << 28;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A rectangle of cells all of the same colour, as the grid is drawn in SVG
+// files, with its position relative to the region exported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CellRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub clr_idx: usize,
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Render the current grid state to an RGBA image, drawing each cell as a
+// square block of px_per_cell x px_per_cell pixels. Unvisited cells are
+// drawn in the white background colour. For unbounded grids the image covers
+// every visited cell.
+pub fn render_image(sim: &Simulation, px_per_cell: u32) -> RgbaImage {
+    render_region(sim, sim.cells().extent(), px_per_cell)
+}
+
+//-----------------------------------------------------------------------------
+// Render the given region of the grid to an RGBA image in the same way as
+// render_image. Cells in the region outside the grid are drawn as unvisited.
+pub fn render_region(sim: &Simulation, extent: Extent, px_per_cell: u32) -> RgbaImage {
+    let px_per_cell = px_per_cell.max(1);
+    let mut img = RgbaImage::from_pixel(
+        extent.width as u32 * px_per_cell,
+        extent.height as u32 * px_per_cell,
+        Rgba(WHITE.to_rgba8()),
+    );
+    let colours = sim.colours();
+
+    for y in 0..extent.height {
+        for x in 0..extent.width {
+            let cell = sim.cells().get(extent.x + x as i64, extent.y + y as i64);
+
+            if cell == BLANK {
+                continue;
+            }
+
+            let pixel = match cell {
+                OBSTACLE => Rgba(OBSTACLE_GREY.to_rgba8()),
+                _ => Rgba(colours[cell].to_rgba8()),
+            };
+            let x0 = x as u32 * px_per_cell;
+            let y0 = y as u32 * px_per_cell;
+
+            for py in y0..y0 + px_per_cell {
+                for px in x0..x0 + px_per_cell {
+                    img.put_pixel(px, py, pixel);
+                }
+            }
+        }
+    }
+
+    img
+}
+
+//-----------------------------------------------------------------------------
+// Render the given region of the grid as a thumbnail no larger than size
+// pixels on either side, keeping its aspect ratio. Small regions are scaled
+// up by a whole number of pixels per cell, large ones scaled down smoothly.
+pub fn render_thumbnail(sim: &Simulation, extent: Extent, size: u32) -> RgbaImage {
+    let size = size.max(1);
+    let longest = extent.width.max(extent.height).max(1) as u32;
+    let img = render_region(sim, extent, size / longest);
+
+    if img.width() <= size && img.height() <= size {
+        return img;
+    }
+
+    let scale = size as f64 / img.width().max(img.height()) as f64;
+    let width = ((img.width() as f64 * scale).round() as u32).max(1);
+    let height = ((img.height() as f64 * scale).round() as u32).max(1);
+    imageops::resize(&img, width, height, FilterType::Triangle)
+}
+
+//-----------------------------------------------------------------------------
+// Render a region of the grid at any scale, e.g. for posters, where
+// px_per_cell needn't be a whole number. With a supersample factor above
+// 1 the region is drawn that many times larger and scaled down smoothly,
+// antialiasing cell edges that fall between pixels. Fails if the scale is
+// invalid or the image would be larger than MAX_RENDER_PIXELS.
+pub fn render_scaled(
+    sim: &Simulation,
+    extent: Extent,
+    px_per_cell: f64,
+    supersample: u32,
+) -> Result<RgbaImage, String> {
+    if !px_per_cell.is_finite() || px_per_cell <= 0.0 {
+        return Err(format!("invalid pixels per cell: {}", px_per_cell));
+    }
+
+    let supersample = supersample.max(1);
+    let scale = (px_per_cell * f64::from(supersample)).ceil() as u64;
+    let (width, height) = (extent.width as u64 * scale, extent.height as u64 * scale);
+
+    if width.saturating_mul(height) > MAX_RENDER_PIXELS || width.max(height) > u64::from(u32::MAX) {
+        return Err(format!(
+            "a {} x {} pixel image is larger than the limit of {} pixels",
+            width, height, MAX_RENDER_PIXELS
+        ));
+    }
+
+    let img = render_region(sim, extent, scale as u32);
+    let target_width = ((extent.width as f64 * px_per_cell).round() as u32).max(1);
+    let target_height = ((extent.height as f64 * px_per_cell).round() as u32).max(1);
+
+    if (img.width(), img.height()) == (target_width, target_height) {
+        return Ok(img);
+    }
+
+    let filter = if supersample > 1 {
+        FilterType::Triangle
+    } else {
+        FilterType::Nearest
+    };
+
+    Ok(imageops::resize(&img, target_width, target_height, filter))
+}
+
+//-----------------------------------------------------------------------------
+// Render the current grid state and write it to a PNG file.
+pub fn save_png<P: AsRef<Path>>(sim: &Simulation, path: P, px_per_cell: u32) -> ImageResult<()> {
+    render_image(sim, px_per_cell).save(path)
+}
+
+//-----------------------------------------------------------------------------
+// Render the current grid state and encode it as PNG data, e.g. to send
+// over the network.
+pub fn encode_png(sim: &Simulation, px_per_cell: u32) -> ImageResult<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    render_image(sim, px_per_cell).write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+//-----------------------------------------------------------------------------
+// Cover the visited cells of a region of the grid with rectangles of a
+// single colour. Each row is split greedily into runs of cells of the same
+// colour, and a run exactly matching one in the row above extends that
+// rectangle down rather than starting a new one, so blocks of colour become
+// a single rectangle.
+pub fn merged_rects(sim: &Simulation, extent: Extent) -> Vec<CellRect> {
+    let mut rects: Vec<CellRect> = Vec::new();
+    let mut above: HashMap<(usize, usize, usize), usize> = HashMap::new();
+
+    for y in 0..extent.height {
+        let mut row = HashMap::new();
+        let mut x = 0;
+
+        while x < extent.width {
+            let cell = |x: usize| sim.cells().get(extent.x + x as i64, extent.y + y as i64);
+            let clr_idx = cell(x);
+            let start = x;
+
+            while x < extent.width && cell(x) == clr_idx {
+                x += 1;
+            }
+
+            if clr_idx == BLANK {
+                continue;
+            }
+
+            let key = (start, x - start, clr_idx);
+
+            let idx = match above.get(&key) {
+                Some(&idx) => {
+                    rects[idx].height += 1;
+                    idx
+                }
+                None => {
+                    rects.push(CellRect {
+                        x: start,
+                        y,
+                        width: x - start,
+                        height: 1,
+                        clr_idx,
+                    });
+                    rects.len() - 1
+                }
+            };
+
+            row.insert(key, idx);
+        }
+
+        above = row;
+    }
+
+    rects
+}
+
+//-----------------------------------------------------------------------------
+// Write the current grid state as an SVG image, one unit per cell, with
+// the cells drawn as merged rectangles grouped by colour over a white
+// background, so the pattern can be printed at any size. For unbounded
+// grids the image covers every visited cell, as for render_image.
+pub fn write_svg<W: Write>(sim: &Simulation, out: &mut W) -> io::Result<()> {
+    let extent = sim.cells().extent();
+    let mut rects = merged_rects(sim, extent);
+    rects.sort_by_key(|r| r.clr_idx);
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" shape-rendering="crispEdges">"#,
+        w = extent.width,
+        h = extent.height
+    )?;
+    writeln!(
+        out,
+        r#"<rect width="{}" height="{}" fill="{}"/>"#,
+        extent.width,
+        extent.height,
+        WHITE.to_hex()
+    )?;
+
+    for group in rects.chunk_by(|a, b| a.clr_idx == b.clr_idx) {
+        let fill = sim.colour_map().cell_colour(group[0].clr_idx).to_hex();
+
+        writeln!(out, r#"<g fill="{}">"#, fill)?;
+
+        for r in group {
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                r.x, r.y, r.width, r.height
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+//-----------------------------------------------------------------------------
+// Write the current grid state to an SVG file.
+pub fn save_svg<P: AsRef<Path>>(sim: &Simulation, path: P) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_svg(sim, &mut out)?;
+    out.flush()
+}
+
+//-----------------------------------------------------------------------------
+// The grid as JSON, giving the region covered, as for render_image, and its
+// cells as an array of rows, each cell holding its colour index or -1 if
+// unvisited and -2 if an obstacle.
+pub fn grid_json(sim: &Simulation) -> Value {
+    let extent = sim.cells().extent();
+
+    let rows: Vec<Vec<i64>> = (0..extent.height as i64)
+        .map(|y| {
+            (0..extent.width as i64)
+                .map(|x| cell_json(sim.cells().get(extent.x + x, extent.y + y)))
+                .collect()
+        })
+        .collect();
+
+    json!({
+        "x": extent.x,
+        "y": extent.y,
+        "width": extent.width,
+        "height": extent.height,
+        "colours": sim.colours().iter().map(|c| c.to_hex()).collect::<Vec<_>>(),
+        "cells": rows,
+    })
+}
+
+//-----------------------------------------------------------------------------
+// The ants as a JSON array of their positions, facings and whether they've
+// stalled.
+pub fn ants_json(ants: &[Ant]) -> Value {
+    ants.iter()
+        .map(|ant| {
+            json!({
+                "x": ant.pos_x,
+                "y": ant.pos_y,
+                "facing": ant.facing.to_char().to_string(),
+                "stalled": ant.stalled,
+            })
+        })
+        .collect()
+}
+
+//-----------------------------------------------------------------------------
+// A delta as JSON, for bringing a copy of the grid from grid_json up to date
+// with the iteration given: each changed cell as an [x, y, colour] array,
+// with colours as in grid_json, and the ants as in ants_json.
+pub fn delta_json(delta: &Delta, iteration: u64) -> Value {
+    let cells: Vec<[i64; 3]> = delta
+        .cells
+        .iter()
+        .map(|&(x, y, clr_idx)| [x, y, cell_json(clr_idx)])
+        .collect();
+
+    json!({
+        "iteration": iteration,
+        "cells": cells,
+        "ants": ants_json(&delta.ants),
+    })
+}
+
+//-----------------------------------------------------------------------------
+// A cell's colour index as JSON, -1 if unvisited and -2 if an obstacle.
+fn cell_json(clr_idx: usize) -> i64 {
+    match clr_idx {
+        BLANK => -1,
+        OBSTACLE => -2,
+        _ => clr_idx as i64,
+    }
+}
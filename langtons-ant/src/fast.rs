@@ -0,0 +1,285 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{wrap, Ant, Boundary, Direction, Facing};
+use crate::grid::{Extent, GridStorage, BLANK, OBSTACLE};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// The largest window, in cells, the fast stepper will grow to before handing
+// back to the normal step function.
+const MAX_WINDOW_CELLS: usize = 1 << 28;
+
+// Most cells of window per move asked for. Copying the grid into the window
+// and back costs far less per cell than the normal step function does per
+// move, but short runs over big grids are still quicker stepped.
+const WINDOW_CELLS_PER_MOVE: u64 = 4;
+
+// Facing encoded as 0 = N, 1 = E, 2 = S, 3 = W, with the matching moves.
+const DX: [i64; 4] = [0, 1, 0, -1];
+const DY: [i64; 4] = [-1, 0, 1, 0];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A flat byte buffer covering a window of the grid. Each cell holds 0 for a
+// blank cell or the colour index plus 1.
+struct Window {
+    extent: Extent,
+    cells: Vec<u8>,
+}
+
+impl Window {
+    // Copy a region of the grid into a new window, or None if the region
+    // holds any obstacles.
+    fn load<G: GridStorage>(grid: &G, extent: Extent) -> Option<Window> {
+        let mut cells = vec![0; extent.width * extent.height];
+
+        for y in 0..extent.height {
+            for x in 0..extent.width {
+                let c = grid.get(extent.x + x as i64, extent.y + y as i64);
+
+                if c == OBSTACLE {
+                    return None;
+                }
+
+                if c != BLANK {
+                    cells[y * extent.width + x] = (c + 1) as u8;
+                }
+            }
+        }
+
+        Some(Window { extent, cells })
+    }
+
+    // Grow the window to double its size in each direction, centred on the
+    // old window, returning false if it would be too big.
+    fn grow(&mut self) -> bool {
+        let old = self.extent;
+        let width = old.width * 2;
+        let height = old.height * 2;
+
+        if width * height > MAX_WINDOW_CELLS {
+            return false;
+        }
+
+        let new = Extent {
+            x: old.x - (old.width / 2) as i64,
+            y: old.y - (old.height / 2) as i64,
+            width,
+            height,
+        };
+
+        let mut cells = vec![0; width * height];
+        let ox = (old.x - new.x) as usize;
+        let oy = (old.y - new.y) as usize;
+
+        for y in 0..old.height {
+            let src = &self.cells[y * old.width..(y + 1) * old.width];
+            let start = (y + oy) * width + ox;
+            cells[start..start + old.width].copy_from_slice(src);
+        }
+
+        self.extent = new;
+        self.cells = cells;
+        true
+    }
+
+    // Write every non-blank cell back to the grid.
+    fn store<G: GridStorage>(&self, grid: &mut G) {
+        for y in 0..self.extent.height {
+            for x in 0..self.extent.width {
+                let v = self.cells[y * self.extent.width + x];
+                if v != 0 {
+                    grid.set(
+                        self.extent.x + x as i64,
+                        self.extent.y + y as i64,
+                        (v - 1) as usize,
+                    );
+                }
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Advance a plain rule ant up to n moves using a macro-stepping loop: the
+// grid is copied into a flat byte window and the ant stepped with
+// precomputed colour and turn tables, only touching the grid again when it
+// leaves the window, in which case the window is grown (unbounded grids) or
+// the boundary mode applied (bounded grids). Returns the number of moves
+// made, which may be less than n if the ant stalls or the window can't grow
+// any further, in which case the caller should carry on with the normal
+// step function. Rules with more than 254 colours, grids with obstacles,
+// grids bigger than the largest window and runs too short for the window to
+// pay for itself are not handled and return 0.
+pub fn advance<G: GridStorage>(ant: &mut Ant, grid: &mut G, n: u64, boundary: Boundary) -> u64 {
+    let num_colours = ant.rule.len();
+
+    if ant.stalled || num_colours == 0 || num_colours > 254 {
+        return 0;
+    }
+
+    // Next cell value and new facing for each cell value and facing.
+    let mut next_value: Vec<u8> = Vec::with_capacity(num_colours + 1);
+    let mut next_facing: Vec<[u8; 4]> = Vec::with_capacity(num_colours + 1);
+
+    for v in 0..=num_colours {
+        let clr_idx = if v == 0 { 0 } else { v - 1 };
+        let turn = match ant.rule.turn(clr_idx) {
+            Direction::L => 3,
+            Direction::R => 1,
+            Direction::U => 2,
+            Direction::N => 0,
+            // Absolute facings don't depend on the current facing.
+            Direction::Face(facing) => {
+                next_value.push(((clr_idx + 1) % num_colours + 1) as u8);
+                next_facing.push([facing_to_index(facing); 4]);
+                continue;
+            }
+        };
+
+        next_value.push(((clr_idx + 1) % num_colours + 1) as u8);
+        next_facing.push([turn % 4, (1 + turn) % 4, (2 + turn) % 4, (3 + turn) % 4]);
+    }
+
+    // An unbounded grid's extent only covers the cells visited so far, so
+    // the ant can be on a blank cell just outside it. The pattern can also
+    // outgrow the largest window, e.g. once a highway has run a long way.
+    let ant_cell = Extent {
+        x: ant.pos_x,
+        y: ant.pos_y,
+        width: 1,
+        height: 1,
+    };
+    let extent = grid.extent().union(ant_cell);
+    let area = extent.width.saturating_mul(extent.height);
+
+    if area > MAX_WINDOW_CELLS || area as u64 > n.saturating_mul(WINDOW_CELLS_PER_MOVE) {
+        return 0;
+    }
+
+    let mut window = match Window::load(grid, extent) {
+        Some(window) => window,
+        None => return 0,
+    };
+
+    let (mut x, mut y) = (ant.pos_x, ant.pos_y);
+    let mut facing = facing_to_index(ant.facing);
+    let n = n.min(u64::MAX - ant.iterations);
+    let mut done = 0;
+
+    while done < n {
+        let e = window.extent;
+        let idx = (y - e.y) as usize * e.width + (x - e.x) as usize;
+        let v = window.cells[idx] as usize;
+
+        window.cells[idx] = next_value[v];
+        facing = next_facing[v][facing as usize];
+        done += 1;
+
+        let mut nx = x + DX[facing as usize];
+        let mut ny = y + DY[facing as usize];
+
+        if !e.contains(nx, ny) {
+            if !grid.contains(nx, ny) {
+                if boundary == Boundary::Stall {
+                    ant.stalled = true;
+                    break;
+                }
+
+                if boundary == Boundary::Wrap {
+                    let (wx, wy) = wrap(grid.extent(), nx, ny);
+                    x = wx;
+                    y = wy;
+                    continue;
+                }
+
+                // Bounce back the way the ant came, staying put if the grid
+                // is only one cell wide.
+                facing = (facing + 2) % 4;
+                nx = x + DX[facing as usize];
+                ny = y + DY[facing as usize];
+
+                if !grid.contains(nx, ny) {
+                    nx = x;
+                    ny = y;
+                }
+
+                x = nx;
+                y = ny;
+                continue;
+            }
+
+            if !window.grow() {
+                x = nx;
+                y = ny;
+                break;
+            }
+        }
+
+        x = nx;
+        y = ny;
+    }
+
+    window.store(grid);
+
+    ant.pos_x = x;
+    ant.pos_y = y;
+    ant.facing = index_to_facing(facing);
+    ant.iterations += done;
+
+    if u64::MAX == ant.iterations {
+        ant.stalled = true;
+    }
+
+    done
+}
+
+//-----------------------------------------------------------------------------
+// Convert a facing to its index, 0 = N, 1 = E, 2 = S, 3 = W.
+fn facing_to_index(facing: Facing) -> u8 {
+    match facing {
+        Facing::N => 0,
+        Facing::E => 1,
+        Facing::S => 2,
+        Facing::W => 3,
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Convert an index back to a facing.
+fn index_to_facing(idx: u8) -> Facing {
+    match idx {
+        0 => Facing::N,
+        1 => Facing::E,
+        2 => Facing::S,
+        _ => Facing::W,
+    }
+}
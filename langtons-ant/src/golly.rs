@@ -0,0 +1,537 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Compatibility with Golly, so turmites and patterns from the cellular
+// automata community can be used directly.
+//
+// Golly describes a turmite by a specification listing, for each state and
+// then each colour, the colour to write, the turn and the next state, e.g.
+// Langton's ant is {{{1,2,0},{0,8,0}}}. Turns are 1 for no turn, 2 for
+// right, 4 for u-turn and 8 for left. When every number is a single digit
+// the same table is written as a rule name, e.g. Turmite_120080. Both forms
+// are accepted, either directly or from a .rule file.
+//
+// Patterns can be read from Golly's .rle files, see the rle module, and its
+// .mc macrocell files. Cells holding a turmite in Golly's own encoding are
+// not supported, the ant is placed in the middle of the pattern instead.
+
+use crate::ant::Direction;
+use crate::grid::GridStorage;
+use crate::rule::Rule;
+use crate::simulation::Simulation;
+use crate::turmite::{Transition, Turmite};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Prefix of Golly's turmite rule names.
+const TURMITE_PREFIX: &str = "Turmite_";
+
+// Level of the 8 x 8 leaf nodes written for two state rules in macrocell
+// files.
+const LEAF_LEVEL: u32 = 3;
+
+// Largest macrocell tree level that will be expanded.
+const MAX_LEVEL: u32 = 40;
+
+// Most non-blank cells, and the longest side, of a macrocell pattern that
+// will be loaded. Nodes can be reused, so a small file can describe a vast
+// pattern. The cells are expanded into a map, kept to a few hundred
+// megabytes, then painted onto a dense grid, kept to the biggest drawn in a
+// window.
+const MAX_PATTERN_CELLS: u64 = 1 << 24;
+const MAX_PATTERN_SIZE: usize = 8192;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A node of a macrocell quadtree. Children are listed nw, ne, sw, se and
+// hold either states (level 1) or node numbers, 0 being an empty node.
+enum Node {
+    Leaf(Vec<(i64, i64)>),
+    Inner(u32, [usize; 4]),
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Parse a turmite from a Golly rule name, e.g. "Turmite_120080", or a
+// specification, e.g. "{{{1,2,0},{0,8,0}}}".
+pub fn parse_turmite(spec: &str) -> Result<Turmite, String> {
+    let spec = spec.trim();
+
+    let states = match spec.strip_prefix(TURMITE_PREFIX) {
+        Some(digits) => parse_rule_name(digits)?,
+        None => parse_spec(spec)?,
+    };
+
+    let mut transitions = Vec::new();
+
+    for (state, colours) in states.iter().enumerate() {
+        for (colour, &[write, turn, next]) in colours.iter().enumerate() {
+            transitions.push(Transition {
+                state,
+                colour,
+                write,
+                turn: turn_from_golly(turn)?,
+                next,
+            });
+        }
+    }
+
+    Turmite::new(transitions)
+}
+
+//-----------------------------------------------------------------------------
+// The Golly specification of a turmite, e.g. "{{{1,2,0},{0,8,0}}}".
+pub fn turmite_spec(turmite: &Turmite) -> String {
+    let states: Vec<String> = (0..turmite.num_states())
+        .map(|s| {
+            let colours: Vec<String> = (0..turmite.num_colours())
+                .map(|c| {
+                    let t = turmite.transition(s, c);
+                    format!("{{{},{},{}}}", t.write, turn_to_golly(t.turn), t.next)
+                })
+                .collect();
+            format!("{{{}}}", colours.join(","))
+        })
+        .collect();
+
+    format!("{{{}}}", states.join(","))
+}
+
+//-----------------------------------------------------------------------------
+// The Golly rule name of a turmite, e.g. "Turmite_120080". Only turmites
+// with up to 10 states and colours have a rule name.
+pub fn turmite_name(turmite: &Turmite) -> Result<String, String> {
+    if turmite.num_states() > 10 || turmite.num_colours() > 10 {
+        return Err(String::from(
+            "only turmites with up to 10 states and colours have a Golly rule name",
+        ));
+    }
+
+    let mut name = String::from(TURMITE_PREFIX);
+
+    for s in 0..turmite.num_states() {
+        for c in 0..turmite.num_colours() {
+            let t = turmite.transition(s, c);
+            name.push_str(&format!("{}{}{}", t.write, turn_to_golly(t.turn), t.next));
+        }
+    }
+
+    Ok(name)
+}
+
+//-----------------------------------------------------------------------------
+// Load a turmite from a Golly .rule file. The turmite is taken from the
+// @RULE name if it is a turmite rule name, otherwise from the first
+// specification found in the file, which Golly's rule generators include
+// in the file's comments.
+pub fn load_rule<P: AsRef<Path>>(path: P) -> io::Result<Turmite> {
+    let text = fs::read_to_string(path)?;
+    rule_file_turmite(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+//-----------------------------------------------------------------------------
+// Find the turmite in the text of a Golly .rule file.
+pub fn rule_file_turmite(text: &str) -> Result<Turmite, String> {
+    let name = text
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("@RULE"))
+        .map(str::trim);
+
+    if let Some(name) = name.filter(|n| n.starts_with(TURMITE_PREFIX)) {
+        return parse_turmite(name);
+    }
+
+    let start = text.find("{{{").ok_or("no turmite found in rule file")?;
+    let end = text[start..]
+        .find("}}}")
+        .ok_or("unterminated turmite specification")?;
+
+    parse_turmite(&text[start..start + end + 3])
+}
+
+//-----------------------------------------------------------------------------
+// Create a blank simulation for a rule given in a pattern file, either
+// directions, e.g. "RL", or a Golly turmite rule name.
+pub(crate) fn pattern_simulation(rule: &str, size: usize, seed: u64) -> Result<Simulation, String> {
+    if rule.starts_with(TURMITE_PREFIX) {
+        return Ok(Simulation::turmite_with_seed(
+            parse_turmite(rule)?,
+            size,
+            seed,
+        ));
+    }
+
+    if Rule::parse(rule).is_err() {
+        return Err(format!("unsupported rule: {}", rule));
+    }
+
+    Ok(Simulation::with_seed(rule, size, seed))
+}
+
+//-----------------------------------------------------------------------------
+// Decode a Golly macrocell (.mc) pattern into a simulation on a dense grid
+// just big enough for the pattern, with the pattern's top left cell at the
+// grid's.
+pub fn parse_macrocell(text: &str) -> Result<Simulation, String> {
+    let mut rule = String::from("RL");
+    let mut nodes = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(r) = line.strip_prefix("#R") {
+            rule = r.trim().to_string();
+        } else if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        } else if line.starts_with(['.', '*', '$']) {
+            nodes.push(parse_leaf(line));
+        } else {
+            nodes.push(parse_node(line, nodes.len())?);
+        }
+    }
+
+    let root = nodes.len();
+
+    if root == 0 {
+        return Err(String::from("no nodes in macrocell pattern"));
+    }
+
+    // The cells in each node are counted before expanding any, so huge
+    // patterns are rejected early and empty nodes skipped.
+    let mut counts: Vec<u64> = Vec::with_capacity(nodes.len());
+
+    for node in &nodes {
+        let count = match node {
+            Node::Leaf(live) => live.len() as u64,
+            Node::Inner(1, states) => states.iter().filter(|&&s| s != 0).count() as u64,
+            Node::Inner(_, children) => children
+                .iter()
+                .filter(|&&c| c != 0)
+                .fold(0u64, |sum, &c| sum.saturating_add(counts[c - 1])),
+        };
+        counts.push(count);
+    }
+
+    if counts[root - 1] > MAX_PATTERN_CELLS {
+        return Err(format!(
+            "macrocell pattern has {} cells, more than the {} supported",
+            counts[root - 1],
+            MAX_PATTERN_CELLS
+        ));
+    }
+
+    let mut cells = HashMap::new();
+    expand(&nodes, &counts, root, 0, 0, &mut cells)?;
+
+    let x0 = cells.keys().map(|c| c.0).min().unwrap_or(0);
+    let y0 = cells.keys().map(|c| c.1).min().unwrap_or(0);
+    let x1 = cells.keys().map(|c| c.0).max().unwrap_or(0);
+    let y1 = cells.keys().map(|c| c.1).max().unwrap_or(0);
+    let size = ((x1 - x0).max(y1 - y0) + 1) as usize;
+
+    if size > MAX_PATTERN_SIZE {
+        return Err(format!(
+            "macrocell pattern is {} cells across, more than the {} supported",
+            size, MAX_PATTERN_SIZE
+        ));
+    }
+
+    let mut sim = pattern_simulation(&rule, size, 0)?;
+    let extent = sim.cells().extent();
+
+    for ((x, y), state) in cells {
+        if state > sim.num_colours() {
+            return Err(format!(
+                "state {} at {}, {} is not a colour of the rule, cells holding an ant aren't supported",
+                state, x, y
+            ));
+        }
+
+        sim.paint(extent.x + x - x0, extent.y + y - y0, state - 1);
+    }
+
+    Ok(sim)
+}
+
+//-----------------------------------------------------------------------------
+// Load a Golly pattern file, either macrocell (.mc) or RLE (.rle).
+pub fn load_pattern<P: AsRef<Path>>(path: P) -> io::Result<Simulation> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+
+    let sim = match path.extension().and_then(|e| e.to_str()) {
+        Some("mc") => parse_macrocell(&text),
+        _ => crate::rle::parse(&text),
+    };
+
+    sim.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+//-----------------------------------------------------------------------------
+// Split the digits of a turmite rule name into (write, turn, next) triples
+// grouped by state. The number of colours is taken from the highest colour
+// written, falling back to the highest next state for the number of states.
+fn parse_rule_name(digits: &str) -> Result<Vec<Vec<[usize; 3]>>, String> {
+    if digits.is_empty()
+        || !digits.len().is_multiple_of(3)
+        || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(format!(
+            "invalid turmite rule name: {}{}",
+            TURMITE_PREFIX, digits
+        ));
+    }
+
+    let triples: Vec<[usize; 3]> = digits
+        .as_bytes()
+        .chunks(3)
+        .map(|t| {
+            [
+                (t[0] - b'0') as usize,
+                (t[1] - b'0') as usize,
+                (t[2] - b'0') as usize,
+            ]
+        })
+        .collect();
+
+    let max_write = triples.iter().map(|t| t[0]).max().unwrap_or(0);
+    let max_next = triples.iter().map(|t| t[2]).max().unwrap_or(0);
+
+    let mut num_colours = max_write + 1;
+
+    if !triples.len().is_multiple_of(num_colours) || triples.len() / num_colours <= max_next {
+        let num_states = max_next + 1;
+
+        if !triples.len().is_multiple_of(num_states) {
+            return Err(format!(
+                "can't split turmite rule name into states: {}{}",
+                TURMITE_PREFIX, digits
+            ));
+        }
+
+        num_colours = triples.len() / num_states;
+    }
+
+    Ok(triples.chunks(num_colours).map(|c| c.to_vec()).collect())
+}
+
+//-----------------------------------------------------------------------------
+// Parse a specification into (write, turn, next) triples grouped by state.
+fn parse_spec(spec: &str) -> Result<Vec<Vec<[usize; 3]>>, String> {
+    let mut states: Vec<Vec<[usize; 3]>> = Vec::new();
+    let mut numbers: Vec<usize> = Vec::new();
+    let mut depth = 0;
+    let mut number = String::new();
+
+    for c in spec.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        if !number.is_empty() {
+            numbers.push(number.parse().map_err(|_| "invalid number")?);
+            number.clear();
+        }
+
+        match c {
+            '{' => {
+                depth += 1;
+
+                if depth == 2 {
+                    states.push(Vec::new());
+                }
+            }
+            '}' => {
+                if depth == 3 {
+                    let triple = match numbers.as_slice() {
+                        &[write, turn, next] => [write, turn, next],
+                        _ => return Err(format!("expected 3 numbers per transition: {}", spec)),
+                    };
+                    states
+                        .last_mut()
+                        .ok_or("invalid specification")?
+                        .push(triple);
+                    numbers.clear();
+                }
+
+                depth -= 1;
+            }
+            ',' => {}
+            c if c.is_whitespace() => {}
+            _ => return Err(format!("invalid turmite specification: {}", spec)),
+        }
+
+        if !(0..=3).contains(&depth) {
+            return Err(format!("invalid turmite specification: {}", spec));
+        }
+    }
+
+    if depth != 0 || states.is_empty() {
+        return Err(format!("invalid turmite specification: {}", spec));
+    }
+
+    Ok(states)
+}
+
+//-----------------------------------------------------------------------------
+// Convert a Golly relative turn to a direction.
+fn turn_from_golly(turn: usize) -> Result<Direction, String> {
+    match turn {
+        1 => Ok(Direction::N),
+        2 => Ok(Direction::R),
+        4 => Ok(Direction::U),
+        8 => Ok(Direction::L),
+        _ => Err(format!("unsupported turmite turn: {}", turn)),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Convert a direction to a Golly relative turn.
+fn turn_to_golly(turn: Direction) -> usize {
+    match turn {
+        Direction::N => 1,
+        Direction::R => 2,
+        Direction::U => 4,
+        Direction::L => 8,
+        Direction::Face(_) => unreachable!("turmites only have relative turns"),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Parse an 8 x 8 two state leaf, e.g. "$.*$..*$***$", listing the live
+// cells.
+fn parse_leaf(line: &str) -> Node {
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0, 0);
+
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            '*' => {
+                cells.push((x, y));
+                x += 1;
+            }
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            _ => {}
+        }
+    }
+
+    Node::Leaf(cells)
+}
+
+//-----------------------------------------------------------------------------
+// Parse a "level nw ne sw se" node line. Child nodes must come earlier in
+// the file than their parents.
+fn parse_node(line: &str, num_nodes: usize) -> Result<Node, String> {
+    let fields: Vec<usize> = line
+        .split_whitespace()
+        .map(|f| {
+            f.parse()
+                .map_err(|_| format!("invalid macrocell node: {}", line))
+        })
+        .collect::<Result<_, _>>()?;
+
+    if fields.len() != 5 || fields[0] == 0 || fields[0] as u32 > MAX_LEVEL {
+        return Err(format!("invalid macrocell node: {}", line));
+    }
+
+    let children = [fields[1], fields[2], fields[3], fields[4]];
+
+    if fields[0] > 1 && children.iter().any(|&c| c > num_nodes) {
+        return Err(format!("macrocell node refers to a later node: {}", line));
+    }
+
+    Ok(Node::Inner(fields[0] as u32, children))
+}
+
+//-----------------------------------------------------------------------------
+// Expand node number n, with its top left corner at (x, y), into the
+// non-blank cells it contains, skipping nodes counted as empty.
+fn expand(
+    nodes: &[Node],
+    counts: &[u64],
+    n: usize,
+    x: i64,
+    y: i64,
+    cells: &mut HashMap<(i64, i64), usize>,
+) -> Result<(), String> {
+    if n == 0 || counts[n - 1] == 0 {
+        return Ok(());
+    }
+
+    match &nodes[n - 1] {
+        Node::Leaf(live) => {
+            for &(dx, dy) in live {
+                cells.insert((x + dx, y + dy), 1);
+            }
+        }
+        Node::Inner(1, states) => {
+            for (i, &state) in states.iter().enumerate() {
+                if state != 0 {
+                    cells.insert((x + (i % 2) as i64, y + (i / 2) as i64), state);
+                }
+            }
+        }
+        Node::Inner(level, children) => {
+            let half = 1i64 << (level - 1);
+
+            for (i, &child) in children.iter().enumerate() {
+                if child != 0 && child_level(nodes, child) != level - 1 {
+                    return Err(String::from("inconsistent macrocell node levels"));
+                }
+
+                expand(
+                    nodes,
+                    counts,
+                    child,
+                    x + (i % 2) as i64 * half,
+                    y + (i / 2) as i64 * half,
+                    cells,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------
+// The level of node number n.
+fn child_level(nodes: &[Node], n: usize) -> u32 {
+    match &nodes[n - 1] {
+        Node::Leaf(_) => LEAF_LEVEL,
+        Node::Inner(level, _) => *level,
+    }
+}
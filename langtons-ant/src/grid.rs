@@ -0,0 +1,792 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The colour index of a cell that has never been visited.
+pub const BLANK: usize = usize::MAX;
+
+//-----------------------------------------------------------------------------
+// The value of an obstacle cell, which ants can't enter. Like BLANK this
+// isn't a colour index, so cells hold either a colour index or one of the
+// two.
+pub const OBSTACLE: usize = usize::MAX - 1;
+
+//-----------------------------------------------------------------------------
+// Unbounded grids are stored in square chunks of 2^CHUNK_BITS cells along
+// each side.
+pub const CHUNK_BITS: u32 = 6;
+pub const CHUNK_SIZE: usize = 1 << CHUNK_BITS;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A rectangular region of the plane in cell coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Extent {
+    pub x: i64,
+    pub y: i64,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Extent {
+    // A region of width by height cells centred on the origin, e.g. a
+    // grid's initial region.
+    pub fn centred(width: usize, height: usize) -> Extent {
+        Extent {
+            x: centred_start(width),
+            y: centred_start(height),
+            width,
+            height,
+        }
+    }
+
+    // Does the region contain the given cell?
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as i64
+            && y < self.y + self.height as i64
+    }
+
+    // Smallest region covering both this and another region.
+    pub fn union(self, other: Extent) -> Extent {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width as i64).max(other.x + other.width as i64);
+        let bottom = (self.y + self.height as i64).max(other.y + other.height as i64);
+
+        Extent {
+            x,
+            y,
+            width: (right - x) as usize,
+            height: (bottom - y) as usize,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Interface to the storage of the grid's cells, so that dense and sparse
+// backends are interchangeable. Cells are addressed by signed coordinates,
+// with the origin, (0, 0), at the centre of the grid where the ant starts,
+// and hold a colour index, BLANK if never visited or OBSTACLE. Coordinates
+// are only translated to pixels or rows and columns when drawn or stored.
+pub trait GridStorage {
+    // Get the colour index of a cell, BLANK for cells outside the grid.
+    fn get(&self, x: i64, y: i64) -> usize;
+
+    // Set the colour index of a cell within the grid.
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize);
+
+    // Is the cell within the grid, i.e. can the ant move onto it?
+    fn contains(&self, x: i64, y: i64) -> bool;
+
+    // The region of the plane covering every cell that may be non-blank.
+    fn extent(&self) -> Extent;
+}
+
+//-----------------------------------------------------------------------------
+// The row structure defnies the current colour code for each cell
+// on a given row. Dense grids are saved as a list of rows.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Row {
+    pub cells: Vec<usize>,
+}
+
+impl Row {
+    pub fn new(num_cells: usize, clr_idx: usize) -> Row {
+        let mut r = Row { cells: Vec::new() };
+        r.cells.resize(num_cells, clr_idx);
+        r
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The grid structure encoding the state of each cell as a numerical value
+// between 0 and n - 1, where there are n colours, one for each move in
+// a rule. The cells are held in a single flat array, row after row from the
+// cell at left, top, so finding a cell takes a multiply rather than
+// indexing a row and then a cell within it, each bounds checked. Grids
+// saved before the origin moved to the centre start at 0, 0.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "GridData", into = "GridData")]
+pub struct Grid {
+    cells: Vec<usize>,
+    width: usize,
+    height: usize,
+    pub left: i64,
+    pub top: i64,
+}
+
+// Serialised form of a dense grid, a list of rows as saved before the cells
+// were flattened, so older saves still load.
+#[derive(Serialize, Deserialize)]
+struct GridData {
+    rows: Vec<Row>,
+    #[serde(default)]
+    left: i64,
+    #[serde(default)]
+    top: i64,
+}
+
+impl Grid {
+    // Create a grid centred on the origin.
+    pub fn new(num_rows: usize, num_cols: usize, clr_idx: usize) -> Grid {
+        Grid {
+            cells: vec![clr_idx; num_rows * num_cols],
+            width: num_cols,
+            height: num_rows,
+            left: centred_start(num_cols),
+            top: centred_start(num_rows),
+        }
+    }
+
+    // The position of a cell within the grid, counting along the rows.
+    fn index(&self, x: i64, y: i64) -> usize {
+        (y - self.top) as usize * self.width + (x - self.left) as usize
+    }
+}
+
+impl GridStorage for Grid {
+    fn get(&self, x: i64, y: i64) -> usize {
+        if self.contains(x, y) {
+            let i = self.index(x, y);
+            debug_assert!(i < self.cells.len());
+
+            // SAFETY: the cell is within the grid, so its index is less than
+            // width * height, the number of cells.
+            unsafe { *self.cells.get_unchecked(i) }
+        } else {
+            BLANK
+        }
+    }
+
+    // Writes keep the single bounds check of the flat index, as the trait
+    // is safe to call and a cell outside the grid must never be written.
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize) {
+        debug_assert!(self.contains(x, y));
+        let i = self.index(x, y);
+        self.cells[i] = clr_idx;
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        self.extent().contains(x, y)
+    }
+
+    fn extent(&self) -> Extent {
+        Extent {
+            x: self.left,
+            y: self.top,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl From<GridData> for Grid {
+    fn from(data: GridData) -> Grid {
+        let width = data.rows.first().map_or(0, |r| r.cells.len());
+        let height = data.rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+
+        // Short rows are padded with blank cells and long ones cut, so the
+        // grid is always width by height.
+        for row in data.rows {
+            cells.extend(
+                row.cells
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(BLANK))
+                    .take(width),
+            );
+        }
+
+        Grid {
+            cells,
+            width,
+            height,
+            left: data.left,
+            top: data.top,
+        }
+    }
+}
+
+impl From<Grid> for GridData {
+    fn from(grid: Grid) -> GridData {
+        let rows = match grid.width {
+            0 => (0..grid.height).map(|_| Row::new(0, BLANK)).collect(),
+            width => grid
+                .cells
+                .chunks(width)
+                .map(|cells| Row {
+                    cells: cells.to_vec(),
+                })
+                .collect(),
+        };
+
+        GridData {
+            rows,
+            left: grid.left,
+            top: grid.top,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A square block of an unbounded grid's cells, stored densely, with the
+// number of them that aren't blank so empty chunks can be dropped.
+#[derive(Clone)]
+struct Chunk {
+    cells: Vec<usize>,
+    visited: usize,
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            cells: vec![BLANK; CHUNK_SIZE * CHUNK_SIZE],
+            visited: 0,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// An unbounded grid so the ant can roam an effectively infinite plane. Cells
+// are stored in CHUNK_SIZE square chunks, dense arrays kept in a hash map
+// and only created once a cell in them is visited, so the regions the ant
+// works over run at nearly the speed of a dense grid. It keeps a nominal
+// size, the region initially shown, centred on the origin, the bounding box
+// of the visited cells and which chunks have changed, so renderers can
+// redraw just those.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "SparseGridData", into = "SparseGridData")]
+pub struct SparseGrid {
+    size: usize,
+    chunks: HashMap<(i64, i64), Chunk>,
+    len: usize,
+    bounds: Option<(i64, i64, i64, i64)>,
+    dirty: HashSet<(i64, i64)>,
+}
+
+// Serialised form of a sparse grid, as JSON map keys must be strings.
+#[derive(Serialize, Deserialize)]
+struct SparseGridData {
+    size: usize,
+    cells: Vec<(i64, i64, usize)>,
+}
+
+impl SparseGrid {
+    // Create an empty sparse grid with the given nominal size.
+    pub fn new(size: usize) -> SparseGrid {
+        SparseGrid {
+            size,
+            chunks: HashMap::new(),
+            len: 0,
+            bounds: None,
+            dirty: HashSet::new(),
+        }
+    }
+
+    // The number of cells that have been visited.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Has no cell been visited yet?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // The number of chunks holding visited cells.
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    // The regions covered by the chunks holding visited cells, in no
+    // particular order.
+    pub fn chunk_extents(&self) -> Vec<Extent> {
+        self.chunks.keys().map(|&key| chunk_extent(key)).collect()
+    }
+
+    // Take the regions covered by the chunks with cells changed since the
+    // last call, including chunks since emptied.
+    pub fn take_dirty_chunks(&mut self) -> Vec<Extent> {
+        self.dirty.drain().map(chunk_extent).collect()
+    }
+
+    // Call f with the coordinates and colour index of every visited cell,
+    // chunk by chunk.
+    fn for_each_visited<F: FnMut(i64, i64, usize)>(&self, mut f: F) {
+        for (&key, chunk) in self.chunks.iter() {
+            let region = chunk_extent(key);
+
+            for (i, &cell) in chunk.cells.iter().enumerate() {
+                if cell != BLANK {
+                    let (x, y) = ((i % CHUNK_SIZE) as i64, (i / CHUNK_SIZE) as i64);
+                    f(region.x + x, region.y + y, cell);
+                }
+            }
+        }
+    }
+}
+
+impl GridStorage for SparseGrid {
+    fn get(&self, x: i64, y: i64) -> usize {
+        let (key, i) = chunk_index(x, y);
+        self.chunks.get(&key).map_or(BLANK, |chunk| chunk.cells[i])
+    }
+
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize) {
+        let (key, i) = chunk_index(x, y);
+
+        if clr_idx == BLANK {
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                if chunk.cells[i] != BLANK {
+                    chunk.cells[i] = BLANK;
+                    chunk.visited -= 1;
+                    self.len -= 1;
+                    self.dirty.insert(key);
+                }
+
+                if chunk.visited == 0 {
+                    self.chunks.remove(&key);
+                }
+            }
+            return;
+        }
+
+        let chunk = self.chunks.entry(key).or_insert_with(Chunk::new);
+
+        if chunk.cells[i] == BLANK {
+            chunk.visited += 1;
+            self.len += 1;
+        }
+
+        chunk.cells[i] = clr_idx;
+        self.dirty.insert(key);
+
+        self.bounds = Some(match self.bounds {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    fn contains(&self, _x: i64, _y: i64) -> bool {
+        true
+    }
+
+    fn extent(&self) -> Extent {
+        let lo = centred_start(self.size);
+        let hi = lo + self.size as i64 - 1;
+        let (x0, y0, x1, y1) = match self.bounds {
+            Some((x0, y0, x1, y1)) => (x0.min(lo), y0.min(lo), x1.max(hi), y1.max(hi)),
+            None => (lo, lo, hi, hi),
+        };
+
+        Extent {
+            x: x0,
+            y: y0,
+            width: (x1 - x0 + 1) as usize,
+            height: (y1 - y0 + 1) as usize,
+        }
+    }
+}
+
+impl From<SparseGridData> for SparseGrid {
+    fn from(data: SparseGridData) -> SparseGrid {
+        let mut grid = SparseGrid::new(data.size);
+        for (x, y, clr_idx) in data.cells {
+            grid.set(x, y, clr_idx);
+        }
+        grid.dirty.clear();
+        grid
+    }
+}
+
+impl From<SparseGrid> for SparseGridData {
+    fn from(grid: SparseGrid) -> SparseGridData {
+        let mut cells = Vec::with_capacity(grid.len);
+        grid.for_each_visited(|x, y, c| cells.push((x, y, c)));
+
+        SparseGridData {
+            size: grid.size,
+            cells,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A fixed size grid packing each cell into 4 or 8 bits rather than a whole
+// usize, cutting memory use 16 or 8 times on big grids and fitting more of
+// the grid in the cache. Cells are stored as codes, 0 for BLANK, 1 for
+// OBSTACLE and the colour index plus 2 otherwise, so 4 bit cells fit rules
+// of up to 14 steps and 8 bit cells up to 254. With 4 bits two cells share
+// each byte, the even numbered cell of a pair in the low nibble. Like the
+// dense grid it's centred on the origin, with its top left cell at left,
+// top.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "PackedGridData", into = "PackedGridData")]
+pub struct PackedGrid {
+    width: usize,
+    height: usize,
+    bits: u8,
+    cells: Vec<u8>,
+    left: i64,
+    top: i64,
+}
+
+// Serialised form of a packed grid, checked when loaded so a corrupt save
+// fails to load rather than giving cells that don't fit the grid.
+#[derive(Serialize, Deserialize)]
+struct PackedGridData {
+    width: usize,
+    height: usize,
+    bits: u8,
+    cells: Vec<u8>,
+    #[serde(default)]
+    left: i64,
+    #[serde(default)]
+    top: i64,
+}
+
+impl PackedGrid {
+    // Create a blank packed grid with cells of 4 or 8 bits.
+    pub fn new(num_rows: usize, num_cols: usize, bits: u8) -> Result<PackedGrid, String> {
+        let bytes = PackedGrid::bytes_needed(num_rows, num_cols, bits)?;
+
+        Ok(PackedGrid {
+            width: num_cols,
+            height: num_rows,
+            bits,
+            cells: vec![0; bytes],
+            left: centred_start(num_cols),
+            top: centred_start(num_rows),
+        })
+    }
+
+    // The number of bits each cell is packed into.
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    // The most colours cells of the given number of bits can hold.
+    pub fn max_colours(bits: u8) -> usize {
+        (1 << bits) - 2
+    }
+
+    // The number of bytes holding the cells.
+    pub fn len_bytes(&self) -> usize {
+        self.cells.len()
+    }
+
+    // The number of bytes holding the cells of a grid of the given size,
+    // packed into 4 or 8 bits each.
+    fn bytes_needed(num_rows: usize, num_cols: usize, bits: u8) -> Result<usize, String> {
+        let num_cells = num_rows
+            .checked_mul(num_cols)
+            .ok_or_else(|| format!("a {} x {} grid is too large", num_cols, num_rows))?;
+
+        match bits {
+            4 => Ok(num_cells.div_ceil(2)),
+            8 => Ok(num_cells),
+            _ => Err(format!(
+                "cells can be packed into 4 or 8 bits, not {}",
+                bits
+            )),
+        }
+    }
+
+    // The position of a cell within the grid, counting along the rows.
+    fn index(&self, x: i64, y: i64) -> usize {
+        (y - self.top) as usize * self.width + (x - self.left) as usize
+    }
+
+    fn code(&self, i: usize) -> u8 {
+        match self.bits {
+            4 => (self.cells[i / 2] >> (4 * (i % 2))) & 0xf,
+            _ => self.cells[i],
+        }
+    }
+
+    fn set_code(&mut self, i: usize, code: u8) {
+        match self.bits {
+            4 => {
+                let shift = 4 * (i % 2);
+                let byte = &mut self.cells[i / 2];
+                *byte = (*byte & !(0xf << shift)) | (code << shift);
+            }
+            _ => self.cells[i] = code,
+        }
+    }
+}
+
+impl GridStorage for PackedGrid {
+    fn get(&self, x: i64, y: i64) -> usize {
+        if !self.contains(x, y) {
+            return BLANK;
+        }
+
+        match self.code(self.index(x, y)) {
+            0 => BLANK,
+            1 => OBSTACLE,
+            code => code as usize - 2,
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize) {
+        debug_assert!(self.contains(x, y));
+        debug_assert!(clr_idx >= OBSTACLE || clr_idx < PackedGrid::max_colours(self.bits));
+
+        let code = match clr_idx {
+            BLANK => 0,
+            OBSTACLE => 1,
+            _ => (clr_idx + 2) as u8,
+        };
+
+        self.set_code(self.index(x, y), code);
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        self.extent().contains(x, y)
+    }
+
+    fn extent(&self) -> Extent {
+        Extent {
+            x: self.left,
+            y: self.top,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl TryFrom<PackedGridData> for PackedGrid {
+    type Error = String;
+
+    fn try_from(data: PackedGridData) -> Result<PackedGrid, String> {
+        let bytes = PackedGrid::bytes_needed(data.height, data.width, data.bits)?;
+
+        if data.cells.len() != bytes {
+            return Err(format!(
+                "a {} x {} grid of {} bit cells needs {} bytes, not {}",
+                data.width,
+                data.height,
+                data.bits,
+                bytes,
+                data.cells.len()
+            ));
+        }
+
+        Ok(PackedGrid {
+            width: data.width,
+            height: data.height,
+            bits: data.bits,
+            cells: data.cells,
+            left: data.left,
+            top: data.top,
+        })
+    }
+}
+
+impl From<PackedGrid> for PackedGridData {
+    fn from(grid: PackedGrid) -> PackedGridData {
+        PackedGridData {
+            width: grid.width,
+            height: grid.height,
+            bits: grid.bits,
+            cells: grid.cells,
+            left: grid.left,
+            top: grid.top,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The grid backend used by a simulation, either a dense fixed size grid, a
+// fixed size grid of packed cells or a sparse unbounded one. The packed grid
+// comes last as serde tries each in turn.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GridBackend {
+    Dense(Grid),
+    Sparse(SparseGrid),
+    Packed(PackedGrid),
+}
+
+impl GridBackend {
+    // The nominal size of the grid as a number of squares along each side.
+    // For a sparse grid this is the size of the region initially shown.
+    pub fn size(&self) -> usize {
+        match self {
+            GridBackend::Dense(g) => g.height,
+            GridBackend::Sparse(g) => g.size,
+            GridBackend::Packed(g) => g.height,
+        }
+    }
+
+    // Convert to a sparse unbounded grid, keeping all visited cells.
+    pub fn to_sparse(&self) -> SparseGrid {
+        match self {
+            GridBackend::Sparse(g) => g.clone(),
+            _ => {
+                let mut sparse = SparseGrid::new(self.size());
+                self.for_each_visited(|x, y, c| sparse.set(x, y, c));
+                sparse
+            }
+        }
+    }
+
+    // Convert to a fixed size grid of packed cells with the given number of
+    // bits, keeping all visited cells. Only bounded grids can be packed.
+    pub fn to_packed(&self, bits: u8) -> Result<PackedGrid, String> {
+        let size = match self {
+            GridBackend::Sparse(_) => return Err("unbounded grids can't be packed".to_string()),
+            _ => self.size(),
+        };
+
+        let extent = self.extent();
+        let mut packed = PackedGrid::new(size, size, bits)?;
+        packed.left = extent.x;
+        packed.top = extent.y;
+
+        let max = PackedGrid::max_colours(bits);
+        let mut fits = true;
+
+        self.for_each_visited(|_, _, c| fits &= c >= OBSTACLE || c < max);
+
+        if !fits {
+            return Err(format!("{} bit cells hold at most {} colours", bits, max));
+        }
+
+        self.for_each_visited(|x, y, c| packed.set(x, y, c));
+        Ok(packed)
+    }
+
+    // Call f with the coordinates and colour index of every non-blank cell,
+    // in no particular order.
+    pub fn for_each_visited<F: FnMut(i64, i64, usize)>(&self, mut f: F) {
+        match self {
+            GridBackend::Dense(g) => {
+                for (i, &cell) in g.cells.iter().enumerate() {
+                    if cell != BLANK {
+                        f(
+                            g.left + (i % g.width) as i64,
+                            g.top + (i / g.width) as i64,
+                            cell,
+                        );
+                    }
+                }
+            }
+            GridBackend::Sparse(g) => g.for_each_visited(f),
+            GridBackend::Packed(g) => {
+                for y in g.top..g.top + g.height as i64 {
+                    for x in g.left..g.left + g.width as i64 {
+                        let cell = g.get(x, y);
+
+                        if cell != BLANK {
+                            f(x, y, cell);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl GridStorage for GridBackend {
+    fn get(&self, x: i64, y: i64) -> usize {
+        match self {
+            GridBackend::Dense(g) => g.get(x, y),
+            GridBackend::Sparse(g) => g.get(x, y),
+            GridBackend::Packed(g) => g.get(x, y),
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, clr_idx: usize) {
+        match self {
+            GridBackend::Dense(g) => g.set(x, y, clr_idx),
+            GridBackend::Sparse(g) => g.set(x, y, clr_idx),
+            GridBackend::Packed(g) => g.set(x, y, clr_idx),
+        }
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        match self {
+            GridBackend::Dense(g) => g.contains(x, y),
+            GridBackend::Sparse(g) => g.contains(x, y),
+            GridBackend::Packed(g) => g.contains(x, y),
+        }
+    }
+
+    fn extent(&self) -> Extent {
+        match self {
+            GridBackend::Dense(g) => g.extent(),
+            GridBackend::Sparse(g) => g.extent(),
+            GridBackend::Packed(g) => g.extent(),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The first coordinate along a side of len cells centred on the origin, so
+// the origin is the middle cell, or the one after the middle for even
+// sizes.
+pub fn centred_start(len: usize) -> i64 {
+    -((len / 2) as i64)
+}
+
+//-----------------------------------------------------------------------------
+// The chunk of an unbounded grid holding a cell, and the cell's index within
+// it. Shifting rounds down, so negative coordinates fall in the chunk to
+// their left or above.
+fn chunk_index(x: i64, y: i64) -> ((i64, i64), usize) {
+    let mask = CHUNK_SIZE as i64 - 1;
+    let key = (x >> CHUNK_BITS, y >> CHUNK_BITS);
+    (
+        key,
+        (((y & mask) as usize) << CHUNK_BITS) | (x & mask) as usize,
+    )
+}
+
+//-----------------------------------------------------------------------------
+// The region covered by a chunk.
+fn chunk_extent((cx, cy): (i64, i64)) -> Extent {
+    Extent {
+        x: cx << CHUNK_BITS,
+        y: cy << CHUNK_BITS,
+        width: CHUNK_SIZE,
+        height: CHUNK_SIZE,
+    }
+}
@@ -0,0 +1,236 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{GridBackend, GridStorage, SparseGrid, BLANK};
+use crate::simulation::Simulation;
+use std::collections::{HashSet, VecDeque};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Number of consecutive periods the ant's motion must repeat for before it
+// is taken to be a highway.
+const REPEATS: usize = 3;
+
+// Fewest moves the ant's motion must repeat for, so short stretches of
+// diagonal steps in chaotic growth aren't mistaken for highways.
+const MIN_SPAN: usize = 1000;
+
+// Number of moves between checks for a highway.
+const CHECK_EVERY: u64 = 500;
+
+// Number of periods a pattern cut out of a highway must rebuild it for on a
+// blank grid.
+const PATTERN_REPEATS: u64 = 4;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A highway, where the ant repeats the same sequence of moves over and over,
+// shifting by the same displacement each time. Langton's original RL ant
+// builds one with a period of 104 moves after about 10,000 moves.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Highway {
+    // Number of moves in each repeat.
+    pub period: u64,
+    // Iteration from which the ant's motion has been periodic, as far back
+    // as the detector remembers.
+    pub start: u64,
+    // How far the ant moves each period.
+    pub displacement: (i64, i64),
+}
+
+//-----------------------------------------------------------------------------
+// Watches the first ant's path for a highway. After every move the ant's
+// position is remembered over a sliding window, and the motion is periodic
+// when, for some period P, each position is the one P moves before shifted
+// by the same non-zero displacement for several periods running.
+pub struct HighwayDetector {
+    max_period: usize,
+    path: VecDeque<(u64, i64, i64)>,
+    next_check: u64,
+    found: Option<Highway>,
+}
+
+impl HighwayDetector {
+    // Create a detector for highways with a period of up to max_period
+    // moves.
+    pub fn new(max_period: usize) -> HighwayDetector {
+        HighwayDetector {
+            max_period: max_period.max(1),
+            path: VecDeque::new(),
+            next_check: 0,
+            found: None,
+        }
+    }
+
+    // The highway found, if any.
+    pub fn found(&self) -> Option<Highway> {
+        self.found
+    }
+
+    // Note the first ant's position after a move, returning the highway
+    // the first time one is found. Moves made without observing them are
+    // treated as a break in the path.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<Highway> {
+        if self.found.is_some() {
+            return None;
+        }
+
+        let ant = sim.ant();
+
+        match self.path.back() {
+            Some(&(iterations, _, _)) if iterations == ant.iterations => return None,
+            Some(&(iterations, _, _)) if iterations + 1 != ant.iterations => self.path.clear(),
+            _ => {}
+        }
+
+        if self.path.len() == (REPEATS + 1) * self.max_period + MIN_SPAN {
+            self.path.pop_front();
+        }
+
+        self.path.push_back((ant.iterations, ant.pos_x, ant.pos_y));
+
+        if ant.iterations < self.next_check {
+            return None;
+        }
+
+        self.next_check = ant.iterations + CHECK_EVERY;
+        self.found = self.detect();
+        self.found
+    }
+
+    // Cut the highway found out of the run, as a pattern that rebuilds it
+    // when placed on a blank grid: the cells the ant crossed over its last
+    // few periods and the ant itself, moved so the ant is at the origin.
+    // The fewest periods that rebuild the highway on their own are taken,
+    // so the pattern is as near the repeating unit as can be. The simulation
+    // must be the one observed, at its last observed move. None if no
+    // highway has been found, or no cut of it rebuilds it, e.g. as it still
+    // reads cells left from before it began.
+    pub fn pattern(&self, sim: &Simulation) -> Option<Simulation> {
+        let highway = self.found?;
+        let period = highway.period as usize;
+        let ant = sim.ant();
+
+        if self.path.back().map(|p| p.0) != Some(ant.iterations) {
+            return None;
+        }
+
+        // The periods of the path recorded since the highway began.
+        let since_start = self.path.iter().filter(|p| p.0 >= highway.start).count();
+
+        (1..=since_start.saturating_sub(1) / period)
+            .map(|periods| self.cut(sim, periods * period))
+            .find(|pattern| self.rebuilds(pattern, highway))
+    }
+
+    // The cells crossed over the last n moves and the first ant, moved so
+    // the ant is at the origin, on an unbounded grid.
+    fn cut(&self, sim: &Simulation, n: usize) -> Simulation {
+        let ant = sim.ant();
+        let mut grid = SparseGrid::new(sim.size());
+        let mut cells = HashSet::new();
+
+        for &(_, x, y) in self.path.iter().skip(self.path.len() - 1 - n) {
+            if cells.insert((x, y)) {
+                let clr_idx = sim.cells().get(x, y);
+
+                if clr_idx != BLANK {
+                    grid.set(x - ant.pos_x, y - ant.pos_y, clr_idx);
+                }
+            }
+        }
+
+        let mut pattern_ant = ant.clone();
+        pattern_ant.pos_x = 0;
+        pattern_ant.pos_y = 0;
+        pattern_ant.iterations = 0;
+
+        sim.with_cells(GridBackend::Sparse(grid), vec![pattern_ant])
+    }
+
+    // Does the pattern, run on its own, repeat the highway's last period
+    // over and over?
+    fn rebuilds(&self, pattern: &Simulation, highway: Highway) -> bool {
+        let period = highway.period as usize;
+        let (dx, dy) = highway.displacement;
+        let last = self.path.len() - 1 - period;
+        let (_, x0, y0) = self.path[last];
+        let mut run = pattern.clone();
+
+        (0..PATTERN_REPEATS).all(|repeat| {
+            (1..=period).all(|i| {
+                run.step();
+
+                let (_, x, y) = self.path[last + i];
+                let shift = repeat as i64;
+                let expected = (x - x0 + shift * dx, y - y0 + shift * dy);
+
+                (run.ant().pos_x, run.ant().pos_y) == expected
+            })
+        })
+    }
+
+    // Look for the shortest period the recent path repeats with.
+    fn detect(&self) -> Option<Highway> {
+        let n = self.path.len();
+        let shift = |i: usize, p: usize| {
+            let (_, x1, y1) = self.path[i];
+            let (_, x0, y0) = self.path[i - p];
+            (x1 - x0, y1 - y0)
+        };
+
+        for p in 1..=self.max_period {
+            let span = (REPEATS * p).max(MIN_SPAN);
+
+            if n < span + p {
+                break;
+            }
+
+            let displacement = shift(n - 1, p);
+
+            if displacement == (0, 0) {
+                continue;
+            }
+
+            if (n - span..n).all(|i| shift(i, p) == displacement) {
+                // Follow the repeats back as far as the path goes.
+                let mut first = n - span;
+
+                while first > p && shift(first - 1, p) == displacement {
+                    first -= 1;
+                }
+
+                return Some(Highway {
+                    period: p as u64,
+                    start: self.path[first - p].0,
+                    displacement,
+                });
+            }
+        }
+
+        None
+    }
+}
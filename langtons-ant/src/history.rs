@@ -0,0 +1,90 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::replay::Change;
+use crate::simulation::Simulation;
+use std::collections::VecDeque;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A ring buffer of the most recent changes made to a simulation, so it can
+// be rewound. The simulation must be tracking its changes, which are taken
+// from it as they are recorded. Rewinding undoes the changes exactly, so it
+// works for turmites, boundaries and cells painted by hand alike.
+pub struct History {
+    changes: VecDeque<Change>,
+    capacity: usize,
+}
+
+impl History {
+    // Create a history holding up to capacity changes, the oldest being
+    // dropped once it is full.
+    pub fn new(capacity: usize) -> History {
+        History {
+            changes: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    // Record the changes made to the simulation since the last call.
+    pub fn record(&mut self, sim: &mut Simulation) {
+        for change in sim.take_changes() {
+            if self.changes.len() == self.capacity {
+                self.changes.pop_front();
+            }
+
+            self.changes.push_back(change);
+        }
+    }
+
+    // Undo up to n of the most recent changes, i.e. ant moves, painted
+    // cells and added ants, returning the number undone.
+    pub fn rewind(&mut self, sim: &mut Simulation, n: u64) -> u64 {
+        self.record(sim);
+
+        let mut undone = 0;
+
+        while undone < n {
+            match self.changes.pop_back() {
+                Some(change) => change.undo(sim),
+                None => break,
+            }
+
+            undone += 1;
+        }
+
+        // Undoing is itself tracked as changes, which mustn't be recorded.
+        sim.take_changes();
+        undone
+    }
+
+    // Number of changes that can be undone.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
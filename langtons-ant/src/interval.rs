@@ -0,0 +1,64 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::simulation::Simulation;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Tracks when something done every given number of iterations, e.g. a
+// recording's frame or a check of the run, is next due.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Interval {
+    every: u64,
+    next: u64,
+}
+
+impl Interval {
+    pub(crate) fn new(every: u64) -> Interval {
+        Interval {
+            every: every.max(1),
+            next: 0,
+        }
+    }
+
+    // Wait a whole interval before the first time, rather than being due at
+    // the start.
+    pub(crate) fn skip_first(&mut self) {
+        self.next = self.every;
+    }
+
+    pub(crate) fn remaining(&self, sim: &Simulation) -> u64 {
+        self.next.saturating_sub(sim.iterations())
+    }
+
+    // Is it due? If so the interval moves on to the next time.
+    pub(crate) fn due(&mut self, sim: &Simulation) -> bool {
+        if sim.iterations() < self.next {
+            return false;
+        }
+
+        self.next = (sim.iterations() / self.every + 1) * self.every;
+        true
+    }
+}
@@ -0,0 +1,110 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+extern crate float_cmp;
+extern crate gif;
+extern crate image;
+#[cfg(feature = "python")]
+extern crate numpy;
+#[cfg(feature = "python")]
+extern crate pyo3;
+extern crate rand;
+extern crate rand_chacha;
+extern crate rayon;
+extern crate serde;
+extern crate serde_json;
+extern crate sha2;
+extern crate toml;
+#[cfg(feature = "web")]
+extern crate wasm_bindgen;
+#[cfg(feature = "web")]
+extern crate web_sys;
+
+pub mod ant;
+pub mod autosave;
+pub mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checkpoint;
+pub mod colour;
+pub mod config;
+pub mod cubic;
+pub mod cycle;
+pub mod delta;
+pub mod divergence;
+pub mod engine;
+pub mod entropy;
+pub mod export;
+pub mod fast;
+pub mod golly;
+pub mod grid;
+pub mod highway;
+pub mod history;
+pub mod interval;
+pub mod palette;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod presets;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random;
+pub mod record;
+pub mod replay;
+pub mod rle;
+pub mod rule;
+#[cfg(feature = "script")]
+pub mod script;
+pub mod seeding;
+pub mod simulation;
+pub mod spawn;
+pub mod stats;
+pub mod stop;
+pub mod surface;
+pub mod swarm;
+pub mod symmetry;
+pub mod timelapse;
+pub mod timeline;
+pub mod trace;
+pub mod turmite;
+pub mod video;
+pub mod visits;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod weighted;
+pub mod zones;
+
+pub use ant::{
+    compute_ant_position, compute_ant_position_by, Ant, Boundary, CollisionPolicy, Direction,
+    Facing, ObstaclePolicy,
+};
+pub use colour::{Colour, ColourMap};
+pub use config::{Config, ConfigError};
+pub use delta::Delta;
+pub use grid::{
+    Extent, Grid, GridBackend, GridStorage, PackedGrid, Row, SparseGrid, BLANK, CHUNK_SIZE,
+    OBSTACLE,
+};
+pub use palette::Palette;
+pub use simulation::Simulation;
+pub use turmite::{Transition, Turmite};
+pub use visits::{Visit, Visits};
+pub use weighted::WeightedRule;
+pub use zones::Zone;
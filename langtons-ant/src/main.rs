@@ -19,566 +19,997 @@
 // and GNU Lesser General Public License along with this program. If
 // not, see <http://www.gnu.org/licenses/>.
 
-extern crate float_cmp;
+extern crate clap;
+extern crate crossterm;
+extern crate directories;
+extern crate egui;
+extern crate env_logger;
+extern crate image;
+extern crate langtons_ant;
+extern crate log;
 extern crate piston_window;
-extern crate rand;
-
-use float_cmp::*;
+extern crate pollster;
+extern crate wgpu;
+extern crate winit;
+
+mod app;
+
+use app::bench;
+use app::camera::Camera;
+use app::cli::{Cli, Command};
+use app::compare;
+use app::explore;
+use app::fork::Forks;
+use app::gpu::{self, Backend};
+use app::gridlines::GridLines;
+use app::headless;
+use app::hud::Hud;
+use app::inspector::Inspector;
+use app::logging;
+use app::marker::AntMarkers;
+use app::menu::{self, MENU_SIZE};
+use app::minimap::Minimap;
+use app::paint::Painter;
+use app::panel::{ControlPanel, PanelAction};
+use app::playback::Playback;
+use app::plot::Plot;
+use app::poster;
+use app::recording::Recordings;
+use app::render::{Display, DisplayMode, GridRenderer, Renderer, MAX_TEXTURE_SIZE};
+use app::savestate::{self, Savestates};
+use app::script::ScriptHooks;
+use app::server;
+use app::settings;
+use app::speed::{Speed, UPDATES_PER_SECOND};
+use app::state::{AppController, AppState};
+use app::stepper::{Stepper, SCRUB_FRACTION};
+use app::surface::{self, Surface};
+use app::tui::{self, RendererMode};
+use app::verify;
+use app::volume;
+use app::worker::{Request, Worker};
+use langtons_ant::config;
+use langtons_ant::engine::{self, RuleEngine};
+use langtons_ant::random;
+use langtons_ant::replay::Replay;
+use langtons_ant::seeding::GridSeed;
+use langtons_ant::spawn::Spawn;
+use langtons_ant::{
+    export, Config, ConfigError, Facing, GridBackend, GridStorage, Palette, Simulation, Turmite,
+};
+use langtons_ant::{golly, palette, presets, rle};
+use log::{error, info, warn};
 use piston_window::*;
-use rand::prelude::*;
-use std::io;
+use std::env;
+use std::mem;
+use std::path::Path;
 use std::process;
 
 //-----------------------------------------------------------------------------
-// ENUMS, STRUCTS AND IMPLS
+// FUNCTIONS
 //-----------------------------------------------------------------------------
 
 //-----------------------------------------------------------------------------
-// Structure to hold colour information.
-struct Colour {
-    r: f32,
-    g: f32,
-    b: f32,
-    a: f32,
-}
-
-impl Colour {
-    // Compare this Colour to another Colour instance.
-    fn compare(&self, other: &Colour) -> bool {
-        approx_eq!(f32, self.r, other.r)
-            && approx_eq!(f32, self.g, other.g)
-            && approx_eq!(f32, self.b, other.b)
-            && approx_eq!(f32, self.a, other.a)
-    }
-
-    fn to_rgba(&self) -> [f32; 4] {
-        [self.r, self.g, self.b, self.a]
-    }
+// Print out a title greeting message to the user in the console.
+fn print_title() {
+    println!("***************************");
+    println!("* LANGTON'S ANT SIMULATOR *");
+    println!("***************************");
+    println!("");
 }
 
 //-----------------------------------------------------------------------------
-// Direction to move.
-#[derive(Copy, Clone)]
-enum Direction {
-    L,
-    R,
+// Print an error and exit. Used for invalid command line arguments and
+// files, which unlike typed input can't be asked for again.
+fn exit_with_error(e: ConfigError) -> ! {
+    error!("{}", e);
+    process::exit(headless::ERROR_EXIT_CODE);
 }
 
 //-----------------------------------------------------------------------------
-// Facing enum for encoding way ant is pointing.
-enum Facing {
-    N,
-    E,
-    S,
-    W,
+// Validate control parameters given on the command line.
+fn process_command_line(cli: &Cli) -> Result<Config, ConfigError> {
+    Config::new(&cli.rule, cli.mps, cli.grid, cli.square)
 }
 
 //-----------------------------------------------------------------------------
-// The Ant structure defining its position, movement rule, associated colours
-// and iteration count.
-struct Ant {
-    pos_x: usize,
-    pos_y: usize,
-    rule: Vec<Direction>,
-    colours: Vec<Colour>,
-    facing: Facing,
-    stalled: bool,
-    iterations: u64,
-}
+// Load a saved simulation state.
+fn load_simulation(path: &Path) -> Result<Simulation, ConfigError> {
+    let loaded = match path.extension().and_then(|e| e.to_str()) {
+        Some("rle") => rle::load(path),
+        Some("mc") => golly::load_pattern(path),
+        _ => Simulation::load(path),
+    };
 
-impl Ant {
-    fn new(x: usize, y: usize) -> Ant {
-        Ant {
-            pos_x: x,
-            pos_y: y,
-            rule: Vec::new(),
-            colours: Vec::new(),
-            facing: Facing::N,
-            stalled: false,
-            iterations: 0,
-        }
-    }
+    let sim = loaded.map_err(|e| ConfigError::Load {
+        name: "state",
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+
+    info!("Loaded state from {}", path.display());
+
+    Ok(sim)
 }
 
 //-----------------------------------------------------------------------------
-// The row structure defnies the current colour code for each cell 
-// on a given row.
-struct Row {
-    cells: Vec<usize>,
-}
+// Load a move log for playback.
+fn load_replay(path: &Path) -> Result<(Simulation, Playback), ConfigError> {
+    let (sim, replay) = Replay::load(path).map_err(|e| ConfigError::Load {
+        name: "move log",
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+
+    info!(
+        "Loaded {} changes to replay from {}",
+        replay.len(),
+        path.display()
+    );
 
-impl Row {
-    fn new(num_cells: usize, clr_idx: usize) -> Row {
-        let mut r = Row { cells: Vec::new() };
-        r.cells.resize(num_cells, clr_idx);
-        r
-    }
+    Ok((sim, Playback::new(replay)))
 }
 
 //-----------------------------------------------------------------------------
-// The grid structure encoding the state of each cell as a numerical value
-// between 0 and n - 1, where there are n colours, one for each move in
-// a rule.
-struct Grid {
-    rows: Vec<Row>,
-}
+// Load a turmite transition table. Golly .rule files are supported, as are
+// Golly rule names and specifications given in place of a file.
+fn load_turmite(path: &Path) -> Result<Turmite, ConfigError> {
+    let loaded = match path.extension().and_then(|e| e.to_str()) {
+        Some("rule") => golly::load_rule(path).map_err(|e| e.to_string()),
+        _ if !path.exists() => golly::parse_turmite(&path.to_string_lossy()),
+        _ => Turmite::load(path).map_err(|e| e.to_string()),
+    };
 
-impl Grid {
-    fn new(num_rows: usize, num_cols: usize, clr_idx: usize) -> Grid {
-        let mut g = Grid {
-            rows: Vec::with_capacity(num_rows),
-        };
-        while g.rows.len() != num_rows {
-            g.rows.push(Row::new(num_cols, clr_idx));
-        }
-        g
-    }
+    loaded.map_err(|reason| ConfigError::Load {
+        name: "turmite",
+        path: path.to_path_buf(),
+        reason,
+    })
 }
 
 //-----------------------------------------------------------------------------
-// FUNCTIONS
-//-----------------------------------------------------------------------------
+// Create a rule engine, either built in and chosen by name or, with the
+// plugins feature, loaded from the library named by plugin:FILE.
+fn create_engine(name: &str, rule: &str) -> Result<Box<dyn RuleEngine>, ConfigError> {
+    let created = match name.strip_prefix("plugin:") {
+        #[cfg(feature = "plugins")]
+        Some(path) => langtons_ant::plugin::PluginEngine::load(path)
+            .map(|e| Box::new(e) as Box<dyn RuleEngine>),
+        #[cfg(not(feature = "plugins"))]
+        Some(_) => Err(String::from("plugins need the plugins feature")),
+        None => engine::builtin(name, rule),
+    };
 
-//-----------------------------------------------------------------------------
-// Function to generate a random colour.
-fn generate_random_colour() -> [f32; 4] {
-    let mut rng = rand::thread_rng();
-    let r: f32 = rng.gen(); // generates a float between 0 and 1
-    let g: f32 = rng.gen(); // generates a float between 0 and 1
-    let b: f32 = rng.gen(); // generates a float between 0 and 1
-    let rgba = [r, g, b, 1.0]; // [Red, Green, Blue, Alpha]
-    rgba
+    created.map_err(|reason| ConfigError::InvalidSetting {
+        name: "engine",
+        reason,
+    })
 }
 
 //-----------------------------------------------------------------------------
-// Function to create a colour object and randomly fill its values.
-fn create_random_colour() -> Colour {
-    let c = generate_random_colour();
-    let colour: Colour = Colour {
-        r: c[0],
-        g: c[1],
-        b: c[2],
-        a: c[3],
+// Replace the simulation's random colours with the given palette, failing if
+// the palette is invalid or has too few colours. Lists of colours are also
+// checked for colours that are hard to tell apart, as gradients with many
+// steps always have some.
+fn apply_palette(sim: &mut Simulation, palette: &str) -> Result<(), ConfigError> {
+    let invalid = |reason| ConfigError::InvalidPalette {
+        palette: palette.to_string(),
+        reason,
     };
-    colour
+
+    let parsed = Palette::parse(palette).map_err(invalid)?;
+    let colours = parsed.colours(sim.num_colours()).map_err(invalid)?;
+
+    if let Palette::Explicit(_) = parsed {
+        if let Some(problem) = palette::contrast_problem(&colours) {
+            warn!("Palette has low contrast: {}", problem);
+        }
+    }
+
+    sim.set_colours(colours);
+    Ok(())
 }
 
 //-----------------------------------------------------------------------------
-// Print out a title greeting message to the user in the console.
-fn print_title() {
-    println!("***************************");
-    println!("* LANGTON'S ANT SIMULATOR *");
-    println!("***************************");
-    println!("");
+// Fill the grid with an initial pattern, failing if the pattern is invalid
+// or its image can't be read.
+fn apply_grid_seed(sim: &mut Simulation, pattern: &str) -> Result<(), ConfigError> {
+    GridSeed::parse(pattern)
+        .and_then(|p| p.apply(sim))
+        .map_err(|reason| ConfigError::InvalidGridSeed {
+            pattern: pattern.to_string(),
+            reason,
+        })
 }
 
 //-----------------------------------------------------------------------------
-// Print our requests to the user for control parameters.
-fn print_input_requests() -> (String, i32, u32, f64) {
-    println!("Please enter a rule using L and R characters, e.g. LR or RLLR etc. Press enter to use default \"RL\". > ");
+// Create the simulation, either playing back a move log, resuming a saved
+// state or starting afresh with the ant centred in the grid, then apply any
+// other setup options given on the command line. When playing back a move
+// log the simulation starts from the log's initial state and is only changed
+// by the log, so the playback is returned too.
+fn create_simulation(
+    cli: Option<&Cli>,
+    config: &Config,
+) -> Result<(Simulation, Option<Playback>), ConfigError> {
+    let seed = cli.and_then(|c| c.seed).unwrap_or_else(random::random_seed);
+
+    if let Some(path) = cli.and_then(|c| c.replay.as_ref()) {
+        let (mut sim, playback) = load_replay(path)?;
+
+        if let Some(palette) = cli.and_then(|c| c.palette.as_ref()) {
+            apply_palette(&mut sim, palette)?;
+        }
 
-    let mut rule = String::new();
+        return Ok((sim, Some(playback)));
+    }
 
-    io::stdin()
-        .read_line(&mut rule)
-        .expect("Failed to read input");
+    let load = cli.and_then(|c| c.load.as_ref());
+    let turmite = cli.and_then(|c| c.turmite.as_ref());
+    let engine = cli.and_then(|c| c.engine.as_ref());
 
-    let mut rule = rule.trim().to_string();
+    let mut sim = match (load, turmite, engine) {
+        (Some(path), _, _) => {
+            let sim = load_simulation(path)?;
 
-    if rule.is_empty() || (rule == "\r\n") || (rule == "\r") || (rule == "\n") {
-        rule = String::from("RL");
-    }
+            if cli.is_some_and(|c| c.load_once) {
+                if let Err(e) = std::fs::remove_file(path) {
+                    warn!("Failed to delete {}: {}", path.display(), e);
+                }
+            }
 
-    for c in rule.chars() {
-        if (c != 'L') && (c != 'R') {
-            println!("ERROR - Invalid rule input: {}", rule);
-            process::exit(0);
+            sim
         }
-    }
+        (None, Some(path), _) => {
+            Simulation::turmite_with_seed(load_turmite(path)?, config.grid_size as usize, seed)
+        }
+        (None, None, Some(name)) => Simulation::engine_with_seed(
+            create_engine(name, &config.rule)?,
+            config.grid_size as usize,
+            seed,
+        ),
+        (None, None, None) => config.simulation(seed),
+    };
 
-    println!(
-        "Please enter number of moves per second (1, 2, 5, 10, 20, 50, 100, 200, 500, 1000). Press enter to use default 10. > "
-    );
+    if let Some(cli) = cli {
+        if let Some(boundary) = cli.boundary {
+            sim.set_boundary(boundary);
+        }
 
-    let mut mps = String::new();
+        if let Some(obstacles) = cli.obstacles {
+            sim.set_obstacles(obstacles);
+        }
 
-    io::stdin()
-        .read_line(&mut mps)
-        .expect("Failed to read input");
+        if let Some(collision) = cli.collision {
+            sim.set_collision(collision);
+        }
 
-    if mps.is_empty() || (mps == "\r\n") || (mps == "\r") || (mps == "\n") {
-        mps = String::from("10");
-    }
+        if cli.unbounded {
+            sim.make_unbounded();
+        }
 
-    let mps: i32 = match mps.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("ERROR - Invalid moves per second = {}", mps);
-            process::exit(0);
+        if let Some(bits) = cli.cell_bits {
+            sim.pack(bits)
+                .map_err(|reason| ConfigError::InvalidSetting {
+                    name: "cell_bits",
+                    reason,
+                })?;
         }
-    };
 
-    let check_mps: Vec<i32> = vec![1, 2, 5, 10, 20, 50, 100, 200, 500, 1000];
+        if let Some(pattern) = cli.seed_grid.as_ref() {
+            apply_grid_seed(&mut sim, pattern)?;
+        }
 
-    if !check_mps.contains(&mps) {
-        println!("ERROR - Invalid moves per second = {}", mps);
-        process::exit(0);
-    }
+        let spawn = Spawn {
+            positions: cli.start.clone(),
+            facings: cli.facing.clone(),
+        };
 
-    println!("Please enter a grid size as a number of squares (10 - 1000). Press enter to use default 150 squares. > ");
+        if !spawn.is_empty() {
+            spawn
+                .apply(&mut sim)
+                .map_err(|reason| ConfigError::InvalidSetting {
+                    name: "start",
+                    reason,
+                })?;
+        }
 
-    let mut grid_size = String::new();
+        for &ant in cli.ant.iter() {
+            if !sim.add_ant(ant.x, ant.y, ant.facing) {
+                return Err(ConfigError::InvalidAnt(ant));
+            }
+        }
 
-    io::stdin()
-        .read_line(&mut grid_size)
-        .expect("Failed to read input");
+        for zone in cli.zone.iter() {
+            sim.add_zone(zone.to_zone()?)
+                .map_err(|reason| ConfigError::InvalidSetting {
+                    name: "zone",
+                    reason,
+                })?;
+        }
 
-    if grid_size.is_empty() || (grid_size == "\r\n") || (grid_size == "\r") || (grid_size == "\n") {
-        grid_size = String::from("150");
+        if let Some(palette) = cli.palette.as_ref() {
+            apply_palette(&mut sim, palette)?;
+        }
     }
 
-    let grid_size: u32 = match grid_size.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("ERROR - Invalid grid size = {}", grid_size);
-            process::exit(0);
-        }
+    Ok((sim, None))
+}
+
+//-----------------------------------------------------------------------------
+// Save the simulation state to a file named after the current iteration, in
+// either JSON or RLE format.
+fn save_simulation(sim: &Simulation, format: &str) {
+    let path = format!("langtons-ant-{}.{}", sim.iterations(), format);
+
+    let saved = match format {
+        "rle" => rle::save(sim, &path),
+        _ => sim.save(&path),
     };
 
-    if (grid_size < 10) || (grid_size > 1000) {
-        println!("ERROR - Invalid grid size = {}", grid_size);
-        process::exit(0);
+    match saved {
+        Ok(()) => info!("Saved state to {}", path),
+        Err(e) => error!("Failed to save state to {}: {}", path, e),
     }
+}
 
-    println!("Please enter the size of a grid square as a number of pixels (1 - 20). Press enter to use default 5 pixels. > ");
-
-    let mut square_size = String::new();
-
-    io::stdin()
-        .read_line(&mut square_size)
-        .expect("Failed to read input");
+//-----------------------------------------------------------------------------
+// Render the grid to a PNG file named after the current iteration.
+fn save_screenshot(sim: &Simulation, px_per_cell: u32) {
+    let path = format!("langtons-ant-{}.png", sim.iterations());
 
-    if square_size.is_empty()
-        || (square_size == "\r\n")
-        || (square_size == "\r")
-        || (square_size == "\n")
-    {
-        square_size = String::from("5");
+    match export::save_png(sim, &path, px_per_cell) {
+        Ok(()) => info!("Saved screenshot to {}", path),
+        Err(e) => error!("Failed to save screenshot to {}: {}", path, e),
     }
+}
 
-    let square_size: f64 = match square_size.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("ERROR - Invalid grid square size = {}", grid_size);
-            process::exit(0);
-        }
-    };
+//-----------------------------------------------------------------------------
+// Undo up to n moves, of the playback when replaying a move log or of the
+// simulation using its recent history.
+fn rewind(sim: &mut Simulation, stepper: &mut Stepper, n: u64) {
+    match stepper.rewind(sim, n) {
+        Some(undone) => info!("Rewound {} moves to iteration {}", undone, sim.iterations()),
+        None => warn!("Rewinding is unavailable while logging moves"),
+    }
+}
 
-    if (square_size < 1.0) || (square_size > 20.0) {
-        println!("ERROR - Invalid grid square size = {}", grid_size);
-        process::exit(0);
+//-----------------------------------------------------------------------------
+// Scrub a fraction of the way through the recent history, or the playback
+// when replaying a move log, backwards if negative.
+fn scrub(sim: &mut Simulation, stepper: &mut Stepper, fraction: f64) {
+    if stepper.scrub_by(sim, fraction) {
+        info!("Scrubbed to iteration {}", sim.iterations());
+    } else {
+        warn!("Scrubbing is unavailable while logging moves");
     }
+}
 
-    let dim: u32 = grid_size * (square_size as u32);
+//-----------------------------------------------------------------------------
+// Start a new simulation with the given rule, keeping the grid size, seed,
+// storage, boundary, obstacle and collision policies of the current one,
+// along with any rule zones that still fit the rule, and tracking changes
+// for the renderer.
+fn restart(sim: &Simulation, rule: &str) -> Simulation {
+    let mut restarted = Simulation::with_seed(rule, sim.size(), sim.seed());
+    restarted.set_boundary(sim.boundary());
+    restarted.set_obstacles(sim.obstacles());
+    restarted.set_collision(sim.collision());
+
+    for zone in sim.zones() {
+        let _ = restarted.add_zone(zone.clone());
+    }
 
-    if dim > 1000 {
-        println!(
-            "ERROR - Invalid grid dimension, grid_size * square_size must be <= 1000, dim = {}",
-            dim
-        );
-        process::exit(0);
+    match sim.cells() {
+        GridBackend::Sparse(_) => restarted.make_unbounded(),
+        // A longer rule may not fit in the packed cells, so is left dense.
+        GridBackend::Packed(grid) => {
+            let _ = restarted.pack(grid.bits());
+        }
+        GridBackend::Dense(_) => {}
     }
 
-    (rule.to_string(), mps, grid_size, square_size)
+    restarted.set_track_dirty(true);
+    restarted
 }
 
 //-----------------------------------------------------------------------------
-// Move ant coming from originally facing North.
-fn move_from_north(ant_dir: Direction, dim: usize, ant: &mut Ant) {
-    match ant_dir {
-        Direction::L => {
-            // Set new direction to face.
-            ant.facing = Facing::W;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if 0 == ant.pos_x {
-                ant.stalled = true;
-            } else {
-                ant.pos_x -= 1;
-            }
-        }
-        Direction::R => {
-            // Set new direction to face.
-            ant.facing = Facing::E;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if dim - 1 == ant.pos_x {
-                ant.stalled = true;
-            } else {
-                ant.pos_x += 1;
-            }
-        }
-    }
+// Open the simulation window, sized to show the grid or fullscreen.
+fn open_window(dim: u32, fullscreen: bool) -> PistonWindow {
+    let mut window: PistonWindow = WindowSettings::new("Langton's Ant", [dim, dim])
+        .exit_on_esc(true)
+        .resizable(true)
+        .fullscreen(fullscreen)
+        .build()
+        .unwrap();
+
+    // Tweak event loop timings. The simulation speed is paced separately
+    // from the update rate, so any number of moves per second can be run.
+    let mut evs = window.get_event_settings();
+    evs.set_ups(UPDATES_PER_SECOND);
+    evs.set_max_fps(UPDATES_PER_SECOND);
+    window.set_event_settings(evs);
+
+    window
 }
 
 //-----------------------------------------------------------------------------
-// Move ant coming from originally facing East.
-fn move_from_east(ant_dir: Direction, dim: usize, ant: &mut Ant) {
-    match ant_dir {
-        Direction::L => {
-            // Set new direction to face.
-            ant.facing = Facing::N;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if 0 == ant.pos_y {
-                ant.stalled = true;
-            } else {
-                ant.pos_y -= 1;
-            }
-        }
-        Direction::R => {
-            // Set new direction to face.
-            ant.facing = Facing::S;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if dim - 1 == ant.pos_y {
-                ant.stalled = true;
-            } else {
-                ant.pos_y += 1;
-            }
-        }
+// Print the built in presets with their rules and descriptions.
+fn print_presets() {
+    println!("Presets, selected with --preset NAME:");
+
+    for preset in presets::PRESETS {
+        println!();
+        println!("{} ({})", preset.name, preset.rule);
+        println!("    {}", preset.description);
     }
 }
 
 //-----------------------------------------------------------------------------
-// Move ant coming from originally facing South.
-fn move_from_south(ant_dir: Direction, dim: usize, ant: &mut Ant) {
-    match ant_dir {
-        Direction::L => {
-            // Set new direction to face.
-            ant.facing = Facing::E;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if dim - 1 == ant.pos_x {
-                ant.stalled = true;
-            } else {
-                ant.pos_x += 1;
-            }
+// The applications main function.
+fn main() {
+    logging::init();
+    print_title();
+
+    // Only fall back to the start menu when no arguments were given.
+    let cli = if env::args().len() > 1 {
+        Some(Cli::parse_layered().unwrap_or_else(|e| exit_with_error(e)))
+    } else {
+        None
+    };
+
+    if let Some(cli) = cli.as_ref() {
+        logging::set_level(cli.log_level);
+    }
+
+    if cli.as_ref().is_some_and(|c| c.list_presets) {
+        print_presets();
+        return;
+    }
+
+    if let Some(Command::Explore(args)) = cli.as_ref().and_then(|c| c.command.as_ref()) {
+        if let Err(e) = config::validate_grid_size(args.grid).and_then(|_| explore::run(args)) {
+            exit_with_error(e);
         }
-        Direction::R => {
-            // Set new direction to face.
-            ant.facing = Facing::W;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if 0 == ant.pos_x {
-                ant.stalled = true;
-            } else {
-                ant.pos_x -= 1;
-            }
+
+        return;
+    }
+
+    if let Some(Command::Bench(args)) = cli.as_ref().and_then(|c| c.command.as_ref()) {
+        if let Err(e) = config::validate_rule(&args.rule)
+            .and(config::validate_grid_size(args.grid))
+            .and_then(|_| bench::run(args))
+        {
+            exit_with_error(e);
         }
+
+        return;
     }
-}
 
-//-----------------------------------------------------------------------------
-// Move ant coming from originally facing West.
-fn move_from_west(ant_dir: Direction, dim: usize, ant: &mut Ant) {
-    match ant_dir {
-        Direction::L => {
-            // Set new direction to face.
-            ant.facing = Facing::S;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if dim - 1 == ant.pos_y {
-                ant.stalled = true;
-            } else {
-                ant.pos_y += 1;
+    if let Some(Command::Render(args)) = cli.as_ref().and_then(|c| c.command.as_ref()) {
+        let rendered = load_simulation(&args.state).and_then(|mut sim| {
+            if let Some(palette) = args.palette.as_ref() {
+                apply_palette(&mut sim, palette)?;
             }
+
+            poster::run(args, &sim)
+        });
+
+        if let Err(e) = rendered {
+            exit_with_error(e);
         }
-        Direction::R => {
-            // Set new direction to face.
-            ant.facing = Facing::N;
-
-            // Move ant in correct direction. Checking for
-            // hitting boundary, in which case we mark ant
-            // as stalled.
-            if 0 == ant.pos_y {
-                ant.stalled = true;
-            } else {
-                ant.pos_y -= 1;
-            }
+
+        return;
+    }
+
+    if let Some(Command::Verify(args)) = cli.as_ref().and_then(|c| c.command.as_ref()) {
+        match verify::run(args) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(e) => exit_with_error(e),
         }
     }
-}
 
-//-----------------------------------------------------------------------------
-// Compute new position of ant updating grif colours as we move ant.
-fn compute_ant_position(ant: &mut Ant, grid: &mut Grid) {
-    // Has ant stalled?
-    if ant.stalled {
+    if let Some(cli) = cli
+        .as_ref()
+        .filter(|c| c.compare.is_some() || c.diverge.is_some())
+    {
+        if let Err(e) = compare::run(cli) {
+            exit_with_error(e);
+        }
+
         return;
     }
 
-    // Grab the current colour index for the ant's current position.
-    let mut cell_clr_idx = grid.rows[ant.pos_y].cells[ant.pos_x];
+    if let Some(cli) = cli.as_ref().filter(|c| c.dimensions == 3) {
+        if let Err(e) = volume::run(cli) {
+            exit_with_error(e);
+        }
 
-    if usize::max_value() == cell_clr_idx {
-        cell_clr_idx = 0;
+        return;
     }
 
-    // Grab direction we need to turn.
-    let ant_dir = ant.rule[cell_clr_idx];
-
-    // Increment cell colour index.
-    cell_clr_idx += 1;
+    if let Some(cli) = cli.as_ref().filter(|c| c.surface == Surface::CubeSphere) {
+        if let Err(e) = surface::run(cli) {
+            exit_with_error(e);
+        }
 
-    if ant.colours.len() == cell_clr_idx {
-        cell_clr_idx = 0;
+        return;
     }
 
-    grid.rows[ant.pos_y].cells[ant.pos_x] = cell_clr_idx;
+    // The app starts in setup, taking the run from the command line or the
+    // start menu, and only goes back there when a run started from the menu
+    // has finished, showing it again with the last run's settings. Closing
+    // the menu's window quits.
+    // The menu first offers the settings of the last run started from it,
+    // kept between runs of the program.
+    let mut app = AppController::new(cli.is_none());
+    let last_run = cli.is_none().then(settings::load_last_run).flatten();
+    let mut reusing = last_run.is_some();
+    let mut defaults = last_run.unwrap_or_default();
+
+    while app.state() == AppState::Setup {
+        let config = match cli {
+            Some(ref cli) => process_command_line(cli).unwrap_or_else(|e| exit_with_error(e)),
+            None => match menu::show(&mut open_window(MENU_SIZE, false), &defaults, reusing) {
+                Some(config) => {
+                    settings::save_last_run(&config);
+                    config
+                }
+                None => return,
+            },
+        };
 
-    // Grab the grid dimension.
-    let dim = grid.rows.len();
+        app.start();
 
-    // Move ant in correctdirection based on way it is currently facing.
-    match ant.facing {
-        Facing::N => move_from_north(ant_dir, dim, ant),
-        Facing::E => move_from_east(ant_dir, dim, ant),
-        Facing::S => move_from_south(ant_dir, dim, ant),
-        Facing::W => move_from_west(ant_dir, dim, ant),
-    }
+        if let Err(e) = run(cli.as_ref(), &config, &mut app) {
+            exit_with_error(e);
+        }
 
-    // Increment the iteration count.
-    if u64::max_value() == ant.iterations {
-        ant.stalled = true;
-    } else {
-        ant.iterations += 1;
+        app.close();
+        app.set_up_again();
+        reusing = true;
+        defaults = config;
     }
 }
 
 //-----------------------------------------------------------------------------
-// The applications main function.
-fn main() {
-    print_title();
-    let (rule, mps, grid_size, square_size) = print_input_requests();
-    println!("");
-    println!("Rule = {}", rule);
-    println!("Moves per second = {}", mps);
-    println!("Grid size (number of squares) = {}", grid_size);
-    println!("Square size (number of pixels) = {}", square_size);
-
-    // Compute fps and moves_per_update control variables.
-    let (fps, moves_per_tick) = match mps {
-        1 => (1 as u64, 1 as i32),
-        2 => (2 as u64, 1 as i32),
-        5 => (5 as u64, 1 as i32),
-        10 => (10 as u64, 1 as i32),
-        20 => (20 as u64, 1 as i32),
-        50 => (50 as u64, 1 as i32),
-        100 => (10 as u64, 10 as i32),
-        200 => (20 as u64, 10 as i32),
-        500 => (50 as u64, 10 as i32),
-        1000 => (50 as u64, 20 as i32),
-        _ => (1 as u64, 1 as i32),
-    };
+// Run a simulation set up from the configuration and any command line
+// options, headless, served, in the terminal or in a window, returning once
+// the run has ended. Fails if the simulation, recordings or script can't be
+// set up.
+fn run(cli: Option<&Cli>, config: &Config, app: &mut AppController) -> Result<(), ConfigError> {
+    let (mut sim, playback) = create_simulation(cli, config)?;
+
+    let grid_size = sim.size() as u32;
+
+    info!("");
+    info!("Rule = {}", sim.rule());
+    info!("Seed = {}", sim.seed());
+    info!("Moves per second = {}", config.mps);
+    info!("Grid size (number of squares) = {}", grid_size);
+    info!("Square size (number of pixels) = {}", config.square_size);
+
+    // Optional GIF and video recordings and move log, only available from
+    // the command line.
+    let mut recordings = cli
+        .as_ref()
+        .map(|c| Recordings::from_cli(c, &mut sim))
+        .transpose()?
+        .unwrap_or_default();
+
+    let script = cli.map(ScriptHooks::from_cli).transpose()?.flatten();
+
+    if let Some(cli) = cli.filter(|c| c.headless) {
+        let reason = headless::run(&mut sim, cli, &mut recordings, script);
+
+        recordings.finish(&mut sim);
+        process::exit(reason.exit_code());
+    }
 
-    // Centre the starting point in the square grid.
-    let start_point: usize = (grid_size as f64 / 2.0) as usize;
+    // Runs the simulation, or plays back a move log, keeping recent changes
+    // to undo with backspace and watching for highways and cycles.
+    let mut stepper = Stepper::new(&mut sim, playback, cli, &recordings, script);
 
-    // Initialise ant's position.
-    let mut ant = Ant::new(start_point, start_point);
-    ant.rule = Vec::with_capacity(rule.len());
-    ant.colours = Vec::with_capacity(rule.len());
+    if let Some(addr) = cli.and_then(|c| c.serve.as_ref()) {
+        if let Err(e) = server::run(&mut sim, stepper, &mut recordings, addr, config.mps) {
+            error!("Failed to serve: {}", e);
+        }
 
-    // Build the route and colour vectors and store in Ant object
-    const WHITE: Colour = Colour {
-        r: 1.0,
-        g: 1.0,
-        b: 1.0,
-        a: 1.0,
-    };
+        recordings.finish(&mut sim);
+        return Ok(());
+    }
 
-    for c in rule.chars() {
-        if c == 'L' {
-            ant.rule.push(Direction::L);
-        } else if c == 'R' {
-            ant.rule.push(Direction::R);
+    if let Some(cli) = cli.filter(|c| c.renderer == RendererMode::Tui) {
+        if let Err(e) = tui::run(&mut sim, stepper, &mut recordings, cli, config.mps) {
+            error!("Failed to draw in the terminal: {}", e);
         }
 
-        let mut col = create_random_colour();
+        recordings.finish(&mut sim);
+        return Ok(());
+    }
 
-        while WHITE.compare(&col) {
-            col = create_random_colour();
+    if sim.size() > MAX_TEXTURE_SIZE {
+        error!(
+            "Grids bigger than {} squares can't be drawn in a window, run with --headless or --renderer tui",
+            MAX_TEXTURE_SIZE
+        );
+        return Ok(());
+    }
+
+    // How the cells are coloured, cycled with V.
+    let mut display = Display {
+        mode: cli.map_or(DisplayMode::Colours, |c| c.display),
+        trail_length: cli.map_or(10_000, |c| c.trail_length),
+    };
+
+    if let Some(cli) = cli.filter(|c| c.backend != Backend::Piston) {
+        if let Err(e) = gpu::run(
+            &mut sim,
+            stepper,
+            &mut recordings,
+            config,
+            cli.rewind,
+            display,
+            cli.backend == Backend::WgpuCompute,
+        ) {
+            error!("Failed to draw with wgpu: {}", e);
         }
 
-        ant.colours.push(col);
+        recordings.finish(&mut sim);
+        return Ok(());
     }
 
     // Grid size in pixels will be multiplication of grid_size in squares
     // by square_size in pixels.
-    let dim: u32 = grid_size * (square_size as u32);
+    let dim = config::window_size(grid_size, config.square_size);
 
-    // Initialise Grid.
-    let mut grid = Grid::new(grid_size as usize, grid_size as usize, usize::max_value());
+    // Create our 2D render window, toggled to and from fullscreen with F11.
+    let mut fullscreen = false;
+    let mut window = open_window(dim, fullscreen);
 
-    // Create our 2D render window.
-    let mut window: PistonWindow = WindowSettings::new("Langton's Ant", [dim, dim])
-        .exit_on_esc(true)
-        .build()
-        .unwrap();
+    // Moves per second, doubled and halved with the + and - keys.
+    let mut speed = Speed::new(config.mps);
 
-    // Tweak event loop timings.
-    let mut evs = window.get_event_settings();
-    evs.set_ups(fps);
-    evs.set_max_fps(fps);
-    window.set_event_settings(evs);
+    // Screenshot options, only available from the command line.
+    let screenshot_every = cli.and_then(|c| c.screenshot_every);
+    let screenshot_scale = cli.map_or(10, |c| c.screenshot_scale);
+    let mut next_screenshot = screenshot_every.unwrap_or(0);
 
-    // Process the events and start drawing.
-    let ant_ref: &mut Ant = &mut ant;
-    let grid_ref: &mut Grid = &mut grid;
+    // Format of states saved with the S key.
+    let save_format = cli.map_or("json", |c| c.save_format.as_str());
+
+    // Only changed cells are updated in the renderer's buffer each frame.
+    sim.set_track_dirty(true);
+    let mut renderer = GridRenderer::new(&mut window, &mut sim, display);
+
+    // Camera for zooming with the mouse wheel and panning by dragging with
+    // the middle mouse button.
+    let mut camera = Camera::new(config.square_size, sim.cells().extent());
+    camera.resize([f64::from(dim); 2]);
+
+    // Overlay showing the iteration count, rule and speed, toggled with H.
+    let mut hud = Hud::new(&mut window);
+
+    // Chart of the ant's distance and the pattern's size against the
+    // iteration, toggled with G.
+    let mut plot = Plot::new(&mut window);
+
+    // Readout of the cell under the cursor, toggled with I.
+    let mut inspector = Inspector::new(&mut window);
+
+    // Triangles showing where each ant is and which way it faces, toggled
+    // with A.
+    let mut markers = AntMarkers::default();
+
+    // Map of the whole grid marking the part in view, toggled with M, that
+    // can be clicked to move the view.
+    let mut minimap = Minimap::default();
+
+    // Lines between the grid squares at high zoom, toggled with L.
+    let mut grid_lines = GridLines::new(cli.is_some_and(|c| c.grid_lines));
+
+    // Control panel for the speed, pausing, rule and colours, toggled with C.
+    let mut panel = ControlPanel::new(&mut window, &config.rule);
+
+    // Painting cells with the left mouse button, in the colour chosen with
+    // [ and ].
+    let mut painter = Painter::default();
+
+    // Quick save slots, stored with Ctrl and a number key and restored with
+    // the number key alone.
+    let mut savestates = Savestates::default();
+    let mut forks = Forks::default();
+    let mut ctrl = false;
 
+    // Facing of ants added with the right mouse button, cycled with tab.
+    let mut new_ant_facing = Facing::N;
+
+    let rewind_moves = cli.map_or(100, |c| c.rewind);
+
+    recordings.capture(&mut sim);
+
+    // With --threaded the simulation runs on a thread of its own, taking the
+    // stepper and recordings with it, and sim becomes a copy kept up to
+    // date from the changes it sends.
+    let mut worker = cli.filter(|c| c.threaded).map(|_| {
+        Worker::spawn(
+            sim.clone(),
+            mem::take(&mut stepper),
+            mem::take(&mut recordings),
+            speed.mps(),
+        )
+    });
+
+    // Editing is disabled during playback as the log holds every change,
+    // and when threaded as the copy isn't the simulation being run.
+    let editable = stepper.is_editable() && worker.is_none();
+
+    // Process the events and start drawing.
     while let Some(e) = window.next() {
-        window.draw_2d(&e, |c, g, _device| {
-            clear([1.0; 4], g);
-            for _ in 0..moves_per_tick {
-                compute_ant_position(ant_ref, grid_ref);
+        if panel.handle_event(&e) {
+            continue;
+        }
+
+        if let Some(Button::Keyboard(Key::LCtrl | Key::RCtrl)) = e.press_args() {
+            ctrl = true;
+        }
+
+        if let Some(Button::Keyboard(Key::LCtrl | Key::RCtrl)) = e.release_args() {
+            ctrl = false;
+        }
+
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            match key {
+                _ if savestate::slot_index(key).is_some() && ctrl => savestates.store(key, &sim),
+                // Restoring, like resetting, isn't possible during playback,
+                // threaded runs or while logging moves.
+                _ if savestate::slot_index(key).is_some() => {
+                    if !editable || recordings.logging_moves() {
+                        warn!("Restoring is unavailable during playback, threaded runs or while logging moves");
+                    } else if let Some(restored) = savestates.restore(key) {
+                        sim = restored;
+                        sim.set_track_dirty(true);
+                        renderer = GridRenderer::new(&mut window, &mut sim, display);
+                        stepper.reset_history(&mut sim);
+                    }
+                }
+                Key::Space => app.toggle_pause(),
+                Key::Right if app.is_paused() => match worker.as_ref() {
+                    Some(worker) => worker.send(Request::Step),
+                    None => stepper.step_forward(&mut sim),
+                },
+                Key::Left if app.is_paused() => match worker.as_ref() {
+                    Some(worker) => worker.send(Request::StepBack),
+                    None => stepper.step_back(&mut sim),
+                },
+                Key::Backspace => match worker.as_ref() {
+                    Some(worker) => worker.send(Request::Rewind(rewind_moves)),
+                    None => rewind(&mut sim, &mut stepper, rewind_moves),
+                },
+                Key::R => match worker.as_ref() {
+                    Some(worker) => worker.send(Request::ToggleReverse),
+                    None => stepper.toggle_reverse(&mut sim),
+                },
+                // Scrubbing pauses the run so the moment reached stays put.
+                Key::LeftBracket | Key::RightBracket => {
+                    let fraction = if key == Key::LeftBracket {
+                        -SCRUB_FRACTION
+                    } else {
+                        SCRUB_FRACTION
+                    };
+
+                    app.pause();
+
+                    match worker.as_mut() {
+                        Some(worker) => {
+                            worker.sync_settings(true, speed.mps());
+                            worker.send(Request::ScrubBy(fraction));
+                        }
+                        None => scrub(&mut sim, &mut stepper, fraction),
+                    }
+                }
+                Key::S => save_simulation(&sim, save_format),
+                Key::P => save_screenshot(&sim, screenshot_scale),
+                Key::Home => camera.reset(),
+                Key::F => camera.toggle_follow(),
+                Key::H => hud.toggle(),
+                Key::G => plot.toggle(),
+                Key::A => markers.toggle(),
+                Key::M => minimap.toggle(),
+                Key::I => inspector.toggle(),
+                Key::L => grid_lines.toggle(),
+                Key::V => {
+                    display.mode = display.mode.next();
+                    renderer.set_display(display, &mut sim);
+                }
+                Key::C => panel.toggle(),
+                Key::E => panel.edit_rule(),
+                Key::N => forks.fork(&sim, None, speed.mps()),
+                Key::F11 => {
+                    // Textures belong to the window they were created for,
+                    // so everything drawn is moved over to the new one.
+                    fullscreen = !fullscreen;
+                    window = open_window(dim, fullscreen);
+                    renderer.reattach(&mut window);
+                    hud.reattach(&mut window);
+                    plot.reattach(&mut window);
+                    inspector.reattach(&mut window);
+                    panel.reattach(&mut window);
+
+                    let size = window.size();
+                    camera.resize([size.width, size.height]);
+                }
+                Key::Equals | Key::Plus | Key::NumPadPlus => speed.faster(),
+                Key::Minus | Key::NumPadMinus => speed.slower(),
+                Key::Tab => {
+                    new_ant_facing = new_ant_facing.clockwise();
+                    info!("New ant facing = {:?}", new_ant_facing);
+                }
+                _ => painter.select_key(key, sim.num_colours()),
+            }
+        }
+
+        // Clicks on the minimap move the view rather than painting.
+        if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
+            if !minimap.press(&mut camera) && editable {
+                painter.press(&mut sim, camera.cursor_cell());
+            }
+        }
+
+        if let Some(Button::Mouse(MouseButton::Left)) = e.release_args() {
+            minimap.release();
+            painter.release();
+        }
+
+        if let Some(Button::Mouse(MouseButton::Right)) = e.press_args().filter(|_| editable) {
+            let (x, y) = camera.cursor_cell();
+            sim.add_ant(x, y, new_ant_facing);
+        }
+
+        if let Some(Button::Mouse(MouseButton::Middle)) = e.press_args() {
+            camera.set_dragging(true);
+        }
+
+        if let Some(Button::Mouse(MouseButton::Middle)) = e.release_args() {
+            camera.set_dragging(false);
+        }
+
+        if let Some(pos) = e.mouse_cursor_args() {
+            camera.cursor_moved(pos);
+            minimap.drag(&mut camera);
+            painter.drag(&mut sim, camera.cursor_cell());
+        }
+
+        if let Some(scroll) = e.mouse_scroll_args() {
+            camera.scroll(scroll[1]);
+        }
+
+        if let Some(args) = e.resize_args() {
+            camera.resize(args.window_size);
+        }
+
+        if let Some(args) = e.update_args() {
+            // Either run could pause itself, e.g. on finding a highway.
+            let stop = match worker.as_mut() {
+                Some(worker) => worker.sync(&mut sim),
+                None if !app.is_paused() => {
+                    let moves = speed.moves(args.dt);
+                    stepper.advance(&mut sim, moves, &mut recordings, &mut speed)
+                }
+                None => false,
+            };
+
+            if stop {
+                app.pause();
+            }
+
+            app.observe(&sim);
+
+            if let Some(every) = screenshot_every {
+                if every > 0 && sim.iterations() >= next_screenshot {
+                    save_screenshot(&sim, screenshot_scale);
+                    next_screenshot = (sim.iterations() / every + 1) * every;
+                }
             }
-            let mut x: u32 = 0;
-            for row in &mut grid_ref.rows {
-                let mut y: u32 = 0;
-                for cell in &mut row.cells {
-                    let xr = x as f64 * square_size;
-                    let yr = y as f64 * square_size;
-                    if *cell != usize::max_value() {
-                        rectangle(
-                            ant_ref.colours[*cell].to_rgba(),
-                            [xr, yr, square_size, square_size],
-                            c.transform,
-                            g,
-                        );
+        }
+
+        if let Some(args) = e.render_args() {
+            // Restarting isn't possible during playback or while logging
+            // moves, as the log must start from its initial state.
+            let can_reset = editable && !recordings.logging_moves();
+
+            // The threaded run's history is kept on its own thread, so can
+            // only be scrubbed with the keys.
+            let timeline = stepper.timeline().filter(|_| worker.is_none());
+
+            for action in panel.frame(
+                args.window_size,
+                &sim,
+                speed.mps(),
+                app.is_paused(),
+                can_reset,
+                timeline,
+            ) {
+                match action {
+                    PanelAction::TogglePause => app.toggle_pause(),
+                    PanelAction::Step if app.is_paused() => match worker.as_ref() {
+                        Some(worker) => worker.send(Request::Step),
+                        None => stepper.step_forward(&mut sim),
+                    },
+                    PanelAction::Step => {}
+                    PanelAction::SetSpeed(mps) => speed.set(mps),
+                    PanelAction::Scrub(position) => {
+                        app.pause();
+                        stepper.scrub(&mut sim, position);
+                    }
+                    PanelAction::SetColour(i, colour) => {
+                        let mut colours = sim.colours().to_vec();
+                        colours[i] = colour;
+
+                        if let Some(worker) = worker.as_ref() {
+                            worker.send(Request::SetColours(colours.clone()));
+                        }
+
+                        sim.set_colours(colours);
+                        renderer.redraw();
+                    }
+                    PanelAction::Reset(rule) => {
+                        sim = restart(&sim, &rule);
+                        renderer = GridRenderer::new(&mut window, &mut sim, display);
+                        stepper.reset_history(&mut sim);
+                        camera.reset();
+                        app.start();
+                        info!("Restarted with rule {}", sim.rule());
                     }
-                    y += 1;
+                    PanelAction::ChangeRule(rule) => match sim.set_rule(&rule) {
+                        Ok(()) => {
+                            renderer.redraw();
+                            stepper.reset_history(&mut sim);
+                            info!("Changed rule to {}", sim.rule());
+                        }
+                        Err(e) => error!("Failed to change the rule: {}", e),
+                    },
+                    PanelAction::Fork(rule) => forks.fork(&sim, Some(&rule), speed.mps()),
                 }
-                x += 1;
             }
+
+            renderer.set_zoom(camera.zoom());
+            renderer.update(&mut sim);
+            hud.frame(&sim);
+            plot.frame(&sim);
+            inspector.frame(&mut sim);
+        }
+
+        window.draw_2d(&e, |c, g, device| {
+            if camera.following() {
+                let ant = sim.ant();
+                camera.centre_on(ant.pos_x, ant.pos_y, c.get_view_size());
+            }
+
+            let transform = camera.transform(c.transform);
+            renderer.draw(transform, g, device);
+            grid_lines.draw(&sim, &camera, c.get_view_size(), transform, g);
+            markers.draw(&sim, transform, g);
+            plot.draw(c, g, device);
+            minimap.draw(&sim, &mut renderer, &camera, c, g, device);
+            hud.draw(&sim, speed.mps(), app.state(), c, g, device);
+            inspector.draw(&sim, &camera, c, g, device);
+            panel.draw(c, g, device);
         });
 
-        let mut title = String::from("Langton's Ant - N = ");
-        title.push_str(ant_ref.iterations.to_string().as_str());
-        window.set_title(title);
+        if let Some(worker) = worker.as_mut() {
+            worker.sync_settings(app.is_paused(), speed.mps());
+        }
     }
+
+    // The thread's simulation is the one run, so is the one recorded.
+    if let Some(worker) = worker {
+        let (finished, finished_recordings) = worker.finish();
+        sim = finished;
+        recordings = finished_recordings;
+    }
+
+    recordings.finish(&mut sim);
+    Ok(())
 }
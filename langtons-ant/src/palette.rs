@@ -0,0 +1,217 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::colour::{Colour, MIN_BACKGROUND_DISTANCE, MIN_COLOUR_DISTANCE, WHITE};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A colour palette to use for the cells instead of randomly generated
+// colours, either an explicit list of colours, one per rule step, or a named
+// gradient sampled evenly for however many colours are needed.
+#[derive(Clone, Debug)]
+pub enum Palette {
+    Explicit(Vec<Colour>),
+    Gradient(&'static [[u8; 3]]),
+}
+
+// Control points for the named gradients.
+const VIRIDIS: [[u8; 3]; 5] = [
+    [68, 1, 84],
+    [59, 82, 139],
+    [33, 145, 140],
+    [94, 201, 98],
+    [253, 231, 37],
+];
+
+const MAGMA: [[u8; 3]; 5] = [
+    [0, 0, 4],
+    [81, 18, 124],
+    [183, 55, 121],
+    [252, 137, 97],
+    [252, 253, 191],
+];
+
+const PLASMA: [[u8; 3]; 5] = [
+    [13, 8, 135],
+    [126, 3, 168],
+    [204, 71, 120],
+    [248, 149, 64],
+    [240, 249, 33],
+];
+
+const CIVIDIS: [[u8; 3]; 5] = [
+    [0, 32, 77],
+    [65, 77, 107],
+    [124, 123, 120],
+    [188, 175, 111],
+    [255, 234, 70],
+];
+
+const GREYS: [[u8; 3]; 2] = [[0, 0, 0], [200, 200, 200]];
+
+// Okabe and Ito's eight colours, chosen to be told apart with any of the
+// common forms of colour blindness.
+const OKABE_ITO: &str = "#e69f00,#56b4e9,#009e73,#f0e442,#0072b2,#d55e00,#cc79a7,#000000";
+
+impl Palette {
+    // Parse a palette from either a gradient name, e.g. "viridis", or a comma
+    // separated list of hex colours, e.g. "#000000,#ff0000,#00ff00".
+    pub fn parse(s: &str) -> Result<Palette, String> {
+        match s.trim().to_lowercase().as_str() {
+            "viridis" => return Ok(Palette::Gradient(&VIRIDIS)),
+            "magma" => return Ok(Palette::Gradient(&MAGMA)),
+            "plasma" => return Ok(Palette::Gradient(&PLASMA)),
+            "cividis" => return Ok(Palette::Gradient(&CIVIDIS)),
+            "greys" => return Ok(Palette::Gradient(&GREYS)),
+            "okabe-ito" | "okabeito" => return Palette::parse(OKABE_ITO),
+            _ => {}
+        }
+
+        let colours = s
+            .split(',')
+            .map(Colour::from_hex)
+            .collect::<Result<Vec<Colour>, String>>()?;
+
+        Ok(Palette::Explicit(colours))
+    }
+
+    // Get the n colours to use, one per rule step. Explicit palettes must
+    // have at least n colours.
+    pub fn colours(&self, n: usize) -> Result<Vec<Colour>, String> {
+        match self {
+            Palette::Explicit(colours) => {
+                if colours.len() < n {
+                    return Err(format!(
+                        "palette has {} colours but {} are needed",
+                        colours.len(),
+                        n
+                    ));
+                }
+                Ok(colours[..n].to_vec())
+            }
+            Palette::Gradient(points) => {
+                Ok((0..n).map(|i| sample_gradient(points, i, n)).collect())
+            }
+        }
+    }
+
+    // The palette used for heatmaps, running from black through purple and
+    // orange to pale yellow.
+    pub fn heat() -> Palette {
+        Palette::Gradient(&MAGMA)
+    }
+
+    // The colour a fraction t, from 0 to 1, of the way along the palette,
+    // e.g. to colour cells by a value rather than a rule step. Explicit
+    // palettes give their nearest colour.
+    pub fn sample(&self, t: f32) -> Colour {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Palette::Explicit(colours) => {
+                colours[(t * (colours.len() - 1) as f32).round() as usize]
+            }
+            Palette::Gradient(points) => gradient_at(points, t),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Check the colours for a rule's steps can be told apart from each other
+// and from the white background, returning a description of the least
+// distinct pair if any are closer than the distances the random colours
+// are kept to.
+pub fn contrast_problem(colours: &[Colour]) -> Option<String> {
+    let mut problems = Vec::new();
+
+    for (i, a) in colours.iter().enumerate() {
+        let distance = a.distance(&WHITE);
+
+        if distance < MIN_BACKGROUND_DISTANCE {
+            problems.push((
+                distance,
+                format!("colour {} ({}) is close to the background", i, a.to_hex()),
+            ));
+        }
+
+        for (j, b) in colours.iter().enumerate().skip(i + 1) {
+            let distance = a.distance(b);
+
+            if distance < MIN_COLOUR_DISTANCE {
+                problems.push((
+                    distance,
+                    format!(
+                        "colours {} ({}) and {} ({}) are close",
+                        i,
+                        a.to_hex(),
+                        j,
+                        b.to_hex()
+                    ),
+                ));
+            }
+        }
+    }
+
+    problems
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(distance, problem)| format!("{}, difference {:.1}", problem, distance))
+}
+
+//-----------------------------------------------------------------------------
+// Sample the i'th of n evenly spaced colours along a gradient, linearly
+// interpolating between its control points.
+fn sample_gradient(points: &[[u8; 3]], i: usize, n: usize) -> Colour {
+    let t = if n > 1 {
+        i as f32 / (n - 1) as f32
+    } else {
+        0.0
+    };
+
+    gradient_at(points, t)
+}
+
+//-----------------------------------------------------------------------------
+// The colour a fraction t of the way along a gradient, linearly
+// interpolating between its control points.
+fn gradient_at(points: &[[u8; 3]], t: f32) -> Colour {
+    let segments = (points.len() - 1) as f32;
+    let pos = t * segments;
+    let idx = (pos.floor() as usize).min(points.len() - 2);
+    let frac = pos - idx as f32;
+
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * frac) / 255.0;
+    let (p0, p1) = (points[idx], points[idx + 1]);
+
+    Colour {
+        r: lerp(p0[0], p1[0]),
+        g: lerp(p0[1], p1[1]),
+        b: lerp(p0[2], p1[2]),
+        a: 1.0,
+    }
+}
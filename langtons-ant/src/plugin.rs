@@ -0,0 +1,218 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Rule engines loaded from dynamic libraries, through a small, stable C ABI
+// so plugins can be written in any language that can export C functions.
+// A plugin exports these functions:
+//
+//   uint32_t langtons_ant_plugin_abi(void);
+//       The ABI version the plugin was written for, PLUGIN_ABI_VERSION.
+//
+//   const char *langtons_ant_plugin_name(void);
+//       A short, NUL terminated name shown in place of the rule.
+//
+//   uint32_t langtons_ant_plugin_colours(void);
+//       The number of colours cells can be painted, at least 1.
+//
+//   void langtons_ant_plugin_step(const PluginInput *in, PluginOutput *out);
+//       Decide a move: given the colour of the ant's cell (blank cells being
+//       colour 0), its facing (0 = N, 1 = E, 2 = S, 3 = W), its state and
+//       its position, fill in the colour to paint the cell, the turn to make
+//       (0 = none, 1 = right, 2 = U-turn, 3 = left) and the ant's next state.
+//
+// The input and output are plain C structs laid out as below. The plugin is
+// trusted: loading it runs its code with the simulator's permissions.
+
+use crate::ant::{turn_and_move, Ant, Boundary, Direction, Facing, ObstaclePolicy};
+use crate::engine::RuleEngine;
+use crate::grid::{GridBackend, GridStorage, BLANK, OBSTACLE};
+use libloading::Library;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::sync::Arc;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Version of the C ABI plugins must be written for.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// What a plugin is told about the ant for each move.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PluginInput {
+    pub colour: u32,
+    pub facing: u32,
+    pub state: u32,
+    pub x: i64,
+    pub y: i64,
+}
+
+//-----------------------------------------------------------------------------
+// A plugin's decision for a move.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PluginOutput {
+    pub colour: u32,
+    pub turn: u32,
+    pub state: u32,
+}
+
+type AbiFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type ColoursFn = unsafe extern "C" fn() -> u32;
+type StepFn = unsafe extern "C" fn(*const PluginInput, *mut PluginOutput);
+
+//-----------------------------------------------------------------------------
+// A rule engine loaded from a dynamic library. Copies share the library,
+// which stays loaded until the last is dropped.
+#[derive(Clone)]
+pub struct PluginEngine {
+    // Keeps the library loaded for as long as step_fn may be called.
+    _library: Arc<Library>,
+    step_fn: StepFn,
+    name: String,
+    num_colours: usize,
+}
+
+impl PluginEngine {
+    // Load a plugin, checking it was written for this version of the ABI.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PluginEngine, String> {
+        // Loading a library runs its initialisers, and the functions are
+        // trusted to have the signatures documented above.
+        unsafe {
+            let library = Library::new(path.as_ref()).map_err(|e| e.to_string())?;
+
+            let abi = *library
+                .get::<AbiFn>(b"langtons_ant_plugin_abi\0")
+                .map_err(|e| e.to_string())?;
+
+            if abi() != PLUGIN_ABI_VERSION {
+                return Err(format!(
+                    "the plugin is for ABI version {}, not {}",
+                    abi(),
+                    PLUGIN_ABI_VERSION
+                ));
+            }
+
+            let name_fn = *library
+                .get::<NameFn>(b"langtons_ant_plugin_name\0")
+                .map_err(|e| e.to_string())?;
+            let colours_fn = *library
+                .get::<ColoursFn>(b"langtons_ant_plugin_colours\0")
+                .map_err(|e| e.to_string())?;
+            let step_fn = *library
+                .get::<StepFn>(b"langtons_ant_plugin_step\0")
+                .map_err(|e| e.to_string())?;
+
+            let name_ptr = name_fn();
+
+            let name = if name_ptr.is_null() {
+                String::from("plugin")
+            } else {
+                CStr::from_ptr(name_ptr).to_string_lossy().into_owned()
+            };
+
+            let num_colours = colours_fn() as usize;
+
+            if num_colours == 0 {
+                return Err(String::from("the plugin has no colours"));
+            }
+
+            Ok(PluginEngine {
+                _library: Arc::new(library),
+                step_fn,
+                name,
+                num_colours,
+            })
+        }
+    }
+}
+
+impl RuleEngine for PluginEngine {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn num_colours(&self) -> usize {
+        self.num_colours
+    }
+
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        let colour = match grid.get(ant.pos_x, ant.pos_y) {
+            BLANK | OBSTACLE => 0,
+            clr_idx => clr_idx as u32,
+        };
+
+        let input = PluginInput {
+            colour,
+            facing: match ant.facing {
+                Facing::N => 0,
+                Facing::E => 1,
+                Facing::S => 2,
+                Facing::W => 3,
+            },
+            state: ant.state as u32,
+            x: ant.pos_x,
+            y: ant.pos_y,
+        };
+
+        let mut output = PluginOutput::default();
+
+        // The library is kept loaded by self, and both structs outlive the
+        // call.
+        unsafe { (self.step_fn)(&input, &mut output) };
+
+        // Out of range colours and turns are wrapped rather than trusted.
+        grid.set(
+            ant.pos_x,
+            ant.pos_y,
+            output.colour as usize % self.num_colours,
+        );
+        ant.state = output.state as usize;
+
+        let turn = match output.turn % 4 {
+            0 => Direction::N,
+            1 => Direction::R,
+            2 => Direction::U,
+            _ => Direction::L,
+        };
+
+        turn_and_move(turn, grid, ant, boundary, obstacles);
+    }
+
+    fn box_clone(&self) -> Box<dyn RuleEngine> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,145 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::config::ConfigFile;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// The built in presets.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "highway",
+        rule: "RL",
+        palette: "#1d3557,#e63946",
+        grid: 400,
+        square: 2.0,
+        mps: 2000,
+        description: "Langton's original ant, which builds a diagonal highway after about 10,000 moves of chaos.",
+    },
+    Preset {
+        name: "chaotic-RLR",
+        rule: "RLR",
+        palette: "plasma",
+        grid: 300,
+        square: 3.0,
+        mps: 5000,
+        description: "Grows chaotically. It is not known whether this ant ever builds a highway.",
+    },
+    Preset {
+        name: "symmetric-LLRR",
+        rule: "LLRR",
+        palette: "viridis",
+        grid: 300,
+        square: 3.0,
+        mps: 5000,
+        description: "Grows symmetrically, repeatedly returning to a symmetric pattern.",
+    },
+    Preset {
+        name: "symmetric-LRRRRRLLR",
+        rule: "LRRRRRLLR",
+        palette: "magma",
+        grid: 250,
+        square: 4.0,
+        mps: 5000,
+        description: "Fills space in a square around itself.",
+    },
+    Preset {
+        name: "invaders-RLLR",
+        rule: "RLLR",
+        palette: "#264653,#2a9d8f,#e9c46a,#e76f51",
+        grid: 300,
+        square: 3.0,
+        mps: 5000,
+        description: "Symmetrical expansion with a mix of space invader and skull patterns.",
+    },
+    Preset {
+        name: "convoluted-highway",
+        rule: "LLRRRLRLRLLR",
+        palette: "viridis",
+        grid: 500,
+        square: 2.0,
+        mps: 20000,
+        description: "Creates a convoluted highway.",
+    },
+    Preset {
+        name: "triangle",
+        rule: "RRLLLRLLLRRR",
+        palette: "plasma",
+        grid: 500,
+        square: 2.0,
+        mps: 20000,
+        description: "Creates a filled triangle shape that grows and moves.",
+    },
+    Preset {
+        name: "square-RLRRRL",
+        rule: "RLRRRL",
+        palette: "magma",
+        grid: 300,
+        square: 3.0,
+        mps: 5000,
+        description: "Spreads out then makes a square path around its perimeter, occasionally growing before locking back into a square.",
+    },
+];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A famous rule with colours and a grid size chosen to show it off.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub rule: &'static str,
+    pub palette: &'static str,
+    pub grid: u32,
+    pub square: f64,
+    pub mps: u64,
+    pub description: &'static str,
+}
+
+impl Preset {
+    // The preset's settings, to be layered under any others given.
+    pub fn settings(&self) -> ConfigFile {
+        ConfigFile {
+            rule: Some(self.rule.to_string()),
+            palette: Some(self.palette.to_string()),
+            grid: Some(self.grid),
+            square: Some(self.square),
+            mps: Some(self.mps),
+            ..ConfigFile::default()
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Find a preset by name, ignoring case.
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+}
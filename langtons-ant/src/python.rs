@@ -0,0 +1,171 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Python bindings, built into an extension module importable as
+// langtons_ant, so experiments can be driven from scripts and notebooks
+// without the desktop application. Build with maturin, e.g.
+// maturin develop --no-default-features --features python
+
+use crate::config;
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::random;
+use crate::simulation::Simulation;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Values of blank and obstacle cells in grid_as_numpy arrays, where other
+// cells hold their colour index.
+const PY_BLANK: i64 = -1;
+const PY_OBSTACLE: i64 = -2;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A simulation exported to Python as langtons_ant.Simulation.
+#[pyclass(name = "Simulation", module = "langtons_ant")]
+pub struct PySimulation {
+    sim: Simulation,
+}
+
+#[pymethods]
+impl PySimulation {
+    // Create a simulation of the given rule on a grid of the given size,
+    // raising ValueError if either is invalid. Without a seed the colours
+    // are chosen at random.
+    #[new]
+    #[pyo3(signature = (rule = "RL", grid_size = 150, seed = None))]
+    fn new(rule: &str, grid_size: u32, seed: Option<u64>) -> PyResult<PySimulation> {
+        let rule = config::validate_rule(rule).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let size = config::validate_grid_size(grid_size)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let seed = seed.unwrap_or_else(random::random_seed);
+
+        Ok(PySimulation {
+            sim: Simulation::with_seed(&rule, size as usize, seed),
+        })
+    }
+
+    // Make up to n moves, stopping early if every ant stalls, returning the
+    // number of moves made.
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, py: Python<'_>, n: u64) -> u64 {
+        let before = self.sim.iterations();
+        let sim = &mut self.sim;
+
+        // Long runs release the GIL so other Python threads can carry on.
+        py.detach(|| sim.advance(n));
+        self.sim.iterations() - before
+    }
+
+    // The grid as a 2D array of int64 indexed [y, x], holding each cell's
+    // colour index, or -1 for blank cells and -2 for obstacles. For an
+    // unbounded grid the array covers the area grown so far, whose top left
+    // cell is given by grid_origin.
+    fn grid_as_numpy<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<i64>> {
+        let grid = self.sim.cells();
+        let extent = grid.extent();
+
+        let cells = Array2::from_shape_fn((extent.height, extent.width), |(y, x)| {
+            match grid.get(extent.x + x as i64, extent.y + y as i64) {
+                BLANK => PY_BLANK,
+                OBSTACLE => PY_OBSTACLE,
+                clr_idx => clr_idx as i64,
+            }
+        });
+
+        cells.into_pyarray(py)
+    }
+
+    // Grid coordinates of the first cell of grid_as_numpy's array.
+    fn grid_origin(&self) -> (i64, i64) {
+        let extent = self.sim.cells().extent();
+        (extent.x, extent.y)
+    }
+
+    // The state of an ant, the first by default, as a dict with its x, y,
+    // facing ("N", "E", "S" or "W"), turmite state, iterations and whether
+    // it has stalled. Raises IndexError if there's no such ant.
+    #[pyo3(signature = (index = 0))]
+    fn ant_state<'py>(&self, py: Python<'py>, index: usize) -> PyResult<Bound<'py, PyDict>> {
+        let ant = self
+            .sim
+            .ants()
+            .get(index)
+            .ok_or_else(|| PyIndexError::new_err(format!("no ant {}", index)))?;
+
+        let state = PyDict::new(py);
+        state.set_item("x", ant.pos_x)?;
+        state.set_item("y", ant.pos_y)?;
+        state.set_item("facing", ant.facing.to_char().to_string())?;
+        state.set_item("state", ant.state)?;
+        state.set_item("iterations", ant.iterations)?;
+        state.set_item("stalled", ant.stalled)?;
+        Ok(state)
+    }
+
+    // Switch to an unbounded grid so the ant never stalls.
+    fn make_unbounded(&mut self) {
+        self.sim.make_unbounded();
+    }
+
+    #[getter]
+    fn iterations(&self) -> u64 {
+        self.sim.iterations()
+    }
+
+    #[getter]
+    fn rule(&self) -> String {
+        self.sim.rule()
+    }
+
+    #[getter]
+    fn num_ants(&self) -> usize {
+        self.sim.ants().len()
+    }
+
+    #[getter]
+    fn is_stalled(&self) -> bool {
+        self.sim.is_stalled()
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The extension module itself, named after the library.
+#[pymodule]
+fn langtons_ant(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySimulation>()?;
+    m.add("BLANK", PY_BLANK)?;
+    m.add("OBSTACLE", PY_OBSTACLE)?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use rand::{Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Streams of the seeded generator, one for each use, so that e.g. starting
+// ants at random cells doesn't change the colours chosen.
+pub const COLOURS_STREAM: u64 = 1;
+pub const TURNS_STREAM: u64 = 2;
+pub const SPAWN_STREAM: u64 = 3;
+pub const GRID_SEED_STREAM: u64 = 4;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A seeded generator drawing from one of its streams. Every random choice in
+// a run is made by one of these, seeded from the run's seed, so the same
+// config and seed give bit-identical runs on every platform, apart from
+// the random colours, see create_distinct_colour. ChaCha8's
+// output is fixed by its algorithm, unlike StdRng's, which may differ
+// between versions of rand and platforms. It's saved with its position in
+// the stream, so a loaded run carries on with the same numbers it would
+// have drawn had it never stopped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SeededRngData", into = "SeededRngData")]
+pub struct SeededRng {
+    rng: ChaCha8Rng,
+}
+
+// Serialised form of a seeded generator.
+#[derive(Clone, Serialize, Deserialize)]
+struct SeededRngData {
+    key: [u8; 32],
+    stream: u64,
+    position: u128,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64, stream: u64) -> SeededRng {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(stream);
+        SeededRng { rng }
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl From<SeededRngData> for SeededRng {
+    fn from(data: SeededRngData) -> SeededRng {
+        let mut rng = ChaCha8Rng::from_seed(data.key);
+        rng.set_stream(data.stream);
+        rng.set_word_pos(data.position);
+        SeededRng { rng }
+    }
+}
+
+impl From<SeededRng> for SeededRngData {
+    fn from(rng: SeededRng) -> SeededRngData {
+        SeededRngData {
+            key: rng.rng.get_seed(),
+            stream: rng.rng.get_stream(),
+            position: rng.rng.get_word_pos(),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A seed for a run when none is given, the one choice not made by a seeded
+// generator.
+pub fn random_seed() -> u64 {
+    rand::random()
+}
@@ -0,0 +1,167 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::colour::WHITE;
+use crate::export::render_region;
+use crate::grid::{Extent, GridStorage};
+use crate::interval::Interval;
+use crate::simulation::Simulation;
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use image::{imageops, Rgba, RgbaImage};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+// Delay between frames in hundredths of a second.
+const FRAME_DELAY: u16 = 5;
+
+// Quantisation speed passed to the GIF encoder, from 1 (best) to 30
+// (fastest). The grid rarely has more than a handful of colours so quality
+// is not a concern.
+const QUANTISE_SPEED: i32 = 10;
+
+//-----------------------------------------------------------------------------
+// Interface to the ways a run can be recorded, each capturing a frame every
+// given number of iterations. Recorders can be sent between threads, so a
+// run can be recorded wherever it's running.
+pub trait Recorder: Send {
+    // Number of iterations until the next frame is due.
+    fn remaining(&self, sim: &Simulation) -> u64;
+
+    // Capture a frame if the simulation has reached the next multiple of
+    // the recording interval.
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()>;
+
+    // Complete the recording once the run is over.
+    fn finish(&mut self) -> io::Result<()>;
+
+    // The file being recorded to.
+    fn path(&self) -> &Path;
+}
+
+//-----------------------------------------------------------------------------
+// Records snapshots of the grid and encodes them as an animated GIF once
+// the run is over. Frames are held in memory until then as, for unbounded
+// grids, the size of the animation isn't known until the last frame has
+// been captured.
+pub struct GifRecorder {
+    path: PathBuf,
+    interval: Interval,
+    px_per_cell: u32,
+    frames: Vec<(Extent, RgbaImage)>,
+}
+
+impl GifRecorder {
+    pub fn new<P: Into<PathBuf>>(path: P, every: u64, px_per_cell: u32) -> GifRecorder {
+        GifRecorder {
+            path: path.into(),
+            interval: Interval::new(every),
+            px_per_cell: px_per_cell.max(1),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    // Encode the captured frames to a looping GIF. Every frame is drawn
+    // onto a canvas covering the extent of all of them so the grid stays
+    // in place as an unbounded grid grows.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), EncodingError> {
+        let bounds = match self.frames.iter().map(|f| f.0).reduce(Extent::union) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+
+        let width = bounds.width as u64 * u64::from(self.px_per_cell);
+        let height = bounds.height as u64 * u64::from(self.px_per_cell);
+
+        if width > u64::from(u16::MAX) || height > u64::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} x {} is too large for a GIF", width, height),
+            )
+            .into());
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = Encoder::new(writer, width as u16, height as u16, &[])?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        for (extent, img) in &self.frames {
+            let mut canvas =
+                RgbaImage::from_pixel(width as u32, height as u32, Rgba(WHITE.to_rgba8()));
+            imageops::replace(
+                &mut canvas,
+                img,
+                (extent.x - bounds.x) * i64::from(self.px_per_cell),
+                (extent.y - bounds.y) * i64::from(self.px_per_cell),
+            );
+
+            let mut frame = Frame::from_rgba_speed(
+                width as u16,
+                height as u16,
+                &mut canvas.into_raw(),
+                QUANTISE_SPEED,
+            );
+            frame.delay = FRAME_DELAY;
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Recorder for GifRecorder {
+    fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()> {
+        if self.interval.due(sim) {
+            let extent = sim.cells().extent();
+            self.frames
+                .push((extent, render_region(sim, extent, self.px_per_cell)));
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.save(&self.path).map_err(|e| match e {
+            EncodingError::Io(e) => e,
+            e => io::Error::other(e),
+        })
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
@@ -0,0 +1,615 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Move logs, recording every change made to a simulation so a run can be
+// played back, forwards or backwards, without re-running the rules.
+//
+// A log file starts with the magic bytes "ANTLOG", a version byte and the
+// initial simulation state as length prefixed JSON. Each change follows as
+// a compact binary record, typically 8 bytes for a move. The top 2 bits of
+// a record's first byte give its kind and the rest of the record is made of
+// LEB128 varints, zigzag encoded where signed, with colour indexes stored
+// plus one so a blank cell is 0.
+//
+//   move    facings and flags, ant, dx, dy, old colour, new colour,
+//           state before, state after
+//   paint   x, y, old colour, new colour
+//   ant     facing in the first byte, x, y
+//   other   a sub kind in bits 4 and 5 of the first byte, then either
+//           face    facings, ant
+//           remove  facing and stalled flag, ant, x, y, state, iterations
+//
+// A replay's hash identifies the run it records, so runs made on different
+// machines can be checked for being frame for frame identical. It's the
+// SHA-256 of the digest of the initial state and every change's record,
+// which leaves out cosmetic details such as the colours.
+
+use crate::ant::{Ant, Facing};
+use crate::checkpoint::StateDigest;
+use crate::grid::BLANK;
+use crate::simulation::Simulation;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+const MAGIC: &[u8] = b"ANTLOG";
+const VERSION: u8 = 1;
+
+// Mixed into replay hashes, to be bumped if what they cover changes.
+const HASH_VERSION: &[u8] = b"ANTREPLAY1";
+
+// Record kinds, held in the top 2 bits of a record's first byte.
+const KIND_MOVE: u8 = 0;
+const KIND_PAINT: u8 = 1;
+const KIND_ANT: u8 = 2;
+const KIND_OTHER: u8 = 3;
+
+// Sub kinds of KIND_OTHER records, held in bits 4 and 5 of the first byte.
+const OTHER_FACE: u8 = 0;
+const OTHER_REMOVE: u8 = 1;
+
+// Facings in the order they are numbered in the log.
+const FACINGS: [Facing; 4] = [Facing::N, Facing::E, Facing::S, Facing::W];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A single move of an ant, holding enough to apply or undo it. The ant was
+// on the cell it changed before the move and had not stalled.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Move {
+    pub ant: usize,
+    pub dx: i64,
+    pub dy: i64,
+    // Colour index of the cell the ant left, before and after the move.
+    pub old: usize,
+    pub new: usize,
+    // The ant's facing and turmite state, before and after the move.
+    pub facing: (Facing, Facing),
+    pub state: (usize, usize),
+    // Whether the ant stalled during the move.
+    pub stalled: bool,
+    // Whether the move was added to the ant's iteration count, which is
+    // only not the case when the count is at its maximum.
+    pub counted: bool,
+}
+
+//-----------------------------------------------------------------------------
+// A change made to a simulation, either by an ant or the user.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Change {
+    Move(Move),
+    Paint {
+        x: i64,
+        y: i64,
+        old: usize,
+        new: usize,
+    },
+    AddAnt {
+        x: i64,
+        y: i64,
+        facing: Facing,
+    },
+    // An ant's facing changed other than by a move, e.g. in a collision.
+    Face {
+        ant: usize,
+        old: Facing,
+        new: Facing,
+    },
+    // An ant was removed, e.g. annihilated in a collision, holding enough
+    // of it to put it back.
+    RemoveAnt {
+        ant: usize,
+        x: i64,
+        y: i64,
+        facing: Facing,
+        state: usize,
+        stalled: bool,
+        iterations: u64,
+    },
+}
+
+impl Change {
+    // Make the change to a simulation.
+    pub(crate) fn apply(&self, sim: &mut Simulation) {
+        match *self {
+            Change::Move(m) => {
+                let ant = &mut sim.ants_mut()[m.ant];
+                let (x, y) = (ant.pos_x, ant.pos_y);
+
+                ant.pos_x += m.dx;
+                ant.pos_y += m.dy;
+                ant.facing = m.facing.1;
+                ant.state = m.state.1;
+                ant.stalled = m.stalled;
+
+                if m.counted {
+                    ant.iterations += 1;
+                }
+
+                sim.paint(x, y, m.new);
+            }
+            Change::Paint { x, y, new, .. } => sim.paint(x, y, new),
+            Change::AddAnt { x, y, facing } => {
+                sim.add_ant(x, y, facing);
+            }
+            Change::Face { ant, new, .. } => sim.ants_mut()[ant].facing = new,
+            Change::RemoveAnt { ant, .. } => {
+                sim.ants_mut().remove(ant);
+            }
+        }
+    }
+
+    // Reverse the change, the simulation being as it was just after the
+    // change was made.
+    pub(crate) fn undo(&self, sim: &mut Simulation) {
+        match *self {
+            Change::Move(m) => {
+                let ant = &mut sim.ants_mut()[m.ant];
+
+                ant.pos_x -= m.dx;
+                ant.pos_y -= m.dy;
+                ant.facing = m.facing.0;
+                ant.state = m.state.0;
+                ant.stalled = false;
+
+                if m.counted {
+                    ant.iterations -= 1;
+                }
+
+                let (x, y) = (ant.pos_x, ant.pos_y);
+                sim.paint(x, y, m.old);
+            }
+            Change::Paint { x, y, old, .. } => sim.paint(x, y, old),
+            Change::AddAnt { .. } => {
+                sim.ants_mut().pop();
+            }
+            Change::Face { ant, old, .. } => sim.ants_mut()[ant].facing = old,
+            Change::RemoveAnt {
+                ant,
+                x,
+                y,
+                facing,
+                state,
+                stalled,
+                iterations,
+            } => {
+                // Every ant shares the first one's rule.
+                let ants = sim.ants_mut();
+                let mut removed = Ant::new(x, y);
+                removed.rule = ants[0].rule.clone();
+                removed.facing = facing;
+                removed.state = state;
+                removed.stalled = stalled;
+                removed.iterations = iterations;
+                ants.insert(ant, removed);
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Writes the changes made to a simulation to a move log file.
+pub struct MoveLog {
+    writer: BufWriter<File>,
+    path: PathBuf,
+}
+
+impl MoveLog {
+    // Create a log starting from the simulation's current state and start
+    // tracking its changes.
+    pub fn create<P: AsRef<Path>>(path: P, sim: &mut Simulation) -> io::Result<MoveLog> {
+        let path = path.as_ref().to_path_buf();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let state = serde_json::to_vec(sim)?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(state.len() as u64).to_le_bytes())?;
+        writer.write_all(&state)?;
+
+        sim.set_track_changes(true);
+        Ok(MoveLog { writer, path })
+    }
+
+    // Write changes taken from the simulation.
+    pub fn write(&mut self, changes: &[Change]) -> io::Result<()> {
+        let mut record = Vec::new();
+
+        for change in changes {
+            record.clear();
+            encode(change, &mut record);
+            self.writer.write_all(&record)?;
+        }
+
+        Ok(())
+    }
+
+    // Flush the file once the run is over.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    // The file being logged to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Plays back a move log by applying its changes to a simulation, or undoing
+// them to go backwards, independently of the simulation's own stepping.
+pub struct Replay {
+    changes: Vec<Change>,
+    position: usize,
+    initial: StateDigest,
+}
+
+//-----------------------------------------------------------------------------
+// Where two replays first differ.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplayDifference {
+    // They start from different states.
+    Initial,
+    // The change at this index differs, or is missing from one of them.
+    Change(usize),
+}
+
+impl Replay {
+    // Read a move log, returning the initial simulation state and a replay
+    // positioned at its start.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<(Simulation, Replay)> {
+        let bytes = fs::read(path)?;
+        let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+
+        if bytes.len() < MAGIC.len() + 9 || !bytes.starts_with(MAGIC) {
+            return Err(invalid("not a move log"));
+        }
+
+        if bytes[MAGIC.len()] != VERSION {
+            return Err(invalid("unsupported move log version"));
+        }
+
+        let mut len = [0; 8];
+        len.copy_from_slice(&bytes[MAGIC.len() + 1..MAGIC.len() + 9]);
+        let start = MAGIC.len() + 9;
+        let end = usize::try_from(u64::from_le_bytes(len))
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| invalid("truncated move log"))?;
+
+        let sim = Simulation::from_reader(&bytes[start..end])?;
+
+        let mut reader = Reader {
+            bytes: &bytes[end..],
+        };
+        let mut changes = Vec::new();
+
+        while !reader.bytes.is_empty() {
+            changes.push(decode(&mut reader).map_err(|e| invalid(&e))?);
+        }
+
+        let replay = Replay {
+            changes,
+            position: 0,
+            initial: StateDigest::of(&sim),
+        };
+
+        Ok((sim, replay))
+    }
+
+    // Number of changes in the log.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // Number of changes applied so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // The hash of the whole run, wherever the replay is positioned.
+    pub fn hash(&self) -> StateDigest {
+        let mut hasher = Sha256::new();
+        hasher.update(HASH_VERSION);
+        hasher.update(self.initial.sha256);
+        hasher.update((self.changes.len() as u64).to_le_bytes());
+
+        let mut record = Vec::new();
+
+        for change in &self.changes {
+            record.clear();
+            encode(change, &mut record);
+            hasher.update(&record);
+        }
+
+        let mut sha256 = [0; 32];
+        sha256.copy_from_slice(&hasher.finalize());
+        StateDigest { sha256 }
+    }
+
+    // Where this replay first differs from another, or None if they record
+    // the same run.
+    pub fn first_difference(&self, other: &Replay) -> Option<ReplayDifference> {
+        if self.initial != other.initial {
+            return Some(ReplayDifference::Initial);
+        }
+
+        // One replay may be the other cut short.
+        let shorter = self.changes.len().min(other.changes.len());
+        let missing = if self.changes.len() == other.changes.len() {
+            None
+        } else {
+            Some(shorter)
+        };
+
+        self.changes
+            .iter()
+            .zip(&other.changes)
+            .position(|(a, b)| a != b)
+            .or(missing)
+            .map(ReplayDifference::Change)
+    }
+
+    // Apply the next change, returning false at the end of the log.
+    pub fn forward(&mut self, sim: &mut Simulation) -> bool {
+        let change = match self.changes.get(self.position) {
+            Some(change) => *change,
+            None => return false,
+        };
+
+        change.apply(sim);
+        self.position += 1;
+        true
+    }
+
+    // Undo the last change applied, returning false at the start of the
+    // log.
+    pub fn back(&mut self, sim: &mut Simulation) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+
+        self.position -= 1;
+        self.changes[self.position].undo(sim);
+        true
+    }
+
+    // Move to the given position in the log, applying or undoing changes
+    // as needed.
+    pub fn seek(&mut self, sim: &mut Simulation, position: usize) {
+        let position = position.min(self.changes.len());
+
+        while self.position < position {
+            self.forward(sim);
+        }
+
+        while self.position > position {
+            self.back(sim);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Reads varints from the records of a move log.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let (&b, rest) = self.bytes.split_first().ok_or("truncated record")?;
+        self.bytes = rest;
+        Ok(b)
+    }
+
+    fn unsigned(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            value |= u64::from(b & 0x7f) << shift;
+
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(String::from("invalid varint"))
+    }
+
+    fn signed(&mut self) -> Result<i64, String> {
+        let v = self.unsigned()?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    fn index(&mut self) -> Result<usize, String> {
+        usize::try_from(self.unsigned()?).map_err(|_| String::from("invalid index"))
+    }
+
+    fn colour(&mut self) -> Result<usize, String> {
+        match self.index()? {
+            0 => Ok(BLANK),
+            c => Ok(c - 1),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Append the encoding of a change to a record buffer.
+fn encode(change: &Change, out: &mut Vec<u8>) {
+    match *change {
+        Change::Move(m) => {
+            out.push(
+                KIND_MOVE << 6
+                    | facing_index(m.facing.0)
+                    | facing_index(m.facing.1) << 2
+                    | (m.stalled as u8) << 4
+                    | (m.counted as u8) << 5,
+            );
+            put_unsigned(out, m.ant as u64);
+            put_signed(out, m.dx);
+            put_signed(out, m.dy);
+            put_colour(out, m.old);
+            put_colour(out, m.new);
+            put_unsigned(out, m.state.0 as u64);
+            put_unsigned(out, m.state.1 as u64);
+        }
+        Change::Paint { x, y, old, new } => {
+            out.push(KIND_PAINT << 6);
+            put_signed(out, x);
+            put_signed(out, y);
+            put_colour(out, old);
+            put_colour(out, new);
+        }
+        Change::AddAnt { x, y, facing } => {
+            out.push(KIND_ANT << 6 | facing_index(facing));
+            put_signed(out, x);
+            put_signed(out, y);
+        }
+        Change::Face { ant, old, new } => {
+            out.push(
+                KIND_OTHER << 6 | OTHER_FACE << 4 | facing_index(old) | facing_index(new) << 2,
+            );
+            put_unsigned(out, ant as u64);
+        }
+        Change::RemoveAnt {
+            ant,
+            x,
+            y,
+            facing,
+            state,
+            stalled,
+            iterations,
+        } => {
+            out.push(
+                KIND_OTHER << 6 | OTHER_REMOVE << 4 | facing_index(facing) | (stalled as u8) << 2,
+            );
+            put_unsigned(out, ant as u64);
+            put_signed(out, x);
+            put_signed(out, y);
+            put_unsigned(out, state as u64);
+            put_unsigned(out, iterations);
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Read the next change from a move log.
+fn decode(reader: &mut Reader) -> Result<Change, String> {
+    let first = reader.byte()?;
+
+    match first >> 6 {
+        KIND_MOVE => Ok(Change::Move(Move {
+            ant: reader.index()?,
+            dx: reader.signed()?,
+            dy: reader.signed()?,
+            old: reader.colour()?,
+            new: reader.colour()?,
+            facing: (
+                FACINGS[(first & 3) as usize],
+                FACINGS[(first >> 2 & 3) as usize],
+            ),
+            state: (reader.index()?, reader.index()?),
+            stalled: first & 0x10 != 0,
+            counted: first & 0x20 != 0,
+        })),
+        KIND_PAINT => Ok(Change::Paint {
+            x: reader.signed()?,
+            y: reader.signed()?,
+            old: reader.colour()?,
+            new: reader.colour()?,
+        }),
+        KIND_ANT => Ok(Change::AddAnt {
+            facing: FACINGS[(first & 3) as usize],
+            x: reader.signed()?,
+            y: reader.signed()?,
+        }),
+        KIND_OTHER => match first >> 4 & 3 {
+            OTHER_FACE => Ok(Change::Face {
+                old: FACINGS[(first & 3) as usize],
+                new: FACINGS[(first >> 2 & 3) as usize],
+                ant: reader.index()?,
+            }),
+            OTHER_REMOVE => Ok(Change::RemoveAnt {
+                facing: FACINGS[(first & 3) as usize],
+                stalled: first & 4 != 0,
+                ant: reader.index()?,
+                x: reader.signed()?,
+                y: reader.signed()?,
+                state: reader.index()?,
+                iterations: reader.unsigned()?,
+            }),
+            sub => Err(format!("unknown record sub kind: {}", sub)),
+        },
+        _ => Err(format!("unknown record kind: {}", first >> 6)),
+    }
+}
+
+fn facing_index(facing: Facing) -> u8 {
+    match facing {
+        Facing::N => 0,
+        Facing::E => 1,
+        Facing::S => 2,
+        Facing::W => 3,
+    }
+}
+
+fn put_unsigned(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+
+    out.push(v as u8);
+}
+
+fn put_signed(out: &mut Vec<u8>, v: i64) {
+    put_unsigned(out, ((v << 1) ^ (v >> 63)) as u64);
+}
+
+fn put_colour(out: &mut Vec<u8>, clr_idx: usize) {
+    put_unsigned(
+        out,
+        if clr_idx == BLANK {
+            0
+        } else {
+            clr_idx as u64 + 1
+        },
+    );
+}
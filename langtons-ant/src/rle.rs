@@ -0,0 +1,521 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{Ant, Boundary, Facing, ObstaclePolicy};
+use crate::colour::Colour;
+use crate::golly;
+use crate::grid::{Extent, GridBackend, GridStorage, BLANK, OBSTACLE};
+use crate::rule::Rule;
+use crate::simulation::Simulation;
+use crate::zones::Zone;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Maximum length of a line of encoded cells.
+const MAX_LINE: usize = 70;
+
+// Highest state that can be encoded, i.e. 254 colours plus blank and the
+// obstacle state.
+const MAX_STATE: usize = 255;
+
+// State of an obstacle cell.
+const OBSTACLE_STATE: usize = MAX_STATE;
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Encode a simulation as RLE text, based on Golly's multi-state RLE format
+// so patterns can be exchanged with it. Fails for rules with too many
+// colours to encode and turmites too big to have a Golly rule name. The
+// text looks like...
+//
+//   #N langtons-ant
+//   #CXRLE Pos=-3,-2
+//   #C grid sparse 150
+//   #C seed 1234
+//   #C boundary stall
+//   #C obstacles reverse
+//   #C colours #1f77b4,#ff7f0e
+//   #C ant 75 75 N 0 11000 0
+//   #C zone 0 0 40 40 LR
+//   x = 8, y = 3, rule = RL
+//   .2AB$3.A$8B!
+//
+// Each cell is a state, "." for a blank cell or "A" for colour 0, "B" for
+// colour 1 and so on, with "pA" to "yN" for colours 24 to 253 as in Golly.
+// The highest state, "yO", is an obstacle. A run of the same state is
+// prefixed with its length and "$" ends a row. The extra simulation state
+// is held in "#C" comment lines, which Golly ignores. Each ant line gives
+// x, y, facing, turmite state, iterations and whether it has stalled, and
+// each zone line x, y, width, height and rule. Turmites are written with
+// Golly's turmite rule name, e.g. "rule = Turmite_120080", see the golly
+// module.
+pub fn encode(sim: &Simulation) -> Result<String, String> {
+    if sim.num_colours() >= OBSTACLE_STATE {
+        return Err(format!("too many colours for RLE: {}", sim.num_colours()));
+    }
+
+    let rule = match sim.turmite() {
+        Some(turmite) => golly::turmite_name(turmite)?,
+        None => sim.rule(),
+    };
+
+    let grid = sim.cells();
+    let extent = grid.extent();
+    // Packing is only how the cells are stored, so packed grids are written
+    // as dense ones.
+    let kind = match grid {
+        GridBackend::Dense(_) | GridBackend::Packed(_) => "dense",
+        GridBackend::Sparse(_) => "sparse",
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "#N langtons-ant");
+    let _ = writeln!(out, "#CXRLE Pos={},{}", extent.x, extent.y);
+    let _ = writeln!(out, "#C grid {} {}", kind, sim.size());
+    let _ = writeln!(out, "#C seed {}", sim.seed());
+    let _ = writeln!(out, "#C boundary {}", sim.boundary().name());
+    let _ = writeln!(out, "#C obstacles {}", sim.obstacles().name());
+
+    let colours: Vec<String> = sim.colours().iter().map(|c| c.to_hex()).collect();
+    let _ = writeln!(out, "#C colours {}", colours.join(","));
+
+    for ant in sim.ants() {
+        let _ = writeln!(
+            out,
+            "#C ant {} {} {} {} {} {}",
+            ant.pos_x,
+            ant.pos_y,
+            ant.facing.to_char(),
+            ant.state,
+            ant.iterations,
+            ant.stalled as u8
+        );
+    }
+
+    for zone in sim.zones() {
+        let _ = writeln!(
+            out,
+            "#C zone {} {} {} {} {}",
+            zone.x,
+            zone.y,
+            zone.width,
+            zone.height,
+            zone.rule_string()
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "x = {}, y = {}, rule = {}",
+        extent.width, extent.height, rule
+    );
+
+    let mut line = String::new();
+    let mut push = |token: String, out: &mut String| {
+        if line.len() + token.len() > MAX_LINE {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    };
+
+    let mut pending_rows = 0;
+
+    for y in 0..extent.height as i64 {
+        if y > 0 {
+            pending_rows += 1;
+        }
+
+        let runs = row_runs(grid, extent, extent.y + y);
+
+        if runs.is_empty() {
+            continue;
+        }
+
+        if pending_rows > 0 {
+            push(run_token(pending_rows, "$"), &mut out);
+            pending_rows = 0;
+        }
+
+        for (state, count) in runs {
+            push(run_token(count, &state_token(state)), &mut out);
+        }
+    }
+
+    push(String::from("!"), &mut out);
+    out.push_str(&line);
+    out.push('\n');
+
+    Ok(out)
+}
+
+//-----------------------------------------------------------------------------
+// Decode RLE text into a simulation. Files without the extra comment lines,
+// e.g. from Golly, get a dense grid just big enough for the pattern, placed
+// at the grid's top left, random colours and a single ant in the middle.
+// Dense grids written before the origin moved to the grid's centre had
+// their top left cell at 0, 0, so their cells, ants and zones are moved to
+// match.
+pub fn parse(text: &str) -> Result<Simulation, String> {
+    let mut pos = None;
+    let mut grid_kind = None;
+    let mut seed = 0;
+    let mut boundary = Boundary::Stall;
+    let mut obstacles = ObstaclePolicy::default();
+    let mut colours = None;
+    let mut ants = Vec::new();
+    let mut zones = Vec::new();
+    let mut header = None;
+    let mut body = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(pos_str) = line.strip_prefix("#CXRLE") {
+            for field in pos_str.split_whitespace() {
+                if let Some(p) = field.strip_prefix("Pos=") {
+                    let (x, y) = p.split_once(',').ok_or("invalid Pos")?;
+                    pos = Some((parse_num(x)?, parse_num(y)?));
+                }
+            }
+        } else if let Some(comment) = line.strip_prefix("#C") {
+            let mut fields = comment.split_whitespace();
+
+            match fields.next() {
+                Some("grid") => {
+                    let kind = fields.next().ok_or("missing grid type")?.to_string();
+                    let size: usize = parse_num(fields.next().unwrap_or(""))?;
+                    grid_kind = Some((kind, size));
+                }
+                Some("seed") => seed = parse_num(fields.next().unwrap_or(""))?,
+                Some("boundary") => boundary = Boundary::parse(fields.next().unwrap_or(""))?,
+                Some("obstacles") => {
+                    obstacles = ObstaclePolicy::parse(fields.next().unwrap_or(""))?
+                }
+                Some("colours") => {
+                    colours = Some(
+                        fields
+                            .next()
+                            .unwrap_or("")
+                            .split(',')
+                            .map(Colour::from_hex)
+                            .collect::<Result<Vec<Colour>, String>>()?,
+                    );
+                }
+                Some("ant") => {
+                    let f: Vec<&str> = fields.collect();
+
+                    if f.len() != 6 {
+                        return Err(format!("invalid ant: {}", comment.trim()));
+                    }
+
+                    let mut ant = Ant::new(parse_num(f[0])?, parse_num(f[1])?);
+                    ant.facing = f[2]
+                        .chars()
+                        .next()
+                        .and_then(Facing::from_char)
+                        .ok_or_else(|| format!("invalid facing: {}", f[2]))?;
+                    ant.state = parse_num(f[3])?;
+                    ant.iterations = parse_num(f[4])?;
+                    ant.stalled = f[5] == "1";
+                    ants.push(ant);
+                }
+                Some("zone") => {
+                    let f: Vec<&str> = fields.collect();
+
+                    if f.len() != 5 {
+                        return Err(format!("invalid zone: {}", comment.trim()));
+                    }
+
+                    zones.push(Zone {
+                        x: parse_num(f[0])?,
+                        y: parse_num(f[1])?,
+                        width: parse_num(f[2])?,
+                        height: parse_num(f[3])?,
+                        rule: Rule::parse(f[4]).map_err(|e| e.to_string())?,
+                    });
+                }
+                _ => {}
+            }
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else if header.is_none() {
+            header = Some(parse_header(line)?);
+        } else {
+            body.push_str(line);
+        }
+    }
+
+    let (width, height, rule) = header.ok_or("missing header line")?;
+
+    let (sparse, size) = match grid_kind {
+        Some((kind, size)) => match kind.as_str() {
+            "dense" => (false, size),
+            "sparse" => (true, size),
+            _ => return Err(format!("unknown grid type: {}", kind)),
+        },
+        None => {
+            pos = None;
+            (false, width.max(height))
+        }
+    };
+
+    let mut sim = golly::pattern_simulation(&rule, size, seed)?;
+
+    if sparse {
+        sim.make_unbounded();
+    }
+
+    let extent = sim.cells().extent();
+    let pos = pos.unwrap_or((extent.x, extent.y));
+    let (dx, dy) = if sparse {
+        (0, 0)
+    } else {
+        (extent.x - pos.0, extent.y - pos.1)
+    };
+
+    for ant in ants.iter_mut() {
+        ant.pos_x += dx;
+        ant.pos_y += dy;
+    }
+
+    for zone in zones.iter_mut() {
+        zone.x += dx;
+        zone.y += dy;
+    }
+
+    sim.set_boundary(boundary);
+    sim.set_obstacles(obstacles);
+
+    for zone in zones {
+        sim.add_zone(zone)?;
+    }
+
+    if let Some(colours) = colours {
+        if colours.len() != sim.num_colours() {
+            return Err(format!(
+                "{} colours given for a rule with {}",
+                colours.len(),
+                sim.num_colours()
+            ));
+        }
+        sim.set_colours(colours);
+    }
+
+    for (x, y, clr_idx) in decode_cells(&body)? {
+        let (x, y) = (pos.0 + dx + x, pos.1 + dy + y);
+
+        if clr_idx != OBSTACLE && clr_idx >= sim.num_colours() {
+            return Err(format!("invalid colour at {}, {}", x, y));
+        }
+
+        if !sim.cells().contains(x, y) {
+            return Err(format!("cell {}, {} is outside the grid", x, y));
+        }
+
+        sim.paint(x, y, clr_idx);
+    }
+
+    if !ants.is_empty() {
+        for ant in ants.iter_mut() {
+            if !sim.cells().contains(ant.pos_x, ant.pos_y) {
+                return Err(format!(
+                    "ant at {}, {} is outside the grid",
+                    ant.pos_x, ant.pos_y
+                ));
+            }
+
+            ant.rule = sim.ant().rule.clone();
+        }
+        sim.set_ants(ants);
+    }
+
+    Ok(sim)
+}
+
+//-----------------------------------------------------------------------------
+// Write a simulation to an RLE file.
+pub fn save<P: AsRef<Path>>(sim: &Simulation, path: P) -> io::Result<()> {
+    let text = encode(sim).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    fs::write(path, text)
+}
+
+//-----------------------------------------------------------------------------
+// Read a simulation from an RLE file.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Simulation> {
+    let text = fs::read_to_string(path)?;
+    parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+//-----------------------------------------------------------------------------
+// The runs of states along a row as (state, count) pairs, without any
+// trailing blank cells.
+fn row_runs<G: GridStorage>(grid: &G, extent: Extent, y: i64) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+
+    for x in extent.x..extent.x + extent.width as i64 {
+        let cell = grid.get(x, y);
+        let state = match cell {
+            BLANK => 0,
+            OBSTACLE => OBSTACLE_STATE,
+            _ => cell + 1,
+        };
+
+        match runs.last_mut() {
+            Some((s, count)) if *s == state => *count += 1,
+            _ => runs.push((state, 1)),
+        }
+    }
+
+    if let Some(&(0, _)) = runs.last() {
+        runs.pop();
+    }
+
+    runs
+}
+
+//-----------------------------------------------------------------------------
+// A token with its run length, which is left out for a run of one.
+fn run_token(count: usize, token: &str) -> String {
+    if count == 1 {
+        token.to_string()
+    } else {
+        format!("{}{}", count, token)
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The token for a state, "." for blank, "A" to "X" for 1 to 24, then "pA"
+// to "pX" for 25 to 48 and so on.
+fn state_token(state: usize) -> String {
+    if state == 0 {
+        return String::from(".");
+    }
+
+    let s = state - 1;
+    let letter = (b'A' + (s % 24) as u8) as char;
+
+    match s / 24 {
+        0 => letter.to_string(),
+        prefix => format!("{}{}", (b'p' + (prefix - 1) as u8) as char, letter),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Decode the encoded cells into (x, y, colour index) triples relative to the
+// top left of the pattern, skipping blank cells. Obstacles are given as
+// OBSTACLE.
+fn decode_cells(body: &str) -> Result<Vec<(i64, i64, usize)>, String> {
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut count = 0;
+    let mut prefix = None;
+
+    for c in body.chars() {
+        let state = match c {
+            '0'..='9' => {
+                count = count * 10 + c.to_digit(10).unwrap_or(0) as i64;
+                continue;
+            }
+            'p'..='y' => {
+                prefix = Some(c as usize - 'p' as usize + 1);
+                continue;
+            }
+            '.' | 'b' => 0,
+            'o' => 1,
+            'A'..='X' => prefix.take().unwrap_or(0) * 24 + (c as usize - 'A' as usize) + 1,
+            '$' => {
+                y += count.max(1);
+                x = 0;
+                count = 0;
+                continue;
+            }
+            '!' => break,
+            c if c.is_whitespace() => continue,
+            _ => return Err(format!("invalid character in RLE: {}", c)),
+        };
+
+        let run = count.max(1);
+
+        if state > MAX_STATE {
+            return Err(format!("invalid state in RLE: {}", state));
+        }
+
+        let clr_idx = match state {
+            0 => BLANK,
+            OBSTACLE_STATE => OBSTACLE,
+            _ => state - 1,
+        };
+
+        if clr_idx != BLANK {
+            for i in 0..run {
+                cells.push((x + i, y, clr_idx));
+            }
+        }
+
+        x += run;
+        count = 0;
+    }
+
+    Ok(cells)
+}
+
+//-----------------------------------------------------------------------------
+// Parse the "x = W, y = H, rule = R" header line.
+fn parse_header(line: &str) -> Result<(usize, usize, String), String> {
+    let mut width = None;
+    let mut height = None;
+    let mut rule = String::from("RL");
+
+    for field in line.split(',') {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("invalid header: {}", line))?;
+
+        match key.trim() {
+            "x" => width = Some(parse_num(value)?),
+            "y" => height = Some(parse_num(value)?),
+            "rule" => rule = value.trim().to_string(),
+            _ => {}
+        }
+    }
+
+    match (width, height) {
+        (Some(w), Some(h)) => Ok((w, h, rule)),
+        _ => Err(format!("invalid header: {}", line)),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Parse a number, with an error message naming the offending text.
+fn parse_num<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("invalid number: {}", s.trim()))
+}
@@ -0,0 +1,319 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{rule_to_string, Direction, Facing, FACINGS};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Default limit on the number of steps in a rule, each of which needs a
+// colour of its own.
+pub const MAX_RULE_LENGTH: usize = 256;
+
+// Characters that may separate the steps of a rule, along with whitespace,
+// e.g. "L-R-R-L" or "L,R,R,L".
+pub(crate) const SEPARATORS: [char; 5] = [',', '-', '_', '/', '|'];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Why a rule couldn't be parsed. Positions count the characters of the rule
+// as given, from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleParseError {
+    // The rule has no steps.
+    Empty,
+    // A character that isn't a step or a separator.
+    InvalidCharacter { character: char, position: usize },
+    // A "^" not followed by N, E, S or W.
+    MissingFacing { position: usize },
+    // The rule has more steps than allowed.
+    TooLong { length: usize, max: usize },
+}
+
+impl RuleParseError {
+    // Where in the rule the problem is, if at a single character.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            RuleParseError::InvalidCharacter { position, .. }
+            | RuleParseError::MissingFacing { position } => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleParseError::Empty => write!(f, "the rule is empty, expected e.g. RL or RLLR"),
+            RuleParseError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "unexpected '{}' at position {}, expected L, R, U, N or ^ and a facing",
+                character, position
+            ),
+            RuleParseError::MissingFacing { position } => write!(
+                f,
+                "expected N, E, S or W after the ^ at position {}",
+                position
+            ),
+            RuleParseError::TooLong { length, max } => write!(
+                f,
+                "the rule has {} steps, at most {} are allowed",
+                length, max
+            ),
+        }
+    }
+}
+
+impl Error for RuleParseError {}
+
+//-----------------------------------------------------------------------------
+// A plain rule's steps, the direction to turn on each colour in turn, e.g.
+// RL for Langton's original ant. Written and parsed as a string, e.g.
+// "RLLR".parse::<Rule>(), and stored in saved states as a list of steps.
+// Each step's effect is worked out once, when the rule is made, so a move
+// is a single table lookup rather than matching the turn against the
+// ant's facing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<Direction>", into = "Vec<Direction>")]
+pub struct Rule {
+    directions: Vec<Direction>,
+    steps: Vec<Step>,
+}
+
+//-----------------------------------------------------------------------------
+// A rule's step for one colour: the colour the cell changes to and the
+// facing the ant turns to from each facing, in the order N, E, S, W.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Step {
+    next: usize,
+    facings: [Facing; 4],
+}
+
+impl Rule {
+    pub fn new(directions: Vec<Direction>) -> Rule {
+        let len = directions.len();
+        let steps = directions
+            .iter()
+            .enumerate()
+            .map(|(clr_idx, &direction)| Step {
+                next: (clr_idx + 1) % len,
+                facings: FACINGS.map(|facing| facing.turned(direction)),
+            })
+            .collect();
+
+        Rule { directions, steps }
+    }
+
+    // Langton's original rule, RL.
+    pub fn langton() -> Rule {
+        Rule::new(vec![Direction::R, Direction::L])
+    }
+
+    // A rule of left and right turns numbered by the bits of a number, the
+    // lowest bit first, R for 1 and L for 0, so 0b01 of length 2 is RL.
+    pub fn from_bits(bits: u64, len: usize) -> Rule {
+        Rule::new(
+            (0..len.min(64))
+                .map(|i| {
+                    if (bits >> i) & 1 == 1 {
+                        Direction::R
+                    } else {
+                        Direction::L
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    // Parse a rule of up to MAX_RULE_LENGTH steps. See parse_with_max_length.
+    pub fn parse(rule: &str) -> Result<Rule, RuleParseError> {
+        Rule::parse_with_max_length(rule, MAX_RULE_LENGTH)
+    }
+
+    // Parse a rule of L, R, U and N steps and absolute facings, "^" and one
+    // of N, E, S or W, e.g. "RL^N". Lower case is accepted, and whitespace
+    // and the separators , - _ / and | between steps are ignored, so
+    // "r-l-^n" is the same rule. Fails at the first problem found, giving
+    // its position, or if the rule has no steps or more than max_length.
+    pub fn parse_with_max_length(rule: &str, max_length: usize) -> Result<Rule, RuleParseError> {
+        let mut directions = Vec::new();
+        let mut chars = rule.chars().enumerate().map(|(i, c)| (i + 1, c));
+
+        while let Some((position, c)) = chars.next() {
+            if c.is_whitespace() || SEPARATORS.contains(&c) {
+                continue;
+            }
+
+            let direction = if c == '^' {
+                chars
+                    .next()
+                    .and_then(|(_, c)| Facing::from_char(c.to_ascii_uppercase()))
+                    .map(Direction::Face)
+                    .ok_or(RuleParseError::MissingFacing { position })?
+            } else {
+                Direction::from_char(c.to_ascii_uppercase()).ok_or(
+                    RuleParseError::InvalidCharacter {
+                        character: c,
+                        position,
+                    },
+                )?
+            };
+
+            directions.push(direction);
+        }
+
+        if directions.is_empty() {
+            return Err(RuleParseError::Empty);
+        }
+
+        if directions.len() > max_length {
+            return Err(RuleParseError::TooLong {
+                length: directions.len(),
+                max: max_length,
+            });
+        }
+
+        Ok(Rule::new(directions))
+    }
+
+    pub fn directions(&self) -> &[Direction] {
+        &self.directions
+    }
+
+    // The direction to turn on a colour.
+    pub fn turn(&self, clr_idx: usize) -> Direction {
+        self.directions[clr_idx]
+    }
+
+    // The colour a cell of the given colour changes to as the ant leaves
+    // it, and the facing the ant turns to from its current facing.
+    pub fn step(&self, clr_idx: usize, facing: Facing) -> (usize, Facing) {
+        let step = &self.steps[clr_idx];
+        (step.next, step.facings[facing as usize])
+    }
+
+    // Number of steps, i.e. colours.
+    pub fn len(&self) -> usize {
+        self.directions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.directions.is_empty()
+    }
+
+    // Does the rule face the ant a fixed way on any colour, e.g. "^N"?
+    pub fn is_absolute(&self) -> bool {
+        self.directions.iter().any(|d| !d.is_relative())
+    }
+
+    // The rule mirrored left to right, swapping left and right turns and
+    // facing east and west, which builds the mirror image of the pattern.
+    pub fn reflected(&self) -> Rule {
+        Rule::new(self.directions.iter().map(|&d| reflect(d)).collect())
+    }
+
+    // The rule's steps rotated to start n steps later, e.g. RLL rotated by
+    // 1 is LLR.
+    pub fn rotated(&self, n: usize) -> Rule {
+        let mut directions = self.directions.clone();
+
+        if !directions.is_empty() {
+            let len = directions.len();
+            directions.rotate_left(n % len);
+        }
+
+        Rule::new(directions)
+    }
+
+    // The representative of the rules that are the same up to rotating the
+    // steps and mirroring, the first of them in alphabetical order, e.g.
+    // LLRR for RRLL, RLLR and LRRL. Useful for grouping rules, e.g. when
+    // surveying rule space, though rotated rules start from a different
+    // colour so needn't build the same pattern from a blank grid.
+    pub fn canonical(&self) -> Rule {
+        let reflected = self.reflected();
+
+        (0..self.len().max(1))
+            .flat_map(|n| vec![self.rotated(n), reflected.rotated(n)])
+            .min_by_key(|rule| rule.to_string())
+            .unwrap_or_default()
+    }
+
+    // Is the rule the same as another up to rotation and mirroring?
+    pub fn is_equivalent(&self, other: &Rule) -> bool {
+        self.len() == other.len() && self.canonical() == other.canonical()
+    }
+}
+
+impl From<Vec<Direction>> for Rule {
+    fn from(directions: Vec<Direction>) -> Rule {
+        Rule::new(directions)
+    }
+}
+
+impl From<Rule> for Vec<Direction> {
+    fn from(rule: Rule) -> Vec<Direction> {
+        rule.directions
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Rule, RuleParseError> {
+        Rule::parse(s)
+    }
+}
+
+// Written in its standard form, upper case without separators, e.g.
+// "RL^N", as Rule::parse accepts.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", rule_to_string(&self.directions))
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A direction mirrored left to right.
+fn reflect(direction: Direction) -> Direction {
+    match direction {
+        Direction::L => Direction::R,
+        Direction::R => Direction::L,
+        Direction::Face(Facing::E) => Direction::Face(Facing::W),
+        Direction::Face(Facing::W) => Direction::Face(Facing::E),
+        other => other,
+    }
+}
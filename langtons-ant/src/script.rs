@@ -0,0 +1,341 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+// Scripting hooks, letting a Rhai script watch and change a run as it
+// goes. The script defines a function on_step(), called every given number
+// of iterations, from which it can read and write the grid and the ants
+// through these functions:
+//
+//   iteration()                  iterations made so far
+//   grid_size()                  width and height of the initial grid
+//   num_colours()                number of colours in the rule
+//   num_ants()                   number of ants
+//   ant_x(), ant_y()             position of the first ant
+//   ant_x(i), ant_y(i)           position of ant i, counting from 0
+//   ant_facing(), ant_facing(i)  facing of an ant, "N", "E", "S" or "W"
+//   cell(x, y)                   colour index of a cell, or BLANK or OBSTACLE
+//   set_cell(x, y, colour)       paint a cell, BLANK or OBSTACLE included
+//   teleport(x, y)               move the first ant to a cell without
+//                                changing any cells
+//   teleport(i, x, y)            move ant i to a cell
+//   set_facing(f)                turn the first ant to face f
+//   set_facing(i, f)             turn ant i to face f
+//   stop()                       stop the run after this call
+//
+// Top level statements run once when the script is loaded, e.g. to set up
+// variables, which keep their values between calls.
+
+use crate::ant::Facing;
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::interval::Interval;
+use crate::simulation::Simulation;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::fs;
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Name of the function called by the run.
+const ON_STEP: &str = "on_step";
+
+// Values scripts use for blank and obstacle cells.
+const SCRIPT_BLANK: i64 = -1;
+const SCRIPT_OBSTACLE: i64 = -2;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// What the run should do after a script's call.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScriptAction {
+    Continue,
+    Stop,
+}
+
+//-----------------------------------------------------------------------------
+// What the script's functions work on. The simulation is only here during
+// a call of on_step.
+#[derive(Default)]
+struct Host {
+    sim: Option<Simulation>,
+    stop: bool,
+}
+
+type SharedHost = Arc<Mutex<Host>>;
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+//-----------------------------------------------------------------------------
+// A loaded script, calling its on_step function every given number of
+// iterations.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    host: SharedHost,
+    interval: Interval,
+}
+
+impl Script {
+    // Load and compile a script file, running its top level statements.
+    pub fn load<P: AsRef<Path>>(path: P, every: u64) -> Result<Script, String> {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Script::from_source(&source, every)
+    }
+
+    // Compile a script, running its top level statements. Fails if the
+    // script doesn't compile, has no on_step function or fails to run.
+    pub fn from_source(source: &str, every: u64) -> Result<Script, String> {
+        let host = SharedHost::default();
+        let engine = create_engine(&host);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == ON_STEP && f.params.is_empty())
+        {
+            return Err(format!("the script has no {}() function", ON_STEP));
+        }
+
+        let mut scope = Scope::new();
+        scope.push_constant("BLANK", SCRIPT_BLANK);
+        scope.push_constant("OBSTACLE", SCRIPT_OBSTACLE);
+
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| e.to_string())?;
+
+        Ok(Script {
+            engine,
+            ast,
+            scope,
+            host,
+            interval: Interval::new(every),
+        })
+    }
+
+    // Number of iterations until on_step is next due to be called.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    // Call on_step if it's due, returning whether the run should stop, or
+    // the script's error.
+    pub fn run(&mut self, sim: &mut Simulation) -> Result<ScriptAction, String> {
+        if !self.interval.due(sim) {
+            return Ok(ScriptAction::Continue);
+        }
+
+        // The script's functions need the simulation for the length of the
+        // call, so it's lent to them, leaving an empty one in its place.
+        let lent = mem::replace(sim, Simulation::with_seed("", 0, 0));
+        self.host.lock().unwrap().sim = Some(lent);
+
+        // Whatever on_step returns is ignored.
+        let result = self
+            .engine
+            .call_fn::<Dynamic>(&mut self.scope, &self.ast, ON_STEP, ())
+            .map(|_| ());
+
+        let mut host = self.host.lock().unwrap();
+        *sim = host.sim.take().unwrap();
+        result.map_err(|e| e.to_string())?;
+
+        if mem::take(&mut host.stop) {
+            Ok(ScriptAction::Stop)
+        } else {
+            Ok(ScriptAction::Continue)
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Create an engine with the functions scripts use to work on the host's
+// simulation.
+fn create_engine(host: &SharedHost) -> Engine {
+    let mut engine = Engine::new();
+
+    let h = host.clone();
+    engine.register_fn("iteration", move || {
+        with_sim(&h, |sim| Ok(sim.iterations() as i64))
+    });
+
+    let h = host.clone();
+    engine.register_fn("grid_size", move || {
+        with_sim(&h, |sim| Ok(sim.size() as i64))
+    });
+
+    let h = host.clone();
+    engine.register_fn("num_colours", move || {
+        with_sim(&h, |sim| Ok(sim.num_colours() as i64))
+    });
+
+    let h = host.clone();
+    engine.register_fn("num_ants", move || {
+        with_sim(&h, |sim| Ok(sim.ants().len() as i64))
+    });
+
+    let h = host.clone();
+    engine.register_fn("ant_x", move || ant_x(&h, 0));
+    let h = host.clone();
+    engine.register_fn("ant_x", move |i: i64| ant_x(&h, i));
+
+    let h = host.clone();
+    engine.register_fn("ant_y", move || ant_y(&h, 0));
+    let h = host.clone();
+    engine.register_fn("ant_y", move |i: i64| ant_y(&h, i));
+
+    let h = host.clone();
+    engine.register_fn("ant_facing", move || ant_facing(&h, 0));
+    let h = host.clone();
+    engine.register_fn("ant_facing", move |i: i64| ant_facing(&h, i));
+
+    let h = host.clone();
+    engine.register_fn("cell", move |x: i64, y: i64| {
+        with_sim(&h, |sim| {
+            Ok(match sim.cells().get(x, y) {
+                BLANK => SCRIPT_BLANK,
+                OBSTACLE => SCRIPT_OBSTACLE,
+                clr_idx => clr_idx as i64,
+            })
+        })
+    });
+
+    let h = host.clone();
+    engine.register_fn("set_cell", move |x: i64, y: i64, colour: i64| {
+        with_sim(&h, |sim| {
+            let clr_idx = match colour {
+                SCRIPT_BLANK => BLANK,
+                SCRIPT_OBSTACLE => OBSTACLE,
+                c if c >= 0 && (c as usize) < sim.num_colours() => c as usize,
+                c => return Err(format!("no colour {}", c).into()),
+            };
+
+            sim.paint(x, y, clr_idx);
+            Ok(())
+        })
+    });
+
+    let h = host.clone();
+    engine.register_fn("teleport", move |x: i64, y: i64| teleport(&h, 0, x, y));
+    let h = host.clone();
+    engine.register_fn("teleport", move |i: i64, x: i64, y: i64| {
+        teleport(&h, i, x, y)
+    });
+
+    let h = host.clone();
+    engine.register_fn("set_facing", move |facing: &str| set_facing(&h, 0, facing));
+    let h = host.clone();
+    engine.register_fn("set_facing", move |i: i64, facing: &str| {
+        set_facing(&h, i, facing)
+    });
+
+    let h = host.clone();
+    engine.register_fn("stop", move || {
+        h.lock().unwrap().stop = true;
+    });
+
+    engine
+}
+
+//-----------------------------------------------------------------------------
+// Call f with the simulation lent to the script, failing outside on_step.
+fn with_sim<T, F>(host: &SharedHost, f: F) -> ScriptResult<T>
+where
+    F: FnOnce(&mut Simulation) -> ScriptResult<T>,
+{
+    match host.lock().unwrap().sim.as_mut() {
+        Some(sim) => f(sim),
+        None => Err(format!("the simulation can only be used in {}()", ON_STEP).into()),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The index of ant i, failing if there's no such ant.
+fn ant_index(sim: &Simulation, i: i64) -> ScriptResult<usize> {
+    if i >= 0 && (i as usize) < sim.ants().len() {
+        Ok(i as usize)
+    } else {
+        Err(format!("no ant {}", i).into())
+    }
+}
+
+fn ant_x(host: &SharedHost, i: i64) -> ScriptResult<i64> {
+    with_sim(host, |sim| Ok(sim.ants()[ant_index(sim, i)?].pos_x))
+}
+
+fn ant_y(host: &SharedHost, i: i64) -> ScriptResult<i64> {
+    with_sim(host, |sim| Ok(sim.ants()[ant_index(sim, i)?].pos_y))
+}
+
+fn ant_facing(host: &SharedHost, i: i64) -> ScriptResult<String> {
+    with_sim(host, |sim| {
+        Ok(sim.ants()[ant_index(sim, i)?].facing.to_char().to_string())
+    })
+}
+
+fn teleport(host: &SharedHost, i: i64, x: i64, y: i64) -> ScriptResult<()> {
+    with_sim(host, |sim| {
+        let i = ant_index(sim, i)?;
+        let facing = sim.ants()[i].facing;
+
+        if sim.place_ant(i, x, y, facing) {
+            Ok(())
+        } else {
+            Err(format!("can't move an ant to ({}, {})", x, y).into())
+        }
+    })
+}
+
+fn set_facing(host: &SharedHost, i: i64, facing: &str) -> ScriptResult<()> {
+    with_sim(host, |sim| {
+        let i = ant_index(sim, i)?;
+        let (x, y) = (sim.ants()[i].pos_x, sim.ants()[i].pos_y);
+
+        match parse_facing(facing) {
+            Some(facing) => {
+                sim.place_ant(i, x, y, facing);
+                Ok(())
+            }
+            None => Err(format!("no facing {}, use N, E, S or W", facing).into()),
+        }
+    })
+}
+
+//-----------------------------------------------------------------------------
+// A facing from its compass letter.
+fn parse_facing(facing: &str) -> Option<Facing> {
+    let mut chars = facing.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Facing::from_char(c),
+        _ => None,
+    }
+}
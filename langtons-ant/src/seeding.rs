@@ -0,0 +1,138 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{Extent, BLANK};
+use crate::random::{SeededRng, GRID_SEED_STREAM};
+use crate::simulation::Simulation;
+use image::imageops::FilterType;
+use rand::{Rng, RngCore};
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Densities are scaled to a threshold for 32 bit random numbers.
+const DENSITY_SCALE: f64 = 4_294_967_296.0;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// An initial pattern to fill the grid with, so a run needn't start from an
+// all blank grid. Colour index 0 behaves exactly like a blank cell, so the
+// patterns are made from the other colour indexes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GridSeed {
+    // Each cell is given a random non-zero colour index with the given
+    // probability, otherwise left blank.
+    Random(f64),
+    // Alternate cells are given colour index 1.
+    Checker,
+    // Cells are coloured from an image scaled to fit the grid, with darker
+    // pixels mapping to higher colour indexes and white left blank.
+    Image(PathBuf),
+}
+
+impl GridSeed {
+    // Parse a grid seed, either "random:DENSITY", e.g. "random:0.3",
+    // "checker", or the path of an image file.
+    pub fn parse(s: &str) -> Result<GridSeed, String> {
+        if s == "checker" {
+            return Ok(GridSeed::Checker);
+        }
+
+        if let Some(density) = s.strip_prefix("random:") {
+            return match density.parse::<f64>() {
+                Ok(d) if (0.0..=1.0).contains(&d) => Ok(GridSeed::Random(d)),
+                _ => Err(format!("invalid density, must be 0 - 1: {}", density)),
+            };
+        }
+
+        Ok(GridSeed::Image(PathBuf::from(s)))
+    }
+
+    // Fill the grid's initial region, i.e. the size x size cells shown at
+    // the start, with the pattern. Random patterns are generated from the
+    // simulation's seed so they are reproducible.
+    pub fn apply(&self, sim: &mut Simulation) -> Result<(), String> {
+        let size = sim.size();
+        let region = Extent::centred(size, size);
+        let num_colours = sim.num_colours();
+
+        // A single colour rule can't show any pattern.
+        if num_colours < 2 {
+            return Ok(());
+        }
+
+        match self {
+            GridSeed::Random(density) => {
+                let mut rng = SeededRng::new(sim.seed(), GRID_SEED_STREAM);
+
+                // Compare whole numbers rather than floats cell by cell, so
+                // the pattern can't depend on how a platform rounds.
+                let threshold = (density * DENSITY_SCALE) as u64;
+                let others = (num_colours - 1) as u32;
+
+                for y in region.y..region.y + size as i64 {
+                    for x in region.x..region.x + size as i64 {
+                        if u64::from(rng.next_u32()) < threshold {
+                            sim.paint(x, y, 1 + rng.gen_range(0..others) as usize);
+                        }
+                    }
+                }
+            }
+            GridSeed::Checker => {
+                for y in region.y..region.y + size as i64 {
+                    for x in region.x..region.x + size as i64 {
+                        if (x + y).rem_euclid(2) == 1 {
+                            sim.paint(x, y, 1);
+                        }
+                    }
+                }
+            }
+            GridSeed::Image(path) => {
+                let img = image::open(path)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?
+                    .resize(size as u32, size as u32, FilterType::Triangle)
+                    .to_luma8();
+
+                // Centre the image in the grid.
+                let x0 = region.x + (size as i64 - i64::from(img.width())) / 2;
+                let y0 = region.y + (size as i64 - i64::from(img.height())) / 2;
+
+                for (px, py, pixel) in img.enumerate_pixels() {
+                    let darkness = 1.0 - f64::from(pixel[0]) / 255.0;
+                    let clr_idx = ((darkness * num_colours as f64) as usize).min(num_colours - 1);
+
+                    sim.paint(
+                        x0 + i64::from(px),
+                        y0 + i64::from(py),
+                        if clr_idx == 0 { BLANK } else { clr_idx },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
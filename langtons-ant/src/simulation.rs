@@ -0,0 +1,1037 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{
+    compute_ant_position, compute_ant_position_by, rule_to_string, wrap, Ant, Boundary,
+    CollisionPolicy, Direction, Facing, ObstaclePolicy,
+};
+use crate::colour::{Colour, ColourMap};
+use crate::delta::Delta;
+use crate::engine::RuleEngine;
+use crate::fast;
+use crate::grid::{Extent, Grid, GridBackend, GridStorage, PackedGrid, BLANK, OBSTACLE};
+use crate::random::{self, SeededRng, COLOURS_STREAM, TURNS_STREAM};
+use crate::replay::{Change, Move};
+use crate::rule::Rule;
+use crate::swarm::{self, MIN_SWARM_ANTS};
+use crate::turmite::{compute_turmite_position, Turmite};
+use crate::visits::Visits;
+use crate::weighted::WeightedRule;
+use crate::zones::{self, Zone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Most ants there can be before collisions stop spawning more, as crowded
+// ants meet ever more often and would otherwise multiply without limit.
+pub const MAX_SPAWNED_ANTS: usize = 10_000;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A self contained Langton's Ant simulation, owning the ants and the grid
+// they walk on. This has no dependency on any rendering so can be driven
+// headlessly by other programs and tests. There is always at least one ant,
+// the first, and any others share its rule. The colours of the cells are
+// kept apart from the ants, in a map from colour index to colour.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Simulation {
+    ants: Vec<Ant>,
+    grid: GridBackend,
+    #[serde(default)]
+    colours: ColourMap,
+    #[serde(default)]
+    turmite: Option<Turmite>,
+    #[serde(default)]
+    weighted: Option<WeightedRule>,
+    // A custom rule engine making every move, which can't be saved.
+    #[serde(skip)]
+    engine: Option<Box<dyn RuleEngine>>,
+    #[serde(default)]
+    zones: Vec<Zone>,
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    boundary: Boundary,
+    #[serde(default)]
+    collision: CollisionPolicy,
+    #[serde(default)]
+    obstacles: ObstaclePolicy,
+    #[serde(skip)]
+    track_dirty: bool,
+    #[serde(skip)]
+    dirty: Vec<(i64, i64)>,
+    #[serde(skip)]
+    track_changes: bool,
+    #[serde(skip)]
+    changes: Vec<Change>,
+    #[serde(skip)]
+    visits: Option<Visits>,
+    // Picks the turns of a probabilistic rule, seeded from the seed and
+    // iteration count when first needed. It's saved, so a loaded run makes
+    // the same choices it would have made had it never stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng: Option<SeededRng>,
+}
+
+impl Simulation {
+    // Create a new simulation for the given rule, e.g. "RL", on a square
+    // grid of size x size cells with the ant centred in the grid. The rule
+    // is either a plain rule as Rule::parse accepts, e.g. "r-l", or a
+    // probabilistic rule, e.g. "L:0.9/R:0.1,R". Any other rule gives an
+    // empty one that makes no moves, so check it first, e.g. with
+    // config::validate_rule. The colours are randomly generated from a
+    // random seed.
+    pub fn new(rule: &str, size: usize) -> Simulation {
+        Simulation::with_seed(rule, size, random::random_seed())
+    }
+
+    // As new, but generating colours from the given seed so that runs are
+    // reproducible.
+    pub fn with_seed(rule: &str, size: usize, seed: u64) -> Simulation {
+        if WeightedRule::is_weighted(rule) {
+            if let Ok(weighted) = WeightedRule::parse(rule) {
+                return Simulation::weighted_with_seed(weighted, size, seed);
+            }
+        }
+
+        let mut rng = SeededRng::new(seed, COLOURS_STREAM);
+
+        // Start the ant at the origin, the centre of the grid.
+        let mut ant = Ant::new(0, 0);
+        ant.rule = Rule::parse(rule).unwrap_or_default();
+
+        // Build the colour map shared by the ants.
+        let colours = ColourMap::distinct(&mut rng, ant.rule.len());
+
+        Simulation {
+            ants: vec![ant],
+            grid: GridBackend::Dense(Grid::new(size, size, BLANK)),
+            colours,
+            turmite: None,
+            weighted: None,
+            engine: None,
+            zones: Vec::new(),
+            seed,
+            boundary: Boundary::Stall,
+            collision: CollisionPolicy::Ignore,
+            obstacles: ObstaclePolicy::Reverse,
+            track_dirty: false,
+            dirty: Vec::new(),
+            track_changes: false,
+            changes: Vec::new(),
+            visits: None,
+            rng: None,
+        }
+    }
+
+    // Create a simulation of a probabilistic rule, where each colour has a
+    // weighted choice of turns picked at random using the seed.
+    pub fn weighted_with_seed(rule: WeightedRule, size: usize, seed: u64) -> Simulation {
+        let plain = rule_to_string(&rule.most_likely());
+        let mut sim = Simulation::with_seed(&plain, size, seed);
+        sim.weighted = Some(rule);
+        sim
+    }
+
+    // Create a new turmite simulation on a square grid of size x size cells
+    // with the ant centred in the grid and starting in state 0.
+    pub fn new_turmite(turmite: Turmite, size: usize) -> Simulation {
+        Simulation::turmite_with_seed(turmite, size, random::random_seed())
+    }
+
+    // As new_turmite, but generating colours from the given seed.
+    pub fn turmite_with_seed(turmite: Turmite, size: usize, seed: u64) -> Simulation {
+        let mut rng = SeededRng::new(seed, COLOURS_STREAM);
+
+        // Start the ant at the origin, the centre of the grid.
+        let ant = Ant::new(0, 0);
+
+        Simulation {
+            ants: vec![ant],
+            grid: GridBackend::Dense(Grid::new(size, size, BLANK)),
+            colours: ColourMap::distinct(&mut rng, turmite.num_colours()),
+            turmite: Some(turmite),
+            weighted: None,
+            engine: None,
+            zones: Vec::new(),
+            seed,
+            boundary: Boundary::Stall,
+            collision: CollisionPolicy::Ignore,
+            obstacles: ObstaclePolicy::Reverse,
+            track_dirty: false,
+            dirty: Vec::new(),
+            track_changes: false,
+            changes: Vec::new(),
+            visits: None,
+            rng: None,
+        }
+    }
+
+    // Create a simulation run by a custom rule engine on a square grid of
+    // size x size cells with the ant centred in the grid, generating a
+    // colour for each of the engine's colours from the seed.
+    pub fn engine_with_seed(engine: Box<dyn RuleEngine>, size: usize, seed: u64) -> Simulation {
+        let mut sim = Simulation::with_seed("", size, seed);
+        let mut rng = SeededRng::new(seed, COLOURS_STREAM);
+
+        sim.colours = ColourMap::distinct(&mut rng, engine.num_colours().max(1));
+
+        sim.engine = Some(engine);
+        sim
+    }
+
+    // Load a simulation previously written by save, resuming exactly where
+    // the saved run left off.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Simulation> {
+        Simulation::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    // Read a simulation written as JSON, e.g. by save. Older saves kept the
+    // colours with each ant rather than in the simulation's colour map, so
+    // the first ant's are moved across.
+    pub(crate) fn from_reader<R: io::Read>(reader: R) -> io::Result<Simulation> {
+        let mut value: serde_json::Value = serde_json::from_reader(reader)?;
+
+        if let Some(state) = value.as_object_mut() {
+            if !state.contains_key("colours") {
+                let colours = state
+                    .get_mut("ants")
+                    .and_then(|ants| ants.get_mut(0))
+                    .and_then(|ant| ant.get_mut("colours"))
+                    .map(serde_json::Value::take);
+
+                if let Some(colours) = colours {
+                    state.insert("colours".to_string(), colours);
+                }
+            }
+        }
+
+        let sim: Simulation = serde_json::from_value(value)?;
+
+        if sim.ants.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no ants"));
+        }
+
+        Ok(sim)
+    }
+
+    // Write the full simulation state, i.e. grid, ant positions, facings,
+    // rule, colours and iteration counts, to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if self.engine.is_some() {
+            return Err(io::Error::other(
+                "runs of custom rule engines can't be saved",
+            ));
+        }
+
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    // Advance the simulation by a single move of each ant, in the order the
+    // ants were added.
+    pub fn step(&mut self) {
+        // Where each ant started, to tell which ants meet during the step.
+        let before: Vec<(i64, i64)> =
+            if self.collision != CollisionPolicy::Ignore && self.ants.len() > 1 {
+                self.ants.iter().map(|a| (a.pos_x, a.pos_y)).collect()
+            } else {
+                Vec::new()
+            };
+
+        for (i, ant) in self.ants.iter_mut().enumerate() {
+            if ant.stalled {
+                continue;
+            }
+
+            // The ant always changes the colour of the cell it's leaving.
+            if self.track_dirty {
+                self.dirty.push((ant.pos_x, ant.pos_y));
+            }
+
+            let (x, y) = (ant.pos_x, ant.pos_y);
+            let (facing, state, iterations) = (ant.facing, ant.state, ant.iterations);
+            let old = self.grid.get(x, y);
+
+            if let Some(ref mut engine) = self.engine {
+                engine.step(&mut self.grid, ant, self.boundary, self.obstacles);
+            } else {
+                match self.turmite {
+                    Some(ref turmite) => compute_turmite_position(
+                        ant,
+                        turmite,
+                        &mut self.grid,
+                        self.boundary,
+                        self.obstacles,
+                    ),
+                    None => match self.weighted {
+                        Some(ref weighted) => {
+                            let seed = self.seed ^ iterations;
+                            let rng = self
+                                .rng
+                                .get_or_insert_with(|| SeededRng::new(seed, TURNS_STREAM));
+
+                            compute_ant_position_by(
+                                ant,
+                                &mut self.grid,
+                                self.boundary,
+                                self.obstacles,
+                                weighted.len(),
+                                |clr_idx| weighted.choose(clr_idx, rng),
+                            )
+                        }
+                        None => match zones::rule_at(&self.zones, x, y) {
+                            Some(rule) => compute_ant_position_by(
+                                ant,
+                                &mut self.grid,
+                                self.boundary,
+                                self.obstacles,
+                                rule.len(),
+                                |clr_idx| rule.turn(clr_idx),
+                            ),
+                            None => compute_ant_position(
+                                ant,
+                                &mut self.grid,
+                                self.boundary,
+                                self.obstacles,
+                            ),
+                        },
+                    },
+                }
+            }
+
+            if self.track_changes {
+                self.changes.push(Change::Move(Move {
+                    ant: i,
+                    dx: ant.pos_x - x,
+                    dy: ant.pos_y - y,
+                    old,
+                    new: self.grid.get(x, y),
+                    facing: (facing, ant.facing),
+                    state: (state, ant.state),
+                    stalled: ant.stalled,
+                    counted: ant.iterations != iterations,
+                }));
+            }
+
+            if let Some(ref mut visits) = self.visits {
+                visits.record(x, y, ant.iterations);
+            }
+        }
+
+        if !before.is_empty() {
+            self.collide(&before);
+        }
+    }
+
+    // Apply the collision policy to any ants that met during a step, given
+    // where each ant was before it. Ants meet when they end up on the same
+    // cell without all having started there, so ants moving in lockstep,
+    // e.g. a spawned ant and its parents, don't keep colliding. Annihilation
+    // always spares one ant, as there must be at least one, and spawning
+    // stops at MAX_SPAWNED_ANTS.
+    fn collide(&mut self, before: &[(i64, i64)]) {
+        let mut cells: BTreeMap<(i64, i64), Vec<usize>> = BTreeMap::new();
+
+        for (i, ant) in self.ants.iter().enumerate() {
+            cells.entry((ant.pos_x, ant.pos_y)).or_default().push(i);
+        }
+
+        let mut removed = Vec::new();
+
+        for ((x, y), ants) in cells {
+            if ants.len() < 2 || ants.iter().all(|&i| before[i] == before[ants[0]]) {
+                continue;
+            }
+
+            match self.collision {
+                CollisionPolicy::Swap => {
+                    let facings: Vec<Facing> = ants.iter().map(|&i| self.ants[i].facing).collect();
+
+                    for (k, &i) in ants.iter().enumerate() {
+                        self.set_facing(i, facings[(k + 1) % facings.len()]);
+                    }
+                }
+                CollisionPolicy::Annihilate => removed.extend(ants),
+                CollisionPolicy::Spawn if self.ants.len() < MAX_SPAWNED_ANTS => {
+                    let facing = self.ants[ants[0]].facing.reverse();
+                    self.add_ant(x, y, facing);
+                }
+                CollisionPolicy::Ignore | CollisionPolicy::Spawn => {}
+            }
+        }
+
+        if removed.len() == self.ants.len() {
+            removed.sort_unstable();
+            removed.remove(0);
+        }
+
+        // Remove from the back so the indexes logged stay valid.
+        removed.sort_unstable_by(|a, b| b.cmp(a));
+
+        for i in removed {
+            self.remove_ant(i);
+        }
+    }
+
+    // Turn an ant to a new facing outside of its move.
+    fn set_facing(&mut self, i: usize, facing: Facing) {
+        let old = self.ants[i].facing;
+        self.ants[i].facing = facing;
+
+        if self.track_changes && old != facing {
+            self.changes.push(Change::Face {
+                ant: i,
+                old,
+                new: facing,
+            });
+        }
+    }
+
+    // Remove an ant, which mustn't be the last.
+    fn remove_ant(&mut self, i: usize) {
+        let ant = self.ants.remove(i);
+
+        if self.track_changes {
+            self.changes.push(Change::RemoveAnt {
+                ant: i,
+                x: ant.pos_x,
+                y: ant.pos_y,
+                facing: ant.facing,
+                state: ant.state,
+                stalled: ant.stalled,
+                iterations: ant.iterations,
+            });
+        }
+    }
+
+    // Advance the simulation by up to n steps, stopping early if every ant
+    // stalls. A single ant following a plain rule uses the fast
+    // macro-stepping loop, which is much quicker than calling step
+    // repeatedly, and many ants are moved together as a swarm, unless
+    // changed cells, moves or visits are being tracked. Probabilistic rules,
+    // rule zones and custom rule engines always take the slow path, as do
+    // swarms whose collisions aren't ignored.
+    pub fn advance(&mut self, n: u64) {
+        let mut remaining = n;
+
+        if self.turmite.is_none()
+            && self.weighted.is_none()
+            && self.engine.is_none()
+            && self.zones.is_empty()
+            && !self.track_dirty
+            && !self.track_changes
+            && self.visits.is_none()
+        {
+            if self.ants.len() == 1 {
+                remaining -= fast::advance(&mut self.ants[0], &mut self.grid, n, self.boundary);
+            } else if self.ants.len() >= MIN_SWARM_ANTS && self.collision == CollisionPolicy::Ignore
+            {
+                remaining -= swarm::advance(
+                    &mut self.ants,
+                    &mut self.grid,
+                    n,
+                    self.boundary,
+                    self.obstacles,
+                );
+            }
+        }
+
+        while remaining > 0 && !self.is_stalled() {
+            self.step();
+            remaining -= 1;
+        }
+    }
+
+    // Check the moves made so far can be undone by unstep, returning why
+    // not if they can't. Only plain rules of relative turns can be run in
+    // reverse, as turmite states, random choices, absolute facings and
+    // collisions, bounces and obstacles all lose track of where the ants
+    // came from.
+    pub fn reversible(&self) -> Result<(), String> {
+        let mut obstacles = false;
+        self.grid
+            .for_each_visited(|_, _, clr_idx| obstacles |= clr_idx == OBSTACLE);
+
+        if self.turmite.is_some() || self.weighted.is_some() || self.engine.is_some() {
+            Err("only plain rules can run in reverse".to_string())
+        } else if self.ants.iter().any(|a| a.rule.is_absolute())
+            || self.zones.iter().any(|z| z.rule.is_absolute())
+        {
+            Err("rules with absolute facings can't run in reverse".to_string())
+        } else if self.ants.len() > 1 && self.collision != CollisionPolicy::Ignore {
+            Err("colliding ants can't run in reverse".to_string())
+        } else if self.boundary == Boundary::Bounce {
+            Err("ants bouncing off the grid edges can't run in reverse".to_string())
+        } else if obstacles {
+            Err("grids with obstacles can't run in reverse".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Undo a move of each ant by running its rule backwards, taking the
+    // ants in the reverse of the order step moves them. Each ant steps back
+    // to the cell behind it, where it restores the cell's previous colour
+    // and undoes the turn that colour gave. Cells returned to colour 0 are
+    // cleared, as they would have been before the ant first visited them,
+    // so a pattern is un-built back to a blank grid. Returns false,
+    // stopping at that ant, if an ant can't step back, e.g. because it's
+    // back at its start. Only valid for simulations that are reversible.
+    pub fn unstep(&mut self) -> bool {
+        (0..self.ants.len()).rev().all(|i| self.unstep_ant(i))
+    }
+
+    fn unstep_ant(&mut self, i: usize) -> bool {
+        let ant = &self.ants[i];
+
+        if ant.iterations == 0 || ant.rule.is_empty() {
+            return false;
+        }
+
+        // A stalled ant made its last move without leaving its cell.
+        let (x, y) = if ant.stalled {
+            (ant.pos_x, ant.pos_y)
+        } else {
+            let (dx, dy) = ant.facing.offset();
+            let (x, y) = (ant.pos_x - dx, ant.pos_y - dy);
+
+            if self.grid.contains(x, y) {
+                (x, y)
+            } else if self.boundary == Boundary::Wrap {
+                wrap(self.grid.extent(), x, y)
+            } else {
+                return false;
+            }
+        };
+
+        // Colour 0 cells are cleared as they're undone, so a blank cell may
+        // be one the ant wrapped round to colour 0 on an earlier visit.
+        let cell = match self.grid.get(x, y) {
+            OBSTACLE => return false,
+            BLANK => 0,
+            clr_idx => clr_idx,
+        };
+
+        let num_colours = ant.rule.len();
+        let old = (cell + num_colours - 1) % num_colours;
+        let rule = zones::rule_at(&self.zones, x, y).unwrap_or(&ant.rule);
+
+        let facing = match rule.directions().get(old) {
+            Some(Direction::L) => ant.facing.clockwise(),
+            Some(Direction::R) => ant.facing.clockwise().reverse(),
+            Some(Direction::U) => ant.facing.reverse(),
+            Some(Direction::N) => ant.facing,
+            _ => return false,
+        };
+
+        if self.track_dirty {
+            self.dirty.push((ant.pos_x, ant.pos_y));
+            self.dirty.push((x, y));
+        }
+
+        self.grid.set(x, y, if old == 0 { BLANK } else { old });
+
+        let ant = &mut self.ants[i];
+        ant.pos_x = x;
+        ant.pos_y = y;
+        ant.facing = facing;
+        ant.stalled = false;
+        ant.iterations -= 1;
+
+        true
+    }
+
+    // Add another ant at the given cell, facing the given way and sharing
+    // the first ant's rule. The new ant's iteration count starts from the
+    // simulation's, so it counts the same steps as the others from then on.
+    // Returns false, adding nothing, if the cell is outside the grid.
+    pub fn add_ant(&mut self, x: i64, y: i64, facing: Facing) -> bool {
+        if !self.grid.contains(x, y) {
+            return false;
+        }
+
+        let mut ant = Ant::new(x, y);
+        ant.rule = self.ants[0].rule.clone();
+        ant.facing = facing;
+        ant.iterations = self.iterations();
+        self.ants.push(ant);
+
+        if self.track_changes {
+            self.changes.push(Change::AddAnt { x, y, facing });
+        }
+
+        true
+    }
+
+    // Move an ant straight to the given cell, facing the given way, without
+    // changing any cells or counting an iteration. A stalled ant can move
+    // again. Returns false, moving nothing, if there is no such ant or the
+    // cell is outside the grid or an obstacle.
+    pub fn place_ant(&mut self, i: usize, x: i64, y: i64, facing: Facing) -> bool {
+        if i >= self.ants.len() || !self.grid.contains(x, y) || self.grid.get(x, y) == OBSTACLE {
+            return false;
+        }
+
+        let ant = &mut self.ants[i];
+        let (old_x, old_y, old_facing) = (ant.pos_x, ant.pos_y, ant.facing);
+
+        ant.pos_x = x;
+        ant.pos_y = y;
+        ant.facing = facing;
+        ant.stalled = false;
+
+        // Logged as a move leaving its cell unchanged, so it can be undone
+        // and played back like any other.
+        if self.track_changes {
+            let clr_idx = self.grid.get(old_x, old_y);
+
+            self.changes.push(Change::Move(Move {
+                ant: i,
+                dx: x - old_x,
+                dy: y - old_y,
+                old: clr_idx,
+                new: clr_idx,
+                facing: (old_facing, facing),
+                state: (ant.state, ant.state),
+                stalled: false,
+                counted: false,
+            }));
+        }
+
+        true
+    }
+
+    // Add a zone where a different rule applies. Later zones take
+    // precedence where zones overlap. Fails if the zone's rule doesn't have
+    // as many steps as the simulation's, or for turmites and probabilistic
+    // rules, which zones can't be used with.
+    pub fn add_zone(&mut self, zone: Zone) -> Result<(), String> {
+        if self.turmite.is_some() || self.weighted.is_some() || self.engine.is_some() {
+            return Err(String::from("zones need a plain rule"));
+        }
+
+        if zone.rule.len() != self.num_colours() {
+            return Err(format!(
+                "zone rule {} must have {} steps, as the rule does",
+                zone.rule_string(),
+                self.num_colours()
+            ));
+        }
+
+        self.zones.push(zone);
+        Ok(())
+    }
+
+    // Change the rule, carrying on from the current grid rather than
+    // starting afresh. Steps in both rules keep their colours and new steps
+    // get random ones. Cells in states past the end of a shorter rule wrap
+    // round, as though counting on through its cycle. Only plain rules can
+    // be changed, and only to rules with as many steps as any zones.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), String> {
+        if self.turmite.is_some()
+            || self.weighted.is_some()
+            || self.engine.is_some()
+            || WeightedRule::is_weighted(rule)
+        {
+            return Err(String::from("only plain rules can be changed"));
+        }
+
+        let rule = Rule::parse(rule).map_err(|e| e.to_string())?;
+        let len = rule.len();
+
+        if !self.zones.is_empty() && len != self.num_colours() {
+            return Err(format!(
+                "the rule must have {} steps, as the zones do",
+                self.num_colours()
+            ));
+        }
+
+        if let GridBackend::Packed(ref grid) = self.grid {
+            if len > PackedGrid::max_colours(grid.bits()) {
+                return Err(format!(
+                    "{} bit cells can't hold {} colours",
+                    grid.bits(),
+                    len
+                ));
+            }
+        }
+
+        let mut rng = SeededRng::new(self.seed ^ len as u64, COLOURS_STREAM);
+        self.colours.resize(&mut rng, len);
+
+        for ant in self.ants.iter_mut() {
+            ant.rule = rule.clone();
+        }
+
+        let mut wrapped = Vec::new();
+        self.grid.for_each_visited(|x, y, clr_idx| {
+            if clr_idx != OBSTACLE && clr_idx >= len {
+                wrapped.push((x, y, clr_idx % len));
+            }
+        });
+
+        for (x, y, clr_idx) in wrapped {
+            self.paint(x, y, clr_idx);
+        }
+
+        Ok(())
+    }
+
+    // The rule zones, in the order they were added.
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+
+    // Replace the ants, e.g. when restoring a saved state. There must be at
+    // least one ant.
+    pub(crate) fn set_ants(&mut self, ants: Vec<Ant>) {
+        assert!(!ants.is_empty());
+        self.ants = ants;
+    }
+
+    // Direct access to the ants, e.g. to replay their logged moves.
+    pub(crate) fn ants_mut(&mut self) -> &mut Vec<Ant> {
+        &mut self.ants
+    }
+
+    // A simulation with the same rule, colours and settings but the given
+    // grid and ants in place of these, e.g. to cut a pattern out of a run.
+    // Zones are left out, as they belong to this grid.
+    pub(crate) fn with_cells(&self, grid: GridBackend, ants: Vec<Ant>) -> Simulation {
+        assert!(!ants.is_empty());
+
+        Simulation {
+            ants,
+            grid,
+            colours: self.colours.clone(),
+            turmite: self.turmite.clone(),
+            weighted: self.weighted.clone(),
+            engine: self.engine.clone(),
+            zones: Vec::new(),
+            seed: self.seed,
+            boundary: self.boundary,
+            collision: self.collision,
+            obstacles: self.obstacles,
+            track_dirty: false,
+            dirty: Vec::new(),
+            track_changes: false,
+            changes: Vec::new(),
+            visits: None,
+            rng: None,
+        }
+    }
+
+    // Set a cell to the given colour index, BLANK to clear it or OBSTACLE,
+    // e.g. to seed the grid with an initial pattern. Cells outside a bounded
+    // grid and invalid colour indexes are ignored.
+    pub fn paint(&mut self, x: i64, y: i64, clr_idx: usize) {
+        let valid = clr_idx == BLANK || clr_idx == OBSTACLE || clr_idx < self.num_colours();
+
+        if !self.grid.contains(x, y) || !valid {
+            return;
+        }
+
+        let old = self.grid.get(x, y);
+        self.grid.set(x, y, clr_idx);
+
+        if self.track_dirty {
+            self.dirty.push((x, y));
+        }
+
+        if self.track_changes {
+            self.changes.push(Change::Paint {
+                x,
+                y,
+                old,
+                new: clr_idx,
+            });
+        }
+    }
+
+    // Enable or disable recording of which cells change, for renderers that
+    // only redraw changed cells. Off by default.
+    pub fn set_track_dirty(&mut self, track: bool) {
+        self.track_dirty = track;
+        self.dirty.clear();
+    }
+
+    // Take the list of cells changed since the last call, as (x, y) pairs.
+    pub fn take_dirty(&mut self) -> Vec<(i64, i64)> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    // Take the regions of an unbounded grid changed since the last call,
+    // chunk by chunk, so renderers can redraw whole chunks rather than
+    // long lists of cells. None for bounded grids, which aren't chunked.
+    pub fn take_dirty_chunks(&mut self) -> Option<Vec<Extent>> {
+        match &mut self.grid {
+            GridBackend::Sparse(g) => Some(g.take_dirty_chunks()),
+            _ => None,
+        }
+    }
+
+    // Take the cells changed since the last call, along with the ants, as a
+    // delta for bringing a copy of the simulation up to date. Changed cells
+    // must be tracked with set_track_dirty.
+    pub fn take_delta(&mut self) -> Delta {
+        let mut dirty = self.take_dirty();
+        dirty.sort_unstable();
+        dirty.dedup();
+
+        Delta {
+            cells: dirty
+                .into_iter()
+                .map(|(x, y)| (x, y, self.grid.get(x, y)))
+                .collect(),
+            ants: self.ants.clone(),
+        }
+    }
+
+    // Bring a copy of a simulation up to date with a delta taken from the
+    // original. The changed cells are tracked as usual, and if visits are
+    // being recorded each changed cell counts as a single visit, as the
+    // delta doesn't say how many times it was visited.
+    pub fn apply_delta(&mut self, delta: Delta) {
+        if !delta.ants.is_empty() {
+            self.ants = delta.ants;
+        }
+
+        let iterations = self.iterations();
+
+        for (x, y, clr_idx) in delta.cells {
+            if !self.grid.contains(x, y) {
+                continue;
+            }
+
+            self.grid.set(x, y, clr_idx);
+
+            if self.track_dirty {
+                self.dirty.push((x, y));
+            }
+
+            if let Some(ref mut visits) = self.visits {
+                visits.record(x, y, iterations);
+            }
+        }
+    }
+
+    // Enable or disable recording of every move and edit, for move logs.
+    // Off by default.
+    pub fn set_track_changes(&mut self, track: bool) {
+        self.track_changes = track;
+        self.changes.clear();
+    }
+
+    // Take the list of changes made since the last call, in order.
+    pub fn take_changes(&mut self) -> Vec<Change> {
+        std::mem::take(&mut self.changes)
+    }
+
+    // Enable or disable recording of the ants' visits to each cell, for
+    // display modes showing their paths. Off by default. Enabling it when
+    // already enabled keeps the visits recorded so far.
+    pub fn set_track_visits(&mut self, track: bool) {
+        match (track, self.visits.is_some()) {
+            (true, false) => self.visits = Some(Visits::default()),
+            (false, _) => self.visits = None,
+            _ => {}
+        }
+    }
+
+    // The visits recorded, if enabled.
+    pub fn visits(&self) -> Option<&Visits> {
+        self.visits.as_ref()
+    }
+
+    // Access the grid cells, each holding a colour index, OBSTACLE, or BLANK
+    // if the cell has never been visited.
+    pub fn cells(&self) -> &GridBackend {
+        &self.grid
+    }
+
+    // Switch to a sparse unbounded grid so the ant can roam an effectively
+    // infinite plane and never stalls at a boundary. Cells already visited
+    // are kept.
+    pub fn make_unbounded(&mut self) {
+        self.grid = GridBackend::Sparse(self.grid.to_sparse());
+    }
+
+    // Switch to a grid packing each cell into 4 or 8 bits, to save memory
+    // on big grids. Fails for unbounded grids and rules with more colours
+    // than the cells can hold.
+    pub fn pack(&mut self, bits: u8) -> Result<(), String> {
+        if bits != 4 && bits != 8 {
+            return Err(format!(
+                "cells can be packed into 4 or 8 bits, not {}",
+                bits
+            ));
+        }
+
+        let max = PackedGrid::max_colours(bits);
+
+        if self.num_colours() > max {
+            return Err(format!(
+                "{} bit cells hold at most {} colours, the rule has {}",
+                bits,
+                max,
+                self.num_colours()
+            ));
+        }
+
+        self.grid = GridBackend::Packed(self.grid.to_packed(bits)?);
+        Ok(())
+    }
+
+    // Set what happens when the ant reaches the edge of a bounded grid.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    // The boundary mode in use.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    // Set what happens when an ant tries to move onto an obstacle.
+    pub fn set_obstacles(&mut self, obstacles: ObstaclePolicy) {
+        self.obstacles = obstacles;
+    }
+
+    // The obstacle policy in use.
+    pub fn obstacles(&self) -> ObstaclePolicy {
+        self.obstacles
+    }
+
+    // Set what happens when ants meet on the same cell.
+    pub fn set_collision(&mut self, collision: CollisionPolicy) {
+        self.collision = collision;
+    }
+
+    // The collision policy in use.
+    pub fn collision(&self) -> CollisionPolicy {
+        self.collision
+    }
+
+    // Replace the cell colours, e.g. with colours from a palette. There must
+    // be one colour per colour index.
+    pub fn set_colours(&mut self, colours: Vec<Colour>) {
+        assert_eq!(colours.len(), self.num_colours());
+        self.colours = ColourMap::new(colours);
+    }
+
+    // The number of distinct cell colours.
+    pub fn num_colours(&self) -> usize {
+        self.colours.len()
+    }
+
+    // The seed used to generate the colours.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Access the turmite transition table, if this is a turmite simulation.
+    pub fn turmite(&self) -> Option<&Turmite> {
+        self.turmite.as_ref()
+    }
+
+    // The plain rule all the ants follow, if they follow the same one and
+    // the moves aren't made by a turmite, a probabilistic rule, rule zones
+    // or a rule engine, so they can be made elsewhere, e.g. on the GPU.
+    pub fn shared_rule(&self) -> Option<&Rule> {
+        let rule = &self.ants[0].rule;
+        let plain = self.turmite.is_none()
+            && self.weighted.is_none()
+            && self.engine.is_none()
+            && self.zones.is_empty();
+
+        Some(rule).filter(|_| plain && self.ants.iter().all(|a| a.rule == *rule))
+    }
+
+    // Access the first ant.
+    pub fn ant(&self) -> &Ant {
+        &self.ants[0]
+    }
+
+    // Access all the ants.
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    // The rule as a string of direction characters, or a description of the
+    // turmite's table size for turmite simulations, or the name of the custom
+    // rule engine making the moves.
+    pub fn rule(&self) -> String {
+        if let Some(ref engine) = self.engine {
+            return engine.name();
+        }
+
+        match self.turmite {
+            Some(ref t) => format!(
+                "turmite ({} states, {} colours)",
+                t.num_states(),
+                t.num_colours()
+            ),
+            None => match self.weighted {
+                Some(ref weighted) => weighted.to_string(),
+                None => self.ants[0].rule.to_string(),
+            },
+        }
+    }
+
+    // The colours associated with each step of the rule.
+    pub fn colours(&self) -> &[Colour] {
+        self.colours.colours()
+    }
+
+    // The map from colour index to colour the cells are drawn with.
+    pub fn colour_map(&self) -> &ColourMap {
+        &self.colours
+    }
+
+    // The grid size as a number of squares along each side. For unbounded
+    // grids this is the size of the region initially shown.
+    pub fn size(&self) -> usize {
+        self.grid.size()
+    }
+
+    // The number of steps made so far, i.e. the moves made by the ant that
+    // has been running longest without stalling.
+    pub fn iterations(&self) -> u64 {
+        self.ants.iter().map(|a| a.iterations).max().unwrap_or(0)
+    }
+
+    // Have all the ants stalled by hitting the grid boundary?
+    pub fn is_stalled(&self) -> bool {
+        self.ants.iter().all(|a| a.stalled)
+    }
+}
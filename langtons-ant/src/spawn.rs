@@ -0,0 +1,175 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{Facing, FACINGS};
+use crate::grid::{Extent, GridStorage, OBSTACLE};
+use crate::random::{SeededRng, SPAWN_STREAM};
+use crate::simulation::Simulation;
+use rand::Rng;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Random cells tried for an ant before giving up on finding one that isn't
+// an obstacle.
+const RANDOM_ATTEMPTS: u32 = 10_000;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Where an ant starts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpawnPosition {
+    Cell(i64, i64),
+    // A random cell of the grid's initial region that isn't an obstacle.
+    Random,
+}
+
+impl SpawnPosition {
+    // Parse a start position, either X,Y, e.g. "10,20", or "random".
+    pub fn parse(s: &str) -> Result<SpawnPosition, String> {
+        let err = || format!("invalid start, expected X,Y or random: {}", s);
+
+        if s.trim().eq_ignore_ascii_case("random") {
+            return Ok(SpawnPosition::Random);
+        }
+
+        match s.split(',').map(str::trim).collect::<Vec<&str>>()[..] {
+            [x, y] => Ok(SpawnPosition::Cell(
+                x.parse().map_err(|_| err())?,
+                y.parse().map_err(|_| err())?,
+            )),
+            _ => Err(err()),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Which way an ant starts facing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpawnFacing {
+    Fixed(Facing),
+    Random,
+}
+
+impl SpawnFacing {
+    // Parse a start facing, either N, E, S or W, or "random".
+    pub fn parse(s: &str) -> Result<SpawnFacing, String> {
+        let s = s.trim();
+
+        if s.eq_ignore_ascii_case("random") {
+            return Ok(SpawnFacing::Random);
+        }
+
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Facing::from_char(c.to_ascii_uppercase())
+                .map(SpawnFacing::Fixed)
+                .ok_or_else(|| format!("invalid facing, expected N, E, S, W or random: {}", s)),
+            _ => Err(format!(
+                "invalid facing, expected N, E, S, W or random: {}",
+                s
+            )),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Where the ants start and which way they face, in place of the first ant
+// starting in the centre of the grid facing north. The first position and
+// facing are the first ant's, and each position after the first adds
+// another ant. Ants without a facing of their own take the last one given,
+// and with no positions only the first ant's facing changes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spawn {
+    pub positions: Vec<SpawnPosition>,
+    pub facings: Vec<SpawnFacing>,
+}
+
+impl Spawn {
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty() && self.facings.is_empty()
+    }
+
+    // Place the ants, adding any more needed. Random positions and facings
+    // are generated from the simulation's seed so they are reproducible.
+    // Fails if a position is off the grid or an obstacle.
+    pub fn apply(&self, sim: &mut Simulation) -> Result<(), String> {
+        let mut rng = SeededRng::new(sim.seed(), SPAWN_STREAM);
+
+        for i in 0..self.positions.len().max(1) {
+            let facing = match self.facings.get(i).or(self.facings.last()) {
+                Some(SpawnFacing::Fixed(facing)) => *facing,
+                Some(SpawnFacing::Random) => {
+                    FACINGS[rng.gen_range(0..FACINGS.len() as u32) as usize]
+                }
+                None => sim.ant().facing,
+            };
+
+            let (x, y) = match self.positions.get(i) {
+                Some(SpawnPosition::Cell(x, y)) => (*x, *y),
+                Some(SpawnPosition::Random) => random_cell(sim, &mut rng)?,
+                None => (sim.ant().pos_x, sim.ant().pos_y),
+            };
+
+            if !sim.cells().contains(x, y) || sim.cells().get(x, y) == OBSTACLE {
+                return Err(format!(
+                    "ant start {}, {} must be on the grid and not an obstacle",
+                    x, y
+                ));
+            }
+
+            if i == 0 {
+                sim.place_ant(0, x, y, facing);
+            } else {
+                sim.add_ant(x, y, facing);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A random cell of the grid's initial region that isn't an obstacle.
+fn random_cell<R: Rng>(sim: &Simulation, rng: &mut R) -> Result<(i64, i64), String> {
+    let size = sim.size() as i64;
+    let region = Extent::centred(sim.size(), sim.size());
+
+    for _ in 0..RANDOM_ATTEMPTS {
+        let x = region.x + rng.gen_range(0..size);
+        let y = region.y + rng.gen_range(0..size);
+
+        if sim.cells().get(x, y) != OBSTACLE {
+            return Ok((x, y));
+        }
+    }
+
+    Err("no free cell found for a random start".to_string())
+}
@@ -0,0 +1,300 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::entropy;
+use crate::grid::{Extent, OBSTACLE};
+use crate::interval::Interval;
+use crate::record::Recorder;
+use crate::simulation::Simulation;
+use crate::symmetry::Symmetry;
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Statistics describing the pattern built so far: how many cells hold each
+// colour, the bounding box of the visited cells and how far the first ant
+// has moved from its start, the centre of the initial grid, the pattern's
+// symmetries, and how disordered it is as its block entropy in bits per cell
+// and its zlib compression ratio.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub iterations: u64,
+    pub counts: Vec<u64>,
+    pub bounds: Option<Extent>,
+    pub displacement: (i64, i64),
+    pub symmetry: Symmetry,
+    pub entropy: f64,
+    pub compression: f64,
+}
+
+impl Stats {
+    // Measure the simulation's current state, scanning the whole grid.
+    pub fn measure(sim: &Simulation) -> Stats {
+        let mut counts = vec![0; sim.num_colours()];
+        let mut bounds: Option<(i64, i64, i64, i64)> = None;
+
+        sim.cells().for_each_visited(|x, y, clr_idx| {
+            // Obstacles aren't part of the pattern the ants build.
+            if clr_idx == OBSTACLE {
+                return;
+            }
+
+            if let Some(count) = counts.get_mut(clr_idx) {
+                *count += 1;
+            }
+
+            bounds = Some(match bounds {
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                None => (x, y, x, y),
+            });
+        });
+
+        let ant = sim.ant();
+
+        let bounds = bounds.map(|(x0, y0, x1, y1)| Extent {
+            x: x0,
+            y: y0,
+            width: (x1 - x0 + 1) as usize,
+            height: (y1 - y0 + 1) as usize,
+        });
+
+        Stats {
+            iterations: sim.iterations(),
+            counts,
+            bounds,
+            displacement: (ant.pos_x, ant.pos_y),
+            symmetry: Symmetry::within(sim.cells(), bounds),
+            entropy: entropy::block_entropy(sim.cells(), bounds),
+            compression: entropy::compression_ratio(sim.cells(), bounds),
+        }
+    }
+
+    // Number of cells visited, i.e. holding any colour.
+    pub fn visited(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    // Straight line distance of the first ant from its start.
+    pub fn distance(&self) -> f64 {
+        let (dx, dy) = self.displacement;
+        (dx as f64).hypot(dy as f64)
+    }
+
+    // The statistics as a JSON object, with a null bounding box when no cell
+    // has been visited.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iteration": self.iterations,
+            "bounds": self.bounds.map(|b| json!({
+                "x": b.x,
+                "y": b.y,
+                "width": b.width,
+                "height": b.height,
+            })),
+            "dx": self.displacement.0,
+            "dy": self.displacement.1,
+            "distance": self.distance(),
+            "symmetry": self.symmetry.to_string(),
+            "entropy": self.entropy,
+            "compression": self.compression,
+            "visited": self.visited(),
+            "counts": self.counts,
+        })
+    }
+
+    // The CSV header line for statistics of a rule with the given number
+    // of colours.
+    pub fn csv_header(num_colours: usize) -> String {
+        let mut header = String::from(
+            "iteration,min_x,min_y,width,height,dx,dy,distance,symmetry,entropy,compression",
+        );
+
+        for i in 0..num_colours {
+            header.push_str(&format!(",colour_{}", i));
+        }
+
+        header
+    }
+
+    // The statistics as a CSV line, with an empty bounding box when no
+    // cell has been visited.
+    pub fn csv_row(&self) -> String {
+        let bounds = match self.bounds {
+            Some(b) => format!("{},{},{},{}", b.x, b.y, b.width, b.height),
+            None => String::from(",,0,0"),
+        };
+
+        let mut row = format!(
+            "{},{},{},{},{:.3},{},{:.3},{:.3}",
+            self.iterations,
+            bounds,
+            self.displacement.0,
+            self.displacement.1,
+            self.distance(),
+            self.symmetry,
+            self.entropy,
+            self.compression
+        );
+
+        for count in &self.counts {
+            row.push_str(&format!(",{}", count));
+        }
+
+        row
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A sample of how far the first ant has moved from its start and the area
+// of the pattern's bounding box, at an iteration.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub iteration: u64,
+    pub distance: f64,
+    pub area: u64,
+}
+
+impl Sample {
+    pub fn from_stats(stats: &Stats) -> Sample {
+        Sample {
+            iteration: stats.iterations,
+            distance: stats.distance(),
+            area: stats.bounds.map_or(0, |b| b.width as u64 * b.height as u64),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Samples of a run's statistics for plotting against the iteration, holding
+// at most a given number. When full every other sample is dropped and only
+// every other sample recorded from then on, so however long the run the
+// samples cover all of it, evenly but ever more sparsely.
+#[derive(Clone, Debug)]
+pub struct StatsHistory {
+    capacity: usize,
+    samples: Vec<Sample>,
+    stride: u64,
+    offered: u64,
+}
+
+impl StatsHistory {
+    pub fn new(capacity: usize) -> StatsHistory {
+        StatsHistory {
+            capacity: capacity.max(2),
+            samples: Vec::new(),
+            stride: 1,
+            offered: 0,
+        }
+    }
+
+    // Record a sample of the statistics. Samples from later iterations than
+    // these, left by a simulation that was reset or rewound, are dropped.
+    pub fn record(&mut self, stats: &Stats) {
+        let sample = Sample::from_stats(stats);
+        self.samples.retain(|s| s.iteration < sample.iteration);
+
+        self.offered += 1;
+
+        if self.offered < self.stride {
+            return;
+        }
+
+        self.offered = 0;
+
+        if self.samples.len() == self.capacity {
+            let mut i = 0;
+
+            self.samples.retain(|_| {
+                i += 1;
+                i % 2 == 1
+            });
+
+            self.stride *= 2;
+        }
+
+        self.samples.push(sample);
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Writes the statistics every given number of iterations to a CSV file, for
+// plotting how a run develops.
+pub struct StatsRecorder {
+    path: PathBuf,
+    interval: Interval,
+    writer: Option<BufWriter<File>>,
+}
+
+impl StatsRecorder {
+    pub fn new<P: Into<PathBuf>>(path: P, every: u64) -> StatsRecorder {
+        StatsRecorder {
+            path: path.into(),
+            interval: Interval::new(every),
+            writer: None,
+        }
+    }
+}
+
+impl Recorder for StatsRecorder {
+    fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()> {
+        if !self.interval.due(sim) {
+            return Ok(());
+        }
+
+        // The file is created on the first capture, once the number of
+        // colour columns is known.
+        let writer = match self.writer {
+            Some(ref mut writer) => writer,
+            None => {
+                let mut writer = BufWriter::new(File::create(&self.path)?);
+                writeln!(writer, "{}", Stats::csv_header(sim.num_colours()))?;
+                self.writer.insert(writer)
+            }
+        };
+
+        writeln!(writer, "{}", Stats::measure(sim).csv_row())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.writer {
+            Some(ref mut writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
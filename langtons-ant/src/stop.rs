@@ -0,0 +1,247 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::interval::Interval;
+use crate::simulation::Simulation;
+use crate::stats::Stats;
+use std::fmt;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Comparison operators. Where two are found at the same place the longer is
+// used, so "<=" isn't read as "<".
+const COMPARISONS: &[(&str, Comparison)] = &[
+    ("<=", Comparison::AtMost),
+    (">=", Comparison::AtLeast),
+    ("==", Comparison::Equal),
+    ("!=", Comparison::NotEqual),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+    ("=", Comparison::Equal),
+];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A measure of the run that stop conditions can test, all taken from the
+// pattern statistics.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Metric {
+    // Number of moves made.
+    Iteration,
+    // The larger of the bounding box's width and height.
+    Bbox,
+    Width,
+    Height,
+    // Area of the bounding box.
+    Area,
+    // Number of cells holding a colour.
+    Visited,
+    // Straight line distance of the first ant from its start.
+    Distance,
+    Entropy,
+    Compression,
+}
+
+impl Metric {
+    // Every metric with its name in stop conditions.
+    pub const ALL: [(&'static str, Metric); 9] = [
+        ("iteration", Metric::Iteration),
+        ("bbox", Metric::Bbox),
+        ("width", Metric::Width),
+        ("height", Metric::Height),
+        ("area", Metric::Area),
+        ("visited", Metric::Visited),
+        ("distance", Metric::Distance),
+        ("entropy", Metric::Entropy),
+        ("compression", Metric::Compression),
+    ];
+
+    pub fn parse(name: &str) -> Result<Metric, String> {
+        Metric::ALL
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, metric)| metric)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Metric::ALL.iter().map(|(n, _)| *n).collect();
+                format!(
+                    "unknown measure {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    pub fn name(self) -> &'static str {
+        Metric::ALL
+            .iter()
+            .find(|&&(_, m)| m == self)
+            .map_or("", |(n, _)| n)
+    }
+
+    // The metric's value for the given statistics. The bounding box is
+    // empty until a cell has been visited.
+    pub fn value(self, stats: &Stats) -> f64 {
+        let (width, height) = stats
+            .bounds
+            .map_or((0, 0), |b| (b.width as u64, b.height as u64));
+
+        match self {
+            Metric::Iteration => stats.iterations as f64,
+            Metric::Bbox => width.max(height) as f64,
+            Metric::Width => width as f64,
+            Metric::Height => height as f64,
+            Metric::Area => (width * height) as f64,
+            Metric::Visited => stats.visited() as f64,
+            Metric::Distance => stats.distance(),
+            Metric::Entropy => stats.entropy,
+            Metric::Compression => stats.compression,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// How a stop condition compares a metric with its value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    AtMost,
+    Greater,
+    AtLeast,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::AtMost => "<=",
+            Comparison::Greater => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+        }
+    }
+
+    pub fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::AtMost => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::AtLeast => lhs >= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A condition for stopping a run, comparing a metric with a number, e.g.
+// "bbox>400" or "entropy < 1.5".
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StopCondition {
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+impl StopCondition {
+    // Parse a condition: a metric name, a comparison, one of < <= > >= ==
+    // != or =, and a number, which may use underscore digit separators.
+    pub fn parse(s: &str) -> Result<StopCondition, String> {
+        let (at, symbol, comparison) = COMPARISONS
+            .iter()
+            .filter_map(|&(symbol, comparison)| s.find(symbol).map(|at| (at, symbol, comparison)))
+            .min_by_key(|&(at, symbol, _)| (at, usize::MAX - symbol.len()))
+            .ok_or_else(|| format!("no comparison in stop condition {}", s))?;
+
+        let metric = Metric::parse(s[..at].trim())?;
+        let number = s[at + symbol.len()..].trim().replace('_', "");
+        let value = number
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("invalid number in stop condition {}", s))?;
+
+        Ok(StopCondition {
+            metric,
+            comparison,
+            value,
+        })
+    }
+
+    // Does the condition hold for the given statistics?
+    pub fn holds(&self, stats: &Stats) -> bool {
+        self.comparison.holds(self.metric.value(stats), self.value)
+    }
+}
+
+impl fmt::Display for StopCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.metric.name(),
+            self.comparison.symbol(),
+            self.value
+        )
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Checks a run against stop conditions every given number of iterations,
+// so runs stop at the same iteration every time. Measuring the pattern
+// scans the whole grid, so checking every move would slow runs down.
+#[derive(Clone, Debug)]
+pub struct StopWatch {
+    conditions: Vec<StopCondition>,
+    interval: Interval,
+}
+
+impl StopWatch {
+    pub fn new(conditions: Vec<StopCondition>, every: u64) -> StopWatch {
+        StopWatch {
+            conditions,
+            interval: Interval::new(every),
+        }
+    }
+
+    // Number of iterations until the next check is due.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    // Check the conditions if a check is due, returning the first that
+    // holds, if any.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<StopCondition> {
+        if self.conditions.is_empty() || !self.interval.due(sim) {
+            return None;
+        }
+
+        let stats = Stats::measure(sim);
+        self.conditions.iter().find(|c| c.holds(&stats)).copied()
+    }
+}
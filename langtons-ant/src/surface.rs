@@ -0,0 +1,450 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{rule_to_string, Direction};
+use crate::colour::{create_distinct_colours, Colour, WHITE};
+use crate::random::{SeededRng, COLOURS_STREAM};
+use crate::rule::Rule;
+use image::{Rgba, RgbaImage};
+use std::f64::consts::PI;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Largest cube face, along each side.
+pub const MAX_FACE_SIZE: usize = 1000;
+
+// Most steps in a surface rule, as each cell's state is stored in a byte
+// with 0 for blank.
+pub const MAX_SURFACE_STEPS: usize = 255;
+
+// Colour of the gaps around the faces in the unfolded net.
+const GAP: [u8; 4] = [128, 128, 128, 255];
+
+// Where each face is drawn in the unfolded net, a cross of tiles four wide
+// and three high.
+const NET: [Tile; 6] = [
+    Tile::new([1, 0], [0, 1, 0], [1, 0, 0], [0, 0, 1]),
+    Tile::new([0, 1], [-1, 0, 0], [0, 0, 1], [0, -1, 0]),
+    Tile::new([1, 1], [0, 0, 1], [1, 0, 0], [0, -1, 0]),
+    Tile::new([2, 1], [1, 0, 0], [0, 0, -1], [0, -1, 0]),
+    Tile::new([3, 1], [0, 0, -1], [-1, 0, 0], [0, -1, 0]),
+    Tile::new([1, 2], [0, -1, 0], [1, 0, 0], [0, 0, -1]),
+];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// How the closed surface is unwrapped into 2D: as the net of the cube
+// unfolded into a cross, or as a longitude and latitude map of the sphere
+// the cube is inflated into.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Projection {
+    #[default]
+    Net,
+    Equirectangular,
+}
+
+impl Projection {
+    pub fn next(self) -> Projection {
+        match self {
+            Projection::Net => Projection::Equirectangular,
+            Projection::Equirectangular => Projection::Net,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Projection::Net => "net",
+            Projection::Equirectangular => "equirectangular",
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A face's tile in the unfolded net: its column and row, the face's outward
+// normal, and the directions across and down the tile as seen from outside
+// the cube.
+struct Tile {
+    at: [usize; 2],
+    normal: [i32; 3],
+    across: [i32; 3],
+    down: [i32; 3],
+}
+
+impl Tile {
+    const fn new(at: [usize; 2], normal: [i32; 3], across: [i32; 3], down: [i32; 3]) -> Tile {
+        Tile {
+            at,
+            normal,
+            across,
+            down,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Langton's ant on a cube-sphere, the surface of a cube whose six faces are
+// each a square grid, joined at their edges so the ant walks a closed
+// surface without any boundary. Walking off the edge of a face takes the
+// ant onto the next face, bending its heading over the edge.
+//
+// Cells are found by their centres, in coordinates doubled so they're whole
+// numbers, with the cube spanning -size to size along each axis. The ant
+// starts in the middle of the front face, the one facing +z, heading up the
+// face along +y, so until it reaches an edge it draws the same pattern as
+// the 2D ant.
+#[derive(Clone, Debug)]
+pub struct CubeSphere {
+    size: usize,
+    // Each cell's state plus one, with 0 for a blank cell.
+    cells: Vec<u8>,
+    rule: Vec<Direction>,
+    colours: Vec<Colour>,
+    position: [i32; 3],
+    heading: [i32; 3],
+    normal: [i32; 3],
+    iterations: u64,
+    seed: u64,
+}
+
+impl CubeSphere {
+    // Create a cube-sphere with faces of the given size, giving each step
+    // of the rule a random colour from the seed. Only relative turns can be
+    // used, as compass facings have no meaning on a closed surface.
+    pub fn with_seed(rule: &str, size: usize, seed: u64) -> Result<CubeSphere, String> {
+        let rule = Rule::parse(rule).map_err(|e| e.to_string())?;
+        let rule = rule.directions().to_vec();
+
+        if rule.len() > MAX_SURFACE_STEPS {
+            return Err(format!(
+                "surface rules must have 1 - {} steps",
+                MAX_SURFACE_STEPS
+            ));
+        }
+
+        if rule.iter().any(|d| !d.is_relative()) {
+            return Err("surface rules can't use absolute facings".to_string());
+        }
+
+        if size == 0 || size > MAX_FACE_SIZE {
+            return Err(format!("face size must be 1 - {}: {}", MAX_FACE_SIZE, size));
+        }
+
+        let mut rng = SeededRng::new(seed, COLOURS_STREAM);
+        let colours = create_distinct_colours(&mut rng, rule.len());
+
+        let centre = 2 * (size / 2) as i32 + 1 - size as i32;
+
+        Ok(CubeSphere {
+            size,
+            cells: vec![0; 6 * size * size],
+            rule,
+            colours,
+            position: [centre, -centre, size as i32],
+            heading: [0, 1, 0],
+            normal: [0, 0, 1],
+            iterations: 0,
+            seed,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    pub fn set_colours(&mut self, colours: Vec<Colour>) {
+        self.colours = colours;
+    }
+
+    pub fn num_colours(&self) -> usize {
+        self.rule.len()
+    }
+
+    pub fn rule(&self) -> String {
+        rule_to_string(&self.rule)
+    }
+
+    // The centre of the ant's cell, in doubled coordinates.
+    pub fn position(&self) -> [i32; 3] {
+        self.position
+    }
+
+    pub fn heading(&self) -> [i32; 3] {
+        self.heading
+    }
+
+    // The outward normal of the face the ant is on.
+    pub fn normal(&self) -> [i32; 3] {
+        self.normal
+    }
+
+    // The state of the cell at the given column and row of a tile in the
+    // unfolded net, numbered across then down from the top, or None if
+    // it's blank.
+    pub fn get(&self, tile: usize, u: usize, v: usize) -> Option<usize> {
+        let tile = &NET[tile];
+        let position = self.tile_position(tile, u, v);
+
+        match self.cells[self.index(position, tile.normal)] {
+            0 => None,
+            state => Some(state as usize - 1),
+        }
+    }
+
+    // Number of cells that aren't blank.
+    pub fn visited(&self) -> usize {
+        self.cells.iter().filter(|&&c| c != 0).count()
+    }
+
+    // Turn according to the state of the ant's cell, advance that cell to
+    // its next state and move the ant one cell forward, over the edge onto
+    // the next face if need be.
+    pub fn step(&mut self) {
+        let index = self.index(self.position, self.normal);
+        let state = self.cells[index].saturating_sub(1) as usize;
+
+        let (heading, normal) = (self.heading, self.normal);
+        self.heading = match self.rule[state] {
+            Direction::L => cross(normal, heading),
+            Direction::R => cross(heading, normal),
+            Direction::U => scale(heading, -1),
+            Direction::N | Direction::Face(_) => heading,
+        };
+
+        self.cells[index] = ((state + 1) % self.rule.len()) as u8 + 1;
+
+        let ahead = add(self.position, scale(self.heading, 2));
+        let axis = major_axis(self.heading);
+
+        if ahead[axis].abs() < self.size as i32 {
+            self.position = ahead;
+        } else {
+            // Half a cell to the edge, then half a cell down the next face.
+            self.position = add(add(self.position, self.heading), scale(self.normal, -1));
+            let heading = self.heading;
+            self.heading = scale(self.normal, -1);
+            self.normal = heading;
+        }
+
+        self.iterations += 1;
+    }
+
+    // Make n moves.
+    pub fn advance(&mut self, n: u64) {
+        for _ in 0..n {
+            self.step();
+        }
+    }
+
+    // Render the surface with the given projection, one pixel per cell in
+    // the net.
+    pub fn render(&self, projection: Projection) -> RgbaImage {
+        match projection {
+            Projection::Net => self.render_net(),
+            Projection::Equirectangular => self.render_equirectangular(),
+        }
+    }
+
+    // Render the net of the cube unfolded into a cross, four faces wide and
+    // three high, with the front face in the middle.
+    pub fn render_net(&self) -> RgbaImage {
+        let size = self.size as u32;
+        let mut image = RgbaImage::from_pixel(4 * size, 3 * size, Rgba(GAP));
+
+        for tile in NET.iter() {
+            let [column, row] = tile.at;
+
+            for v in 0..self.size {
+                for u in 0..self.size {
+                    let position = self.tile_position(tile, u, v);
+                    image.put_pixel(
+                        (column * self.size + u) as u32,
+                        (row * self.size + v) as u32,
+                        self.colour_at(position, tile.normal),
+                    );
+                }
+            }
+        }
+
+        image
+    }
+
+    // Render the sphere the cube is inflated into as a map, longitude
+    // across and latitude down, four cells wide by two high for each cell
+    // along a face, with the front face in the middle and the +y face at
+    // the top.
+    pub fn render_equirectangular(&self) -> RgbaImage {
+        let size = self.size as u32;
+        let (width, height) = (4 * size, 2 * size);
+
+        RgbaImage::from_fn(width, height, |x, y| {
+            let longitude = (x as f64 + 0.5) / width as f64 * 2.0 * PI - PI;
+            let latitude = PI / 2.0 - (y as f64 + 0.5) / height as f64 * PI;
+            let direction = [
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+                latitude.cos() * longitude.cos(),
+            ];
+
+            let (position, normal) = self.cell_towards(direction);
+            self.colour_at(position, normal)
+        })
+    }
+
+    // Where the ant appears in the given projection, in the pixels of its
+    // rendered image.
+    pub fn ant_pixel(&self, projection: Projection) -> [f64; 2] {
+        match projection {
+            Projection::Net => {
+                let tile = NET.iter().find(|t| t.normal == self.normal).unwrap();
+                let [column, row] = tile.at;
+                let offset = |axis: [i32; 3]| {
+                    ((dot(self.position, axis) + self.size as i32 - 1) / 2) as f64 + 0.5
+                };
+
+                [
+                    (column * self.size) as f64 + offset(tile.across),
+                    (row * self.size) as f64 + offset(tile.down),
+                ]
+            }
+            Projection::Equirectangular => {
+                let [x, y, z] = self.position.map(f64::from);
+                let longitude = x.atan2(z);
+                let latitude = (y / (x * x + y * y + z * z).sqrt()).asin();
+                let size = self.size as f64;
+
+                [
+                    (longitude + PI) / (2.0 * PI) * 4.0 * size,
+                    (PI / 2.0 - latitude) / PI * 2.0 * size,
+                ]
+            }
+        }
+    }
+
+    // The centre of the cell at a column and row of a face's tile.
+    fn tile_position(&self, tile: &Tile, u: usize, v: usize) -> [i32; 3] {
+        let size = self.size as i32;
+
+        add(
+            scale(tile.normal, size),
+            add(
+                scale(tile.across, 2 * u as i32 + 1 - size),
+                scale(tile.down, 2 * v as i32 + 1 - size),
+            ),
+        )
+    }
+
+    // The cell, and the normal of its face, seen looking out from the
+    // centre of the cube in the given direction.
+    fn cell_towards(&self, direction: [f64; 3]) -> ([i32; 3], [i32; 3]) {
+        let size = self.size as i32;
+        let axis = (0..3)
+            .max_by(|&a, &b| direction[a].abs().total_cmp(&direction[b].abs()))
+            .unwrap();
+
+        let mut normal = [0; 3];
+        normal[axis] = direction[axis].signum() as i32;
+
+        let mut position = [0; 3];
+
+        for (i, p) in position.iter_mut().enumerate() {
+            *p = if i == axis {
+                normal[axis] * size
+            } else {
+                let t = direction[i] / direction[axis].abs() * size as f64;
+                let cell = ((t + size as f64) / 2.0)
+                    .floor()
+                    .clamp(0.0, size as f64 - 1.0);
+                2 * cell as i32 + 1 - size
+            };
+        }
+
+        (position, normal)
+    }
+
+    fn colour_at(&self, position: [i32; 3], normal: [i32; 3]) -> Rgba<u8> {
+        match self.cells[self.index(position, normal)] {
+            0 => Rgba(WHITE.to_rgba8()),
+            state => Rgba(self.colours[state as usize - 1].to_rgba8()),
+        }
+    }
+
+    // Index of a cell from its centre and the normal of its face, the faces
+    // ordered -x, +x, -y, +y, -z, +z, each stored row by row along its two
+    // other axes.
+    fn index(&self, position: [i32; 3], normal: [i32; 3]) -> usize {
+        let size = self.size as i32;
+        let axis = major_axis(normal);
+        let face = 2 * axis + usize::from(normal[axis] > 0);
+        let mut others = (0..3)
+            .filter(|&i| i != axis)
+            .map(|i| ((position[i] + size - 1) / 2) as usize);
+        let (u, v) = (others.next().unwrap(), others.next().unwrap());
+
+        (face * self.size + v) * self.size + u
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The axis a vector along an axis points along.
+fn major_axis(a: [i32; 3]) -> usize {
+    (0..3).max_by_key(|&i| a[i].abs()).unwrap()
+}
+
+fn add(a: [i32; 3], b: [i32; 3]) -> [i32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale(a: [i32; 3], s: i32) -> [i32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [i32; 3], b: [i32; 3]) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [i32; 3], b: [i32; 3]) -> [i32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
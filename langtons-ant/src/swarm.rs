@@ -0,0 +1,249 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{compute_ant_position, move_forward, Ant, Boundary, ObstaclePolicy, FACINGS};
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::rule::Rule;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// The fewest ants worth moving as a swarm. With fewer, gathering them into
+// arrays and back costs more than the batched passes save.
+pub const MIN_SWARM_ANTS: usize = 16;
+
+// Facing encoded as 0 = N, 1 = E, 2 = S, 3 = W, with the matching moves.
+const DX: [i64; 4] = [0, 1, 0, -1];
+const DY: [i64; 4] = [-1, 0, 1, 0];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Many ants following plain rules, held as a structure of arrays, one per
+// field, rather than an array of ants, and moved a pass at a time over all
+// of them: changing the colours of their cells, turning them and finding
+// the cells ahead, then moving them. The turning pass is table lookups and
+// arithmetic without branches, which the compiler can vectorise, and the
+// moving pass only drops to the scalar move for ants leaving the grid or
+// blocked by an obstacle. Each distinct rule's colour and turn tables are
+// stored once, one after another, with each ant's offset into them.
+struct Swarm {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    facings: Vec<u8>,
+    iterations: Vec<u64>,
+    stalled: Vec<bool>,
+    active: usize,
+    bases: Vec<usize>,
+    lens: Vec<usize>,
+    // The table entry for the colour under each ant on the current move,
+    // and the cell ahead once it has turned.
+    entries: Vec<usize>,
+    ahead_xs: Vec<i64>,
+    ahead_ys: Vec<i64>,
+    next: Vec<usize>,
+    turns: Vec<[u8; 4]>,
+}
+
+impl Swarm {
+    // Gather the ants into a swarm, building the tables for their rules,
+    // which mustn't be empty.
+    fn new(ants: &[Ant]) -> Swarm {
+        let mut rules: Vec<(&Rule, usize)> = Vec::new();
+        let mut next = Vec::new();
+        let mut turns = Vec::new();
+        let mut bases = Vec::with_capacity(ants.len());
+
+        for ant in ants {
+            let base = match rules.iter().find(|(rule, _)| **rule == ant.rule) {
+                Some(&(_, base)) => base,
+                None => {
+                    let base = next.len();
+
+                    for clr_idx in 0..ant.rule.len() {
+                        next.push(ant.rule.step(clr_idx, FACINGS[0]).0);
+                        turns.push(FACINGS.map(|f| ant.rule.step(clr_idx, f).1 as u8));
+                    }
+
+                    rules.push((&ant.rule, base));
+                    base
+                }
+            };
+
+            bases.push(base);
+        }
+
+        Swarm {
+            xs: ants.iter().map(|a| a.pos_x).collect(),
+            ys: ants.iter().map(|a| a.pos_y).collect(),
+            facings: ants.iter().map(|a| a.facing as u8).collect(),
+            iterations: ants.iter().map(|a| a.iterations).collect(),
+            stalled: ants.iter().map(|a| a.stalled).collect(),
+            active: ants.iter().filter(|a| !a.stalled).count(),
+            entries: bases.clone(),
+            bases,
+            lens: ants.iter().map(|a| a.rule.len()).collect(),
+            ahead_xs: vec![0; ants.len()],
+            ahead_ys: vec![0; ants.len()],
+            next,
+            turns,
+        }
+    }
+
+    // Write the ants' new positions, facings and counts back.
+    fn store(&self, ants: &mut [Ant]) {
+        for (i, ant) in ants.iter_mut().enumerate() {
+            ant.pos_x = self.xs[i];
+            ant.pos_y = self.ys[i];
+            ant.facing = FACINGS[self.facings[i] as usize];
+            ant.iterations = self.iterations[i];
+            ant.stalled = self.stalled[i];
+        }
+    }
+
+    // Move every ant once, as Simulation::step does for plain rules when
+    // collisions are ignored. No ant may be standing on an obstacle.
+    fn step<G: GridStorage>(
+        &mut self,
+        grid: &mut G,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        let n = self.xs.len();
+
+        // The cells are changed in turn, so ants sharing a cell see each
+        // other's changes just as they would moving one at a time. As no ant
+        // paints over an obstacle, and moves only read the grid to look for
+        // them, the later passes see the same grid moving in turn would.
+        for i in 0..n {
+            if self.stalled[i] {
+                continue;
+            }
+
+            let (x, y) = (self.xs[i], self.ys[i]);
+            let mut clr_idx = grid.get(x, y);
+
+            if clr_idx == BLANK {
+                clr_idx = 0;
+            }
+
+            let base = self.bases[i];
+            grid.set(x, y, self.next[base..base + self.lens[i]][clr_idx]);
+            self.entries[i] = base + clr_idx;
+        }
+
+        // Stalled ants keep their facing, and are left where they are below.
+        for i in 0..n {
+            let turned = self.turns[self.entries[i]][self.facings[i] as usize];
+            let facing = if self.stalled[i] {
+                self.facings[i]
+            } else {
+                turned
+            };
+
+            self.facings[i] = facing;
+            self.ahead_xs[i] = self.xs[i] + DX[facing as usize];
+            self.ahead_ys[i] = self.ys[i] + DY[facing as usize];
+        }
+
+        for i in 0..n {
+            if self.stalled[i] {
+                continue;
+            }
+
+            let (x, y) = (self.ahead_xs[i], self.ahead_ys[i]);
+
+            if grid.contains(x, y) && grid.get(x, y) != OBSTACLE {
+                self.xs[i] = x;
+                self.ys[i] = y;
+            } else {
+                let mut ant = Ant::new(self.xs[i], self.ys[i]);
+                ant.facing = FACINGS[self.facings[i] as usize];
+                move_forward(grid, &mut ant, boundary, obstacles);
+
+                self.xs[i] = ant.pos_x;
+                self.ys[i] = ant.pos_y;
+                self.facings[i] = ant.facing as u8;
+                self.stalled[i] = ant.stalled;
+            }
+
+            if u64::MAX == self.iterations[i] {
+                self.stalled[i] = true;
+            } else {
+                self.iterations[i] += 1;
+            }
+
+            if self.stalled[i] {
+                self.active -= 1;
+            }
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Advance many ants following plain rules up to n moves each, moving them
+// as a swarm, and return the number of moves made, which is less than n if
+// they all stall. The result is the same as stepping them one at a time with
+// collisions ignored. Ants standing on obstacles, painted under them since
+// they last moved, are first moved one at a time, painting over the
+// obstacles, as the swarm can't. Ants with empty rules aren't handled and
+// return 0.
+pub fn advance<G: GridStorage>(
+    ants: &mut [Ant],
+    grid: &mut G,
+    n: u64,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) -> u64 {
+    if n == 0 || ants.iter().any(|a| a.rule.is_empty()) {
+        return 0;
+    }
+
+    let mut done = 0;
+
+    if ants
+        .iter()
+        .any(|a| !a.stalled && grid.get(a.pos_x, a.pos_y) == OBSTACLE)
+    {
+        for ant in ants.iter_mut() {
+            compute_ant_position(ant, grid, boundary, obstacles);
+        }
+
+        done = 1;
+    }
+
+    let mut swarm = Swarm::new(ants);
+
+    while done < n && swarm.active > 0 {
+        swarm.step(grid, boundary, obstacles);
+        done += 1;
+    }
+
+    swarm.store(ants);
+    done
+}
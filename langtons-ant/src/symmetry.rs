@@ -0,0 +1,194 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::{Extent, GridBackend, GridStorage, BLANK, OBSTACLE};
+use crate::interval::Interval;
+use crate::simulation::Simulation;
+use crate::stats::Stats;
+use std::fmt;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The symmetries of a pattern about the centre of its bounding box: its
+// order of rotational symmetry and the lines it's a mirror image across.
+// Quarter turns and the diagonal mirrors need a square bounding box.
+// Obstacles aren't part of the pattern, so count as blank cells.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Symmetry {
+    // 1 for no rotational symmetry, 2 for half turns or 4 for quarter turns.
+    pub rotation: u8,
+    // Mirror images across the vertical and horizontal centre lines, i.e.
+    // swapping left and right, or top and bottom.
+    pub left_right: bool,
+    pub top_bottom: bool,
+    // Mirror images across the diagonal from the top left corner, and the
+    // one from the top right corner.
+    pub diagonal: bool,
+    pub anti_diagonal: bool,
+}
+
+impl Symmetry {
+    pub const NONE: Symmetry = Symmetry {
+        rotation: 1,
+        left_right: false,
+        top_bottom: false,
+        diagonal: false,
+        anti_diagonal: false,
+    };
+
+    // The symmetries of the cells within a pattern's bounding box, or none
+    // when there is no pattern.
+    pub fn within(cells: &GridBackend, bounds: Option<Extent>) -> Symmetry {
+        let b = match bounds {
+            Some(b) => b,
+            None => return Symmetry::NONE,
+        };
+
+        let (w, h) = (b.width as i64, b.height as i64);
+        let square = w == h;
+
+        // Whether every cell matches the one it's mapped to.
+        let maps_to = |f: &dyn Fn(i64, i64) -> (i64, i64)| {
+            (0..h).all(|dy| {
+                (0..w).all(|dx| {
+                    let (mx, my) = f(dx, dy);
+                    pattern_cell(cells, b.x + dx, b.y + dy)
+                        == pattern_cell(cells, b.x + mx, b.y + my)
+                })
+            })
+        };
+
+        let half_turn = maps_to(&|dx, dy| (w - 1 - dx, h - 1 - dy));
+        let quarter_turn = half_turn && square && maps_to(&|dx, dy| (w - 1 - dy, dx));
+
+        Symmetry {
+            rotation: if quarter_turn {
+                4
+            } else if half_turn {
+                2
+            } else {
+                1
+            },
+            left_right: maps_to(&|dx, dy| (w - 1 - dx, dy)),
+            top_bottom: maps_to(&|dx, dy| (dx, h - 1 - dy)),
+            diagonal: square && maps_to(&|dx, dy| (dy, dx)),
+            anti_diagonal: square && maps_to(&|dx, dy| (w - 1 - dy, w - 1 - dx)),
+        }
+    }
+
+    // Measure the symmetries of the simulation's current pattern, scanning
+    // the whole grid.
+    pub fn measure(sim: &Simulation) -> Symmetry {
+        Stats::measure(sim).symmetry
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        *self != Symmetry::NONE
+    }
+}
+
+impl fmt::Display for Symmetry {
+    // The symmetries separated by spaces, e.g. "4-fold left-right", or
+    // "none".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+
+        if self.rotation > 1 {
+            parts.push(format!("{}-fold", self.rotation));
+        }
+
+        let mirrors = [
+            (self.left_right, "left-right"),
+            (self.top_bottom, "top-bottom"),
+            (self.diagonal, "diagonal"),
+            (self.anti_diagonal, "anti-diagonal"),
+        ];
+
+        for (mirrored, name) in mirrors.iter() {
+            if *mirrored {
+                parts.push(name.to_string());
+            }
+        }
+
+        if parts.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Checks a run's pattern for symmetry every given number of iterations,
+// spotting when symmetry appears, changes or breaks.
+#[derive(Copy, Clone, Debug)]
+pub struct SymmetryWatch {
+    interval: Interval,
+    last: Symmetry,
+}
+
+impl SymmetryWatch {
+    pub fn new(every: u64) -> SymmetryWatch {
+        SymmetryWatch {
+            interval: Interval::new(every),
+            last: Symmetry::NONE,
+        }
+    }
+
+    // Number of iterations until the next check is due.
+    pub fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    // Check the pattern if a check is due, returning the symmetries before
+    // and after if they've changed since the last check.
+    pub fn observe(&mut self, sim: &Simulation) -> Option<(Symmetry, Symmetry)> {
+        if !self.interval.due(sim) {
+            return None;
+        }
+
+        let symmetry = Symmetry::measure(sim);
+
+        if symmetry == self.last {
+            return None;
+        }
+
+        let old = self.last;
+        self.last = symmetry;
+        Some((old, symmetry))
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The colour index of a cell as part of the pattern, obstacles being blank.
+fn pattern_cell(cells: &GridBackend, x: i64, y: i64) -> usize {
+    match cells.get(x, y) {
+        OBSTACLE => BLANK,
+        clr_idx => clr_idx,
+    }
+}
@@ -0,0 +1,220 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::colour::WHITE;
+use crate::export::render_region;
+use crate::grid::{Extent, GridStorage};
+use crate::interval::Interval;
+use crate::record::Recorder;
+use crate::simulation::Simulation;
+use image::{imageops, Rgba, RgbaImage};
+use std::io;
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Gap between the tiles of a contact sheet, in pixels.
+const SHEET_GAP: u32 = 8;
+
+// Colour of the gaps between tiles, setting off the tiles' white
+// backgrounds.
+const SHEET_GAP_COLOUR: [u8; 4] = [128, 128, 128, 255];
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// How the frames of a time-lapse are combined into one image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimelapseLayout {
+    // Every frame laid over the others with equal weight, so cells coloured
+    // early in the run and never changed are solid and those reached late
+    // are faint.
+    Blend,
+    // The frames side by side in rows, earliest first, as a contact sheet.
+    Sheet,
+}
+
+impl TimelapseLayout {
+    // Parse a layout from its name, e.g. "sheet".
+    pub fn parse(s: &str) -> Result<TimelapseLayout, String> {
+        match s.trim().to_lowercase().as_str() {
+            "blend" => Ok(TimelapseLayout::Blend),
+            "sheet" => Ok(TimelapseLayout::Sheet),
+            _ => Err(format!("unknown time-lapse layout: {}", s)),
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// Records the grid at a fixed number of evenly spaced iterations and
+// combines the snapshots into a single image once the run is over, so the
+// pattern's growth can be seen in one picture. The first snapshot is taken
+// after the first interval rather than of the starting grid. As for GIF
+// recordings, each snapshot is drawn in place on a canvas covering all of
+// them, so an unbounded grid's growth lines up.
+pub struct TimelapseRecorder {
+    path: PathBuf,
+    interval: Interval,
+    frames: usize,
+    layout: TimelapseLayout,
+    px_per_cell: u32,
+    snapshots: Vec<(Extent, RgbaImage)>,
+}
+
+impl TimelapseRecorder {
+    pub fn new<P: Into<PathBuf>>(
+        path: P,
+        every: u64,
+        frames: usize,
+        layout: TimelapseLayout,
+        px_per_cell: u32,
+    ) -> TimelapseRecorder {
+        let mut interval = Interval::new(every.max(1));
+        interval.skip_first();
+
+        TimelapseRecorder {
+            path: path.into(),
+            interval,
+            frames: frames.max(1),
+            layout,
+            px_per_cell: px_per_cell.max(1),
+            snapshots: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    // Combine the snapshots taken so far into one image, or None if there
+    // are none.
+    pub fn composite(&self) -> Option<RgbaImage> {
+        let bounds = self.snapshots.iter().map(|s| s.0).reduce(Extent::union)?;
+        let width = bounds.width as u32 * self.px_per_cell;
+        let height = bounds.height as u32 * self.px_per_cell;
+
+        let frames: Vec<RgbaImage> = self
+            .snapshots
+            .iter()
+            .map(|(extent, img)| {
+                let mut canvas = RgbaImage::from_pixel(width, height, Rgba(WHITE.to_rgba8()));
+                imageops::replace(
+                    &mut canvas,
+                    img,
+                    (extent.x - bounds.x) * i64::from(self.px_per_cell),
+                    (extent.y - bounds.y) * i64::from(self.px_per_cell),
+                );
+                canvas
+            })
+            .collect();
+
+        Some(match self.layout {
+            TimelapseLayout::Blend => blend(&frames, width, height),
+            TimelapseLayout::Sheet => sheet(&frames, width, height),
+        })
+    }
+}
+
+impl Recorder for TimelapseRecorder {
+    fn remaining(&self, sim: &Simulation) -> u64 {
+        if self.snapshots.len() >= self.frames {
+            u64::MAX
+        } else {
+            self.interval.remaining(sim)
+        }
+    }
+
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()> {
+        if self.snapshots.len() < self.frames && self.interval.due(sim) {
+            let extent = sim.cells().extent();
+            self.snapshots
+                .push((extent, render_region(sim, extent, self.px_per_cell)));
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.composite() {
+            Some(img) => img.save(&self.path).map_err(io::Error::other),
+            None => Ok(()),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The mean of each pixel over the frames, all of the given size.
+fn blend(frames: &[RgbaImage], width: u32, height: u32) -> RgbaImage {
+    let mut sums = vec![0u64; width as usize * height as usize * 4];
+
+    for frame in frames {
+        for (sum, &c) in sums.iter_mut().zip(frame.as_raw().iter()) {
+            *sum += u64::from(c);
+        }
+    }
+
+    let n = frames.len().max(1) as u64;
+    let pixels = sums.iter().map(|&sum| ((sum + n / 2) / n) as u8).collect();
+
+    RgbaImage::from_raw(width, height, pixels).unwrap()
+}
+
+//-----------------------------------------------------------------------------
+// The frames, all of the given size, tiled in rows across a sheet with as
+// many columns as rows, or one more.
+fn sheet(frames: &[RgbaImage], width: u32, height: u32) -> RgbaImage {
+    let columns = (frames.len() as f64).sqrt().ceil().max(1.0) as u32;
+    let rows = (frames.len() as u32).div_ceil(columns);
+
+    let mut sheet = RgbaImage::from_pixel(
+        columns * (width + SHEET_GAP) + SHEET_GAP,
+        rows * (height + SHEET_GAP) + SHEET_GAP,
+        Rgba(SHEET_GAP_COLOUR),
+    );
+
+    for (i, frame) in frames.iter().enumerate() {
+        let (column, row) = (i as u32 % columns, i as u32 / columns);
+        imageops::replace(
+            &mut sheet,
+            frame,
+            i64::from(SHEET_GAP + column * (width + SHEET_GAP)),
+            i64::from(SHEET_GAP + row * (height + SHEET_GAP)),
+        );
+    }
+
+    sheet
+}
@@ -0,0 +1,218 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Ant;
+use crate::grid::{GridBackend, GridStorage, BLANK};
+use crate::replay::Change;
+use crate::simulation::Simulation;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A copy of the grid and ants at a position in a timeline.
+struct Keyframe {
+    position: usize,
+    grid: GridBackend,
+    ants: Vec<Ant>,
+}
+
+impl Keyframe {
+    fn of(sim: &Simulation, position: usize) -> Keyframe {
+        Keyframe {
+            position,
+            grid: sim.cells().clone(),
+            ants: sim.ants().to_vec(),
+        }
+    }
+
+    // Put the simulation's cells and ants back as they were. Only the cells
+    // that differ are painted, so renderers and deltas see just those.
+    fn restore(&self, sim: &mut Simulation) {
+        let mut cells = Vec::new();
+
+        self.grid.for_each_visited(|x, y, c| {
+            if sim.cells().get(x, y) != c {
+                cells.push((x, y, c));
+            }
+        });
+
+        sim.cells().for_each_visited(|x, y, _| {
+            if self.grid.get(x, y) == BLANK {
+                cells.push((x, y, BLANK));
+            }
+        });
+
+        for (x, y, c) in cells {
+            sim.paint(x, y, c);
+        }
+
+        sim.set_ants(self.ants.clone());
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The recent history of a simulation as keyframes, copies of its grid and
+// ants taken every interval changes, and the changes between them, so the
+// run can be scrubbed backwards and forwards to any position rather than
+// only rewound a step at a time. Seeking goes from the current position or
+// from the nearest keyframe before the target, whichever is closer. Like
+// History the simulation must be tracking its changes. Positions count the
+// changes recorded since the timeline was created, and once more than
+// capacity changes are held the oldest keyframe and the changes after it
+// are dropped. Recording after seeking back discards the changes that were
+// ahead, as the run has taken a new course.
+pub struct Timeline {
+    keyframes: VecDeque<Keyframe>,
+    changes: VecDeque<Change>,
+    start: usize,
+    position: usize,
+    capacity: usize,
+    interval: usize,
+}
+
+impl Timeline {
+    // Create a timeline starting from the simulation's current state,
+    // holding around capacity changes with a keyframe every interval.
+    pub fn new(sim: &mut Simulation, capacity: usize, interval: usize) -> Timeline {
+        sim.take_changes();
+
+        let mut keyframes = VecDeque::new();
+        keyframes.push_back(Keyframe::of(sim, 0));
+
+        Timeline {
+            keyframes,
+            changes: VecDeque::new(),
+            start: 0,
+            position: 0,
+            capacity: capacity.max(1),
+            interval: interval.clamp(1, capacity.max(1)),
+        }
+    }
+
+    // The earliest position that can be reached.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    // The latest position that can be reached.
+    pub fn end(&self) -> usize {
+        self.start + self.changes.len()
+    }
+
+    // The position the simulation is at.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Number of changes held.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // Number of keyframes held.
+    pub fn num_keyframes(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    // Record the changes made to the simulation since the last call, taking
+    // a keyframe once interval changes have passed since the last one.
+    pub fn record(&mut self, sim: &mut Simulation) {
+        let changes = sim.take_changes();
+
+        if changes.is_empty() {
+            return;
+        }
+
+        if self.position < self.end() {
+            self.changes.truncate(self.position - self.start);
+            let position = self.position;
+            self.keyframes.retain(|k| k.position <= position);
+        }
+
+        self.position += changes.len();
+        self.changes.extend(changes);
+
+        let last = self.keyframes.back().map_or(self.start, |k| k.position);
+
+        if self.position - last >= self.interval {
+            self.keyframes.push_back(Keyframe::of(sim, self.position));
+        }
+
+        while self.changes.len() > self.capacity && self.keyframes.len() > 1 {
+            self.keyframes.pop_front();
+            let start = self.keyframes[0].position;
+            self.changes.drain(..start - self.start);
+            self.start = start;
+        }
+    }
+
+    // Move the simulation to a position, clamped to those held, returning
+    // the position reached.
+    pub fn seek(&mut self, sim: &mut Simulation, position: usize) -> usize {
+        self.record(sim);
+
+        let position = position.clamp(self.start, self.end());
+        let direct = position.abs_diff(self.position);
+
+        let keyframe = self
+            .keyframes
+            .iter()
+            .rev()
+            .find(|k| k.position <= position)
+            .filter(|k| position - k.position < direct);
+
+        if let Some(keyframe) = keyframe {
+            keyframe.restore(sim);
+            self.position = keyframe.position;
+        }
+
+        while self.position < position {
+            self.changes[self.position - self.start].apply(sim);
+            self.position += 1;
+        }
+
+        while self.position > position {
+            self.position -= 1;
+            self.changes[self.position - self.start].undo(sim);
+        }
+
+        // Seeking is itself tracked as changes, which mustn't be recorded.
+        sim.take_changes();
+        self.position
+    }
+
+    // Go back up to n changes, returning the number undone.
+    pub fn rewind(&mut self, sim: &mut Simulation, n: u64) -> u64 {
+        self.record(sim);
+
+        let before = self.position;
+        let back = before.saturating_sub(usize::try_from(n).unwrap_or(usize::MAX));
+        (before - self.seek(sim, back)) as u64
+    }
+}
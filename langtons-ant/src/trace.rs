@@ -0,0 +1,224 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Facing;
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use crate::interval::Interval;
+use crate::record::Recorder;
+use crate::simulation::Simulation;
+use serde_json::json;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Formats a trace can be written in, chosen by the file's extension.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TraceFormat {
+    // One line per step, with a header line.
+    Csv,
+    // An array with one object per step.
+    Json,
+}
+
+impl TraceFormat {
+    // The format for a file, JSON for a .json extension, otherwise CSV.
+    pub fn for_path(path: &Path) -> TraceFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => TraceFormat::Json,
+            _ => TraceFormat::Csv,
+        }
+    }
+}
+
+//-----------------------------------------------------------------------------
+// The first ant's state before a traced step, waiting for the step to be
+// made to find the cell's new colour.
+#[derive(Copy, Clone, Debug)]
+struct Pending {
+    iteration: u64,
+    x: i64,
+    y: i64,
+    facing: Facing,
+    before: usize,
+}
+
+//-----------------------------------------------------------------------------
+// Writes the first ant's trajectory to a CSV or JSON file: for every given
+// number of iterations, the iteration before the step, the ant's position
+// and facing, and the colour of the cell it left before and after the step.
+// Blank cells have no colour, written as an empty field or null, and
+// obstacles are written as "obstacle".
+pub struct TraceRecorder {
+    path: PathBuf,
+    format: TraceFormat,
+    interval: Interval,
+    writer: Option<BufWriter<File>>,
+    pending: Option<Pending>,
+    rows: u64,
+}
+
+impl TraceRecorder {
+    pub fn new<P: Into<PathBuf>>(path: P, every: u64) -> TraceRecorder {
+        let path = path.into();
+
+        TraceRecorder {
+            format: TraceFormat::for_path(&path),
+            path,
+            interval: Interval::new(every),
+            writer: None,
+            pending: None,
+            rows: 0,
+        }
+    }
+
+    // Number of steps written so far.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    // Write the step started by the pending state, now the cell's new
+    // colour is known.
+    fn write_row(&mut self, pending: Pending, after: usize) -> io::Result<()> {
+        let writer = match self.writer {
+            Some(ref mut writer) => writer,
+            None => {
+                let mut writer = BufWriter::new(File::create(&self.path)?);
+
+                match self.format {
+                    TraceFormat::Csv => {
+                        writeln!(writer, "iteration,x,y,facing,colour_before,colour_after")?
+                    }
+                    TraceFormat::Json => write!(writer, "[")?,
+                }
+
+                self.writer.insert(writer)
+            }
+        };
+
+        match self.format {
+            TraceFormat::Csv => writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                pending.iteration,
+                pending.x,
+                pending.y,
+                pending.facing.to_char(),
+                csv_colour(pending.before),
+                csv_colour(after)
+            )?,
+            TraceFormat::Json => {
+                let separator = if self.rows == 0 { "" } else { "," };
+                let row = json!({
+                    "iteration": pending.iteration,
+                    "x": pending.x,
+                    "y": pending.y,
+                    "facing": pending.facing.to_char().to_string(),
+                    "colour_before": json_colour(pending.before),
+                    "colour_after": json_colour(after),
+                });
+
+                write!(writer, "{}\n{}", separator, row)?;
+            }
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+impl Recorder for TraceRecorder {
+    // A traced step is captured again straight after it's made.
+    fn remaining(&self, sim: &Simulation) -> u64 {
+        match self.pending {
+            Some(pending) => (pending.iteration + 1).saturating_sub(sim.iterations()),
+            None => self.interval.remaining(sim),
+        }
+    }
+
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()> {
+        if let Some(pending) = self.pending.take() {
+            // A step rewound before it was written is dropped.
+            if sim.iterations() > pending.iteration {
+                self.write_row(pending, sim.cells().get(pending.x, pending.y))?;
+            }
+        }
+
+        if self.interval.due(sim) {
+            let ant = sim.ant();
+
+            self.pending = Some(Pending {
+                iteration: sim.iterations(),
+                x: ant.pos_x,
+                y: ant.pos_y,
+                facing: ant.facing,
+                before: sim.cells().get(ant.pos_x, ant.pos_y),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.writer {
+            Some(ref mut writer) => {
+                if self.format == TraceFormat::Json {
+                    writeln!(writer, "\n]")?;
+                }
+
+                writer.flush()
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A cell's colour index as a CSV field, empty for a blank cell.
+fn csv_colour(clr_idx: usize) -> String {
+    match clr_idx {
+        BLANK => String::new(),
+        OBSTACLE => String::from("obstacle"),
+        _ => clr_idx.to_string(),
+    }
+}
+
+//-----------------------------------------------------------------------------
+// A cell's colour index as a JSON value, null for a blank cell.
+fn json_colour(clr_idx: usize) -> serde_json::Value {
+    match clr_idx {
+        BLANK => serde_json::Value::Null,
+        OBSTACLE => json!("obstacle"),
+        _ => json!(clr_idx),
+    }
+}
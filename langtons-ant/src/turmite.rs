@@ -0,0 +1,190 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::{turn_and_move, Ant, Boundary, Direction, ObstaclePolicy};
+use crate::grid::{GridStorage, BLANK, OBSTACLE};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A single entry in a turmite's transition table. When the ant is in the
+// given state on a cell of the given colour it writes a new colour to the
+// cell, turns and changes to the next state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Transition {
+    pub state: usize,
+    pub colour: usize,
+    pub write: usize,
+    pub turn: Direction,
+    pub next: usize,
+}
+
+//-----------------------------------------------------------------------------
+// The on disk TOML layout of a turmite, a list of transitions, e.g.
+//
+// [[transitions]]
+// state = 0
+// colour = 0
+// write = 1
+// turn = "R"
+// next = 0
+#[derive(Deserialize)]
+struct TurmiteFile {
+    transitions: Vec<Transition>,
+}
+
+//-----------------------------------------------------------------------------
+// A turmite, i.e. a multi-state ant, defined by a full transition table
+// mapping (state, colour) to (write colour, turn, next state).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Turmite {
+    num_states: usize,
+    num_colours: usize,
+    table: Vec<Transition>,
+}
+
+impl Turmite {
+    // Create a turmite from a list of transitions, which must define every
+    // (state, colour) pair exactly once.
+    pub fn new(transitions: Vec<Transition>) -> Result<Turmite, String> {
+        if transitions.is_empty() {
+            return Err(String::from("turmite has no transitions"));
+        }
+
+        let num_states = transitions.iter().map(|t| t.state).max().unwrap_or(0) + 1;
+        let num_colours = transitions.iter().map(|t| t.colour).max().unwrap_or(0) + 1;
+
+        let mut table: Vec<Option<Transition>> = vec![None; num_states * num_colours];
+
+        for t in transitions {
+            if t.write >= num_colours {
+                return Err(format!("write colour {} out of range", t.write));
+            }
+
+            if t.next >= num_states {
+                return Err(format!("next state {} out of range", t.next));
+            }
+
+            if !t.turn.is_relative() {
+                return Err(format!("absolute turn {} isn't supported", t.turn));
+            }
+
+            let idx = t.state * num_colours + t.colour;
+
+            if table[idx].is_some() {
+                return Err(format!(
+                    "duplicate transition for state {}, colour {}",
+                    t.state, t.colour
+                ));
+            }
+
+            table[idx] = Some(t);
+        }
+
+        let mut full_table = Vec::with_capacity(table.len());
+
+        for (idx, t) in table.into_iter().enumerate() {
+            match t {
+                Some(t) => full_table.push(t),
+                None => {
+                    return Err(format!(
+                        "missing transition for state {}, colour {}",
+                        idx / num_colours,
+                        idx % num_colours
+                    ))
+                }
+            }
+        }
+
+        Ok(Turmite {
+            num_states,
+            num_colours,
+            table: full_table,
+        })
+    }
+
+    // Parse a turmite from TOML text.
+    pub fn from_toml(text: &str) -> Result<Turmite, String> {
+        let file: TurmiteFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        Turmite::new(file.transitions)
+    }
+
+    // Load a turmite from a TOML file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Turmite> {
+        let text = fs::read_to_string(path)?;
+        Turmite::from_toml(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // The number of internal states.
+    pub fn num_states(&self) -> usize {
+        self.num_states
+    }
+
+    // The number of cell colours.
+    pub fn num_colours(&self) -> usize {
+        self.num_colours
+    }
+
+    // Look up the transition for the given state and colour.
+    pub fn transition(&self, state: usize, colour: usize) -> &Transition {
+        &self.table[state * self.num_colours + colour]
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Compute new position of a turmite ant, writing the cell colour and
+// updating its state as given by the transition table.
+pub fn compute_turmite_position<G: GridStorage>(
+    ant: &mut Ant,
+    turmite: &Turmite,
+    grid: &mut G,
+    boundary: Boundary,
+    obstacles: ObstaclePolicy,
+) {
+    // Has ant stalled?
+    if ant.stalled {
+        return;
+    }
+
+    // Grab the current colour index for the ant's current position.
+    let mut cell_clr_idx = grid.get(ant.pos_x, ant.pos_y);
+
+    if BLANK == cell_clr_idx || OBSTACLE == cell_clr_idx {
+        cell_clr_idx = 0;
+    }
+
+    let t = turmite.transition(ant.state, cell_clr_idx);
+
+    grid.set(ant.pos_x, ant.pos_y, t.write);
+    ant.state = t.next;
+
+    turn_and_move(t.turn, grid, ant, boundary, obstacles);
+}
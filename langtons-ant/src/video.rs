@@ -0,0 +1,151 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::colour::WHITE;
+use crate::export::render_region;
+use crate::grid::GridStorage;
+use crate::interval::Interval;
+use crate::record::Recorder;
+use crate::simulation::Simulation;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Streams snapshots of the grid to ffmpeg as raw RGBA frames, which it
+// encodes to a video in whatever format the output file's extension asks
+// for, e.g. MP4 or WebM. Frames are written as they are captured, so long
+// runs don't have to be held in memory. The grid is scaled to fit the frame
+// size, keeping square cells, so an unbounded grid zooms out as it grows.
+pub struct VideoRecorder {
+    path: PathBuf,
+    interval: Interval,
+    width: u32,
+    height: u32,
+    ffmpeg: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl VideoRecorder {
+    // Start ffmpeg writing to the given path. Odd frame sizes are rounded
+    // down to even, as most video codecs require.
+    pub fn new<P: Into<PathBuf>>(
+        path: P,
+        every: u64,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> io::Result<VideoRecorder> {
+        let path = path.into();
+        let width = (width & !1).max(2);
+        let height = (height & !1).max(2);
+
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .arg("-s")
+            .arg(format!("{}x{}", width, height))
+            .arg("-r")
+            .arg(fps.max(1).to_string())
+            .args(["-i", "-", "-pix_fmt", "yuv420p"])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        let stdin = ffmpeg.stdin.take();
+
+        Ok(VideoRecorder {
+            path,
+            interval: Interval::new(every),
+            width,
+            height,
+            ffmpeg,
+            stdin,
+        })
+    }
+
+    // Render the grid scaled to fit the frame, centred on a white
+    // background.
+    fn render_frame(&self, sim: &Simulation) -> RgbaImage {
+        let extent = sim.cells().extent();
+        let scale = (f64::from(self.width) / extent.width as f64)
+            .min(f64::from(self.height) / extent.height as f64);
+        let w = ((extent.width as f64 * scale) as u32).clamp(1, self.width);
+        let h = ((extent.height as f64 * scale) as u32).clamp(1, self.height);
+
+        let grid = imageops::resize(&render_region(sim, extent, 1), w, h, FilterType::Nearest);
+
+        let mut frame = RgbaImage::from_pixel(self.width, self.height, Rgba(WHITE.to_rgba8()));
+        imageops::replace(
+            &mut frame,
+            &grid,
+            i64::from((self.width - w) / 2),
+            i64::from((self.height - h) / 2),
+        );
+
+        frame
+    }
+}
+
+impl Recorder for VideoRecorder {
+    fn remaining(&self, sim: &Simulation) -> u64 {
+        self.interval.remaining(sim)
+    }
+
+    fn capture(&mut self, sim: &Simulation) -> io::Result<()> {
+        if !self.interval.due(sim) {
+            return Ok(());
+        }
+
+        let frame = self.render_frame(sim);
+
+        match self.stdin.as_mut() {
+            Some(stdin) => stdin.write_all(frame.as_raw()),
+            None => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "ffmpeg is no longer running",
+            )),
+        }
+    }
+
+    // Close ffmpeg's input so it finishes encoding, and wait for it.
+    fn finish(&mut self) -> io::Result<()> {
+        drop(self.stdin.take());
+
+        let status = self.ffmpeg.wait()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("ffmpeg exited with {}", status)))
+        }
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
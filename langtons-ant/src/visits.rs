@@ -0,0 +1,77 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// What's known about the ants' visits to a single cell.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Visit {
+    // The simulation's iteration count when an ant last left the cell.
+    pub last: u64,
+    // The number of times an ant has left the cell.
+    pub count: u64,
+}
+
+//-----------------------------------------------------------------------------
+// A record of the cells the ants have visited, for display modes showing
+// the ants' paths rather than just the cells' colours. Only visited cells
+// are stored, so it works for both bounded and unbounded grids.
+#[derive(Clone, Default)]
+pub struct Visits {
+    cells: HashMap<(i64, i64), Visit>,
+    max_count: u64,
+}
+
+impl Visits {
+    // Note an ant leaving a cell at the given iteration.
+    pub fn record(&mut self, x: i64, y: i64, iteration: u64) {
+        let visit = self.cells.entry((x, y)).or_default();
+        visit.last = iteration;
+        visit.count += 1;
+        self.max_count = self.max_count.max(visit.count);
+    }
+
+    // The visits to a cell, or None if no ant has left it since recording
+    // started.
+    pub fn get(&self, x: i64, y: i64) -> Option<Visit> {
+        self.cells.get(&(x, y)).copied()
+    }
+
+    // The most times any one cell has been visited, e.g. to scale a
+    // heatmap.
+    pub fn max_count(&self) -> u64 {
+        self.max_count
+    }
+
+    // The number of cells visited.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
@@ -0,0 +1,98 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::config;
+use crate::export;
+use crate::simulation::Simulation;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, ImageData};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A simulation exported to JavaScript, for running in a browser page. The
+// page drives it, advancing it each animation frame and drawing the grid to
+// a canvas at one pixel per cell, to be scaled up with CSS. Iteration
+// counts are passed as f64 since JavaScript numbers can't hold a u64.
+#[wasm_bindgen]
+pub struct WebSimulation {
+    sim: Simulation,
+}
+
+#[wasm_bindgen]
+impl WebSimulation {
+    // Create a simulation of the given rule on a grid of the given size,
+    // failing with a message if either is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rule: &str, grid_size: u32, seed: u32) -> Result<WebSimulation, JsValue> {
+        let checked = config::validate_rule(rule)
+            .and_then(|rule| config::validate_grid_size(grid_size).map(|size| (rule, size)));
+
+        match checked {
+            Ok((rule, size)) => Ok(WebSimulation {
+                sim: Simulation::with_seed(&rule, size as usize, u64::from(seed)),
+            }),
+            Err(e) => Err(JsValue::from_str(&e.to_string())),
+        }
+    }
+
+    // Make up to n moves, stopping early if the ant stalls.
+    pub fn advance(&mut self, n: u32) {
+        self.sim.advance(u64::from(n));
+    }
+
+    // Switch to an unbounded grid so the ant never stalls.
+    pub fn make_unbounded(&mut self) {
+        self.sim.make_unbounded();
+    }
+
+    pub fn iterations(&self) -> f64 {
+        self.sim.iterations() as f64
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.sim.is_stalled()
+    }
+
+    pub fn rule(&self) -> String {
+        self.sim.rule()
+    }
+
+    // Draw the grid into the context's canvas, resizing the canvas to fit
+    // the grid, which grows as the ant roams an unbounded grid.
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        let image = export::render_image(&self.sim, 1);
+        let (width, height) = image.dimensions();
+
+        if let Some(canvas) = ctx.canvas() {
+            if canvas.width() != width || canvas.height() != height {
+                canvas.set_width(width);
+                canvas.set_height(height);
+            }
+        }
+
+        let data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&image), width, height)?;
+        ctx.put_image_data(&data, 0.0, 0.0)
+    }
+}
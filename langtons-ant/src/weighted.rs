@@ -0,0 +1,199 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::ant::Direction;
+use crate::rule::Rule;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+//-----------------------------------------------------------------------------
+// CONSTANTS
+//-----------------------------------------------------------------------------
+
+// Weights are scaled to thresholds for 32 bit random numbers.
+const WEIGHT_SCALE: f64 = 4_294_967_296.0;
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A probabilistic rule, where each colour maps to a choice of turns with
+// given weights rather than a single turn, for studying noisy ants. Rules
+// are written as a comma separated list of steps, each either a plain
+// direction or a slash separated list of directions and weights, e.g.
+// "L:0.9/R:0.1,R" turns left nine times in ten on the first colour and
+// always right on the second. Weights needn't add up to one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "WeightedRuleData", into = "WeightedRuleData")]
+pub struct WeightedRule {
+    steps: Vec<Vec<(Direction, f64)>>,
+    // For each step, the running totals of its weights scaled to 32 bit
+    // whole numbers, worked out once so that picking a turn needs no float
+    // arithmetic and so can't depend on how a platform rounds.
+    thresholds: Vec<Vec<u64>>,
+}
+
+// Serialised form of a weighted rule.
+#[derive(Clone, Serialize, Deserialize)]
+struct WeightedRuleData {
+    steps: Vec<Vec<(Direction, f64)>>,
+}
+
+impl WeightedRule {
+    // Is the rule written in the weighted form, rather than as a plain
+    // string of directions?
+    pub fn is_weighted(rule: &str) -> bool {
+        rule.contains(':')
+    }
+
+    // Parse a rule written in the weighted form.
+    pub fn parse(rule: &str) -> Result<WeightedRule, String> {
+        let steps = rule
+            .split(',')
+            .map(parse_step)
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(WeightedRule::from(WeightedRuleData { steps }))
+    }
+
+    // Number of steps, i.e. colours, in the rule.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    // The most likely turn for each step, e.g. to stand in for the rule
+    // where a single direction per step is needed.
+    pub fn most_likely(&self) -> Vec<Direction> {
+        self.steps
+            .iter()
+            .map(|choices| {
+                choices
+                    .iter()
+                    .fold(choices[0], |best, &c| if c.1 > best.1 { c } else { best })
+                    .0
+            })
+            .collect()
+    }
+
+    // Pick the turn for a cell of the given colour index at random,
+    // according to the weights.
+    pub fn choose<R: RngCore>(&self, clr_idx: usize, rng: &mut R) -> Direction {
+        let choices = &self.steps[clr_idx];
+        let pick = u64::from(rng.next_u32());
+        let chosen = self.thresholds[clr_idx]
+            .iter()
+            .position(|&threshold| pick < threshold)
+            .unwrap_or(choices.len() - 1);
+
+        choices[chosen].0
+    }
+}
+
+impl From<WeightedRuleData> for WeightedRule {
+    fn from(data: WeightedRuleData) -> WeightedRule {
+        let thresholds = data
+            .steps
+            .iter()
+            .map(|choices| {
+                let total: f64 = choices.iter().map(|c| c.1).sum();
+                let mut sum = 0.0;
+
+                choices
+                    .iter()
+                    .map(|c| {
+                        sum += c.1;
+                        (sum / total * WEIGHT_SCALE) as u64
+                    })
+                    .collect()
+            })
+            .collect();
+
+        WeightedRule {
+            steps: data.steps,
+            thresholds,
+        }
+    }
+}
+
+impl From<WeightedRule> for WeightedRuleData {
+    fn from(rule: WeightedRule) -> WeightedRuleData {
+        WeightedRuleData { steps: rule.steps }
+    }
+}
+
+impl fmt::Display for WeightedRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|choices| match choices.as_slice() {
+                [(direction, _)] => direction.to_string(),
+                _ => choices
+                    .iter()
+                    .map(|(d, w)| format!("{}:{}", d, w))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            })
+            .collect();
+
+        write!(f, "{}", steps.join(","))
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// Parse a single step, either a plain direction, e.g. "R", or weighted
+// choices, e.g. "L:0.9/R:0.1".
+fn parse_step(step: &str) -> Result<Vec<(Direction, f64)>, String> {
+    let step = step.trim();
+
+    if step.is_empty() {
+        return Err(String::from("empty step"));
+    }
+
+    step.split('/')
+        .map(|choice| {
+            let (direction, weight) = match choice.split_once(':') {
+                Some((d, w)) => (d.trim(), w.trim()),
+                None => (choice.trim(), "1"),
+            };
+
+            let direction = match Rule::parse(direction).as_ref().map(Rule::directions) {
+                Ok([d]) => *d,
+                _ => return Err(format!("invalid direction in step: {}", step)),
+            };
+
+            match weight.parse::<f64>() {
+                Ok(w) if w.is_finite() && w > 0.0 => Ok((direction, w)),
+                _ => Err(format!("invalid weight in step: {}", step)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,74 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use crate::grid::Extent;
+use crate::rule::Rule;
+use serde::{Deserialize, Serialize};
+
+//-----------------------------------------------------------------------------
+// ENUMS, STRUCTS AND IMPLS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// A rectangular region of the grid where a different rule applies, so an
+// ant crossing into it changes behaviour. The rule must have as many steps
+// as the simulation's own, so the cells keep the same colours either side
+// of the zone's edge.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub x: i64,
+    pub y: i64,
+    pub width: usize,
+    pub height: usize,
+    pub rule: Rule,
+}
+
+impl Zone {
+    // The cells covered by the zone.
+    pub fn extent(&self) -> Extent {
+        Extent {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // The zone's rule as a string of direction characters.
+    pub fn rule_string(&self) -> String {
+        self.rule.to_string()
+    }
+}
+
+//-----------------------------------------------------------------------------
+// FUNCTIONS
+//-----------------------------------------------------------------------------
+
+//-----------------------------------------------------------------------------
+// The rule applying at a cell, from the last of the zones covering it, or
+// None if no zone covers it.
+pub fn rule_at(zones: &[Zone], x: i64, y: i64) -> Option<&Rule> {
+    zones
+        .iter()
+        .rev()
+        .find(|zone| zone.extent().contains(x, y))
+        .map(|zone| &zone.rule)
+}
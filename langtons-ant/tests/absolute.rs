@@ -0,0 +1,91 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::ant::rule_to_string;
+use langtons_ant::config::validate_rule;
+use langtons_ant::rule::{Rule, RuleParseError};
+use langtons_ant::{Direction, Facing, GridStorage, Simulation, WeightedRule};
+
+#[test]
+fn absolute_facings_parse_and_print() {
+    let rule = Rule::parse("L R ^N ^").unwrap_err();
+    assert_eq!(rule, RuleParseError::MissingFacing { position: 8 });
+
+    let rule = Rule::parse("L R ^N ^e").unwrap();
+    assert_eq!(
+        rule.directions(),
+        vec![
+            Direction::L,
+            Direction::R,
+            Direction::Face(Facing::N),
+            Direction::Face(Facing::E)
+        ]
+    );
+    assert_eq!(rule_to_string(rule.directions()), "LR^N^E");
+
+    assert!(validate_rule("LR^S").is_ok());
+    assert!(validate_rule("LR^X").is_err());
+    assert!(validate_rule("^").is_err());
+}
+
+#[test]
+fn absolute_facings_ignore_the_current_facing() {
+    // Every cell turns the ant to face east, so it walks straight east
+    // whatever it was facing.
+    let mut sim = Simulation::with_seed("^E^E", 40, 1);
+    assert_eq!(sim.rule(), "^E^E");
+    assert_eq!(sim.num_colours(), 2);
+
+    for _ in 0..5 {
+        sim.step();
+    }
+
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (5, 0));
+    assert_eq!(sim.ant().facing, Facing::E);
+}
+
+#[test]
+fn the_fast_path_matches_stepping() {
+    let mut fast = Simulation::with_seed("L^NR^W", 80, 6);
+    let mut slow = Simulation::with_seed("L^NR^W", 80, 6);
+    slow.set_track_dirty(true);
+
+    fast.advance(20_000);
+    slow.advance(20_000);
+
+    assert_eq!(fast.iterations(), slow.iterations());
+    assert_eq!(
+        (fast.ant().pos_x, fast.ant().pos_y, fast.ant().facing),
+        (slow.ant().pos_x, slow.ant().pos_y, slow.ant().facing)
+    );
+
+    for y in 0..80 {
+        for x in 0..80 {
+            assert_eq!(fast.cells().get(x, y), slow.cells().get(x, y));
+        }
+    }
+}
+
+#[test]
+fn weighted_rules_can_use_absolute_facings() {
+    let rule = WeightedRule::parse("^N:0.5/L:0.5,R").unwrap();
+    assert_eq!(rule.to_string(), "^N:0.5/L:0.5,R");
+}
@@ -0,0 +1,132 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::autosave::{parse_duration, Autosave, AutosaveSpec};
+use langtons_ant::checkpoint::StateDigest;
+use langtons_ant::Simulation;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("langtons_ant_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn specs_are_parsed() {
+    assert_eq!(
+        AutosaveSpec::parse("every=60s dir=autosaves/"),
+        Ok(AutosaveSpec {
+            every: Duration::from_secs(60),
+            dir: PathBuf::from("autosaves/"),
+            keep: 3,
+        })
+    );
+    assert_eq!(
+        AutosaveSpec::parse("keep=5,every=2m"),
+        Ok(AutosaveSpec {
+            every: Duration::from_secs(120),
+            dir: PathBuf::from("autosaves"),
+            keep: 5,
+        })
+    );
+    assert_eq!(AutosaveSpec::parse(""), Ok(AutosaveSpec::default()));
+
+    assert!(AutosaveSpec::parse("every=soon").is_err());
+    assert!(AutosaveSpec::parse("keep=0").is_err());
+    assert!(AutosaveSpec::parse("dir=").is_err());
+    assert!(AutosaveSpec::parse("often").is_err());
+}
+
+#[test]
+fn durations_are_parsed() {
+    assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+    assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+    assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+    assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+
+    assert!(parse_duration("0s").is_err());
+    assert!(parse_duration("5d").is_err());
+    assert!(parse_duration("m").is_err());
+}
+
+#[test]
+fn saves_roll_over_the_oldest() {
+    let dir = temp_dir("autosave_roll");
+    let mut autosave = Autosave::new(AutosaveSpec {
+        every: Duration::from_secs(3600),
+        dir: dir.clone(),
+        keep: 2,
+    });
+
+    let mut sim = Simulation::with_seed("RLLR", 50, 1);
+    assert!(!autosave.is_due());
+    assert!(autosave.observe(&sim).is_none());
+
+    let mut paths = Vec::new();
+
+    for _ in 0..3 {
+        sim.advance(100);
+        paths.push(autosave.save(&sim).unwrap());
+    }
+
+    assert_eq!(paths[0], dir.join("autosave-1.json"));
+    assert_eq!(paths[1], dir.join("autosave-2.json"));
+    assert_eq!(paths[2], paths[0]);
+
+    let mut names: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["autosave-1.json", "autosave-2.json"]);
+
+    // The newest save is resumed exactly.
+    let resumed = Simulation::load(&paths[2]).unwrap();
+    assert_eq!(resumed.iterations(), 300);
+    assert_eq!(StateDigest::of(&resumed), StateDigest::of(&sim));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn due_saves_are_made() {
+    let dir = temp_dir("autosave_due");
+    let mut autosave = Autosave::new(AutosaveSpec {
+        every: Duration::from_millis(1),
+        dir: dir.clone(),
+        keep: 3,
+    });
+
+    std::thread::sleep(Duration::from_millis(5));
+
+    let sim = Simulation::with_seed("RL", 20, 2);
+    assert!(autosave.is_due());
+    assert_eq!(
+        autosave.observe(&sim),
+        Some(Ok(dir.join("autosave-1.json")))
+    );
+    assert!(!autosave.is_due());
+
+    let _ = fs::remove_dir_all(&dir);
+}
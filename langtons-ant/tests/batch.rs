@@ -0,0 +1,62 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{batch, GridStorage, Simulation};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//-----------------------------------------------------------------------------
+// Run a rule with the given seed, returning a digest of its final state.
+fn run(rule: &str, seed: u64) -> (u64, Vec<usize>) {
+    let mut sim = Simulation::with_seed(rule, 60, seed);
+    sim.advance(5000);
+
+    let cells = (-30..30).map(|x| sim.cells().get(x, 0)).collect();
+    (sim.iterations(), cells)
+}
+
+#[test]
+fn simulations_can_be_sent_between_threads() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Simulation>();
+}
+
+#[test]
+fn results_match_sequential_runs_in_order() {
+    let jobs: Vec<(&str, u64)> = ["RL", "LLRR", "LRRRRRLLR", "RLR"]
+        .iter()
+        .flat_map(|&rule| (0..4).map(move |seed| (rule, seed)))
+        .collect();
+    let completed = AtomicUsize::new(0);
+
+    let results = batch::run(
+        jobs.clone(),
+        4,
+        |(rule, seed)| run(rule, seed),
+        || {
+            completed.fetch_add(1, Ordering::Relaxed);
+        },
+    )
+    .unwrap();
+
+    let expected: Vec<_> = jobs.iter().map(|&(rule, seed)| run(rule, seed)).collect();
+    assert_eq!(results, expected);
+    assert_eq!(completed.load(Ordering::Relaxed), jobs.len());
+}
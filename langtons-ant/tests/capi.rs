@@ -0,0 +1,104 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(feature = "capi")]
+
+use langtons_ant::capi::*;
+use langtons_ant::{GridStorage, Simulation, BLANK};
+use std::ffi::CString;
+use std::ptr;
+
+#[test]
+fn a_simulation_runs_through_the_c_interface() {
+    let rule = CString::new("LLRR").unwrap();
+
+    unsafe {
+        let sim = langtons_ant_create(rule.as_ptr(), 60, 7);
+        assert!(!sim.is_null());
+        assert_eq!(langtons_ant_grid_size(sim), 60);
+
+        assert_eq!(langtons_ant_step(sim, 2000), 2000);
+        assert_eq!(langtons_ant_iterations(sim), 2000);
+
+        let mut plain = Simulation::with_seed("LLRR", 60, 7);
+        plain.advance(2000);
+
+        let (mut x, mut y) = (0, 0);
+        assert!(langtons_ant_ant_position(sim, &mut x, &mut y));
+        assert_eq!((x, y), (plain.ant().pos_x, plain.ant().pos_y));
+
+        for y in 0..60 {
+            for x in 0..60 {
+                let expected = match plain.cells().get(x, y) {
+                    BLANK => LANGTONS_ANT_BLANK,
+                    clr_idx => clr_idx as i32,
+                };
+                assert_eq!(langtons_ant_read_cell(sim, x, y), expected);
+            }
+        }
+
+        assert_eq!(langtons_ant_read_cell(sim, -1, 1000), LANGTONS_ANT_BLANK);
+        langtons_ant_destroy(sim);
+    }
+}
+
+#[test]
+fn invalid_settings_and_null_pointers_are_rejected() {
+    let bad = CString::new("RX").unwrap();
+    let good = CString::new("RL").unwrap();
+
+    unsafe {
+        assert!(langtons_ant_create(ptr::null(), 60, 1).is_null());
+        assert!(langtons_ant_create(bad.as_ptr(), 60, 1).is_null());
+        assert!(langtons_ant_create(good.as_ptr(), 0, 1).is_null());
+
+        assert_eq!(langtons_ant_step(ptr::null_mut(), 10), 0);
+        assert_eq!(langtons_ant_iterations(ptr::null()), 0);
+        assert_eq!(
+            langtons_ant_read_cell(ptr::null(), 0, 0),
+            LANGTONS_ANT_BLANK
+        );
+        assert!(!langtons_ant_ant_position(
+            ptr::null(),
+            ptr::null_mut(),
+            ptr::null_mut()
+        ));
+        langtons_ant_destroy(ptr::null_mut());
+    }
+}
+
+#[test]
+fn stalled_ants_report_no_position() {
+    let rule = CString::new("RL").unwrap();
+
+    unsafe {
+        let sim = langtons_ant_create(rule.as_ptr(), 20, 1);
+        let moves = langtons_ant_step(sim, 1_000_000);
+
+        assert!(moves < 1_000_000);
+        assert!(!langtons_ant_ant_position(
+            sim,
+            ptr::null_mut(),
+            ptr::null_mut()
+        ));
+        langtons_ant_destroy(sim);
+    }
+}
@@ -0,0 +1,144 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::checkpoint::{Checkpoint, CheckpointWatch, StateDigest};
+use langtons_ant::{Colour, GridBackend, Simulation};
+
+#[test]
+fn digest_is_the_same_for_every_backend() {
+    let mut dense = Simulation::with_seed("RRLLLRLLLRRR", 120, 4);
+    let mut packed = dense.clone();
+    packed.pack(4).unwrap();
+    let mut sparse = dense.clone();
+    sparse.make_unbounded();
+
+    dense.advance(5_000);
+    packed.advance(5_000);
+    sparse.advance(5_000);
+
+    assert!(matches!(packed.cells(), GridBackend::Packed(_)));
+    assert!(matches!(sparse.cells(), GridBackend::Sparse(_)));
+    assert_eq!(StateDigest::of(&packed), StateDigest::of(&dense));
+    assert_eq!(StateDigest::of(&sparse), StateDigest::of(&dense));
+}
+
+#[test]
+fn digest_ignores_the_colours_drawn() {
+    // Random colours may differ between platforms, so only colour indices
+    // are hashed.
+    let mut sim = Simulation::with_seed("LLRR", 40, 9);
+    sim.advance(500);
+    let digest = StateDigest::of(&sim);
+
+    let grey = Colour::from_hex("#808080").unwrap();
+    sim.set_colours(vec![grey; sim.num_colours()]);
+    assert_eq!(StateDigest::of(&sim), digest);
+
+    assert_eq!(
+        StateDigest::of(&Simulation::with_seed("LLRR", 40, 10)),
+        StateDigest::of(&Simulation::with_seed("LLRR", 40, 9))
+    );
+}
+
+#[test]
+fn digest_is_the_same_however_the_moves_are_made() {
+    let mut fast = Simulation::with_seed("LLRR", 100, 1);
+    let mut slow = fast.clone();
+
+    fast.advance(3_000);
+
+    for _ in 0..3_000 {
+        slow.step();
+    }
+
+    assert_eq!(StateDigest::of(&slow), StateDigest::of(&fast));
+}
+
+#[test]
+fn digest_changes_with_the_state() {
+    let mut sim = Simulation::with_seed("RL", 60, 0);
+    let start = StateDigest::of(&sim);
+
+    sim.step();
+    let first = StateDigest::of(&sim);
+    assert_ne!(first, start);
+
+    sim.step();
+    assert_ne!(StateDigest::of(&sim), first);
+}
+
+// A fixed value, so a change to how the ant moves, or to how the state is
+// hashed, is caught.
+#[test]
+fn digest_of_a_known_run() {
+    let mut sim = Simulation::with_seed("RL", 150, 0);
+    sim.advance(11_000);
+
+    assert_eq!(StateDigest::of(&sim).hash64(), 0xf225_0d4d_453e_3163);
+}
+
+#[test]
+fn checkpoints_read_back_as_printed() {
+    let mut sim = Simulation::with_seed("RL", 60, 0);
+    sim.advance(250);
+
+    let checkpoint = Checkpoint::of(&sim);
+    let line = checkpoint.to_string();
+
+    assert!(line.starts_with("Checkpoint 250 "));
+    assert_eq!(Checkpoint::parse(&line), Some(checkpoint));
+    assert_eq!(Checkpoint::parse("Moves = 250"), None);
+
+    // The 64 bit hash must agree with the SHA-256.
+    let wrong = format!(
+        "Checkpoint 250 {:016x} {}",
+        checkpoint.digest.hash64() ^ 1,
+        &line[32..]
+    );
+    assert_eq!(Checkpoint::parse(&wrong), None);
+}
+
+#[test]
+fn watch_takes_checkpoints_and_checks_those_expected() {
+    let mut reference = Simulation::with_seed("RL", 60, 0);
+    reference.advance(150);
+    let expected = Checkpoint::of(&reference);
+
+    let mut sim = Simulation::with_seed("RL", 60, 0);
+    let mut watch = CheckpointWatch::new(Some(100), vec![expected]);
+
+    assert_eq!(
+        watch.observe(&sim).map(|(c, e)| (c.iteration, e)),
+        Some((0, None))
+    );
+    assert_eq!(watch.remaining(&sim), 100);
+
+    sim.advance(100);
+    assert!(watch.observe(&sim).is_some());
+    assert_eq!(watch.remaining(&sim), 50);
+
+    sim.advance(50);
+    let (taken, checked) = watch.observe(&sim).unwrap();
+    assert_eq!(checked, Some(expected));
+    assert_eq!(taken, expected);
+    assert_eq!(watch.unchecked(), 0);
+    assert_eq!(watch.remaining(&sim), 50);
+}
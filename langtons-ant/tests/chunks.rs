@@ -0,0 +1,138 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{Extent, GridStorage, Simulation, SparseGrid, BLANK, CHUNK_SIZE};
+
+// The region covered by the chunk at a chunk column and row.
+fn chunk(cx: i64, cy: i64) -> Extent {
+    let size = CHUNK_SIZE as i64;
+
+    Extent {
+        x: cx * size,
+        y: cy * size,
+        width: CHUNK_SIZE,
+        height: CHUNK_SIZE,
+    }
+}
+
+#[test]
+fn cells_either_side_of_chunk_edges_are_kept_apart() {
+    let mut grid = SparseGrid::new(10);
+    let size = CHUNK_SIZE as i64;
+    let cells = [
+        (-1, -1),
+        (0, 0),
+        (-1, 0),
+        (size - 1, 0),
+        (size, 0),
+        (-size, 3),
+    ];
+
+    for (i, &(x, y)) in cells.iter().enumerate() {
+        grid.set(x, y, i);
+    }
+
+    for (i, &(x, y)) in cells.iter().enumerate() {
+        assert_eq!(grid.get(x, y), i, "cell {}, {}", x, y);
+    }
+
+    assert_eq!(grid.get(1, 0), BLANK);
+    assert_eq!(grid.len(), cells.len());
+    assert_eq!(grid.num_chunks(), 4);
+}
+
+#[test]
+fn emptied_chunks_are_dropped() {
+    let mut grid = SparseGrid::new(10);
+    grid.set(5, 5, 0);
+    grid.set(6, 5, 1);
+    grid.set(6, 5, 2);
+    assert_eq!(grid.len(), 2);
+
+    grid.set(5, 5, BLANK);
+    grid.set(6, 5, BLANK);
+    grid.set(7, 5, BLANK);
+
+    assert!(grid.is_empty());
+    assert_eq!(grid.num_chunks(), 0);
+    assert_eq!(grid.get(6, 5), BLANK);
+}
+
+#[test]
+fn changed_chunks_are_flagged_once() {
+    let mut grid = SparseGrid::new(10);
+    grid.set(3, 3, 0);
+    grid.set(4, 3, 0);
+    grid.set(-1, -70, 1);
+
+    let mut dirty = grid.take_dirty_chunks();
+    dirty.sort_by_key(|e| (e.y, e.x));
+    assert_eq!(dirty, vec![chunk(-1, -2), chunk(0, 0)]);
+    assert!(grid.take_dirty_chunks().is_empty());
+
+    // Emptying a chunk still needs it redrawn.
+    grid.set(-1, -70, BLANK);
+    assert_eq!(grid.take_dirty_chunks(), vec![chunk(-1, -2)]);
+    assert_eq!(grid.chunk_extents(), vec![chunk(0, 0)]);
+}
+
+#[test]
+fn unbounded_runs_match_bounded_ones_until_the_edge() {
+    let mut bounded = Simulation::with_seed("LRRRRRLLR", 400, 3);
+    let mut unbounded = bounded.clone();
+    unbounded.make_unbounded();
+
+    bounded.advance(50_000);
+    unbounded.advance(50_000);
+
+    assert!(!bounded.is_stalled());
+    assert_eq!(
+        (bounded.ant().pos_x, bounded.ant().pos_y),
+        (unbounded.ant().pos_x, unbounded.ant().pos_y)
+    );
+
+    let mut visited = 0;
+    bounded.cells().for_each_visited(|x, y, c| {
+        visited += 1;
+        assert_eq!(unbounded.cells().get(x, y), c);
+    });
+    unbounded.cells().for_each_visited(|_, _, _| visited -= 1);
+    assert_eq!(visited, 0);
+}
+
+#[test]
+fn chunked_grids_save_their_cells() {
+    let mut sim = Simulation::with_seed("LLRR", 20, 5);
+    sim.make_unbounded();
+    sim.advance(20_000);
+    assert!(sim.take_dirty_chunks().map_or(0, |c| c.len()) > 1);
+
+    let json = serde_json::to_string(&sim).unwrap();
+    let loaded: Simulation = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.cells().extent(), sim.cells().extent());
+
+    let mut same = true;
+    sim.cells()
+        .for_each_visited(|x, y, c| same &= loaded.cells().get(x, y) == c);
+    assert!(same);
+
+    assert_eq!(Simulation::with_seed("RL", 20, 5).take_dirty_chunks(), None);
+}
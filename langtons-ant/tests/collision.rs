@@ -0,0 +1,101 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{CollisionPolicy, Facing, Simulation};
+
+//-----------------------------------------------------------------------------
+// Two ants that meet on their first move, the first at the centre of the
+// grid facing north and the second two cells east facing south. On the
+// blank grid both turn right, onto the cell between them.
+fn meeting(collision: CollisionPolicy) -> Simulation {
+    let mut sim = Simulation::with_seed("RL", 20, 1);
+    sim.set_collision(collision);
+    assert!(sim.add_ant(2, 0, Facing::S));
+    sim
+}
+
+fn positions(sim: &Simulation) -> Vec<(i64, i64, Facing)> {
+    sim.ants()
+        .iter()
+        .map(|a| (a.pos_x, a.pos_y, a.facing))
+        .collect()
+}
+
+#[test]
+fn collision_policies_parse_by_name() {
+    for policy in &[
+        CollisionPolicy::Ignore,
+        CollisionPolicy::Swap,
+        CollisionPolicy::Annihilate,
+        CollisionPolicy::Spawn,
+    ] {
+        assert_eq!(CollisionPolicy::parse(policy.name()), Ok(*policy));
+    }
+
+    assert_eq!(CollisionPolicy::parse(" Swap "), Ok(CollisionPolicy::Swap));
+    assert!(CollisionPolicy::parse("merge").is_err());
+    assert_eq!(CollisionPolicy::default(), CollisionPolicy::Ignore);
+}
+
+#[test]
+fn ignored_collisions_leave_the_ants_alone() {
+    let mut sim = meeting(CollisionPolicy::Ignore);
+    sim.step();
+
+    assert_eq!(positions(&sim), vec![(1, 0, Facing::E), (1, 0, Facing::W)]);
+}
+
+#[test]
+fn swapped_collisions_exchange_facings() {
+    let mut sim = meeting(CollisionPolicy::Swap);
+    sim.step();
+
+    assert_eq!(positions(&sim), vec![(1, 0, Facing::W), (1, 0, Facing::E)]);
+}
+
+#[test]
+fn annihilation_removes_the_ants_but_never_the_last() {
+    let mut sim = meeting(CollisionPolicy::Annihilate);
+    sim.step();
+    assert_eq!(positions(&sim), vec![(1, 0, Facing::E)]);
+
+    let mut sim = meeting(CollisionPolicy::Annihilate);
+    assert!(sim.add_ant(-7, -7, Facing::N));
+    sim.step();
+    assert_eq!(positions(&sim), vec![(-6, -7, Facing::E)]);
+}
+
+#[test]
+fn spawning_adds_an_ant_once_per_meeting() {
+    let mut sim = meeting(CollisionPolicy::Spawn);
+    sim.step();
+
+    assert_eq!(
+        positions(&sim),
+        vec![(1, 0, Facing::E), (1, 0, Facing::W), (1, 0, Facing::W)]
+    );
+    assert_eq!(sim.ants()[2].iterations, sim.iterations());
+
+    // The ants all left the same cell, so landing together again isn't a
+    // new meeting.
+    sim.step();
+    assert_eq!(sim.ants().len(), 3);
+}
@@ -0,0 +1,166 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::colour::{
+    ciede2000, create_distinct_colours, Colour, MIN_BACKGROUND_DISTANCE, MIN_COLOUR_DISTANCE,
+    OBSTACLE_GREY, WHITE,
+};
+use langtons_ant::{palette, ColourMap, Facing, Palette, Simulation, BLANK, OBSTACLE};
+use rand::prelude::*;
+
+// Reference pairs from Sharma, Wu and Dalal's CIEDE2000 test data.
+const SHARMA_PAIRS: [([f32; 3], [f32; 3], f32); 4] = [
+    ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+    ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+    ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+    (
+        [60.2574, -34.0099, 36.2677],
+        [60.4626, -34.1751, 39.4387],
+        1.2644,
+    ),
+];
+
+#[test]
+fn ciede2000_matches_reference_data() {
+    for (lab1, lab2, expected) in SHARMA_PAIRS.iter() {
+        assert!((ciede2000(*lab1, *lab2) - expected).abs() < 1e-3);
+        assert!((ciede2000(*lab2, *lab1) - expected).abs() < 1e-3);
+    }
+
+    assert_eq!(WHITE.distance(&WHITE), 0.0);
+}
+
+#[test]
+fn white_is_lightness_100() {
+    let [l, a, b] = WHITE.to_lab();
+    assert!((l - 100.0).abs() < 0.01);
+    assert!(a.abs() < 0.01 && b.abs() < 0.01);
+}
+
+#[test]
+fn random_colours_are_distinct() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let colours = create_distinct_colours(&mut rng, 8);
+
+    assert_eq!(colours.len(), 8);
+
+    for (i, a) in colours.iter().enumerate() {
+        assert!(a.distance(&WHITE) >= MIN_BACKGROUND_DISTANCE);
+
+        for b in &colours[i + 1..] {
+            assert!(a.distance(b) >= MIN_COLOUR_DISTANCE);
+        }
+    }
+
+    assert!(palette::contrast_problem(&colours).is_none());
+}
+
+#[test]
+fn many_random_colours_still_avoid_the_background() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let colours = create_distinct_colours(&mut rng, 64);
+
+    assert_eq!(colours.len(), 64);
+    assert!(colours
+        .iter()
+        .all(|c| c.distance(&WHITE) >= MIN_BACKGROUND_DISTANCE));
+}
+
+#[test]
+fn accessible_palettes_parse() {
+    let okabe_ito = Palette::parse("okabe-ito").unwrap().colours(8).unwrap();
+    assert_eq!(okabe_ito[0].to_hex(), "#e69f00");
+    assert!(palette::contrast_problem(&okabe_ito).is_none());
+
+    assert!(Palette::parse("OkabeIto").unwrap().colours(9).is_err());
+
+    let cividis = Palette::parse("cividis").unwrap().colours(2).unwrap();
+    assert_eq!(cividis[0].to_hex(), "#00204d");
+    assert_eq!(cividis[1].to_hex(), "#ffea46");
+}
+
+#[test]
+fn close_colours_are_reported() {
+    let colours = [
+        Colour::from_hex("#ff0000").unwrap(),
+        Colour::from_hex("#fe0101").unwrap(),
+        Colour::from_hex("#0000ff").unwrap(),
+    ];
+
+    let problem = palette::contrast_problem(&colours).unwrap();
+    assert!(problem.starts_with("colours 0 (#ff0000) and 1 (#fe0101) are close"));
+
+    let pale = [Colour::from_hex("#fafafa").unwrap()];
+    assert!(palette::contrast_problem(&pale)
+        .unwrap()
+        .contains("close to the background"));
+}
+
+#[test]
+fn colour_maps_give_each_cell_its_colour() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut map = ColourMap::distinct(&mut rng, 3);
+    let second = map.get(1).unwrap();
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get(3).map(|c| c.to_hex()), None);
+    assert_eq!(map.cell_colour(1).to_hex(), second.to_hex());
+    assert_eq!(map.cell_colour(BLANK).to_hex(), WHITE.to_hex());
+    assert_eq!(map.cell_colour(OBSTACLE).to_hex(), OBSTACLE_GREY.to_hex());
+
+    map.resize(&mut rng, 5);
+    assert_eq!(map.len(), 5);
+    assert_eq!(map.colours()[1].to_hex(), second.to_hex());
+
+    map.resize(&mut rng, 2);
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn colours_belong_to_the_simulation_not_the_ants() {
+    let mut sim = Simulation::with_seed("RLR", 20, 3);
+    sim.add_ant(2, 2, Facing::E);
+
+    let colours = vec![Colour::from_hex("#ff0000").unwrap(); 3];
+    sim.set_colours(colours);
+    assert_eq!(sim.colour_map().cell_colour(2).to_hex(), "#ff0000");
+
+    // The ants carry no colours of their own, so saves keep one map.
+    let json = serde_json::to_value(&sim).unwrap();
+    assert_eq!(json["colours"].as_array().map(Vec::len), Some(3));
+    assert!(json["ants"][1].get("colours").is_none());
+}
+
+#[test]
+fn saves_with_colours_on_the_ants_still_load() {
+    let sim = Simulation::with_seed("LLRR", 20, 8);
+    let mut json = serde_json::to_value(&sim).unwrap();
+    let colours = json.as_object_mut().unwrap().remove("colours").unwrap();
+    json["ants"][0]["colours"] = colours;
+
+    let path = std::env::temp_dir().join("langtons-ant-old-colours.json");
+    std::fs::write(&path, json.to_string()).unwrap();
+    let loaded = Simulation::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    let hex = |s: &Simulation| s.colours().iter().map(|c| c.to_hex()).collect::<Vec<_>>();
+    assert_eq!(hex(&loaded), hex(&sim));
+}
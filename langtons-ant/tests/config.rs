@@ -0,0 +1,195 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::config::{self, AntSpec, Config, ConfigError, ConfigFile, UNLIMITED_MPS};
+use langtons_ant::rule::RuleParseError;
+use langtons_ant::Facing;
+
+#[test]
+fn valid_parameters_are_accepted() {
+    let config = Config::new("LRRN", 100, 200, 5.0).unwrap();
+
+    assert_eq!(config.rule, "LRRN");
+    assert_eq!(config.mps, 100);
+    assert_eq!(config.grid_size, 200);
+    assert_eq!(config.square_size, 5.0);
+    assert_eq!(config.simulation(1).rule(), "LRRN");
+
+    let default = Config::default();
+    assert_eq!(
+        Config::new(
+            &default.rule,
+            default.mps,
+            default.grid_size,
+            default.square_size
+        ),
+        Ok(default)
+    );
+}
+
+#[test]
+fn invalid_parameters_are_reported() {
+    assert_eq!(
+        Config::new("LRX", 10, 150, 5.0),
+        Err(ConfigError::InvalidRuleSyntax {
+            rule: "LRX".to_string(),
+            error: RuleParseError::InvalidCharacter {
+                character: 'X',
+                position: 3
+            }
+        })
+    );
+    assert_eq!(
+        Config::new("", 10, 150, 5.0),
+        Err(ConfigError::InvalidRuleSyntax {
+            rule: String::new(),
+            error: RuleParseError::Empty
+        })
+    );
+    assert_eq!(
+        Config::new("RL", 0, 150, 5.0),
+        Err(ConfigError::MpsOutOfRange(0))
+    );
+    assert_eq!(
+        Config::new("RL", 10, 5, 5.0),
+        Err(ConfigError::GridSizeOutOfRange(5))
+    );
+    assert_eq!(
+        Config::new("RL", 10, 150, 25.0),
+        Err(ConfigError::SquareSizeOutOfRange(25.0))
+    );
+    // Big grids are scaled to fit the window rather than rejected.
+    assert!(Config::new("RL", 10, 500, 5.0).is_ok());
+    // There's no upper limit on the speed.
+    assert_eq!(
+        Config::new("RL", u64::MAX, 150, 5.0).unwrap().mps,
+        UNLIMITED_MPS
+    );
+    assert_eq!(config::window_size(500, 5.0), config::MAX_WINDOW_SIZE);
+    assert_eq!(config::window_size(150, 5.0), 750);
+}
+
+#[test]
+fn grids_too_big_for_memory_are_rejected() {
+    if config::available_memory().is_none() {
+        return;
+    }
+
+    assert!(matches!(
+        config::validate_grid_size(u32::MAX),
+        Err(ConfigError::GridTooLarge { size: u32::MAX, .. })
+    ));
+}
+
+#[test]
+fn numbers_are_parsed_with_their_names_in_errors() {
+    assert_eq!(config::parse_number::<u32>("grid size", " 150\n"), Ok(150));
+
+    let e = config::parse_number::<u32>("grid size", "lots").unwrap_err();
+    assert_eq!(
+        e,
+        ConfigError::InvalidNumber {
+            name: "grid size",
+            input: "lots".to_string()
+        }
+    );
+    assert_eq!(e.to_string(), "Invalid grid size = lots");
+}
+
+#[test]
+fn config_files_are_read() {
+    let file = ConfigFile::parse(
+        r#"
+rule = "LLRR"
+grid = 200
+mps = 1000
+palette = "viridis"
+boundary = "wrap"
+video_size = [1280, 720]
+
+[[ants]]
+x = 10
+y = 20
+facing = "E"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(file.rule.as_deref(), Some("LLRR"));
+    assert_eq!(file.grid, Some(200));
+    assert_eq!(file.mps, Some(1000));
+    assert_eq!(file.boundary.as_deref(), Some("wrap"));
+    assert_eq!(file.video_size, Some((1280, 720)));
+    assert_eq!(file.square, None);
+    assert_eq!(
+        file.ants,
+        Some(vec![AntSpec {
+            x: 10,
+            y: 20,
+            facing: Facing::E
+        }])
+    );
+}
+
+#[test]
+fn config_files_with_unknown_settings_are_rejected() {
+    assert!(ConfigFile::parse("rules = \"RL\"").is_err());
+    assert!(ConfigFile::parse("grid = \"big\"").is_err());
+    assert_eq!(ConfigFile::parse(""), Ok(ConfigFile::default()));
+
+    let path = std::env::temp_dir().join("langtons-ant-missing-config.toml");
+    assert!(matches!(
+        ConfigFile::load(&path),
+        Err(ConfigError::Load { name: "config", .. })
+    ));
+}
+
+#[test]
+fn ants_are_parsed() {
+    assert_eq!(
+        AntSpec::parse("-3, 7, w"),
+        Ok(AntSpec {
+            x: -3,
+            y: 7,
+            facing: Facing::W
+        })
+    );
+    assert!(AntSpec::parse("1,2").is_err());
+    assert!(AntSpec::parse("1,2,Q").is_err());
+    assert!(AntSpec::parse("1,2,EE").is_err());
+}
+
+#[test]
+fn configs_round_trip_through_toml() {
+    let config = Config::new("L R ^N", 250, 400, 2.5).unwrap();
+    assert_eq!(Config::from_toml(&config.to_toml()), Ok(config));
+
+    let config = Config::from_toml("rule = \"RLLR\"\ngrid = 60\n").unwrap();
+    assert_eq!(config.rule, "RLLR");
+    assert_eq!(config.grid_size, 60);
+    assert_eq!(config.mps, Config::default().mps);
+
+    assert_eq!(
+        Config::from_toml("grid = 5\n"),
+        Err(ConfigError::GridSizeOutOfRange(5))
+    );
+    assert!(Config::from_toml("rule = ").is_err());
+}
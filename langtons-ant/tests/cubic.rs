@@ -0,0 +1,123 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::cubic::{parse_rule, Axis, Lattice, Turn, TurnConvention, MAX_LATTICE_SIZE};
+use langtons_ant::{Boundary, GridStorage, Simulation, BLANK};
+
+#[test]
+fn rules_parse_with_climb_and_dive() {
+    assert_eq!(
+        parse_rule("lr CD").unwrap(),
+        vec![Turn::L, Turn::R, Turn::Climb, Turn::Dive]
+    );
+    assert!(parse_rule("LRX").is_err());
+    assert!(parse_rule("").is_err());
+    assert!(parse_rule(&"L".repeat(256)).is_err());
+
+    assert!(Lattice::with_seed("RL", MAX_LATTICE_SIZE + 1, TurnConvention::Planar, 1).is_err());
+    assert_eq!(TurnConvention::parse("Roll"), Ok(TurnConvention::Roll));
+    assert!(TurnConvention::parse("yaw").is_err());
+}
+
+#[test]
+fn planar_runs_match_the_2d_ant() {
+    let size = 40;
+    let mut lattice = Lattice::with_seed("RLL", size, TurnConvention::Planar, 7).unwrap();
+    let mut sim = Simulation::with_seed("RLL", size, 7);
+    sim.set_boundary(Boundary::Wrap);
+
+    lattice.advance(3000);
+    for _ in 0..3000 {
+        sim.step();
+    }
+
+    let centre = size / 2;
+    assert_eq!(lattice.position()[2], centre);
+    let mut visited = 0;
+    sim.cells().for_each_visited(|_, _, _| visited += 1);
+    assert_eq!(lattice.visited(), visited);
+
+    // The 2D grid is centred on the origin, the lattice counts from 0.
+    let start = sim.cells().extent();
+    for y in 0..size {
+        for x in 0..size {
+            let cell = sim.cells().get(start.x + x as i64, start.y + y as i64);
+            let expected = if cell == BLANK { None } else { Some(cell) };
+            assert_eq!(lattice.get(x, y, centre), expected, "cell {}, {}", x, y);
+        }
+    }
+
+    let ant = sim.ant();
+    assert_eq!(
+        [lattice.position()[0], lattice.position()[1]],
+        [
+            (ant.pos_x - start.x) as usize,
+            (ant.pos_y - start.y) as usize
+        ]
+    );
+}
+
+#[test]
+fn roll_turns_leave_the_starting_plane() {
+    let size = 30;
+    let mut lattice = Lattice::with_seed("RL", size, TurnConvention::Roll, 3).unwrap();
+    lattice.advance(500);
+
+    let centre = size / 2;
+    let off_plane = (0..size)
+        .flat_map(|z| (0..size).flat_map(move |y| (0..size).map(move |x| (x, y, z))))
+        .any(|(x, y, z)| z != centre && lattice.get(x, y, z).is_some());
+
+    assert!(off_plane);
+}
+
+#[test]
+fn climbing_loops_back_to_the_start() {
+    let mut lattice = Lattice::with_seed("C", 20, TurnConvention::Planar, 1).unwrap();
+    let start = lattice.position();
+
+    lattice.step();
+    assert_eq!(lattice.heading(), [0, 0, -1]);
+    assert_eq!(lattice.up(), [0, 1, 0]);
+
+    lattice.advance(3);
+    assert_eq!(lattice.position(), start);
+    assert_eq!(lattice.iterations(), 4);
+}
+
+#[test]
+fn projections_show_the_nearest_cells() {
+    let size = 24;
+    let mut lattice = Lattice::with_seed("RL", size, TurnConvention::Planar, 5).unwrap();
+    lattice.advance(400);
+
+    let centre = size / 2;
+    let slice = lattice.render_slice(Axis::Z, centre);
+    let projection = lattice.render_projection(Axis::Z);
+    let white = image::Rgba([255, 255, 255, 255]);
+
+    for (x, y, pixel) in slice.enumerate_pixels() {
+        assert_eq!(*pixel == white, *projection.get_pixel(x, y) == white);
+    }
+
+    assert_eq!(Axis::X.to_slice([1, 2, 3]), ([2, 3], 1));
+    assert_eq!(Axis::Z.next(), Axis::X);
+}
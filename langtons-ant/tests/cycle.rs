@@ -0,0 +1,107 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::cycle::CycleDetector;
+use langtons_ant::{Boundary, GridStorage, Simulation};
+
+//-----------------------------------------------------------------------------
+// Step a simulation until a cycle is found or the given number of moves have
+// been made.
+fn run(sim: &mut Simulation, moves: u64) -> CycleDetector {
+    let mut detector = CycleDetector::new();
+    detector.observe(sim);
+
+    for _ in 0..moves {
+        sim.step();
+
+        if detector.observe(sim).is_some() {
+            break;
+        }
+    }
+
+    detector
+}
+
+//-----------------------------------------------------------------------------
+// Check the state really does repeat with the given period from the given
+// iteration, and not from the iteration before.
+fn assert_cycles(mut sim: Simulation, period: u64, start: u64) {
+    let snapshot = |sim: &Simulation| {
+        let e = sim.cells().extent();
+        let mut cells = Vec::new();
+
+        for y in e.y..e.y + e.height as i64 {
+            for x in e.x..e.x + e.width as i64 {
+                cells.push(sim.cells().get(x, y).min(sim.num_colours()) % sim.num_colours());
+            }
+        }
+
+        let ants: Vec<_> = sim
+            .ants()
+            .iter()
+            .map(|a| (a.pos_x, a.pos_y, a.facing, a.state))
+            .collect();
+        (cells, format!("{:?}", ants))
+    };
+
+    if start > 0 {
+        sim.advance(start - 1);
+        let before = snapshot(&sim);
+        let mut later = sim.clone();
+        later.advance(period);
+        assert_ne!(before, snapshot(&later));
+        sim.step();
+    }
+
+    let first = snapshot(&sim);
+    sim.advance(period);
+    assert_eq!(first, snapshot(&sim));
+}
+
+#[test]
+fn wrapped_grid_cycles() {
+    let mut sim = Simulation::with_seed("RL", 4, 1);
+    sim.set_boundary(Boundary::Wrap);
+    let start = sim.clone();
+
+    let cycle = run(&mut sim, 1_000_000).found().expect("no cycle found");
+    assert!(cycle.period > 0);
+    assert_cycles(start, cycle.period, cycle.start);
+}
+
+#[test]
+fn bounced_grid_cycles() {
+    let mut sim = Simulation::with_seed("LRR", 5, 2);
+    sim.set_boundary(Boundary::Bounce);
+    assert!(sim.add_ant(1, 1, langtons_ant::Facing::E));
+    let start = sim.clone();
+
+    let cycle = run(&mut sim, 5_000_000).found().expect("no cycle found");
+    assert_cycles(start, cycle.period, cycle.start);
+}
+
+#[test]
+fn unbounded_growth_has_no_cycle() {
+    let mut sim = Simulation::with_seed("LLRR", 50, 1);
+    sim.make_unbounded();
+
+    assert_eq!(run(&mut sim, 100_000).found(), None);
+}
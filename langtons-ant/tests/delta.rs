@@ -0,0 +1,109 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{Facing, GridStorage, Simulation};
+use std::sync::mpsc;
+use std::thread;
+
+//-----------------------------------------------------------------------------
+// Check two simulations have the same cells and ants.
+fn assert_same(a: &Simulation, b: &Simulation) {
+    assert_eq!(a.iterations(), b.iterations());
+    assert_eq!(a.ants().len(), b.ants().len());
+
+    for (ant_a, ant_b) in a.ants().iter().zip(b.ants()) {
+        assert_eq!((ant_a.pos_x, ant_a.pos_y), (ant_b.pos_x, ant_b.pos_y));
+        assert_eq!(ant_a.facing, ant_b.facing);
+    }
+
+    let extent = a.cells().extent();
+    assert_eq!(extent, b.cells().extent());
+
+    for y in extent.y..extent.y + extent.height as i64 {
+        for x in extent.x..extent.x + extent.width as i64 {
+            assert_eq!(a.cells().get(x, y), b.cells().get(x, y));
+        }
+    }
+}
+
+#[test]
+fn deltas_keep_a_copy_in_step() {
+    let mut sim = Simulation::with_seed("LLRR", 60, 3);
+    sim.set_track_dirty(true);
+    let mut copy = sim.clone();
+
+    for _ in 0..20 {
+        sim.advance(250);
+        copy.apply_delta(sim.take_delta());
+        assert_same(&sim, &copy);
+    }
+}
+
+#[test]
+fn deltas_list_each_changed_cell_once() {
+    let mut sim = Simulation::with_seed("RL", 60, 3);
+    sim.set_track_dirty(true);
+    sim.advance(2000);
+
+    let delta = sim.take_delta();
+    let mut cells: Vec<_> = delta.cells.iter().map(|&(x, y, _)| (x, y)).collect();
+    cells.dedup();
+    assert_eq!(cells.len(), delta.cells.len());
+
+    assert!(sim.take_delta().is_empty());
+}
+
+#[test]
+fn deltas_carry_new_ants_and_painted_cells() {
+    let mut sim = Simulation::with_seed("RL", 60, 3);
+    sim.set_track_dirty(true);
+    let mut copy = sim.clone();
+
+    sim.add_ant(10, 10, Facing::E);
+    sim.paint(40, 40, 0);
+    sim.advance(100);
+    copy.apply_delta(sim.take_delta());
+
+    assert_same(&sim, &copy);
+}
+
+#[test]
+fn deltas_can_be_sent_between_threads() {
+    let mut sim = Simulation::with_seed("LRRRRRLLR", 50, 9);
+    sim.make_unbounded();
+    sim.set_track_dirty(true);
+    let mut copy = sim.clone();
+
+    let (tx, rx) = mpsc::channel();
+    let runner = thread::spawn(move || {
+        for _ in 0..10 {
+            sim.advance(1000);
+            tx.send(sim.take_delta()).unwrap();
+        }
+        sim
+    });
+
+    for delta in rx {
+        copy.apply_delta(delta);
+    }
+
+    assert_same(&runner.join().unwrap(), &copy);
+}
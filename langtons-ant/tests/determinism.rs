@@ -0,0 +1,132 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::checkpoint::StateDigest;
+use langtons_ant::random::{SeededRng, SPAWN_STREAM, TURNS_STREAM};
+use langtons_ant::replay::{MoveLog, Replay, ReplayDifference};
+use langtons_ant::seeding::GridSeed;
+use langtons_ant::spawn::{Spawn, SpawnFacing, SpawnPosition};
+use langtons_ant::Simulation;
+use rand::RngCore;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "langtons-ant-determinism-{}-{}.antlog",
+        name,
+        std::process::id()
+    ))
+}
+
+// Log a run of the given rule and seed for n iterations and load it back.
+fn logged_run(name: &str, rule: &str, seed: u64, n: u64) -> Replay {
+    let path = temp_path(name);
+    let mut sim = Simulation::with_seed(rule, 60, seed);
+
+    let mut log = MoveLog::create(&path, &mut sim).unwrap();
+    sim.advance(n);
+    log.write(&sim.take_changes()).unwrap();
+    log.finish().unwrap();
+
+    let (_, replay) = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    replay
+}
+
+//-----------------------------------------------------------------------------
+// The values below were recorded once and must never change, whatever the
+// platform, so runs from a seed are the same everywhere.
+#[test]
+fn seeded_runs_are_the_same_everywhere() {
+    assert_eq!(
+        SeededRng::new(42, TURNS_STREAM).next_u64(),
+        0x2f01_1690_14e7_3fff
+    );
+    assert_eq!(
+        SeededRng::new(42, SPAWN_STREAM).next_u64(),
+        0x5c97_cf06_8ade_5889
+    );
+
+    let mut weighted = Simulation::with_seed("L:0.7/R:0.3,R:0.6/U:0.4", 80, 7);
+    weighted.advance(5000);
+    assert_eq!(StateDigest::of(&weighted).hash64(), 0x03b2_1bda_9459_1058);
+
+    let mut seeded = Simulation::with_seed("LRRL", 80, 7);
+    GridSeed::Random(0.3).apply(&mut seeded).unwrap();
+    Spawn {
+        positions: vec![SpawnPosition::Random; 3],
+        facings: vec![SpawnFacing::Random],
+    }
+    .apply(&mut seeded)
+    .unwrap();
+    seeded.advance(5000);
+    assert_eq!(StateDigest::of(&seeded).hash64(), 0x446d_16bb_81a9_cd70);
+
+    assert_eq!(
+        logged_run("pinned", "RLR", 3, 2000).hash().to_string(),
+        "3a0da649a359fcaf 3a0da649a359fcafe4fa3b673e0343349763f9605d2d7bf64030856a91ec1de8"
+    );
+}
+
+#[test]
+fn loaded_weighted_runs_make_the_same_choices() {
+    let mut sim = Simulation::with_seed("L:0.5/R:0.5,R:0.9/N:0.1", 80, 11);
+    sim.advance(1000);
+
+    let json = serde_json::to_string(&sim).unwrap();
+    let mut loaded: Simulation = serde_json::from_str(&json).unwrap();
+
+    sim.advance(3000);
+    loaded.advance(3000);
+    assert_eq!(StateDigest::of(&loaded), StateDigest::of(&sim));
+}
+
+#[test]
+fn replays_of_the_same_run_have_the_same_hash() {
+    let a = logged_run("same-a", "L:0.8/R:0.2,R", 5, 3000);
+    let b = logged_run("same-b", "L:0.8/R:0.2,R", 5, 3000);
+
+    assert_eq!(a.hash(), b.hash());
+    assert_eq!(a.first_difference(&b), None);
+}
+
+#[test]
+fn replays_of_different_runs_show_where_they_differ() {
+    let run = logged_run("diff-a", "L:0.8/R:0.2,R", 5, 3000);
+    let reseeded = logged_run("diff-b", "L:0.8/R:0.2,R", 6, 3000);
+    let shorter = logged_run("diff-c", "L:0.8/R:0.2,R", 5, 2000);
+    let other_rule = logged_run("diff-d", "RL", 5, 3000);
+
+    assert_ne!(run.hash(), reseeded.hash());
+    assert!(matches!(
+        run.first_difference(&reseeded),
+        Some(ReplayDifference::Change(i)) if i < 3000
+    ));
+
+    assert_ne!(run.hash(), shorter.hash());
+    assert_eq!(
+        run.first_difference(&shorter),
+        Some(ReplayDifference::Change(2000))
+    );
+
+    assert_ne!(run.hash(), other_rule.hash());
+    assert!(run.first_difference(&other_rule).is_some());
+}
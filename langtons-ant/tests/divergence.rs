@@ -0,0 +1,73 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::divergence::{differences, flip};
+use langtons_ant::{GridStorage, Simulation, OBSTACLE};
+
+#[test]
+fn flip_moves_a_cell_on_to_the_next_colour() {
+    let mut sim = Simulation::with_seed("RLR", 20, 0);
+
+    assert!(flip(&mut sim, 3, 4));
+    assert_eq!(sim.cells().get(3, 4), 1);
+    assert!(flip(&mut sim, 3, 4));
+    assert!(flip(&mut sim, 3, 4));
+    assert_eq!(sim.cells().get(3, 4), 0);
+
+    sim.paint(5, 5, OBSTACLE);
+    assert!(!flip(&mut sim, 5, 5));
+    assert!(!flip(&mut sim, 10, 0));
+}
+
+#[test]
+fn identical_runs_have_no_differences() {
+    let mut a = Simulation::with_seed("LLRR", 60, 3);
+    let mut b = a.clone();
+
+    a.advance(2_000);
+    b.advance(2_000);
+
+    assert!(differences(&a, &b).is_empty());
+}
+
+#[test]
+fn a_flipped_cell_spreads() {
+    let a = Simulation::with_seed("RL", 80, 0);
+    let mut b = a.clone();
+    flip(&mut b, 1, 0);
+
+    assert_eq!(differences(&a, &b), vec![(1, 0)]);
+
+    let (mut a, mut b) = (a, b);
+    a.advance(5_000);
+    b.advance(5_000);
+
+    assert!(differences(&a, &b).len() > 1);
+}
+
+#[test]
+fn unvisited_cells_match_colour_zero() {
+    let a = Simulation::with_seed("RL", 20, 0);
+    let mut b = a.clone();
+    b.paint(2, 2, 0);
+
+    assert!(differences(&a, &b).is_empty());
+}
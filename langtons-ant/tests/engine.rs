@@ -0,0 +1,214 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::ant::turn_and_move;
+use langtons_ant::config::ConfigFile;
+use langtons_ant::engine::{self, LangtonEngine, ParityEngine, RuleEngine};
+use langtons_ant::{
+    Ant, Boundary, Direction, GridBackend, GridStorage, ObstaclePolicy, Simulation, Transition,
+    Turmite, BLANK,
+};
+
+// An engine that paints every cell it leaves with colour 1 and always turns
+// right, so the ant circles a 2x2 square.
+#[derive(Clone)]
+struct Circler {
+    moves: u64,
+}
+
+impl RuleEngine for Circler {
+    fn name(&self) -> String {
+        String::from("circler")
+    }
+
+    fn num_colours(&self) -> usize {
+        2
+    }
+
+    fn step(
+        &mut self,
+        grid: &mut GridBackend,
+        ant: &mut Ant,
+        boundary: Boundary,
+        obstacles: ObstaclePolicy,
+    ) {
+        self.moves += 1;
+        grid.set(ant.pos_x, ant.pos_y, 1);
+        turn_and_move(Direction::R, grid, ant, boundary, obstacles);
+    }
+
+    fn box_clone(&self) -> Box<dyn RuleEngine> {
+        Box::new(self.clone())
+    }
+}
+
+// Do both simulations have the same cells and ant position?
+fn same_run(a: &Simulation, b: &Simulation) -> bool {
+    let (size, start) = (a.size() as i64, -(a.size() as i64 / 2));
+    let range = || start..start + size;
+
+    (a.ant().pos_x, a.ant().pos_y) == (b.ant().pos_x, b.ant().pos_y)
+        && range().all(|y| range().all(|x| a.cells().get(x, y) == b.cells().get(x, y)))
+}
+
+#[test]
+fn a_custom_engine_makes_every_move() {
+    let mut sim = Simulation::engine_with_seed(Box::new(Circler { moves: 0 }), 20, 1);
+    let (x, y) = (sim.ant().pos_x, sim.ant().pos_y);
+
+    assert_eq!(sim.rule(), "circler");
+    assert_eq!(sim.num_colours(), 2);
+
+    sim.advance(8);
+
+    assert_eq!(sim.iterations(), 8);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (x, y));
+    assert_eq!(sim.cells().get(x, y), 1);
+    assert_eq!(sim.cells().get(x + 1, y), 1);
+    assert_eq!(sim.cells().get(x + 1, y + 1), 1);
+    assert_eq!(sim.cells().get(x, y + 1), 1);
+    assert_eq!(sim.cells().get(x - 1, y), BLANK);
+}
+
+#[test]
+fn the_langton_engine_matches_the_simulation() {
+    for rule in ["RL", "LLRR", "RRLLLRLLLRRR"].iter() {
+        let mut plain = Simulation::with_seed(rule, 80, 3);
+        let mut engine =
+            Simulation::engine_with_seed(Box::new(LangtonEngine::new(rule).unwrap()), 80, 3);
+
+        plain.advance(3000);
+        engine.advance(3000);
+
+        assert_eq!(engine.rule(), *rule);
+        assert!(same_run(&plain, &engine));
+    }
+
+    assert!(LangtonEngine::new("").is_err());
+    assert!(LangtonEngine::new("RX").is_err());
+}
+
+#[test]
+fn turmites_run_as_engines_too() {
+    let table = vec![
+        Transition {
+            state: 0,
+            colour: 0,
+            write: 1,
+            turn: Direction::R,
+            next: 1,
+        },
+        Transition {
+            state: 0,
+            colour: 1,
+            write: 0,
+            turn: Direction::L,
+            next: 0,
+        },
+        Transition {
+            state: 1,
+            colour: 0,
+            write: 1,
+            turn: Direction::L,
+            next: 0,
+        },
+        Transition {
+            state: 1,
+            colour: 1,
+            write: 1,
+            turn: Direction::R,
+            next: 1,
+        },
+    ];
+    let turmite = Turmite::new(table).unwrap();
+
+    let mut plain = Simulation::turmite_with_seed(turmite.clone(), 60, 5);
+    let mut engine = Simulation::engine_with_seed(Box::new(turmite), 60, 5);
+
+    plain.advance(2000);
+    engine.advance(2000);
+
+    assert!(same_run(&plain, &engine));
+}
+
+#[test]
+fn the_parity_engine_turns_by_its_neighbours() {
+    let mut sim = Simulation::engine_with_seed(Box::new(ParityEngine), 40, 2);
+    let (x, y) = (sim.ant().pos_x, sim.ant().pos_y);
+
+    // No neighbours are coloured, so the ant toggles its cell and turns
+    // right, moving east from facing north.
+    sim.step();
+    assert_eq!(sim.cells().get(x, y), 1);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (x + 1, y));
+
+    // One neighbour, the cell it just left, is coloured so it turns left.
+    sim.step();
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (x + 1, y - 1));
+    assert_eq!(sim.rule(), "parity");
+}
+
+#[test]
+fn engine_runs_clone_with_their_engine() {
+    let mut sim = Simulation::engine_with_seed(Box::new(ParityEngine), 40, 2);
+    sim.advance(50);
+
+    let mut copy = sim.clone();
+    sim.advance(50);
+    copy.advance(50);
+
+    assert!(same_run(&sim, &copy));
+    assert_eq!(copy.rule(), "parity");
+}
+
+#[test]
+fn engine_runs_cant_change_rule_or_be_saved() {
+    let mut sim = Simulation::engine_with_seed(Box::new(ParityEngine), 40, 2);
+    let path = std::env::temp_dir().join("langtons_ant_engine_save.json");
+
+    assert!(sim.set_rule("RL").is_err());
+    assert!(sim.save(&path).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn builtin_engines_are_chosen_by_name() {
+    for name in engine::BUILTIN_ENGINES.iter() {
+        assert!(engine::builtin(name, "RL").is_ok());
+    }
+
+    assert_eq!(engine::builtin("langton", "LLRR").unwrap().name(), "LLRR");
+    assert_eq!(engine::builtin("parity", "LLRR").unwrap().num_colours(), 2);
+    assert!(engine::builtin("langton", "RX").is_err());
+    assert!(engine::builtin("nonesuch", "RL").is_err());
+
+    let file = ConfigFile::parse("engine = \"parity\"\n").unwrap();
+    assert_eq!(file.engine.as_deref(), Some("parity"));
+}
+
+#[cfg(feature = "plugins")]
+#[test]
+fn missing_plugins_fail_to_load() {
+    use langtons_ant::plugin::PluginEngine;
+
+    let path = std::env::temp_dir().join("langtons_ant_no_such_plugin.so");
+    assert!(PluginEngine::load(&path).is_err());
+}
@@ -0,0 +1,84 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::entropy::{block_entropy, compression_ratio};
+use langtons_ant::stats::Stats;
+use langtons_ant::{Extent, Simulation};
+
+// A simulation with a width by height box of cells painted, each in the
+// colour given by f.
+fn painted<F: Fn(i64, i64) -> usize>(width: i64, height: i64, f: F) -> Simulation {
+    let mut sim = Simulation::with_seed("RLR", 100, 1);
+
+    for y in 0..height {
+        for x in 0..width {
+            sim.paint(x, y, f(x, y));
+        }
+    }
+
+    sim
+}
+
+fn bounds(width: i64, height: i64) -> Option<Extent> {
+    Some(Extent {
+        x: 0,
+        y: 0,
+        width: width as usize,
+        height: height as usize,
+    })
+}
+
+#[test]
+fn no_pattern_has_no_entropy() {
+    let sim = Simulation::with_seed("RL", 40, 1);
+    assert_eq!(block_entropy(sim.cells(), None), 0.0);
+    assert_eq!(compression_ratio(sim.cells(), None), 0.0);
+
+    let stats = Stats::measure(&sim);
+    assert_eq!((stats.entropy, stats.compression), (0.0, 0.0));
+}
+
+#[test]
+fn a_single_colour_has_no_entropy() {
+    let sim = painted(40, 40, |_, _| 1);
+    assert_eq!(block_entropy(sim.cells(), bounds(40, 40)), 0.0);
+    assert!(compression_ratio(sim.cells(), bounds(40, 40)) < 0.05);
+}
+
+#[test]
+fn a_checkerboard_is_made_of_two_blocks() {
+    // Half the 2 by 2 blocks start on each colour, so the entropy is one bit
+    // per block.
+    let sim = painted(40, 40, |x, y| ((x + y) % 2) as usize);
+    let entropy = block_entropy(sim.cells(), bounds(40, 40));
+    assert!((entropy - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn disorder_scores_higher_than_order() {
+    let ordered = painted(60, 60, |x, _| (x % 3) as usize);
+    let disordered = painted(60, 60, |x, y| ((x * 7919 + y * 104_729) % 13 % 3) as usize);
+
+    let ordered = Stats::measure(&ordered);
+    let disordered = Stats::measure(&disordered);
+    assert!(disordered.entropy > ordered.entropy);
+    assert!(disordered.compression > ordered.compression);
+}
@@ -0,0 +1,217 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{export, Extent, GridStorage, Simulation, BLANK, OBSTACLE};
+
+#[test]
+fn small_regions_are_scaled_up_to_the_thumbnail() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    sim.paint(10, 10, 1);
+    let extent = Extent {
+        x: 10,
+        y: 10,
+        width: 4,
+        height: 2,
+    };
+
+    let img = export::render_thumbnail(&sim, extent, 100);
+    assert_eq!((img.width(), img.height()), (100, 50));
+    assert_eq!(img.get_pixel(0, 0).0, sim.colours()[1].to_rgba8());
+    assert_eq!(img.get_pixel(24, 24).0, sim.colours()[1].to_rgba8());
+}
+
+#[test]
+fn large_regions_are_scaled_down_keeping_their_shape() {
+    let mut sim = Simulation::with_seed("LLRR", 300, 2);
+    sim.advance(20000);
+    let extent = sim.cells().extent();
+
+    let img = export::render_thumbnail(&sim, extent, 64);
+    assert_eq!((img.width(), img.height()), (64, 64));
+
+    let wide = Extent {
+        height: 150,
+        ..extent
+    };
+    let img = export::render_thumbnail(&sim, wide, 64);
+    assert_eq!((img.width(), img.height()), (64, 32));
+}
+
+#[test]
+fn grids_encode_as_png_data() {
+    let mut sim = Simulation::with_seed("RL", 40, 3);
+    sim.advance(500);
+
+    let png = export::encode_png(&sim, 2).unwrap();
+    let img = image::load_from_memory(&png).unwrap().to_rgba8();
+
+    assert_eq!(img, export::render_image(&sim, 2));
+}
+
+#[test]
+fn grids_convert_to_json_rows_of_cells() {
+    let mut sim = Simulation::with_seed("LLRR", 30, 4);
+    sim.paint(-12, -13, 2);
+    sim.paint(-10, -8, OBSTACLE);
+
+    // Rows and columns count from the grid's top left corner.
+    let json = export::grid_json(&sim);
+    assert_eq!(
+        (json["x"].as_i64(), json["y"].as_i64()),
+        (Some(-15), Some(-15))
+    );
+    assert_eq!(json["width"].as_u64(), Some(30));
+    assert_eq!(json["height"].as_u64(), Some(30));
+    assert_eq!(json["colours"].as_array().unwrap().len(), 4);
+
+    let cells = json["cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 30);
+    assert_eq!(cells[2][3], 2);
+    assert_eq!(cells[7][5], -2);
+    assert_eq!(cells[0][0], -1);
+}
+
+#[test]
+fn deltas_convert_to_json_that_brings_a_copy_up_to_date() {
+    let mut sim = Simulation::with_seed("RL", 30, 5);
+    sim.set_track_dirty(true);
+    let before = export::grid_json(&sim);
+
+    sim.advance(200);
+    let delta = export::delta_json(&sim.take_delta(), sim.iterations());
+    assert_eq!(delta["iteration"], 200);
+    assert_eq!(delta["ants"][0]["x"], sim.ant().pos_x);
+    assert_eq!(
+        delta["ants"][0]["facing"],
+        sim.ant().facing.to_char().to_string()
+    );
+
+    // Applying the changed cells to the earlier grid gives the current one.
+    let (left, top) = (before["x"].as_i64().unwrap(), before["y"].as_i64().unwrap());
+    let mut cells = before["cells"].clone();
+    for cell in delta["cells"].as_array().unwrap() {
+        let (x, y) = (cell[0].as_i64().unwrap(), cell[1].as_i64().unwrap());
+        cells[(y - top) as usize][(x - left) as usize] = cell[2].clone();
+    }
+
+    assert_eq!(cells, export::grid_json(&sim)["cells"]);
+}
+
+#[test]
+fn blocks_of_colour_merge_into_single_rectangles() {
+    let mut sim = Simulation::with_seed("LLRR", 20, 6);
+    let extent = sim.cells().extent();
+    let mut paint = |x, y, clr_idx| sim.paint(extent.x + x, extent.y + y, clr_idx);
+
+    for y in 4..7 {
+        for x in 2..6 {
+            paint(x, y, 2);
+        }
+    }
+
+    paint(8, 4, 1);
+    paint(9, 4, 1);
+    paint(8, 5, 1);
+
+    // Rectangles are placed relative to the region's top left corner.
+    let rects = export::merged_rects(&sim, extent);
+
+    assert!(rects.contains(&export::CellRect {
+        x: 2,
+        y: 4,
+        width: 4,
+        height: 3,
+        clr_idx: 2,
+    }));
+
+    // Runs of different widths aren't merged.
+    let ones: Vec<_> = rects.iter().filter(|r| r.clr_idx == 1).collect();
+    assert_eq!(ones.len(), 2);
+}
+
+#[test]
+fn merged_rectangles_cover_exactly_the_visited_cells() {
+    let mut sim = Simulation::with_seed("LLRR", 60, 7);
+    sim.advance(5000);
+    sim.paint(1, 1, OBSTACLE);
+
+    let extent = sim.cells().extent();
+    let mut covered = vec![vec![None; extent.width]; extent.height];
+
+    for r in export::merged_rects(&sim, extent) {
+        for row in covered.iter_mut().skip(r.y).take(r.height) {
+            for cell in row.iter_mut().skip(r.x).take(r.width) {
+                assert!(cell.is_none(), "rectangles overlap");
+                *cell = Some(r.clr_idx);
+            }
+        }
+    }
+
+    for (y, row) in covered.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let clr_idx = sim.cells().get(extent.x + x as i64, extent.y + y as i64);
+            assert_eq!(*cell, Some(clr_idx).filter(|&c| c != BLANK));
+        }
+    }
+}
+
+#[test]
+fn svg_groups_rectangles_by_colour() {
+    let mut sim = Simulation::with_seed("RL", 10, 8);
+    sim.paint(-2, -2, 1);
+
+    let mut svg = Vec::new();
+    export::write_svg(&sim, &mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+
+    assert!(svg.contains(r#"viewBox="0 0 10 10""#));
+    assert!(svg.contains(&format!(r#"<g fill="{}">"#, sim.colours()[1].to_hex())));
+    assert!(svg.contains(r#"<rect x="3" y="3" width="1" height="1"/>"#));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn renders_scale_to_fractional_cell_sizes() {
+    let mut sim = Simulation::with_seed("RL", 20, 9);
+    let extent = sim.cells().extent();
+    sim.paint(extent.x, extent.y, 1);
+
+    let img = export::render_scaled(&sim, extent, 4.0, 1).unwrap();
+    assert_eq!(img, export::render_region(&sim, extent, 4));
+
+    let img = export::render_scaled(&sim, extent, 2.5, 4).unwrap();
+    assert_eq!((img.width(), img.height()), (50, 50));
+    assert_eq!(img.get_pixel(0, 0).0, sim.colours()[1].to_rgba8());
+
+    // The cell's edge falls half way across a pixel, which is blended.
+    assert_ne!(img.get_pixel(2, 0).0, sim.colours()[1].to_rgba8());
+    assert_ne!(img.get_pixel(2, 0).0, [255; 4]);
+}
+
+#[test]
+fn oversized_renders_are_rejected() {
+    let sim = Simulation::with_seed("RL", 1000, 10);
+    let extent = sim.cells().extent();
+
+    assert!(export::render_scaled(&sim, extent, 100.0, 4).is_err());
+    assert!(export::render_scaled(&sim, extent, 0.0, 1).is_err());
+    assert!(export::render_scaled(&sim, extent, f64::NAN, 1).is_err());
+}
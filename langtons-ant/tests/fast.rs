@@ -0,0 +1,76 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::Simulation;
+
+//-----------------------------------------------------------------------------
+// The ant's position, facing and count, and the visited cells.
+type State = ((i64, i64, u64), Vec<(i64, i64, usize)>);
+
+fn state(sim: &Simulation) -> State {
+    let ant = sim.ant();
+    let mut cells = Vec::new();
+    sim.cells()
+        .for_each_visited(|x, y, c| cells.push((x, y, c)));
+    cells.sort_unstable();
+
+    ((ant.pos_x, ant.pos_y, ant.iterations), cells)
+}
+
+#[test]
+fn repeated_advances_on_an_unbounded_grid_match_stepping() {
+    let mut sim = Simulation::with_seed("RL", 10, 1);
+    sim.make_unbounded();
+    let mut stepped = sim.clone();
+
+    // Long runs take the fast path and short ones are stepped, and each can
+    // leave the ant outside the cells visited so far.
+    for &moves in [500, 1, 2_000, 37, 5_000, 800, 12_000]
+        .iter()
+        .cycle()
+        .take(21)
+    {
+        sim.advance(moves);
+
+        for _ in 0..moves {
+            stepped.step();
+        }
+
+        assert!(state(&sim) == state(&stepped));
+        assert_eq!(sim.ant().facing, stepped.ant().facing);
+    }
+}
+
+#[test]
+fn advances_on_a_bounded_grid_match_stepping() {
+    let mut sim = Simulation::with_seed("LLRR", 32, 1);
+    let mut stepped = sim.clone();
+
+    for &moves in &[1, 5_000, 3, 20_000] {
+        sim.advance(moves);
+
+        for _ in 0..moves {
+            stepped.step();
+        }
+
+        assert!(state(&sim) == state(&stepped));
+    }
+}
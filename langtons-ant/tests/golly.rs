@@ -0,0 +1,153 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{golly, Direction, GridStorage, Simulation, Turmite, BLANK};
+
+//-----------------------------------------------------------------------------
+// Check a turmite moves exactly as the given rule does.
+fn assert_runs_as_rule(turmite: Turmite, rule: &str) {
+    let mut a = Simulation::turmite_with_seed(turmite, 60, 1);
+    let mut b = Simulation::with_seed(rule, 60, 1);
+
+    for _ in 0..5000 {
+        a.step();
+        b.step();
+        assert_eq!(
+            (a.ant().pos_x, a.ant().pos_y),
+            (b.ant().pos_x, b.ant().pos_y)
+        );
+    }
+}
+
+#[test]
+fn rule_names_and_specifications_are_read() {
+    assert_runs_as_rule(golly::parse_turmite("Turmite_120080").unwrap(), "RL");
+    assert_runs_as_rule(
+        golly::parse_turmite("{{{1,2,0},{2,8,0},{0,2,0}}}").unwrap(),
+        "RLR",
+    );
+
+    let turmite = golly::parse_turmite("Turmite_181181121010").unwrap();
+    assert_eq!(turmite.num_states(), 2);
+    assert_eq!(turmite.num_colours(), 2);
+    assert_eq!(turmite.transition(1, 0).turn, Direction::R);
+    assert_eq!(turmite.transition(1, 1).next, 0);
+}
+
+#[test]
+fn turmites_are_written_back() {
+    let name = "Turmite_181181121010";
+    let turmite = golly::parse_turmite(name).unwrap();
+
+    assert_eq!(golly::turmite_name(&turmite).unwrap(), name);
+
+    let spec = golly::turmite_spec(&turmite);
+    assert_eq!(spec, "{{{1,8,1},{1,8,1}},{{1,2,1},{0,1,0}}}");
+    assert_eq!(
+        golly::turmite_spec(&golly::parse_turmite(&spec).unwrap()),
+        spec
+    );
+}
+
+#[test]
+fn rule_files_are_read() {
+    let named = "@RULE Turmite_120080\n\n@TABLE\nn_states:6\n";
+    assert_runs_as_rule(golly::rule_file_turmite(named).unwrap(), "RL");
+
+    let spec = "@RULE MyTurmite\n# Generated from {{{1,2,0},{0,8,0}}}\n@TABLE\n";
+    assert_runs_as_rule(golly::rule_file_turmite(spec).unwrap(), "RL");
+}
+
+#[test]
+fn invalid_turmites_are_rejected() {
+    assert!(golly::parse_turmite("Turmite_12008").is_err());
+    assert!(golly::parse_turmite("Turmite_130080").is_err());
+    assert!(golly::parse_turmite("{{{1,2},{0,8,0}}}").is_err());
+    assert!(golly::parse_turmite("{{{1,2,0},{0,8,0}}").is_err());
+    assert!(golly::rule_file_turmite("@RULE Life\n@TABLE\n").is_err());
+}
+
+#[test]
+fn macrocell_patterns_are_read() {
+    // A glider in a two state 8 x 8 leaf, placed in the south east quarter
+    // of a level 4 node.
+    let text = "[M2] (golly 4.2)\n#R RL\n$.*$..*$***$\n4 0 0 0 1\n";
+    let sim = golly::parse_macrocell(text).unwrap();
+
+    assert_eq!(sim.size(), 3);
+    assert_eq!(sim.cells().get(0, -1), 0);
+    assert_eq!(sim.cells().get(1, 0), 0);
+    assert_eq!(sim.cells().get(-1, 1), 0);
+    assert_eq!(sim.cells().get(1, 1), 0);
+    assert_eq!(sim.cells().get(-1, -1), BLANK);
+
+    // Multi-state leaves give the state of each of the 4 cells.
+    let text = "[M2] (golly 4.2)\n#R LRR\n1 0 2 3 0\n1 1 0 0 0\n2 1 0 0 2\n";
+    let sim = golly::parse_macrocell(text).unwrap();
+
+    assert_eq!(sim.size(), 3);
+    assert_eq!(sim.cells().get(0, -1), 1);
+    assert_eq!(sim.cells().get(-1, 0), 2);
+    assert_eq!(sim.cells().get(1, 1), 0);
+    assert_eq!(sim.cells().get(1, -1), BLANK);
+}
+
+#[test]
+fn invalid_macrocell_patterns_are_rejected() {
+    assert!(golly::parse_macrocell("[M2]\n").is_err());
+    assert!(golly::parse_macrocell("[M2]\n#R RL\n1 0 5 0 0\n").is_err());
+    assert!(golly::parse_macrocell("[M2]\n#R RL\n2 3 0 0 0\n1 1 0 0 0\n").is_err());
+    assert!(golly::parse_macrocell("[M2]\n#R B3/S23\n1 1 0 0 0\n").is_err());
+}
+
+#[test]
+fn huge_macrocell_patterns_are_rejected() {
+    // A cell in each corner of a level 40 node, by chains of nodes holding
+    // a single cell in their top left and bottom right corners.
+    let mut text = String::from("[M2]\n#R RL\n*\n");
+    let (mut top_left, mut bottom_right) = (1, 1);
+
+    for level in 4..40 {
+        text.push_str(&format!("{} {} 0 0 0\n", level, top_left));
+        text.push_str(&format!("{} 0 0 0 {}\n", level, bottom_right));
+        top_left = 2 * level - 6;
+        bottom_right = top_left + 1;
+    }
+
+    text.push_str(&format!("40 {} 0 0 {}\n", top_left, bottom_right));
+    assert!(golly::parse_macrocell(&text).is_err_and(|e| e.contains("across")));
+
+    // Every node four copies of the last, giving 4^36 cells, or none at all
+    // from an empty leaf, which is quick to load.
+    for leaf in &["*", "$"] {
+        let mut text = format!("[M2]\n#R RL\n{}\n", leaf);
+
+        for level in 4..40 {
+            let n = level - 3;
+            text.push_str(&format!("{} {} {} {} {}\n", level, n, n, n, n));
+        }
+
+        match golly::parse_macrocell(&text) {
+            Ok(_) => assert_eq!(*leaf, "$"),
+            Err(e) => assert!(*leaf == "*" && e.contains("cells,"), "{}", e),
+        }
+    }
+}
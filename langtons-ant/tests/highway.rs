@@ -0,0 +1,114 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::highway::HighwayDetector;
+use langtons_ant::{rle, Simulation, BLANK};
+
+//-----------------------------------------------------------------------------
+// Step a simulation until a highway is found or the given number of moves
+// have been made.
+fn run(sim: &mut Simulation, detector: &mut HighwayDetector, moves: u64) {
+    for _ in 0..moves {
+        sim.step();
+
+        if detector.observe(sim).is_some() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn langtons_highway_is_found() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    sim.make_unbounded();
+    let mut detector = HighwayDetector::new(1000);
+
+    run(&mut sim, &mut detector, 20_000);
+
+    let highway = detector.found().expect("no highway found");
+    assert_eq!(highway.period, 104);
+    assert_eq!(highway.displacement.0.abs(), 2);
+    assert_eq!(highway.displacement.1.abs(), 2);
+    assert!(
+        (9_900..10_100).contains(&highway.start),
+        "{}",
+        highway.start
+    );
+    assert!(sim.iterations() < 12_000);
+}
+
+#[test]
+fn chaotic_and_bounded_runs_have_no_highway() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    sim.make_unbounded();
+    let mut detector = HighwayDetector::new(1000);
+    run(&mut sim, &mut detector, 9_000);
+    assert_eq!(detector.found(), None);
+
+    // LLRR grows symmetrically forever without a highway.
+    let mut sim = Simulation::with_seed("LLRR", 100, 1);
+    sim.make_unbounded();
+    let mut detector = HighwayDetector::new(1000);
+    run(&mut sim, &mut detector, 50_000);
+    assert_eq!(detector.found(), None);
+}
+
+#[test]
+fn langtons_highway_is_cut_out_as_a_pattern() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    sim.make_unbounded();
+    let mut detector = HighwayDetector::new(1000);
+    assert!(detector.pattern(&sim).is_none());
+
+    run(&mut sim, &mut detector, 20_000);
+    let highway = detector.found().expect("no highway found");
+    let pattern = detector.pattern(&sim).expect("no pattern cut");
+
+    // A few periods' cells, far fewer than the run visited.
+    let mut cells = 0;
+    pattern
+        .cells()
+        .for_each_visited(|_, _, clr_idx| cells += (clr_idx != BLANK) as usize);
+    assert!(cells > 0 && cells < 400, "{}", cells);
+    assert_eq!((pattern.ant().pos_x, pattern.ant().pos_y), (0, 0));
+    assert_eq!(pattern.ant().facing, sim.ant().facing);
+    assert_eq!(pattern.iterations(), 0);
+
+    // Saved and placed on a blank grid it rebuilds the highway.
+    let mut reseeded = rle::parse(&rle::encode(&pattern).unwrap()).unwrap();
+    reseeded.advance(20 * highway.period);
+    assert_eq!(
+        (reseeded.ant().pos_x, reseeded.ant().pos_y),
+        (20 * highway.displacement.0, 20 * highway.displacement.1)
+    );
+
+    let mut rebuilt = HighwayDetector::new(1000);
+    run(&mut reseeded, &mut rebuilt, 5_000);
+    let found = rebuilt.found().expect("no highway rebuilt");
+    assert_eq!(
+        (found.period, found.displacement),
+        (highway.period, highway.displacement)
+    );
+
+    // Only the moment the highway was found can be cut.
+    sim.step();
+    assert!(detector.pattern(&sim).is_none());
+}
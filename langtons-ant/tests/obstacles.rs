@@ -0,0 +1,104 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{GridStorage, ObstaclePolicy, Simulation, BLANK, OBSTACLE};
+
+//-----------------------------------------------------------------------------
+// An ant at the centre of the grid facing north, about to turn right onto
+// an obstacle on its first move.
+fn blocked(obstacles: ObstaclePolicy) -> Simulation {
+    let mut sim = Simulation::with_seed("RL", 20, 1);
+    sim.set_obstacles(obstacles);
+    sim.paint(1, 0, OBSTACLE);
+    sim
+}
+
+#[test]
+fn obstacle_policies_parse_by_name() {
+    for policy in &[
+        ObstaclePolicy::Reverse,
+        ObstaclePolicy::Left,
+        ObstaclePolicy::Right,
+        ObstaclePolicy::Stall,
+    ] {
+        assert_eq!(ObstaclePolicy::parse(policy.name()), Ok(*policy));
+    }
+
+    assert!(ObstaclePolicy::parse("bounce").is_err());
+    assert_eq!(ObstaclePolicy::default(), ObstaclePolicy::Reverse);
+}
+
+#[test]
+fn blocked_ants_stay_put_and_follow_the_policy() {
+    use langtons_ant::Facing::*;
+
+    for &(policy, facing, stalled) in &[
+        (ObstaclePolicy::Reverse, W, false),
+        (ObstaclePolicy::Left, N, false),
+        (ObstaclePolicy::Right, S, false),
+        (ObstaclePolicy::Stall, E, true),
+    ] {
+        let mut sim = blocked(policy);
+        sim.step();
+
+        let ant = sim.ant();
+        assert_eq!((ant.pos_x, ant.pos_y), (0, 0), "{:?}", policy);
+        assert_eq!(ant.facing, facing, "{:?}", policy);
+        assert_eq!(ant.stalled, stalled, "{:?}", policy);
+        assert_eq!(sim.cells().get(0, 0), 1);
+        assert_eq!(sim.cells().get(1, 0), OBSTACLE);
+    }
+}
+
+#[test]
+fn ants_never_enter_obstacles() {
+    let mut sim = Simulation::with_seed("LLRR", 40, 3);
+    sim.set_obstacles(ObstaclePolicy::Right);
+
+    // A box the ant starts inside.
+    for i in -15..15 {
+        for &(x, y) in &[(i, -15), (i, 14), (-15, i), (14, i)] {
+            sim.paint(x, y, OBSTACLE);
+        }
+    }
+
+    sim.advance(50_000);
+
+    assert_eq!(sim.iterations(), 50_000);
+    assert!(!sim.is_stalled());
+
+    for i in -15..15 {
+        for &(x, y) in &[(i, -15), (i, 14), (-15, i), (14, i)] {
+            assert_eq!(sim.cells().get(x, y), OBSTACLE);
+        }
+    }
+
+    assert_eq!(sim.cells().get(-20, -20), BLANK);
+}
+
+#[test]
+fn obstacles_can_be_erased() {
+    let mut sim = blocked(ObstaclePolicy::Reverse);
+    sim.paint(1, 0, BLANK);
+    sim.step();
+
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (1, 0));
+}
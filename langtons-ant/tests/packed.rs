@@ -0,0 +1,145 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{GridBackend, GridStorage, PackedGrid, Simulation, BLANK, OBSTACLE};
+
+// Do two simulations have the same cells?
+fn same_cells(a: &Simulation, b: &Simulation) -> bool {
+    let extent = a.cells().extent();
+
+    extent == b.cells().extent()
+        && (extent.y..extent.y + extent.height as i64).all(|y| {
+            (extent.x..extent.x + extent.width as i64)
+                .all(|x| a.cells().get(x, y) == b.cells().get(x, y))
+        })
+}
+
+#[test]
+fn packed_cells_hold_every_code() {
+    for &bits in &[4, 8] {
+        // Three rows of five cells, from (-2, -1) to (2, 1).
+        let mut grid = PackedGrid::new(3, 5, bits).unwrap();
+        assert_eq!(grid.get(2, 1), BLANK);
+
+        let max = PackedGrid::max_colours(bits);
+        let values = [0, 1, max - 1, OBSTACLE, BLANK];
+
+        for (i, &value) in values.iter().enumerate() {
+            grid.set(i as i64 - 2, 0, value);
+        }
+
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(grid.get(i as i64 - 2, 0), value);
+        }
+
+        // Neighbours sharing a byte are left alone.
+        assert_eq!(grid.get(-2, 1), BLANK);
+        assert_eq!(grid.get(2, -1), BLANK);
+    }
+
+    assert_eq!(PackedGrid::new(10, 10, 4).unwrap().len_bytes(), 50);
+    assert_eq!(PackedGrid::new(10, 10, 8).unwrap().len_bytes(), 100);
+    assert!(PackedGrid::new(10, 10, 6).is_err());
+}
+
+#[test]
+fn packed_runs_match_dense_runs() {
+    for &bits in &[4, 8] {
+        let mut dense = Simulation::with_seed("RRLLLRLLLRRR", 80, 9);
+        let mut packed = dense.clone();
+        packed.pack(bits).unwrap();
+
+        dense.advance(20_000);
+        packed.advance(20_000);
+
+        assert!(matches!(packed.cells(), GridBackend::Packed(_)));
+        assert_eq!(packed.iterations(), dense.iterations());
+        assert!(same_cells(&dense, &packed));
+    }
+}
+
+#[test]
+fn packing_keeps_visited_cells() {
+    let mut sim = Simulation::with_seed("LR", 40, 2);
+    sim.advance(500);
+    sim.paint(3, 3, OBSTACLE);
+
+    let mut packed = sim.clone();
+    packed.pack(4).unwrap();
+    assert!(same_cells(&sim, &packed));
+}
+
+#[test]
+fn packing_checks_the_rule_fits() {
+    let mut sim = Simulation::with_seed("LRLRLRLRLRLRLRL", 20, 1);
+    assert!(sim.pack(4).is_err());
+    assert!(sim.pack(8).is_ok());
+
+    let mut sim = Simulation::with_seed("LR", 20, 1);
+    sim.make_unbounded();
+    assert!(sim.pack(8).is_err());
+}
+
+#[test]
+fn packed_grids_round_trip_through_json() {
+    let mut sim = Simulation::with_seed("LLRR", 30, 4);
+    sim.pack(4).unwrap();
+    sim.advance(1000);
+
+    let path = std::env::temp_dir().join("langtons-ant-packed.json");
+    sim.save(&path).unwrap();
+    let loaded = Simulation::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(matches!(loaded.cells(), GridBackend::Packed(_)));
+    assert!(same_cells(&sim, &loaded));
+}
+
+#[test]
+fn corrupt_packed_grids_fail_to_load() {
+    let mut sim = Simulation::with_seed("LLRR", 30, 4);
+    sim.pack(4).unwrap();
+    sim.advance(1000);
+
+    let path = std::env::temp_dir().join("langtons-ant-packed-corrupt.json");
+    sim.save(&path).unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+    let mut bad_bits = saved.clone();
+    bad_bits["grid"]["bits"] = 5.into();
+
+    let mut short_cells = saved.clone();
+    short_cells["grid"]["cells"]
+        .as_array_mut()
+        .unwrap()
+        .truncate(10);
+
+    let mut too_large = saved;
+    too_large["grid"]["width"] = usize::MAX.into();
+
+    for corrupt in [bad_bits, short_cells, too_large] {
+        std::fs::write(&path, corrupt.to_string()).unwrap();
+        assert!(Simulation::load(&path).is_err());
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
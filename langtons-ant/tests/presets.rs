@@ -0,0 +1,68 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::config::{Config, ConfigFile};
+use langtons_ant::{presets, Palette};
+
+#[test]
+fn presets_are_valid() {
+    for preset in presets::PRESETS {
+        assert!(
+            Config::new(preset.rule, preset.mps, preset.grid, preset.square).is_ok(),
+            "{}",
+            preset.name
+        );
+
+        let colours = Palette::parse(preset.palette).and_then(|p| p.colours(preset.rule.len()));
+        assert!(colours.is_ok(), "{}", preset.name);
+        assert!(!preset.description.is_empty());
+    }
+}
+
+#[test]
+fn presets_are_found_by_unique_names() {
+    for preset in presets::PRESETS {
+        assert_eq!(presets::find(preset.name), Some(preset));
+        assert_eq!(
+            presets::PRESETS
+                .iter()
+                .filter(|p| p.name.eq_ignore_ascii_case(preset.name))
+                .count(),
+            1
+        );
+    }
+
+    assert_eq!(presets::find("HIGHWAY").map(|p| p.rule), Some("RL"));
+    assert_eq!(presets::find("no-such-preset"), None);
+}
+
+#[test]
+fn settings_are_layered_over_presets() {
+    let preset = presets::find("highway").unwrap();
+    let file = ConfigFile::parse("grid = 200\nseed = 7").unwrap();
+    let layered = file.or(preset.settings());
+
+    assert_eq!(layered.rule.as_deref(), Some("RL"));
+    assert_eq!(layered.grid, Some(200));
+    assert_eq!(layered.seed, Some(7));
+    assert_eq!(layered.palette.as_deref(), Some(preset.palette));
+    assert_eq!(layered.square, Some(preset.square));
+}
@@ -0,0 +1,224 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::history::History;
+use langtons_ant::replay::{MoveLog, Replay};
+use langtons_ant::{golly, Boundary, CollisionPolicy, Facing, GridStorage, Simulation};
+use std::path::PathBuf;
+
+//-----------------------------------------------------------------------------
+// Check two simulations hold the same grid and ants.
+fn assert_same(a: &Simulation, b: &Simulation) {
+    // Sparse grids keep cells set back to blank, so compare over both.
+    let (p, q) = (a.cells().extent(), b.cells().extent());
+    let (x0, y0) = (p.x.min(q.x), p.y.min(q.y));
+    let x1 = (p.x + p.width as i64).max(q.x + q.width as i64);
+    let y1 = (p.y + p.height as i64).max(q.y + q.height as i64);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            assert_eq!(
+                a.cells().get(x, y),
+                b.cells().get(x, y),
+                "cell {}, {}",
+                x,
+                y
+            );
+        }
+    }
+
+    assert_eq!(a.ants().len(), b.ants().len());
+
+    for (p, q) in a.ants().iter().zip(b.ants()) {
+        assert_eq!((p.pos_x, p.pos_y), (q.pos_x, q.pos_y));
+        assert_eq!(p.facing, q.facing);
+        assert_eq!(p.state, q.state);
+        assert_eq!(p.iterations, q.iterations);
+        assert_eq!(p.stalled, q.stalled);
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "langtons-ant-test-{}-{}.antlog",
+        name,
+        std::process::id()
+    ))
+}
+
+//-----------------------------------------------------------------------------
+// Log a run, made by the given function, and check playing it back forwards
+// reaches the same state and playing it backwards returns to the start.
+fn check_replay(name: &str, mut sim: Simulation, run: impl Fn(&mut Simulation, &mut MoveLog)) {
+    let path = temp_path(name);
+    let start = serde_json::to_string(&sim).unwrap();
+
+    let mut log = MoveLog::create(&path, &mut sim).unwrap();
+    run(&mut sim, &mut log);
+    log.write(&sim.take_changes()).unwrap();
+    log.finish().unwrap();
+
+    let (mut replayed, mut replay) = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let initial: Simulation = serde_json::from_str(&start).unwrap();
+    assert_same(&initial, &replayed);
+
+    while replay.forward(&mut replayed) {}
+    assert_eq!(replay.position(), replay.len());
+    assert_same(&sim, &replayed);
+
+    replay.seek(&mut replayed, replay.len() / 2);
+    replay.seek(&mut replayed, replay.len());
+    assert_same(&sim, &replayed);
+
+    while replay.back(&mut replayed) {}
+    assert_eq!(replay.position(), 0);
+    assert_same(&initial, &replayed);
+}
+
+#[test]
+fn moves_replay_forwards_and_backwards() {
+    let mut sim = Simulation::with_seed("LRRL", 40, 3);
+    sim.advance(500);
+
+    check_replay("moves", sim, |sim, log| {
+        for _ in 0..20 {
+            sim.advance(500);
+            log.write(&sim.take_changes()).unwrap();
+        }
+    });
+}
+
+#[test]
+fn edits_and_extra_ants_replay() {
+    let sim = Simulation::with_seed("RL", 30, 8);
+
+    check_replay("edits", sim, |sim, log| {
+        sim.advance(100);
+        sim.paint(3, 4, 1);
+        sim.paint(100, 100, 1);
+        assert!(sim.add_ant(5, 5, Facing::E));
+        sim.advance(5000);
+        sim.paint(3, 4, langtons_ant::BLANK);
+        log.write(&sim.take_changes()).unwrap();
+        sim.advance(5000);
+    });
+}
+
+#[test]
+fn stalls_bounces_and_turmites_replay() {
+    let sim = Simulation::with_seed("RL", 12, 1);
+
+    check_replay("stall", sim, |sim, _| {
+        sim.advance(2000);
+        assert!(sim.is_stalled());
+    });
+
+    let mut sim = Simulation::with_seed("LLRR", 12, 1);
+    sim.set_boundary(Boundary::Bounce);
+    check_replay("bounce", sim, |sim, _| sim.advance(3000));
+
+    let mut sim =
+        Simulation::turmite_with_seed(golly::parse_turmite("Turmite_181181121010").unwrap(), 30, 2);
+    sim.make_unbounded();
+    check_replay("turmite", sim, |sim, _| sim.advance(4000));
+}
+
+#[test]
+fn collisions_replay() {
+    for &policy in &[
+        CollisionPolicy::Swap,
+        CollisionPolicy::Annihilate,
+        CollisionPolicy::Spawn,
+    ] {
+        let mut sim = Simulation::with_seed("RL", 16, 4);
+        sim.set_boundary(Boundary::Wrap);
+        sim.set_collision(policy);
+
+        for i in 0..6 {
+            assert!(sim.add_ant(2 * i - 8, i - 5, Facing::E));
+        }
+
+        check_replay(policy.name(), sim, |sim, _| sim.advance(400));
+    }
+}
+
+#[test]
+fn history_rewinds_exactly() {
+    let mut sim = Simulation::with_seed("RLR", 30, 6);
+    sim.set_boundary(Boundary::Bounce);
+    sim.set_track_changes(true);
+    sim.advance(1000);
+
+    let mut history = History::new(2000);
+    sim.take_changes();
+    let start = serde_json::to_string(&sim).unwrap();
+
+    sim.advance(700);
+    history.record(&mut sim);
+    sim.paint(2, 2, 2);
+    assert!(sim.add_ant(7, 7, Facing::S));
+    sim.advance(600);
+
+    assert_eq!(history.rewind(&mut sim, 10_000), 700 + 2 + 600 * 2);
+    let initial: Simulation = serde_json::from_str(&start).unwrap();
+    assert_same(&initial, &sim);
+    assert!(history.is_empty());
+}
+
+#[test]
+fn history_keeps_the_most_recent_changes() {
+    let mut sim = Simulation::with_seed("LR", 50, 2);
+    sim.set_track_changes(true);
+    let mut history = History::new(100);
+
+    sim.advance(400);
+    history.record(&mut sim);
+    assert_eq!(history.len(), 100);
+
+    let mut expected = Simulation::with_seed("LR", 50, 2);
+    expected.advance(300);
+
+    assert_eq!(history.rewind(&mut sim, 1000), 100);
+    assert_same(&expected, &sim);
+}
+
+#[test]
+fn invalid_logs_are_rejected() {
+    let path = temp_path("invalid");
+
+    std::fs::write(&path, b"not a move log").unwrap();
+    assert!(Replay::load(&path).is_err());
+
+    let mut sim = Simulation::with_seed("RL", 10, 1);
+    let mut log = MoveLog::create(&path, &mut sim).unwrap();
+    sim.advance(10);
+    log.write(&sim.take_changes()).unwrap();
+    log.finish().unwrap();
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.pop();
+    std::fs::write(&path, &bytes).unwrap();
+    assert!(Replay::load(&path).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,113 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::checkpoint::StateDigest;
+use langtons_ant::{Boundary, CollisionPolicy, Facing, Simulation, OBSTACLE};
+
+#[test]
+fn reversing_returns_to_earlier_states() {
+    let mut sim = Simulation::with_seed("RLLR", 80, 1);
+    sim.advance(3000);
+    let earlier = StateDigest::of(&sim);
+    sim.advance(5000);
+
+    assert!(sim.reversible().is_ok());
+
+    for _ in 0..5000 {
+        assert!(sim.unstep());
+    }
+
+    assert_eq!(sim.iterations(), 3000);
+    assert_eq!(StateDigest::of(&sim), earlier);
+}
+
+#[test]
+fn runs_reverse_to_a_blank_start() {
+    let mut sim = Simulation::with_seed("LURN", 60, 2);
+    let start = (sim.ant().pos_x, sim.ant().pos_y);
+    sim.advance(2000);
+
+    while sim.unstep() {}
+
+    assert_eq!(sim.iterations(), 0);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), start);
+    assert_eq!(sim.ant().facing, Facing::N);
+    assert_eq!(
+        StateDigest::of(&sim),
+        StateDigest::of(&Simulation::with_seed("LURN", 60, 2))
+    );
+}
+
+#[test]
+fn wrapped_and_stalled_moves_reverse() {
+    let mut sim = Simulation::with_seed("RL", 20, 3);
+    sim.set_boundary(Boundary::Wrap);
+    sim.advance(4000);
+    let wrapped = StateDigest::of(&sim);
+    sim.advance(1000);
+
+    for _ in 0..1000 {
+        assert!(sim.unstep());
+    }
+    assert_eq!(StateDigest::of(&sim), wrapped);
+
+    let mut sim = Simulation::with_seed("RL", 20, 4);
+    sim.advance(100_000);
+    assert!(sim.is_stalled());
+    let stalled_at = sim.iterations();
+
+    assert!(sim.unstep());
+    assert!(!sim.is_stalled());
+    assert_eq!(sim.iterations(), stalled_at - 1);
+}
+
+#[test]
+fn several_ants_reverse_in_turn() {
+    let mut sim = Simulation::with_seed("RL", 60, 5);
+    sim.add_ant(1, 0, Facing::E);
+    sim.add_ant(-5, -2, Facing::S);
+    sim.advance(1500);
+    let earlier = StateDigest::of(&sim);
+    sim.advance(1500);
+
+    for _ in 0..1500 {
+        assert!(sim.unstep());
+    }
+    assert_eq!(StateDigest::of(&sim), earlier);
+}
+
+#[test]
+fn irreversible_runs_are_refused() {
+    assert!(Simulation::new("R^N", 30).reversible().is_err());
+
+    let mut sim = Simulation::new("RL", 30);
+    sim.set_boundary(Boundary::Bounce);
+    assert!(sim.reversible().is_err());
+
+    let mut sim = Simulation::new("RL", 30);
+    sim.paint(3, 3, OBSTACLE);
+    assert!(sim.reversible().is_err());
+
+    let mut sim = Simulation::new("RL", 30);
+    sim.add_ant(3, 3, Facing::E);
+    sim.set_collision(CollisionPolicy::Swap);
+    assert!(sim.reversible().is_err());
+}
@@ -0,0 +1,241 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{
+    rle, Boundary, Direction, Facing, GridStorage, ObstaclePolicy, Simulation, Transition, Turmite,
+    BLANK, OBSTACLE,
+};
+
+//-----------------------------------------------------------------------------
+// Check two simulations hold the same grid, ants and settings.
+fn assert_same(a: &Simulation, b: &Simulation) {
+    let extent = a.cells().extent();
+    assert_eq!(extent, b.cells().extent());
+
+    for y in extent.y..extent.y + extent.height as i64 {
+        for x in extent.x..extent.x + extent.width as i64 {
+            assert_eq!(
+                a.cells().get(x, y),
+                b.cells().get(x, y),
+                "cell {}, {}",
+                x,
+                y
+            );
+        }
+    }
+
+    assert_eq!(a.ants().len(), b.ants().len());
+
+    for (p, q) in a.ants().iter().zip(b.ants()) {
+        assert_eq!((p.pos_x, p.pos_y), (q.pos_x, q.pos_y));
+        assert_eq!(p.facing, q.facing);
+        assert_eq!(p.iterations, q.iterations);
+        assert_eq!(p.stalled, q.stalled);
+    }
+
+    let hex = |s: &Simulation| s.colours().iter().map(|c| c.to_hex()).collect::<Vec<_>>();
+    assert_eq!(hex(a), hex(b));
+    assert_eq!(a.rule(), b.rule());
+    assert_eq!(a.seed(), b.seed());
+    assert_eq!(a.size(), b.size());
+    assert_eq!(a.boundary(), b.boundary());
+    assert_eq!(a.obstacles(), b.obstacles());
+}
+
+//-----------------------------------------------------------------------------
+// Encode and decode a simulation, checking nothing is lost and that both
+// carry on identically.
+fn round_trip(mut sim: Simulation) {
+    let text = rle::encode(&sim).unwrap();
+    let mut loaded = rle::parse(&text).unwrap();
+    assert_same(&sim, &loaded);
+
+    for _ in 0..1000 {
+        sim.step();
+        loaded.step();
+    }
+    assert_same(&sim, &loaded);
+}
+
+#[test]
+fn dense_grid_round_trips() {
+    let mut sim = Simulation::with_seed("RL", 80, 7);
+    sim.advance(5000);
+    round_trip(sim);
+}
+
+#[test]
+fn empty_grid_round_trips() {
+    round_trip(Simulation::with_seed("LLRR", 20, 1));
+}
+
+#[test]
+fn sparse_grid_with_negative_coordinates_round_trips() {
+    let mut sim = Simulation::with_seed("LLRR", 10, 3);
+    sim.make_unbounded();
+    assert!(sim.add_ant(2, 2, Facing::W));
+    sim.advance(20000);
+    assert!(sim.cells().extent().x < 0);
+    round_trip(sim);
+}
+
+#[test]
+fn bounce_boundary_and_stalled_ants_round_trip() {
+    let mut sim = Simulation::with_seed("RL", 20, 5);
+    sim.advance(5000);
+    assert!(sim.is_stalled());
+    round_trip(sim);
+
+    let mut sim = Simulation::with_seed("LRRRRRLLR", 30, 5);
+    sim.set_boundary(Boundary::Bounce);
+    sim.advance(5000);
+    round_trip(sim);
+}
+
+#[test]
+fn many_colours_use_prefixed_states() {
+    let rule: String = "LR".repeat(20);
+    let mut sim = Simulation::with_seed(&rule, 40, 9);
+
+    for x in 0..40 {
+        sim.paint(x - 20, 0, x as usize);
+    }
+
+    let text = rle::encode(&sim).unwrap();
+    assert!(text.contains("pA"));
+    round_trip(sim);
+}
+
+#[test]
+fn obstacles_round_trip() {
+    let mut sim = Simulation::with_seed("LLRR", 30, 2);
+    sim.set_obstacles(ObstaclePolicy::Left);
+
+    for x in -10..10 {
+        sim.paint(x, 8, OBSTACLE);
+    }
+
+    sim.advance(2000);
+
+    let text = rle::encode(&sim).unwrap();
+    assert!(text.contains("yO"));
+    round_trip(sim);
+}
+
+#[test]
+fn lines_are_wrapped() {
+    let mut sim = Simulation::with_seed("LRN", 200, 2);
+
+    for x in 0..200 {
+        sim.paint(x - 100, 5, x as usize % 3);
+    }
+
+    let text = rle::encode(&sim).unwrap();
+    assert!(text.lines().all(|l| l.len() <= 70 || l.starts_with('#')));
+    round_trip(sim);
+}
+
+#[test]
+fn golly_pattern_is_read() {
+    let sim = rle::parse("#N Glider\nx = 3, y = 3, rule = RL\nbo$2bo$3o!\n").unwrap();
+
+    assert_eq!(sim.size(), 3);
+    assert_eq!(sim.cells().get(0, -1), 0);
+    assert_eq!(sim.cells().get(1, 0), 0);
+    assert_eq!(sim.cells().get(-1, 1), 0);
+    assert_eq!(sim.cells().get(-1, -1), BLANK);
+    assert_eq!(sim.ants().len(), 1);
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    assert!(rle::parse("").is_err());
+    assert!(rle::parse("x = 2, y = 2, rule = RL\n3B!").is_err());
+    assert!(rle::parse("x = 2, y = 2, rule = RL\nA?!").is_err());
+    assert!(rle::parse("#C grid dense 2\nx = 2, y = 2, rule = RL\n3A!").is_err());
+}
+
+#[test]
+fn ants_outside_the_grid_are_rejected() {
+    let pattern = "x = 2, y = 2, rule = RL\n2A!";
+
+    for ant in &["1000 0", "0 -3", "2 0"] {
+        let text = format!("#C grid dense 4\n#C ant {} N 0 0 0\n{}", ant, pattern);
+        assert!(rle::parse(&text).is_err(), "ant at {} was loaded", ant);
+    }
+
+    let text = format!("#C grid dense 4\n#C ant 1 -2 N 0 0 0\n{}", pattern);
+    let sim = rle::parse(&text).unwrap();
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (1, -2));
+}
+
+#[test]
+fn turmites_round_trip_with_golly_rule_names() {
+    let table = vec![
+        Transition {
+            state: 0,
+            colour: 0,
+            write: 1,
+            turn: Direction::R,
+            next: 1,
+        },
+        Transition {
+            state: 0,
+            colour: 1,
+            write: 1,
+            turn: Direction::L,
+            next: 0,
+        },
+        Transition {
+            state: 1,
+            colour: 0,
+            write: 0,
+            turn: Direction::N,
+            next: 0,
+        },
+        Transition {
+            state: 1,
+            colour: 1,
+            write: 0,
+            turn: Direction::N,
+            next: 1,
+        },
+    ];
+    let mut sim = Simulation::turmite_with_seed(Turmite::new(table).unwrap(), 60, 4);
+    sim.advance(3000);
+
+    let text = rle::encode(&sim).unwrap();
+    assert!(text.contains("rule = Turmite_121180010011"));
+    round_trip(sim);
+}
+
+#[test]
+fn files_round_trip() {
+    let mut sim = Simulation::with_seed("RLR", 50, 11);
+    sim.advance(3000);
+
+    let path = std::env::temp_dir().join(format!("langtons-ant-test-{}.rle", std::process::id()));
+    rle::save(&sim, &path).unwrap();
+    let loaded = rle::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_same(&sim, &loaded);
+}
@@ -0,0 +1,181 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::config::validate_rule;
+use langtons_ant::rule::{Rule, RuleParseError, MAX_RULE_LENGTH};
+use langtons_ant::{Direction, Facing, Simulation};
+
+#[test]
+fn rules_parse_to_their_directions() {
+    let rule = Rule::parse("LRUN^S").unwrap();
+
+    assert_eq!(
+        rule.directions(),
+        &[
+            Direction::L,
+            Direction::R,
+            Direction::U,
+            Direction::N,
+            Direction::Face(Facing::S)
+        ]
+    );
+    assert_eq!(rule.len(), 5);
+    assert_eq!(rule.to_string(), "LRUN^S");
+}
+
+#[test]
+fn lower_case_and_separators_are_accepted() {
+    let expected = Rule::parse("RLLR^N").unwrap();
+
+    for input in [
+        "rllr^n",
+        "R L L R ^N",
+        "r-l-l-r-^n",
+        "R,L,L,R,^N",
+        " r_l/l|r ^n ",
+    ] {
+        assert_eq!(Rule::parse(input), Ok(expected.clone()), "{}", input);
+    }
+
+    assert_eq!(validate_rule("r-l").unwrap(), "RL");
+}
+
+#[test]
+fn errors_give_the_offending_character_and_position() {
+    let error = Rule::parse("RL-LX").unwrap_err();
+
+    assert_eq!(
+        error,
+        RuleParseError::InvalidCharacter {
+            character: 'X',
+            position: 5
+        }
+    );
+    assert_eq!(error.position(), Some(5));
+    assert!(error.to_string().contains("'X' at position 5"));
+
+    assert_eq!(
+        Rule::parse("rl?"),
+        Err(RuleParseError::InvalidCharacter {
+            character: '?',
+            position: 3
+        })
+    );
+}
+
+#[test]
+fn a_facing_must_follow_a_caret() {
+    assert_eq!(
+        Rule::parse("RL^"),
+        Err(RuleParseError::MissingFacing { position: 3 })
+    );
+    assert_eq!(
+        Rule::parse("R^QL"),
+        Err(RuleParseError::MissingFacing { position: 2 })
+    );
+}
+
+#[test]
+fn empty_rules_are_rejected() {
+    assert_eq!(Rule::parse(""), Err(RuleParseError::Empty));
+    assert_eq!(Rule::parse(" - , "), Err(RuleParseError::Empty));
+}
+
+#[test]
+fn rules_longer_than_the_limit_are_rejected() {
+    assert!(Rule::parse(&"RL".repeat(MAX_RULE_LENGTH / 2)).is_ok());
+    assert_eq!(
+        Rule::parse(&"R".repeat(MAX_RULE_LENGTH + 1)),
+        Err(RuleParseError::TooLong {
+            length: MAX_RULE_LENGTH + 1,
+            max: MAX_RULE_LENGTH
+        })
+    );
+
+    // Separators don't count towards the length.
+    assert!(Rule::parse_with_max_length("R-L-L-R", 4).is_ok());
+    assert_eq!(
+        Rule::parse_with_max_length("RLLRR", 4),
+        Err(RuleParseError::TooLong { length: 5, max: 4 })
+    );
+}
+
+#[test]
+fn rules_display_in_standard_form_and_parse_back() {
+    let rule: Rule = "r l ^e".parse().unwrap();
+
+    assert_eq!(rule.to_string(), "RL^E");
+    assert_eq!(rule.to_string().parse::<Rule>(), Ok(rule));
+    assert!("RLX".parse::<Rule>().is_err());
+}
+
+#[test]
+fn helper_constructors_build_the_expected_steps() {
+    assert_eq!(Rule::langton().to_string(), "RL");
+    assert_eq!(Rule::from_bits(0b1001, 4).to_string(), "RLLR");
+    assert_eq!(
+        Rule::from(vec![Direction::L, Direction::U]).to_string(),
+        "LU"
+    );
+    assert_eq!(Rule::langton().turn(1), Direction::L);
+}
+
+#[test]
+fn rules_are_equivalent_up_to_rotation_and_reflection() {
+    let llrr = Rule::parse("LLRR").unwrap();
+
+    assert_eq!(llrr.rotated(1).to_string(), "LRRL");
+    assert_eq!(llrr.reflected().to_string(), "RRLL");
+
+    for other in ["RRLL", "RLLR", "LRRL", "LLRR"] {
+        let other = Rule::parse(other).unwrap();
+        assert_eq!(other.canonical(), llrr);
+        assert!(other.is_equivalent(&llrr));
+    }
+
+    assert_eq!(Rule::langton().canonical().to_string(), "LR");
+    assert_eq!(Rule::parse("R^E").unwrap().canonical().to_string(), "L^W");
+    assert!(!llrr.is_equivalent(&Rule::parse("LRLR").unwrap()));
+    assert!(!llrr.is_equivalent(&Rule::parse("LLR").unwrap()));
+}
+
+#[test]
+fn ants_hold_their_rule() {
+    let sim = Simulation::with_seed("LLRR", 20, 1);
+
+    assert_eq!(sim.ant().rule, Rule::parse("LLRR").unwrap());
+    assert_eq!(sim.ant().rule.len(), sim.colours().len());
+}
+
+#[test]
+fn simulations_parse_rules_the_same_way() {
+    let mut sim = Simulation::with_seed("r-l", 20, 1);
+    assert_eq!(sim.rule(), "RL");
+    assert_eq!(sim.num_colours(), 2);
+
+    sim.set_rule("l, l, r, r").unwrap();
+    assert_eq!(sim.rule(), "LLRR");
+
+    let error = sim.set_rule("llrx").unwrap_err();
+    assert!(error.contains("'x' at position 4"), "{}", error);
+
+    assert_eq!(Simulation::with_seed("RLX", 20, 1).num_colours(), 0);
+}
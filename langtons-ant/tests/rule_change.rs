@@ -0,0 +1,104 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{GridStorage, Simulation, Zone, BLANK, OBSTACLE};
+
+#[test]
+fn changing_the_rule_keeps_the_grid() {
+    let mut sim = Simulation::with_seed("LLRR", 60, 3);
+    sim.advance(2000);
+
+    let colours = sim.colours().to_vec();
+    let ant = sim.ant().clone();
+    let mut before = Vec::new();
+    sim.cells()
+        .for_each_visited(|x, y, c| before.push((x, y, c)));
+
+    sim.set_rule("LLRRLR").unwrap();
+
+    assert_eq!(sim.rule(), "LLRRLR");
+    assert_eq!(sim.num_colours(), 6);
+    assert_eq!(sim.iterations(), 2000);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (ant.pos_x, ant.pos_y));
+
+    // The first four colours are kept.
+    for (kept, old) in sim.colours().iter().zip(colours.iter()) {
+        assert!(kept.compare(old));
+    }
+
+    for (x, y, c) in before {
+        assert_eq!(sim.cells().get(x, y), c);
+    }
+
+    // The longer rule runs on from there.
+    sim.advance(2000);
+    let mut highest = 0;
+    sim.cells()
+        .for_each_visited(|_, _, c| highest = highest.max(c));
+    assert_eq!(highest, 5);
+}
+
+#[test]
+fn states_past_a_shorter_rule_wrap_round() {
+    let mut sim = Simulation::with_seed("RRLLLRLLLRRR", 60, 5);
+    sim.advance(5000);
+    sim.paint(1, 1, OBSTACLE);
+
+    let mut before = Vec::new();
+    sim.cells()
+        .for_each_visited(|x, y, c| before.push((x, y, c)));
+
+    sim.set_rule("RL").unwrap();
+    assert_eq!(sim.num_colours(), 2);
+
+    for (x, y, c) in before {
+        let expected = if c == OBSTACLE { OBSTACLE } else { c % 2 };
+        assert_eq!(sim.cells().get(x, y), expected);
+    }
+
+    assert_eq!(sim.cells().get(59, 59), BLANK);
+    sim.advance(1000);
+}
+
+#[test]
+fn only_plain_rules_can_be_changed_to() {
+    let mut sim = Simulation::with_seed("LR", 40, 1);
+    assert!(sim.set_rule("LX").is_err());
+    assert!(sim.set_rule("").is_err());
+    assert!(sim.set_rule("L:1,R:1|R").is_err());
+
+    let mut sim = Simulation::with_seed("LR", 40, 1);
+    sim.add_zone(Zone {
+        x: 0,
+        y: 0,
+        width: 10,
+        height: 10,
+        rule: langtons_ant::rule::Rule::langton(),
+    })
+    .unwrap();
+    assert!(sim.set_rule("LLR").is_err());
+    assert!(sim.set_rule("RR").is_ok());
+
+    let mut sim = Simulation::with_seed("LR", 40, 1);
+    sim.pack(4).unwrap();
+    assert!(sim.set_rule(&"LR".repeat(8)).is_err());
+    assert!(sim.set_rule("LRL").is_ok());
+}
@@ -0,0 +1,93 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{Extent, GridStorage, Simulation};
+
+#[test]
+fn a_stored_copy_resumes_exactly_where_it_was_taken() {
+    let mut sim = Simulation::with_seed("RLR", 60, 3);
+    sim.advance(5_000);
+    let stored = sim.clone();
+
+    sim.advance(5_000);
+    let mut restored = stored.clone();
+    assert_eq!(restored.iterations(), 5_000);
+    restored.advance(5_000);
+
+    assert_eq!(restored.iterations(), sim.iterations());
+    assert_eq!(
+        (
+            restored.ant().pos_x,
+            restored.ant().pos_y,
+            restored.ant().facing
+        ),
+        (sim.ant().pos_x, sim.ant().pos_y, sim.ant().facing)
+    );
+
+    for y in -30..30 {
+        for x in -30..30 {
+            assert_eq!(restored.cells().get(x, y), sim.cells().get(x, y));
+        }
+    }
+}
+
+#[test]
+fn saves_from_before_centred_coordinates_keep_their_origin() {
+    let mut sim = Simulation::with_seed("RL", 20, 4);
+    sim.advance(300);
+
+    // Older saves put the grid's top left cell at 0, 0 and give no offset.
+    let mut json = serde_json::to_value(&sim).unwrap();
+    let grid = json["grid"].as_object_mut().unwrap();
+    assert_eq!(grid.remove("left"), Some(serde_json::json!(-10)));
+    assert_eq!(grid.remove("top"), Some(serde_json::json!(-10)));
+    json["ants"][0]["pos_x"] = (sim.ant().pos_x + 10).into();
+    json["ants"][0]["pos_y"] = (sim.ant().pos_y + 10).into();
+
+    let path = std::env::temp_dir().join("langtons-ant-old-origin.json");
+    std::fs::write(&path, json.to_string()).unwrap();
+    let mut loaded = Simulation::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+        loaded.cells().extent(),
+        Extent {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 20
+        }
+    );
+
+    for y in 0..20 {
+        for x in 0..20 {
+            assert_eq!(loaded.cells().get(x, y), sim.cells().get(x - 10, y - 10));
+        }
+    }
+
+    // The ant carries on as it would have.
+    loaded.advance(300);
+    sim.advance(300);
+    assert_eq!(
+        (loaded.ant().pos_x - 10, loaded.ant().pos_y - 10),
+        (sim.ant().pos_x, sim.ant().pos_y)
+    );
+}
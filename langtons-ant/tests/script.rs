@@ -0,0 +1,122 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(feature = "script")]
+
+use langtons_ant::script::{Script, ScriptAction};
+use langtons_ant::{Facing, GridStorage, Simulation, BLANK, OBSTACLE};
+
+#[test]
+fn scripts_need_an_on_step_function() {
+    let err = Script::from_source("fn setup() {}", 1).err().unwrap();
+    assert!(err.contains("on_step"));
+
+    assert!(Script::from_source("fn on_step( {", 1).is_err());
+    assert!(Script::from_source("fn on_step() {}", 1).is_ok());
+}
+
+#[test]
+fn the_simulation_is_only_available_in_on_step() {
+    let err = Script::from_source("let n = iteration(); fn on_step() {}", 1)
+        .err()
+        .unwrap();
+    assert!(err.contains("on_step"));
+}
+
+#[test]
+fn scripts_are_called_every_k_iterations() {
+    let source = r#"
+        fn on_step() {
+            set_cell(iteration(), -40, 1);
+        }
+    "#;
+
+    let mut script = Script::from_source(source, 10).unwrap();
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+
+    assert_eq!(script.run(&mut sim), Ok(ScriptAction::Continue));
+    assert_eq!(script.remaining(&sim), 10);
+
+    for _ in 0..30 {
+        sim.step();
+        script.run(&mut sim).unwrap();
+    }
+
+    let painted: Vec<i64> = (0..=30).filter(|&x| sim.cells().get(x, -40) == 1).collect();
+    assert_eq!(painted, vec![0, 10, 20, 30]);
+}
+
+#[test]
+fn scripts_read_and_change_the_grid_and_ants() {
+    let source = r#"
+        fn on_step() {
+            if cell(30, 30) == BLANK && ant_facing() == "N" {
+                set_cell(30, 30, 1);
+                set_cell(-50, -50, OBSTACLE);
+                teleport(10, 20);
+                set_facing("E");
+            }
+        }
+    "#;
+
+    let mut script = Script::from_source(source, 1).unwrap();
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    script.run(&mut sim).unwrap();
+
+    assert_eq!(sim.cells().get(30, 30), 1);
+    assert_eq!(sim.cells().get(-50, -50), OBSTACLE);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (10, 20));
+    assert_eq!(sim.ant().facing, Facing::E);
+    assert_eq!(sim.iterations(), 0);
+    assert_eq!(sim.cells().get(10, 20), BLANK);
+}
+
+#[test]
+fn scripts_can_stop_the_run() {
+    let source = r#"
+        fn on_step() {
+            if ant_x() > 2 {
+                stop();
+            }
+        }
+    "#;
+
+    let mut script = Script::from_source(source, 1).unwrap();
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+
+    while script.run(&mut sim) == Ok(ScriptAction::Continue) {
+        sim.step();
+    }
+
+    assert_eq!(sim.ant().pos_x, 3);
+}
+
+#[test]
+fn script_errors_leave_the_simulation_in_place() {
+    let mut script = Script::from_source("fn on_step() { teleport(-55, 0); }", 1).unwrap();
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    sim.advance(100);
+
+    let err = script.run(&mut sim).unwrap_err();
+    assert!(err.contains("(-55, 0)"));
+    assert_eq!(sim.iterations(), 100);
+    assert_eq!(sim.size(), 100);
+}
@@ -0,0 +1,131 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::spawn::{Spawn, SpawnFacing, SpawnPosition};
+use langtons_ant::{Facing, Simulation, OBSTACLE};
+
+#[test]
+fn specs_parse() {
+    assert_eq!(
+        SpawnPosition::parse("10, 20"),
+        Ok(SpawnPosition::Cell(10, 20))
+    );
+    assert_eq!(SpawnPosition::parse("Random"), Ok(SpawnPosition::Random));
+    assert!(SpawnPosition::parse("10").is_err());
+    assert!(SpawnPosition::parse("a,b").is_err());
+
+    assert_eq!(SpawnFacing::parse("e"), Ok(SpawnFacing::Fixed(Facing::E)));
+    assert_eq!(SpawnFacing::parse("random"), Ok(SpawnFacing::Random));
+    assert!(SpawnFacing::parse("NE").is_err());
+    assert!(SpawnFacing::parse("X").is_err());
+}
+
+#[test]
+fn the_first_ant_moves_to_its_start() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    let spawn = Spawn {
+        positions: vec![SpawnPosition::Cell(5, 7)],
+        facings: vec![SpawnFacing::Fixed(Facing::E)],
+    };
+
+    spawn.apply(&mut sim).unwrap();
+    assert_eq!(sim.ants().len(), 1);
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), (5, 7));
+    assert_eq!(sim.ant().facing, Facing::E);
+}
+
+#[test]
+fn a_facing_alone_keeps_the_ant_centred() {
+    let mut sim = Simulation::with_seed("RL", 50, 2);
+    let centre = (sim.ant().pos_x, sim.ant().pos_y);
+    let spawn = Spawn {
+        positions: Vec::new(),
+        facings: vec![SpawnFacing::Fixed(Facing::S)],
+    };
+
+    spawn.apply(&mut sim).unwrap();
+    assert_eq!((sim.ant().pos_x, sim.ant().pos_y), centre);
+    assert_eq!(sim.ant().facing, Facing::S);
+}
+
+#[test]
+fn later_starts_add_ants_taking_the_last_facing() {
+    let mut sim = Simulation::with_seed("RL", 50, 3);
+    let spawn = Spawn {
+        positions: vec![
+            SpawnPosition::Cell(1, 1),
+            SpawnPosition::Cell(2, 2),
+            SpawnPosition::Cell(3, 3),
+        ],
+        facings: vec![SpawnFacing::Fixed(Facing::E), SpawnFacing::Fixed(Facing::W)],
+    };
+
+    spawn.apply(&mut sim).unwrap();
+    let ants: Vec<_> = sim
+        .ants()
+        .iter()
+        .map(|a| (a.pos_x, a.pos_y, a.facing))
+        .collect();
+
+    assert_eq!(
+        ants,
+        vec![(1, 1, Facing::E), (2, 2, Facing::W), (3, 3, Facing::W)]
+    );
+}
+
+#[test]
+fn random_starts_follow_the_seed() {
+    let spawn = Spawn {
+        positions: vec![SpawnPosition::Random, SpawnPosition::Random],
+        facings: vec![SpawnFacing::Random],
+    };
+
+    let place = |seed| {
+        let mut sim = Simulation::with_seed("RL", 50, seed);
+        spawn.apply(&mut sim).unwrap();
+        sim.ants()
+            .iter()
+            .map(|a| (a.pos_x, a.pos_y, a.facing))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(place(4), place(4));
+    assert_ne!(place(4), place(5));
+
+    for (x, y, _) in place(6) {
+        assert!((-25..25).contains(&x) && (-25..25).contains(&y));
+    }
+}
+
+#[test]
+fn starts_off_the_grid_or_on_obstacles_are_rejected() {
+    let mut sim = Simulation::with_seed("RL", 50, 7);
+    sim.paint(4, 4, OBSTACLE);
+
+    for position in [SpawnPosition::Cell(50, 0), SpawnPosition::Cell(4, 4)] {
+        let spawn = Spawn {
+            positions: vec![position],
+            facings: Vec::new(),
+        };
+
+        assert!(spawn.apply(&mut sim).is_err());
+    }
+}
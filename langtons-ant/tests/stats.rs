@@ -0,0 +1,121 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::stats::{Stats, StatsHistory};
+use langtons_ant::{Extent, Simulation};
+
+#[test]
+fn stats_describe_the_pattern() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let empty = Stats::measure(&sim);
+    assert_eq!(empty.bounds, None);
+    assert_eq!(empty.visited(), 0);
+    assert_eq!(empty.csv_row(), "0,,,0,0,0,0,0.000,none,0.000,0.000,0,0");
+
+    sim.step();
+    sim.step();
+    sim.paint(-40, -30, 0);
+
+    // The ant turns right onto (1, 0) then right again onto (1, 1).
+    let stats = Stats::measure(&sim);
+    assert_eq!(stats.iterations, 2);
+    assert_eq!(stats.counts, vec![1, 2]);
+    assert_eq!(
+        stats.bounds,
+        Some(Extent {
+            x: -40,
+            y: -30,
+            width: 42,
+            height: 31
+        })
+    );
+    assert_eq!(stats.displacement, (1, 1));
+    assert!((stats.distance() - 2f64.sqrt()).abs() < 1e-9);
+}
+
+#[test]
+fn csv_has_a_column_per_colour() {
+    let header = Stats::csv_header(3);
+    assert_eq!(
+        header,
+        "iteration,min_x,min_y,width,height,dx,dy,distance,symmetry,entropy,compression,colour_0,colour_1,colour_2"
+    );
+
+    let mut sim = Simulation::with_seed("LRR", 50, 1);
+    sim.advance(1000);
+    let row = Stats::measure(&sim).csv_row();
+    assert_eq!(row.split(',').count(), header.split(',').count());
+}
+
+#[test]
+fn stats_convert_to_json() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    let empty = Stats::measure(&sim).to_json();
+    assert!(empty["bounds"].is_null());
+    assert_eq!(empty["visited"], 0);
+
+    sim.advance(1000);
+    let stats = Stats::measure(&sim);
+    let json = stats.to_json();
+
+    assert_eq!(json["iteration"], 1000);
+    assert_eq!(json["visited"], stats.visited());
+    assert_eq!(json["bounds"]["width"], stats.bounds.unwrap().width);
+    assert_eq!(json["counts"].as_array().unwrap().len(), 2);
+    assert_eq!(json["symmetry"], stats.symmetry.to_string());
+}
+
+#[test]
+fn history_thins_out_to_cover_the_whole_run() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let mut history = StatsHistory::new(10);
+
+    for _ in 0..100 {
+        sim.advance(10);
+        history.record(&Stats::measure(&sim));
+    }
+
+    let samples = history.samples();
+    assert!(samples.len() <= 10);
+    assert!(samples.len() >= 5);
+    assert_eq!(samples[0].iteration, 10);
+    assert!(samples.last().unwrap().iteration >= 900);
+    assert!(samples.windows(2).all(|w| w[0].iteration < w[1].iteration));
+    assert!(samples.iter().all(|s| s.area > 0));
+}
+
+#[test]
+fn history_drops_samples_after_a_reset() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let mut history = StatsHistory::new(10);
+
+    for _ in 0..5 {
+        sim.advance(10);
+        history.record(&Stats::measure(&sim));
+    }
+
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    sim.advance(25);
+    history.record(&Stats::measure(&sim));
+
+    let iterations: Vec<u64> = history.samples().iter().map(|s| s.iteration).collect();
+    assert_eq!(iterations, vec![10, 20, 25]);
+}
@@ -0,0 +1,120 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::stats::Stats;
+use langtons_ant::stop::{Comparison, Metric, StopCondition, StopWatch};
+use langtons_ant::Simulation;
+
+#[test]
+fn stop_conditions_parse_with_or_without_spaces() {
+    let condition = StopCondition::parse("bbox>400").unwrap();
+    assert_eq!(condition.metric, Metric::Bbox);
+    assert_eq!(condition.comparison, Comparison::Greater);
+    assert_eq!(condition.value, 400.0);
+
+    let condition = StopCondition::parse(" Entropy <= 1.5 ").unwrap();
+    assert_eq!(condition.metric, Metric::Entropy);
+    assert_eq!(condition.comparison, Comparison::AtMost);
+    assert_eq!(condition.value, 1.5);
+
+    assert_eq!(
+        StopCondition::parse("iteration>=1_000_000").unwrap().value,
+        1_000_000.0
+    );
+    assert_eq!(
+        StopCondition::parse("visited=10").unwrap().comparison,
+        Comparison::Equal
+    );
+    assert_eq!(
+        StopCondition::parse("width != 3").unwrap().to_string(),
+        "width != 3"
+    );
+
+    assert!(StopCondition::parse("bbox").is_err());
+    assert!(StopCondition::parse("size>4").is_err());
+    assert!(StopCondition::parse("bbox>big").is_err());
+    assert!(StopCondition::parse("bbox>inf").is_err());
+}
+
+#[test]
+fn metrics_are_taken_from_the_stats() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let empty = Stats::measure(&sim);
+    assert_eq!(Metric::Bbox.value(&empty), 0.0);
+    assert_eq!(Metric::Area.value(&empty), 0.0);
+
+    sim.advance(2000);
+    let stats = Stats::measure(&sim);
+    let bounds = stats.bounds.unwrap();
+
+    assert_eq!(Metric::Iteration.value(&stats), 2000.0);
+    assert_eq!(
+        Metric::Bbox.value(&stats),
+        bounds.width.max(bounds.height) as f64
+    );
+    assert_eq!(
+        Metric::Area.value(&stats),
+        (bounds.width * bounds.height) as f64
+    );
+    assert_eq!(Metric::Visited.value(&stats), stats.visited() as f64);
+    assert_eq!(Metric::Distance.value(&stats), stats.distance());
+}
+
+#[test]
+fn the_watch_stops_at_the_first_check_a_condition_holds() {
+    let conditions = vec![
+        StopCondition::parse("iteration>5000").unwrap(),
+        StopCondition::parse("bbox>=20").unwrap(),
+    ];
+    let mut watch = StopWatch::new(conditions, 100);
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+
+    let stopped = loop {
+        if let Some(condition) = watch.observe(&sim) {
+            break condition;
+        }
+
+        let due = watch.remaining(&sim);
+        assert!(due > 0);
+        sim.advance(due);
+    };
+
+    // Checks only happen every 100 iterations, so the run stops at the
+    // same point every time.
+    assert_eq!(stopped.metric, Metric::Bbox);
+    assert_eq!(sim.iterations() % 100, 0);
+    assert!(Metric::Bbox.value(&Stats::measure(&sim)) >= 20.0);
+
+    let mut earlier = Simulation::with_seed("RL", 100, 1);
+    earlier.advance(sim.iterations() - 100);
+    assert!(Metric::Bbox.value(&Stats::measure(&earlier)) < 20.0);
+}
+
+#[test]
+fn a_watch_without_conditions_never_stops() {
+    let mut watch = StopWatch::new(Vec::new(), 1);
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+
+    for _ in 0..100 {
+        sim.step();
+        assert!(watch.observe(&sim).is_none());
+    }
+}
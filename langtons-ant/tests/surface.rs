@@ -0,0 +1,112 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::surface::{CubeSphere, Projection};
+use langtons_ant::{GridStorage, Simulation, BLANK};
+
+// Index of the front face's tile in the net.
+const FRONT: usize = 2;
+
+#[test]
+fn surface_rules_must_be_relative() {
+    assert!(CubeSphere::with_seed("RL", 20, 1).is_ok());
+    assert!(CubeSphere::with_seed("R^N", 20, 1).is_err());
+    assert!(CubeSphere::with_seed("RX", 20, 1).is_err());
+    assert!(CubeSphere::with_seed("RL", 0, 1).is_err());
+}
+
+#[test]
+fn the_front_face_matches_the_2d_ant_until_an_edge() {
+    // The 2D ant stays within 20 cells of its start for its first 500 moves.
+    let size = 60;
+    let mut sphere = CubeSphere::with_seed("RL", size, 4).unwrap();
+    let mut sim = Simulation::with_seed("RL", size, 4);
+
+    sphere.advance(500);
+    for _ in 0..500 {
+        sim.step();
+    }
+
+    assert_eq!(sphere.normal(), [0, 0, 1]);
+    let mut visited = 0;
+    sim.cells().for_each_visited(|_, _, _| visited += 1);
+    assert_eq!(sphere.visited(), visited);
+
+    // The 2D grid is centred on the origin, the face's tiles count from 0.
+    let start = sim.cells().extent();
+    for v in 0..size {
+        for u in 0..size {
+            let cell = sim.cells().get(start.x + u as i64, start.y + v as i64);
+            let expected = if cell == BLANK { None } else { Some(cell) };
+            assert_eq!(sphere.get(FRONT, u, v), expected, "cell {}, {}", u, v);
+        }
+    }
+}
+
+#[test]
+fn walking_straight_circles_the_cube() {
+    // With no turns the ant goes over the top, around the back and under
+    // the bottom, back to where it started after four faces.
+    let size = 10;
+    let mut sphere = CubeSphere::with_seed("N", size, 1).unwrap();
+    let start = (sphere.position(), sphere.heading(), sphere.normal());
+
+    sphere.advance(6);
+    assert_eq!(sphere.normal(), [0, 1, 0]);
+    assert_eq!(sphere.heading(), [0, 0, -1]);
+
+    sphere.advance(4 * size as u64 - 6);
+    assert_eq!(
+        (sphere.position(), sphere.heading(), sphere.normal()),
+        start
+    );
+    assert_eq!(sphere.visited(), 4 * size);
+}
+
+#[test]
+fn the_ant_never_stalls_on_a_closed_surface() {
+    let mut sphere = CubeSphere::with_seed("RL", 8, 2).unwrap();
+    sphere.advance(100_000);
+
+    assert_eq!(sphere.iterations(), 100_000);
+    assert!(sphere.visited() > 6 * 8 * 8 / 2);
+}
+
+#[test]
+fn projections_have_their_own_sizes() {
+    let mut sphere = CubeSphere::with_seed("RL", 12, 3).unwrap();
+    sphere.advance(200);
+
+    let net = sphere.render(Projection::Net);
+    assert_eq!(net.dimensions(), (48, 36));
+
+    let map = sphere.render(Projection::Equirectangular);
+    assert_eq!(map.dimensions(), (48, 24));
+
+    // The ant starts in the middle of the front face, which is in the
+    // middle of both.
+    let sphere = CubeSphere::with_seed("RL", 12, 3).unwrap();
+    assert_eq!(sphere.ant_pixel(Projection::Net), [18.5, 18.5]);
+
+    let [x, y] = sphere.ant_pixel(Projection::Equirectangular);
+    assert!((x - 24.0).abs() < 1.0 && (y - 12.0).abs() < 1.0);
+    assert_eq!(Projection::Net.next(), Projection::Equirectangular);
+}
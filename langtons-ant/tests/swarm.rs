@@ -0,0 +1,112 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{
+    Boundary, CollisionPolicy, Facing, GridStorage, ObstaclePolicy, Simulation, OBSTACLE,
+};
+
+//-----------------------------------------------------------------------------
+// A grid of the given size with 40 ants scattered over it, some sharing
+// cells, and a few obstacles, one under an ant.
+fn swarm(rule: &str, size: usize, boundary: Boundary) -> Simulation {
+    let mut sim = Simulation::with_seed(rule, size, 1);
+    sim.set_boundary(boundary);
+    sim.set_obstacles(ObstaclePolicy::Left);
+
+    for i in 0..40i64 {
+        let facing = [Facing::N, Facing::E, Facing::S, Facing::W][i as usize % 4];
+        assert!(sim.add_ant((i * 7) % 9 - 4, (i * 5) % 7 - 3, facing));
+    }
+
+    for &(x, y) in &[(3, 3), (-2, 4), (5, -1), (-4, -3)] {
+        sim.paint(x, y, OBSTACLE);
+    }
+
+    sim
+}
+
+// The ants' positions, facings, counts and stalls, and the visited cells.
+type State = (Vec<(i64, i64, Facing, u64, bool)>, Vec<(i64, i64, usize)>);
+
+fn state(sim: &Simulation) -> State {
+    let ants = sim
+        .ants()
+        .iter()
+        .map(|a| (a.pos_x, a.pos_y, a.facing, a.iterations, a.stalled))
+        .collect();
+
+    let mut cells = Vec::new();
+    sim.cells()
+        .for_each_visited(|x, y, c| cells.push((x, y, c)));
+    cells.sort_unstable();
+
+    (ants, cells)
+}
+
+// Advance one copy as a swarm and step another one move at a time.
+fn assert_same_as_stepping(mut sim: Simulation, moves: u64) {
+    let mut stepped = sim.clone();
+    sim.advance(moves);
+
+    for _ in 0..moves {
+        stepped.step();
+    }
+
+    assert!(state(&sim) == state(&stepped));
+}
+
+#[test]
+fn swarms_move_as_if_one_ant_at_a_time() {
+    for &boundary in &[Boundary::Wrap, Boundary::Bounce, Boundary::Stall] {
+        assert_same_as_stepping(swarm("RL", 24, boundary), 3_000);
+        assert_same_as_stepping(swarm("LLRR^NU", 24, boundary), 3_000);
+    }
+}
+
+#[test]
+fn unbounded_swarms_move_as_if_one_ant_at_a_time() {
+    let mut sim = swarm("RLR", 24, Boundary::Stall);
+    sim.make_unbounded();
+
+    assert_same_as_stepping(sim, 5_000);
+}
+
+#[test]
+fn swarms_paint_over_obstacles_under_ants_between_moves() {
+    let mut sim = swarm("RL", 24, Boundary::Wrap);
+    sim.advance(100);
+
+    let ant = &sim.ants()[10];
+    let (x, y) = (ant.pos_x, ant.pos_y);
+    sim.paint(x, y, OBSTACLE);
+    assert_eq!(sim.cells().get(x, y), OBSTACLE);
+
+    assert_same_as_stepping(sim.clone(), 1);
+    assert_same_as_stepping(sim, 500);
+}
+
+#[test]
+fn swarms_that_collide_move_one_ant_at_a_time() {
+    let mut sim = swarm("RL", 24, Boundary::Wrap);
+    sim.set_collision(CollisionPolicy::Swap);
+
+    assert_same_as_stepping(sim, 1_000);
+}
@@ -0,0 +1,119 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::stats::Stats;
+use langtons_ant::symmetry::{Symmetry, SymmetryWatch};
+use langtons_ant::Simulation;
+
+// A simulation with the given cells painted in colour 0, the ant parked
+// well away from them.
+fn painted(cells: &[(i64, i64)]) -> Simulation {
+    let mut sim = Simulation::with_seed("RL", 40, 1);
+
+    for &(x, y) in cells {
+        sim.paint(x, y, 0);
+    }
+
+    sim
+}
+
+#[test]
+fn an_empty_grid_has_no_symmetry() {
+    let sim = Simulation::with_seed("RL", 40, 1);
+    assert_eq!(Symmetry::measure(&sim), Symmetry::NONE);
+    assert!(!Symmetry::NONE.is_symmetric());
+    assert_eq!(Symmetry::NONE.to_string(), "none");
+}
+
+#[test]
+fn a_plus_sign_has_every_symmetry() {
+    let sim = painted(&[(5, 4), (4, 5), (5, 5), (6, 5), (5, 6)]);
+    let symmetry = Symmetry::measure(&sim);
+
+    assert_eq!(symmetry.rotation, 4);
+    assert!(symmetry.left_right && symmetry.top_bottom);
+    assert!(symmetry.diagonal && symmetry.anti_diagonal);
+    assert_eq!(
+        symmetry.to_string(),
+        "4-fold left-right top-bottom diagonal anti-diagonal"
+    );
+}
+
+#[test]
+fn shapes_have_just_their_own_symmetries() {
+    // An L shape is only a mirror image across its diagonal.
+    let l_shape = Symmetry::measure(&painted(&[(4, 4), (4, 5), (4, 6), (5, 6), (6, 6)]));
+    assert_eq!(
+        l_shape,
+        Symmetry {
+            anti_diagonal: true,
+            ..Symmetry::NONE
+        }
+    );
+
+    // An S shape has only half turn symmetry.
+    let s_shape = Symmetry::measure(&painted(&[(5, 4), (6, 4), (5, 5), (4, 6), (5, 6)]));
+    assert_eq!(
+        s_shape,
+        Symmetry {
+            rotation: 2,
+            ..Symmetry::NONE
+        }
+    );
+
+    // A 2 by 1 bar has both mirrors and half turns, but a quarter turn
+    // needs a square.
+    let bar = Symmetry::measure(&painted(&[(4, 4), (5, 4)]));
+    assert_eq!(bar.to_string(), "2-fold left-right top-bottom");
+}
+
+#[test]
+fn symmetry_is_in_the_stats() {
+    let sim = painted(&[(4, 4), (5, 4)]);
+    let row = Stats::measure(&sim).csv_row();
+    assert!(row.contains(",2-fold left-right top-bottom,"));
+}
+
+#[test]
+fn the_watch_reports_symmetry_appearing_and_breaking() {
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let mut watch = SymmetryWatch::new(4);
+    assert_eq!(watch.observe(&sim), None);
+    assert_eq!(watch.remaining(&sim), 4);
+
+    // The first four moves paint a 2 by 2 block.
+    sim.advance(4);
+    let (old, new) = watch.observe(&sim).unwrap();
+    assert_eq!(old, Symmetry::NONE);
+    assert_eq!(new.rotation, 4);
+
+    sim.advance(2);
+    assert_eq!(watch.observe(&sim), None);
+
+    // Then the ant flips the block's first cell back.
+    sim.advance(2);
+    let (_, changed) = watch.observe(&sim).unwrap();
+    assert_eq!(changed.to_string(), "2-fold diagonal anti-diagonal");
+
+    // The chaotic pattern that follows has no symmetry.
+    sim.advance(1000);
+    assert_eq!(watch.observe(&sim), Some((changed, Symmetry::NONE)));
+}
@@ -0,0 +1,88 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::record::Recorder;
+use langtons_ant::timelapse::{TimelapseLayout, TimelapseRecorder};
+use langtons_ant::Simulation;
+
+// Run a simulation to the given iteration, capturing frames as a recording
+// would.
+fn record(sim: &mut Simulation, recorder: &mut TimelapseRecorder, iterations: u64) {
+    while sim.iterations() < iterations {
+        let due = recorder.remaining(sim).min(iterations - sim.iterations());
+        sim.advance(due.max(1));
+        recorder.capture(sim).unwrap();
+    }
+}
+
+#[test]
+fn layouts_parse_by_name() {
+    assert_eq!(TimelapseLayout::parse("Sheet"), Ok(TimelapseLayout::Sheet));
+    assert_eq!(TimelapseLayout::parse("blend"), Ok(TimelapseLayout::Blend));
+    assert!(TimelapseLayout::parse("grid").is_err());
+}
+
+#[test]
+fn snapshots_are_spaced_evenly_and_limited() {
+    let mut sim = Simulation::with_seed("RL", 60, 1);
+    let mut recorder = TimelapseRecorder::new("unused.png", 100, 4, TimelapseLayout::Sheet, 1);
+
+    assert!(recorder.composite().is_none());
+
+    record(&mut sim, &mut recorder, 1000);
+    assert_eq!(recorder.len(), 4);
+}
+
+#[test]
+fn sheets_tile_the_snapshots() {
+    let mut sim = Simulation::with_seed("RL", 40, 2);
+    let mut recorder = TimelapseRecorder::new("unused.png", 50, 5, TimelapseLayout::Sheet, 2);
+    record(&mut sim, &mut recorder, 250);
+
+    // Five tiles of 80 pixels fill two of three columns' rows, with gaps.
+    let img = recorder.composite().unwrap();
+    assert_eq!((img.width(), img.height()), (3 * 88 + 8, 2 * 88 + 8));
+}
+
+#[test]
+fn blends_fade_cells_reached_late() {
+    let mut sim = Simulation::with_seed("RL", 40, 3);
+    let mut recorder = TimelapseRecorder::new("unused.png", 100, 2, TimelapseLayout::Blend, 1);
+
+    sim.advance(100);
+    recorder.capture(&sim).unwrap();
+    sim.paint(-20, -20, 1);
+    sim.advance(100);
+    recorder.capture(&sim).unwrap();
+
+    let img = recorder.composite().unwrap();
+    assert_eq!((img.width(), img.height()), (40, 40));
+
+    // The corner cell is only coloured in the second snapshot, so is half
+    // way between its colour and white.
+    let colour = sim.colours()[1].to_rgba8();
+    let pixel = img.get_pixel(0, 0).0;
+
+    for c in 0..3 {
+        let expected = (u32::from(colour[c]) + 255).div_ceil(2);
+        assert_eq!(u32::from(pixel[c]), expected);
+    }
+}
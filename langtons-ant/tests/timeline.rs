@@ -0,0 +1,105 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::checkpoint::StateDigest;
+use langtons_ant::timeline::Timeline;
+use langtons_ant::{Facing, Simulation};
+
+// A run of the given number of moves, recorded into a timeline as it goes.
+fn recorded(moves: u64, capacity: usize, interval: usize) -> (Simulation, Timeline) {
+    let mut sim = Simulation::with_seed("LLRR", 60, 2);
+    sim.set_track_changes(true);
+    let mut timeline = Timeline::new(&mut sim, capacity, interval);
+
+    for _ in 0..moves {
+        sim.step();
+        timeline.record(&mut sim);
+    }
+
+    (sim, timeline)
+}
+
+#[test]
+fn scrubbing_reaches_every_recorded_moment() {
+    let (mut sim, mut timeline) = recorded(3_000, 10_000, 500);
+    assert_eq!((timeline.start(), timeline.end()), (0, 3_000));
+    assert_eq!(timeline.num_keyframes(), 7);
+
+    let mut expected = Simulation::with_seed("LLRR", 60, 2);
+    let mut digests = vec![StateDigest::of(&expected)];
+    for _ in 0..3_000 {
+        expected.step();
+        digests.push(StateDigest::of(&expected));
+    }
+
+    // Backwards and forwards, near to and far from keyframes.
+    for &position in &[1_234, 10, 2_999, 0, 2_001, 1_500, 3_000] {
+        assert_eq!(timeline.seek(&mut sim, position), position);
+        assert_eq!(StateDigest::of(&sim), digests[position], "{}", position);
+    }
+
+    assert_eq!(timeline.seek(&mut sim, 5_000), 3_000);
+}
+
+#[test]
+fn recording_after_scrubbing_back_takes_a_new_course() {
+    let (mut sim, mut timeline) = recorded(1_000, 10_000, 300);
+    timeline.seek(&mut sim, 400);
+
+    assert!(sim.add_ant(5, 5, Facing::E));
+    sim.step();
+    timeline.record(&mut sim);
+
+    // The ant added and both ants' moves.
+    assert_eq!(timeline.end(), 403);
+    let branched = StateDigest::of(&sim);
+
+    timeline.seek(&mut sim, 0);
+    assert_eq!(timeline.seek(&mut sim, 403), 403);
+    assert_eq!(StateDigest::of(&sim), branched);
+}
+
+#[test]
+fn the_oldest_changes_are_dropped_a_keyframe_at_a_time() {
+    let (mut sim, mut timeline) = recorded(5_000, 1_000, 250);
+
+    assert!(timeline.len() <= 1_000 && timeline.len() > 750);
+    assert_eq!(timeline.end(), 5_000);
+    assert_eq!(timeline.start() % 250, 0);
+
+    let mut expected = Simulation::with_seed("LLRR", 60, 2);
+    expected.advance(timeline.start() as u64);
+
+    assert_eq!(timeline.seek(&mut sim, 0), timeline.start());
+    assert_eq!(StateDigest::of(&sim), StateDigest::of(&expected));
+}
+
+#[test]
+fn rewinding_steps_back_through_the_timeline() {
+    let (mut sim, mut timeline) = recorded(700, 10_000, 200);
+    let mut expected = Simulation::with_seed("LLRR", 60, 2);
+    expected.advance(200);
+
+    assert_eq!(timeline.rewind(&mut sim, 500), 500);
+    assert_eq!(StateDigest::of(&sim), StateDigest::of(&expected));
+    assert_eq!(timeline.rewind(&mut sim, 1_000), 200);
+    assert_eq!(timeline.position(), 0);
+}
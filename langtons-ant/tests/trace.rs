@@ -0,0 +1,104 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::record::Recorder;
+use langtons_ant::trace::{TraceFormat, TraceRecorder};
+use langtons_ant::Simulation;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("langtons-ant-{}-{}", std::process::id(), name))
+}
+
+// Run the simulation to the given iteration, capturing the trace as the
+// headless runner does.
+fn run_traced(sim: &mut Simulation, trace: &mut TraceRecorder, end: u64) {
+    trace.capture(sim).unwrap();
+
+    while sim.iterations() < end {
+        sim.advance(trace.remaining(sim).min(end - sim.iterations()));
+        trace.capture(sim).unwrap();
+    }
+
+    trace.finish().unwrap();
+}
+
+#[test]
+fn the_format_follows_the_extension() {
+    assert_eq!(
+        TraceFormat::for_path(Path::new("trace.csv")),
+        TraceFormat::Csv
+    );
+    assert_eq!(
+        TraceFormat::for_path(Path::new("trace.JSON")),
+        TraceFormat::Json
+    );
+    assert_eq!(TraceFormat::for_path(Path::new("trace")), TraceFormat::Csv);
+}
+
+#[test]
+fn every_step_is_traced_with_the_cell_before_and_after() {
+    let path = temp_path("trace.csv");
+    let mut sim = Simulation::with_seed("RL", 100, 1);
+    let mut trace = TraceRecorder::new(&path, 1);
+
+    // The ant turns right onto (1, 0), right again onto (1, 1), then right
+    // onto (0, 1) and back onto its first cell, the origin, now colour 1.
+    run_traced(&mut sim, &mut trace, 5);
+    assert_eq!(trace.rows(), 5);
+
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "iteration,x,y,facing,colour_before,colour_after",
+            "0,0,0,N,,1",
+            "1,1,0,E,,1",
+            "2,1,1,S,,1",
+            "3,0,1,W,,1",
+            "4,0,0,N,1,0",
+        ]
+    );
+}
+
+#[test]
+fn every_kth_step_is_traced_as_json() {
+    let path = temp_path("trace.json");
+    let mut sim = Simulation::with_seed("LLRR", 100, 1);
+    let mut trace = TraceRecorder::new(&path, 10);
+
+    run_traced(&mut sim, &mut trace, 1000);
+    assert_eq!(trace.rows(), 100);
+
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&text).unwrap();
+    assert_eq!(rows.len(), 100);
+    assert_eq!(rows[0]["iteration"], 0);
+    assert_eq!(rows[0]["colour_before"], serde_json::Value::Null);
+    assert_eq!(rows[0]["colour_after"], 1);
+    assert_eq!(rows[99]["iteration"], 990);
+}
@@ -0,0 +1,109 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{GridStorage, Simulation};
+
+#[test]
+fn visits_are_not_recorded_by_default() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    sim.advance(100);
+
+    assert!(sim.visits().is_none());
+}
+
+#[test]
+fn visits_record_when_each_cell_was_last_left() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    sim.set_track_visits(true);
+
+    sim.step();
+    let visit = sim.visits().unwrap().get(0, 0).unwrap();
+    assert_eq!(visit.last, 1);
+
+    sim.advance(9);
+    let visits = sim.visits().unwrap();
+
+    // Only visited cells are stored, and none was left later than the
+    // latest move.
+    assert!(visits.len() <= 10);
+    assert!(visits.get(-25, -25).is_none());
+    assert!((-25..25)
+        .flat_map(|y| (-25..25).map(move |x| (x, y)))
+        .filter_map(|(x, y)| visits.get(x, y))
+        .all(|v| v.last <= sim.iterations()));
+}
+
+#[test]
+fn tracking_visits_does_not_change_the_run() {
+    let mut plain = Simulation::with_seed("LLRR", 60, 7);
+    let mut tracked = Simulation::with_seed("LLRR", 60, 7);
+    tracked.set_track_visits(true);
+
+    plain.advance(5000);
+    tracked.advance(5000);
+
+    assert_eq!(plain.iterations(), tracked.iterations());
+
+    for y in -30..30 {
+        for x in -30..30 {
+            assert_eq!(plain.cells().get(x, y), tracked.cells().get(x, y));
+        }
+    }
+}
+
+#[test]
+fn enabling_twice_keeps_the_visits_so_far() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    sim.set_track_visits(true);
+    sim.advance(20);
+
+    let before = sim.visits().unwrap().len();
+    sim.set_track_visits(true);
+    assert_eq!(sim.visits().unwrap().len(), before);
+
+    sim.set_track_visits(false);
+    assert!(sim.visits().is_none());
+}
+
+#[test]
+fn visits_count_every_departure() {
+    let mut sim = Simulation::with_seed("RL", 50, 1);
+    sim.set_track_visits(true);
+    sim.advance(1000);
+
+    let visits = sim.visits().unwrap();
+    let mut total = 0;
+    let mut max = 0;
+
+    for y in -25..25 {
+        for x in -25..25 {
+            if let Some(visit) = visits.get(x, y) {
+                total += visit.count;
+                max = max.max(visit.count);
+            }
+        }
+    }
+
+    // Each move leaves exactly one cell.
+    assert_eq!(total, 1000);
+    assert_eq!(visits.max_count(), max);
+    assert!(max > 1);
+}
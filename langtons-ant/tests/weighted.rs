@@ -0,0 +1,90 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::{Direction, GridStorage, Simulation, WeightedRule};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn weighted_rules_parse_and_display_canonically() {
+    let rule = WeightedRule::parse("L:0.9/R:0.1, R").unwrap();
+
+    assert_eq!(rule.len(), 2);
+    assert_eq!(rule.most_likely(), vec![Direction::L, Direction::R]);
+    assert_eq!(rule.to_string(), "L:0.9/R:0.1,R");
+    assert_eq!(WeightedRule::parse(&rule.to_string()).unwrap(), rule);
+}
+
+#[test]
+fn malformed_weighted_rules_are_rejected() {
+    for rule in &[
+        "L:0.9/R:", "L:0/R:1", "L:-1,R", "X:1", "LR:1", "L:1,,R", "L:inf",
+    ] {
+        assert!(WeightedRule::parse(rule).is_err(), "{}", rule);
+    }
+
+    assert!(WeightedRule::is_weighted("L:1,R"));
+    assert!(!WeightedRule::is_weighted("LR"));
+}
+
+#[test]
+fn choices_follow_the_weights() {
+    let rule = WeightedRule::parse("L:0.9/R:0.1").unwrap();
+    let mut rng = StdRng::seed_from_u64(3);
+
+    let lefts = (0..10_000)
+        .filter(|_| rule.choose(0, &mut rng) == Direction::L)
+        .count();
+
+    assert!((8_700..9_300).contains(&lefts), "{}", lefts);
+}
+
+#[test]
+fn weighted_runs_are_reproducible_from_the_seed() {
+    let mut first = Simulation::with_seed("L:0.7/R:0.3,R:0.8/L:0.2", 80, 11);
+    let mut second = Simulation::with_seed("L:0.7/R:0.3,R:0.8/L:0.2", 80, 11);
+
+    first.advance(5000);
+    second.advance(5000);
+
+    assert_eq!(first.rule(), "L:0.7/R:0.3,R:0.8/L:0.2");
+    assert!(same_run(&first, &second));
+}
+
+#[test]
+fn certain_choices_match_the_plain_rule() {
+    let mut weighted = Simulation::with_seed("R:1,L:1", 60, 5);
+    let mut plain = Simulation::with_seed("RL", 60, 5);
+
+    weighted.advance(3000);
+    plain.advance(3000);
+
+    assert!(same_run(&weighted, &plain));
+}
+
+// Do both simulations have the same cells and ant position?
+fn same_run(a: &Simulation, b: &Simulation) -> bool {
+    let (size, start) = (a.size() as i64, -(a.size() as i64 / 2));
+    let range = || start..start + size;
+
+    (a.ant().pos_x, a.ant().pos_y) == (b.ant().pos_x, b.ant().pos_y)
+        && range().all(|y| range().all(|x| a.cells().get(x, y) == b.cells().get(x, y)))
+}
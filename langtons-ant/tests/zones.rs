@@ -0,0 +1,128 @@
+// This file is part of CoreLibrary containing useful reusable utility
+// classes.
+//
+// Copyright (C) 2020 onwards, Duncan Crutchley
+// Contact <dac1976github@outlook.com>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published
+// by the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License and GNU Lesser General Public License
+// for more details.
+//
+// You should have received a copy of the GNU General Public License
+// and GNU Lesser General Public License along with this program. If
+// not, see <http://www.gnu.org/licenses/>.
+
+use langtons_ant::config::{ConfigFile, ZoneSpec};
+use langtons_ant::rule::Rule;
+use langtons_ant::{rle, Direction, GridStorage, Simulation, Zone};
+
+fn zone(x: i64, y: i64, width: usize, height: usize, rule: &str) -> Zone {
+    ZoneSpec {
+        x,
+        y,
+        width,
+        height,
+        rule: rule.to_string(),
+    }
+    .to_zone()
+    .unwrap()
+}
+
+// Do both simulations have the same cells and ant position?
+fn same_run(a: &Simulation, b: &Simulation) -> bool {
+    let (size, start) = (a.size() as i64, -(a.size() as i64 / 2));
+    let range = || start..start + size;
+
+    (a.ant().pos_x, a.ant().pos_y) == (b.ant().pos_x, b.ant().pos_y)
+        && range().all(|y| range().all(|x| a.cells().get(x, y) == b.cells().get(x, y)))
+}
+
+#[test]
+fn zones_parse_from_the_command_line_and_config_files() {
+    let spec = ZoneSpec::parse("-5, 10, 20, 30, LR").unwrap();
+    assert_eq!((spec.x, spec.y, spec.width, spec.height), (-5, 10, 20, 30));
+    assert_eq!(
+        spec.to_zone().unwrap().rule.directions(),
+        &[Direction::L, Direction::R]
+    );
+
+    assert!(ZoneSpec::parse("1,2,3,LR").is_err());
+    assert!(ZoneSpec::parse("1,2,-3,4,LR").is_err());
+    assert!(ZoneSpec::parse("1,2,3,4,LX").unwrap().to_zone().is_err());
+
+    let file = ConfigFile::parse("[[zones]]\nx = 1\ny = 2\nwidth = 3\nheight = 4\nrule = \"RL\"\n")
+        .unwrap();
+    assert_eq!(
+        file.zones.unwrap()[0],
+        ZoneSpec::parse("1,2,3,4,RL").unwrap()
+    );
+}
+
+#[test]
+fn zone_rules_must_match_the_rule_length() {
+    let mut sim = Simulation::with_seed("LLRR", 40, 1);
+
+    assert!(sim.add_zone(zone(0, 0, 10, 10, "LR")).is_err());
+    assert!(sim.add_zone(zone(0, 0, 10, 10, "RLLR")).is_ok());
+    assert_eq!(sim.zones().len(), 1);
+
+    let mut weighted = Simulation::with_seed("L:0.5/R:0.5,R", 40, 1);
+    assert!(weighted.add_zone(zone(0, 0, 10, 10, "LR")).is_err());
+}
+
+#[test]
+fn a_zone_covering_the_grid_replaces_the_rule() {
+    let mut zoned = Simulation::with_seed("RL", 60, 4);
+    zoned.add_zone(zone(-30, -30, 60, 60, "LR")).unwrap();
+    let mut plain = Simulation::with_seed("LR", 60, 4);
+
+    zoned.advance(3000);
+    plain.advance(3000);
+
+    assert!(same_run(&zoned, &plain));
+}
+
+#[test]
+fn zones_elsewhere_change_nothing_until_reached() {
+    let mut zoned = Simulation::with_seed("RL", 60, 4);
+    zoned.add_zone(zone(-30, -30, 5, 5, "LR")).unwrap();
+    let mut plain = Simulation::with_seed("RL", 60, 4);
+
+    zoned.advance(200);
+    plain.advance(200);
+    assert!(same_run(&zoned, &plain));
+
+    // Later zones take precedence.
+    zoned.add_zone(zone(-10, -10, 20, 20, "RL")).unwrap();
+    zoned.add_zone(zone(-5, -5, 10, 10, "LR")).unwrap();
+    assert_eq!(
+        langtons_ant::zones::rule_at(zoned.zones(), 0, 0).map(Rule::directions),
+        Some(&[Direction::L, Direction::R][..])
+    );
+    assert_eq!(
+        langtons_ant::zones::rule_at(zoned.zones(), -9, -9).map(Rule::directions),
+        Some(&[Direction::R, Direction::L][..])
+    );
+    assert_eq!(langtons_ant::zones::rule_at(zoned.zones(), 20, 20), None);
+}
+
+#[test]
+fn zones_are_saved() {
+    let mut sim = Simulation::with_seed("LLRR", 40, 2);
+    sim.add_zone(zone(-3, 5, 12, 8, "RRLL")).unwrap();
+    sim.advance(500);
+
+    let loaded = rle::parse(&rle::encode(&sim).unwrap()).unwrap();
+    assert_eq!(loaded.zones(), sim.zones());
+
+    let json = serde_json::to_string(&sim).unwrap();
+    let loaded: Simulation = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.zones(), sim.zones());
+}